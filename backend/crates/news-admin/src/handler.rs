@@ -1,13 +1,13 @@
 use crate::claude;
-use aws_sdk_dynamodb::types::AttributeValue;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use news_core::changes::{AdminAction, ChangeRequest, ChangeStatus, ChangeStore};
-use news_core::config::{ConfigStore, DynamicFeed};
+use news_core::config::ConfigStore;
+use news_core::discovery;
+use news_core::store::{self, ChangeStorage};
 use serde::Deserialize;
-use std::collections::HashMap;
 use tracing::{info, warn};
 
 #[derive(Clone)]
@@ -38,7 +38,7 @@ pub async fn handle_command(
     }
 
     // Get current service config for context
-    let current_config = match state.config_store.get_service_config().await {
+    let current_config = match store::service_config(&state.config_store).await {
         Ok(c) => c,
         Err(e) => {
             warn!(error = %e, "Failed to load service config");
@@ -166,7 +166,14 @@ pub async fn apply_change(
     let mut errors = Vec::new();
 
     for action in &change.actions {
-        match apply_action(&state.config_store, action).await {
+        // Feed URLs are checked here because apply_action can't fetch
+        if let AdminAction::AddFeed { url, source_type, scrape, .. } = action {
+            if let Err(e) = discovery::validate_feed(&state.http_client, url, *source_type, scrape.as_ref()).await {
+                errors.push(format!("{:?}: {}", action, e));
+                continue;
+            }
+        }
+        match store::apply_action(&state.config_store, action).await {
             Ok(()) => applied += 1,
             Err(e) => errors.push(format!("{:?}: {}", action, e)),
         }
@@ -175,7 +182,7 @@ pub async fn apply_change(
     // Update change status
     let _ = state
         .change_store
-        .update_status(&change_id, ChangeStatus::Applied)
+        .update_change_status(&change_id, ChangeStatus::Applied)
         .await;
 
     info!(change_id = %change_id, applied, errors = errors.len(), "Change applied");
@@ -198,7 +205,7 @@ pub async fn reject_change(
 ) -> Response {
     match state
         .change_store
-        .update_status(&change_id, ChangeStatus::Rejected)
+        .update_change_status(&change_id, ChangeStatus::Rejected)
         .await
     {
         Ok(()) => (
@@ -213,88 +220,3 @@ pub async fn reject_change(
             .into_response(),
     }
 }
-
-/// Apply a single admin action to the config store.
-async fn apply_action(config_store: &ConfigStore, action: &AdminAction) -> Result<(), String> {
-    match action {
-        AdminAction::AddFeed {
-            url,
-            source,
-            category,
-        } => {
-            let feed_id = format!(
-                "feed-{}",
-                uuid::Uuid::new_v4().to_string().split('-').next().unwrap_or("x")
-            );
-            let feed = DynamicFeed {
-                feed_id,
-                url: url.clone(),
-                source: source.clone(),
-                category: category.clone(),
-                enabled: true,
-                added_by: Some("admin-chat".into()),
-            };
-            config_store
-                .put_feed(&feed)
-                .await
-                .map_err(|e| e.to_string())
-        }
-        AdminAction::RemoveFeed { feed_id } => {
-            config_store
-                .delete_feed(feed_id)
-                .await
-                .map_err(|e| e.to_string())
-        }
-        AdminAction::EnableFeed { feed_id } => {
-            // Read current feed, update enabled
-            update_feed_enabled(config_store, feed_id, true).await
-        }
-        AdminAction::DisableFeed { feed_id } => {
-            update_feed_enabled(config_store, feed_id, false).await
-        }
-        AdminAction::ToggleFeature { feature, enabled } => {
-            config_store
-                .set_feature_flag(feature, *enabled, None)
-                .await
-                .map_err(|e| e.to_string())
-        }
-        AdminAction::SetGroupingThreshold { threshold } => {
-            let mut extra = HashMap::new();
-            extra.insert(
-                "similarity_threshold".into(),
-                AttributeValue::N(threshold.to_string()),
-            );
-            config_store
-                .set_feature_flag("grouping", true, Some(extra))
-                .await
-                .map_err(|e| e.to_string())
-        }
-        AdminAction::AddCategory { .. }
-        | AdminAction::RemoveCategory { .. }
-        | AdminAction::RenameCategory { .. }
-        | AdminAction::ReorderCategories { .. } => {
-            Err("Category management is not supported in DynamoDB admin. Use the server API instead.".into())
-        }
-    }
-}
-
-async fn update_feed_enabled(
-    config_store: &ConfigStore,
-    feed_id: &str,
-    enabled: bool,
-) -> Result<(), String> {
-    let feeds = config_store
-        .get_all_feeds()
-        .await
-        .map_err(|e| e.to_string())?;
-    let feed = feeds
-        .into_iter()
-        .find(|f| f.feed_id == feed_id)
-        .ok_or_else(|| format!("Feed not found: {}", feed_id))?;
-
-    let updated = DynamicFeed { enabled, ..feed };
-    config_store
-        .put_feed(&updated)
-        .await
-        .map_err(|e| e.to_string())
-}
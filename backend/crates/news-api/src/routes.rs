@@ -6,6 +6,7 @@ use news_core::config::ConfigStore;
 use news_core::dynamo::ArticleStore;
 use news_core::grouping;
 use news_core::models::{ArticlesResponse, Category, CategoryInfo};
+use news_core::store::{ArticleStorage, CategoryStorage, FeatureStorage};
 use serde::Deserialize;
 
 #[derive(Clone)]
@@ -17,11 +18,12 @@ pub struct AppState {
 #[derive(Deserialize)]
 pub struct ArticlesQuery {
     pub category: Option<String>,
-    pub limit: Option<i32>,
+    pub limit: Option<usize>,
     pub cursor: Option<String>,
+    pub lang: Option<String>,
 }
 
-/// GET /api/articles?category=&limit=&cursor=
+/// GET /api/articles?category=&limit=&cursor=&lang=
 pub async fn get_articles(
     State(state): State<AppState>,
     Query(params): Query<ArticlesQuery>,
@@ -30,11 +32,16 @@ pub async fn get_articles(
         .category
         .as_deref()
         .and_then(Category::from_str);
-    let limit = params.limit.unwrap_or(30).min(100).max(1);
+    let limit = params.limit.unwrap_or(30).clamp(1, 100);
 
     let result = state
         .article_store
-        .query_articles(category.as_ref(), limit, params.cursor.as_deref())
+        .query_articles(
+            category.as_ref(),
+            params.lang.as_deref(),
+            limit,
+            params.cursor.as_deref(),
+        )
         .await;
 
     match result {
@@ -42,10 +49,13 @@ pub async fn get_articles(
             // Apply grouping if feature is enabled
             if let Ok(flags) = state.config_store.get_feature_flags().await {
                 if flags.grouping_enabled && articles.len() > 1 {
-                    let titles: Vec<&str> =
-                        articles.iter().map(|a| a.title.as_str()).collect();
+                    let texts: Vec<String> = articles
+                        .iter()
+                        .map(|a| grouping::story_text(&a.title, a.description.as_deref()))
+                        .collect();
+                    let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
                     let groups =
-                        grouping::group_articles(&titles, flags.grouping_threshold);
+                        grouping::group_articles_lsh(&texts, flags.grouping_threshold);
 
                     for group in &groups {
                         if group.len() > 1 {
@@ -109,16 +119,33 @@ pub async fn get_articles(
 }
 
 /// GET /api/categories
-pub async fn get_categories() -> Response {
-    (
-        StatusCode::OK,
-        [
-            (header::CACHE_CONTROL, "public, max-age=3600"),
-            (header::CONTENT_TYPE, "application/json; charset=utf-8"),
-        ],
-        Json(CategoryInfo::all()),
-    )
-        .into_response()
+pub async fn get_categories(State(state): State<AppState>) -> Response {
+    match state.config_store.get_categories().await {
+        Ok(categories) => {
+            let visible: Vec<CategoryInfo> = categories
+                .iter()
+                .filter(|c| c.visible)
+                .map(|c| c.info())
+                .collect();
+            (
+                StatusCode::OK,
+                [
+                    (header::CACHE_CONTROL, "public, max-age=3600"),
+                    (header::CONTENT_TYPE, "application/json; charset=utf-8"),
+                ],
+                Json(visible),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to load categories");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Internal server error"})),
+            )
+                .into_response()
+        }
+    }
 }
 
 /// GET /health
@@ -0,0 +1,73 @@
+//! Compare pairwise and LSH grouping as the number of articles grows.
+//!
+//! Run with `cargo bench -p news-core --bench grouping`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use news_core::grouping::{group_articles, group_articles_lsh, story_text};
+use std::hint::black_box;
+
+const SYLLABLES: [&str; 40] = [
+    "か", "き", "く", "け", "こ", "さ", "し", "す", "せ", "そ", "た", "ち", "つ", "て", "と", "な", "に",
+    "ぬ", "ね", "の", "ま", "み", "む", "め", "も", "ら", "り", "る", "れ", "ろ", "政", "経", "済", "市",
+    "場", "国", "際", "選", "挙", "株",
+];
+
+/// Deterministic xorshift so every run benchmarks the same corpus.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+
+    fn words(&mut self, n: usize) -> String {
+        (0..n)
+            .map(|_| {
+                (0..3)
+                    .map(|_| SYLLABLES[self.next() % SYLLABLES.len()])
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("・")
+    }
+}
+
+/// `n` articles where every story is reported by three outlets with slightly different wording.
+fn headlines(n: usize) -> Vec<String> {
+    let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+    let mut texts = Vec::with_capacity(n);
+    while texts.len() < n {
+        let title = rng.words(5);
+        let description = rng.words(12);
+        for variant in [title.clone(), format!("{}（速報）", title), format!("【独自】{}", title)] {
+            texts.push(story_text(&variant, Some(&description)));
+        }
+    }
+    texts.truncate(n);
+    texts
+}
+
+fn bench_grouping(c: &mut Criterion) {
+    let mut group = c.benchmark_group("group_articles");
+    group.sample_size(10);
+    for n in [100, 500, 2000, 5000] {
+        let texts = headlines(n);
+        let refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
+        // Pairwise takes tens of seconds per run beyond a few hundred articles
+        if n <= 500 {
+            group.bench_with_input(BenchmarkId::new("pairwise", n), &refs, |b, refs| {
+                b.iter(|| group_articles(black_box(refs), 0.3))
+            });
+        }
+        group.bench_with_input(BenchmarkId::new("lsh", n), &refs, |b, refs| {
+            b.iter(|| group_articles_lsh(black_box(refs), 0.3))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_grouping);
+criterion_main!(benches);
@@ -0,0 +1,265 @@
+use crate::models::{Article, Category};
+use crate::text::contains_keyword;
+use serde::{Deserialize, Serialize};
+
+/// Confidence that an article belongs to its feed's category.
+pub const FEED_CONFIDENCE: f32 = 0.7;
+/// Confidence in the category the AI analyzer picked.
+pub const AI_CONFIDENCE: f32 = 0.8;
+/// Confidence added per distinct keyword a rule matches, up to [`MAX_KEYWORD_CONFIDENCE`].
+pub const KEYWORD_CONFIDENCE: f32 = 0.3;
+pub const MAX_KEYWORD_CONFIDENCE: f32 = 0.6;
+/// Combined confidence a category needs before the article is filed under it.
+/// A single keyword hit is not enough on its own; two are.
+pub const ASSIGN_THRESHOLD: f32 = 0.5;
+
+/// What suggested a category for an article.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelSource {
+    Feed,
+    Ai,
+    Keyword,
+}
+
+impl LabelSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Feed => "feed",
+            Self::Ai => "ai",
+            Self::Keyword => "keyword",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "feed" => Some(Self::Feed),
+            "ai" => Some(Self::Ai),
+            "keyword" => Some(Self::Keyword),
+            _ => None,
+        }
+    }
+}
+
+/// A category assigned to an article, with the combined confidence of every source
+/// that suggested it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryLabel {
+    pub category: Category,
+    pub confidence: f32,
+    pub sources: Vec<LabelSource>,
+}
+
+/// An analyzed article whose AI category differs from its feed's, for tuning feeds and
+/// keyword rules.
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryDisagreement {
+    pub article_id: String,
+    pub title: String,
+    pub source: String,
+    pub feed_category: String,
+    pub ai_category: String,
+    /// What the article ended up filed under.
+    pub categories: Vec<CategoryLabel>,
+    pub analyzed_at: Option<String>,
+}
+
+/// How many analyzed articles from `feed_category` feeds the AI put in `ai_category`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryDisagreementCount {
+    pub feed_category: String,
+    pub ai_category: String,
+    pub count: i64,
+}
+
+/// Words that point to a category when they appear in an article's title or description.
+/// ASCII keywords match whole words, case-insensitively; others match as substrings.
+pub struct KeywordRule {
+    pub category: Category,
+    pub keywords: &'static [&'static str],
+}
+
+pub const KEYWORD_RULES: &[KeywordRule] = &[
+    KeywordRule {
+        category: Category::Sports,
+        keywords: &[
+            "野球", "サッカー", "五輪", "オリンピック", "パラリンピック", "Jリーグ", "W杯",
+            "ワールドカップ", "大相撲", "ゴルフ", "テニス", "ラグビー", "バスケ", "陸上", "競馬",
+            "MLB", "NBA", "NFL", "baseball", "football", "soccer", "Olympics", "tennis", "golf",
+            "rugby",
+        ],
+    },
+    KeywordRule {
+        category: Category::Tech,
+        keywords: &[
+            "人工知能", "生成AI", "半導体", "スマホ", "スマートフォン", "アプリ", "ソフトウェア",
+            "サイバー攻撃", "不正アクセス", "iPhone", "Android", "AI", "software", "smartphone",
+            "semiconductor", "chip", "startup", "cybersecurity", "LLM",
+        ],
+    },
+    KeywordRule {
+        category: Category::Business,
+        keywords: &[
+            "株価", "日経平均", "決算", "為替", "円安", "円高", "金利", "日銀", "GDP", "買収",
+            "上場", "赤字", "黒字", "earnings", "stocks", "inflation", "merger", "acquisition",
+            "IPO", "revenue",
+        ],
+    },
+    KeywordRule {
+        category: Category::Entertainment,
+        keywords: &[
+            "映画", "ドラマ", "俳優", "女優", "歌手", "アイドル", "アニメ", "芸能", "紅白",
+            "movie", "film", "album", "celebrity", "Netflix", "box office",
+        ],
+    },
+    KeywordRule {
+        category: Category::Science,
+        keywords: &[
+            "研究チーム", "宇宙", "JAXA", "NASA", "論文", "ロケット", "気候変動", "遺伝子",
+            "化石", "素粒子", "research", "scientists", "space", "climate", "fossil", "genome",
+        ],
+    },
+];
+
+/// Map the analyzer's category string onto a [`Category`]. "other" and unknown values
+/// say nothing about where the article belongs.
+pub fn ai_category(s: &str) -> Option<Category> {
+    match s.trim().to_lowercase().as_str() {
+        "other" | "" => None,
+        s => Category::from_str(s),
+    }
+}
+
+/// Categories suggested by [`KEYWORD_RULES`] for `text`, with their confidence.
+pub fn keyword_labels(text: &str) -> Vec<(Category, f32)> {
+    let text = text.to_lowercase();
+    KEYWORD_RULES
+        .iter()
+        .filter_map(|rule| {
+            let hits = rule
+                .keywords
+                .iter()
+                .filter(|k| contains_keyword(&text, &k.to_lowercase()))
+                .count();
+            (hits > 0).then(|| {
+                let confidence = (hits as f32 * KEYWORD_CONFIDENCE).min(MAX_KEYWORD_CONFIDENCE);
+                (rule.category.clone(), confidence)
+            })
+        })
+        .collect()
+}
+
+/// Combine the feed's category, the AI analyzer's category (if the article has been
+/// analyzed) and keyword rules over `text` into the categories the article is filed
+/// under, most confident first. Confidences from independent sources combine as
+/// 1 - Π(1 - c), so agreement raises confidence; categories below
+/// [`ASSIGN_THRESHOLD`] are dropped. The feed's category is always kept, so an article
+/// never disappears from the section it was published in.
+pub fn assign(feed_category: &Category, ai: Option<&Category>, text: &str) -> Vec<CategoryLabel> {
+    let mut suggestions = vec![(feed_category.clone(), FEED_CONFIDENCE, LabelSource::Feed)];
+    if let Some(ai) = ai {
+        suggestions.push((ai.clone(), AI_CONFIDENCE, LabelSource::Ai));
+    }
+    suggestions.extend(
+        keyword_labels(text)
+            .into_iter()
+            .map(|(category, confidence)| (category, confidence, LabelSource::Keyword)),
+    );
+
+    let mut labels: Vec<CategoryLabel> = Vec::new();
+    for (category, confidence, source) in suggestions {
+        match labels.iter_mut().find(|l| l.category == category) {
+            Some(label) => {
+                label.confidence = 1.0 - (1.0 - label.confidence) * (1.0 - confidence);
+                label.sources.push(source);
+            }
+            None => labels.push(CategoryLabel {
+                category,
+                confidence,
+                sources: vec![source],
+            }),
+        }
+    }
+    labels.retain(|l| l.confidence >= ASSIGN_THRESHOLD);
+    labels.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    labels
+}
+
+/// [`assign`] over an article's title, description and any section and keywords its
+/// page declared.
+pub fn for_article(article: &Article, ai: Option<&Category>) -> Vec<CategoryLabel> {
+    let mut text = article.title.clone();
+    if let Some(ref description) = article.description {
+        text.push('\n');
+        text.push_str(description);
+    }
+    if let Some(ref meta) = article.metadata {
+        if let Some(ref section) = meta.section {
+            text.push('\n');
+            text.push_str(section);
+        }
+        for keyword in &meta.keywords {
+            text.push('\n');
+            text.push_str(keyword);
+        }
+    }
+    assign(&article.category, ai, &text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(labels: &[CategoryLabel]) -> Vec<Category> {
+        labels.iter().map(|l| l.category.clone()).collect()
+    }
+
+    #[test]
+    fn feed_category_alone() {
+        let labels = assign(&Category::General, None, "首相が会見");
+        assert_eq!(categories(&labels), vec![Category::General]);
+        assert_eq!(labels[0].sources, vec![LabelSource::Feed]);
+        assert_eq!(labels[0].confidence, FEED_CONFIDENCE);
+    }
+
+    #[test]
+    fn ai_category_adds_a_label_and_outranks_the_feed() {
+        let labels = assign(&Category::General, Some(&Category::Sports), "大谷が本塁打");
+        assert_eq!(categories(&labels), vec![Category::Sports, Category::General]);
+        assert_eq!(labels[0].sources, vec![LabelSource::Ai]);
+    }
+
+    #[test]
+    fn agreement_raises_confidence() {
+        let labels = assign(&Category::Sports, Some(&Category::Sports), "プロ野球 日本シリーズ");
+        assert_eq!(labels.len(), 1);
+        assert_eq!(
+            labels[0].sources,
+            vec![LabelSource::Feed, LabelSource::Ai, LabelSource::Keyword]
+        );
+        assert!(labels[0].confidence > AI_CONFIDENCE);
+    }
+
+    #[test]
+    fn one_keyword_is_not_enough_but_two_are() {
+        let one = assign(&Category::General, None, "サッカー日本代表が会見");
+        assert_eq!(categories(&one), vec![Category::General]);
+        let two = assign(&Category::General, None, "W杯予選 サッカー日本代表が勝利");
+        assert_eq!(categories(&two), vec![Category::General, Category::Sports]);
+        assert_eq!(two[1].sources, vec![LabelSource::Keyword]);
+    }
+
+    #[test]
+    fn ascii_keywords_match_whole_words() {
+        assert!(keyword_labels("The officials said").is_empty());
+        assert!(!keyword_labels("New AI model").is_empty());
+    }
+
+    #[test]
+    fn ai_category_strings() {
+        assert_eq!(ai_category("Sports"), Some(Category::Sports));
+        assert_eq!(ai_category("other"), None);
+        assert_eq!(ai_category("politics"), None);
+    }
+}
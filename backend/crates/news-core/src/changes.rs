@@ -1,7 +1,13 @@
-use crate::config::ServiceConfig;
+use crate::config::{ContentPolicy, ServiceConfig};
+use crate::filters::FeedFilters;
+use crate::sources::{ScrapeRules, SourceType};
 #[cfg(feature = "dynamo")]
 use crate::error::{AppError, Result};
 #[cfg(feature = "dynamo")]
+use crate::store::ChangeStorage;
+#[cfg(feature = "dynamo")]
+use async_trait::async_trait;
+#[cfg(feature = "dynamo")]
 use aws_sdk_dynamodb::types::AttributeValue;
 #[cfg(feature = "dynamo")]
 use aws_sdk_dynamodb::Client;
@@ -33,6 +39,7 @@ impl ChangeStatus {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "pending" => Some(Self::Pending),
@@ -52,6 +59,10 @@ pub enum AdminAction {
         url: String,
         source: String,
         category: String,
+        #[serde(default)]
+        source_type: SourceType,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scrape: Option<ScrapeRules>,
     },
     RemoveFeed {
         feed_id: String,
@@ -62,6 +73,36 @@ pub enum AdminAction {
     DisableFeed {
         feed_id: String,
     },
+    /// Pin a feed's poll interval; `None` returns it to the adaptive schedule.
+    SetPollInterval {
+        feed_id: String,
+        #[serde(default)]
+        seconds: Option<u64>,
+    },
+    /// Re-enable a quarantined feed and reset its failure count.
+    ReleaseQuarantine {
+        feed_id: String,
+    },
+    /// Control whether a source's pages may be fetched and used for AI features.
+    SetContentPolicy {
+        feed_id: String,
+        policy: ContentPolicy,
+    },
+    /// Replace the include/exclude rules applied to a feed's items.
+    SetFeedFilters {
+        feed_id: String,
+        filters: FeedFilters,
+    },
+    /// Remove a feed's filters so every item is stored again.
+    ClearFeedFilters {
+        feed_id: String,
+    },
+    /// Add the feeds of an OPML document, skipping duplicates and unknown categories.
+    ImportFeeds {
+        opml: String,
+        #[serde(default)]
+        default_category: Option<String>,
+    },
     ToggleFeature {
         feature: String,
         enabled: bool,
@@ -111,9 +152,12 @@ impl ChangeStore {
     pub fn new(client: Client, table_name: String) -> Self {
         Self { client, table_name }
     }
+}
 
-    /// Create a new change request.
-    pub async fn create_change(&self, change: &ChangeRequest) -> Result<()> {
+#[cfg(feature = "dynamo")]
+#[async_trait]
+impl ChangeStorage for ChangeStore {
+    async fn create_change(&self, change: &ChangeRequest) -> Result<()> {
         let actions_json =
             serde_json::to_string(&change.actions).map_err(AppError::SerdeError)?;
 
@@ -163,8 +207,7 @@ impl ChangeStore {
         Ok(())
     }
 
-    /// Get a change request by ID.
-    pub async fn get_change(&self, change_id: &str) -> Result<Option<ChangeRequest>> {
+    async fn get_change(&self, change_id: &str) -> Result<Option<ChangeRequest>> {
         let output = self
             .client
             .get_item()
@@ -183,8 +226,7 @@ impl ChangeStore {
         Ok(item_to_change(&item))
     }
 
-    /// Update the status of a change request.
-    pub async fn update_status(
+    async fn update_change_status(
         &self,
         change_id: &str,
         status: ChangeStatus,
@@ -205,8 +247,8 @@ impl ChangeStore {
         Ok(())
     }
 
-    /// List recent change requests (most recent first, up to limit).
-    pub async fn list_changes(&self, limit: i32) -> Result<Vec<ChangeRequest>> {
+    /// Scans the table, so only suited to the small number of changes an admin makes.
+    async fn list_changes(&self, limit: usize) -> Result<Vec<ChangeRequest>> {
         // Scan for all CHANGE# items — for small scale this is fine
         let output = self
             .client
@@ -215,7 +257,7 @@ impl ChangeStore {
             .filter_expression("begins_with(pk, :prefix) AND sk = :meta")
             .expression_attribute_values(":prefix", AttributeValue::S("CHANGE#".into()))
             .expression_attribute_values(":meta", AttributeValue::S("META".into()))
-            .limit(limit)
+            .limit(limit as i32)
             .send()
             .await
             .map_err(|e| AppError::DynamoError(e.into_service_error().to_string()))?;
@@ -287,20 +329,44 @@ mod tests {
             url: "https://example.com/feed".into(),
             source: "Example".into(),
             category: "tech".into(),
+            source_type: SourceType::NewsSitemap,
+            scrape: None,
         };
         let json = serde_json::to_string(&action).unwrap();
         assert!(json.contains("\"type\":\"add_feed\""));
         let parsed: AdminAction = serde_json::from_str(&json).unwrap();
         match parsed {
-            AdminAction::AddFeed { url, source, category } => {
+            AdminAction::AddFeed { url, source, category, source_type, .. } => {
                 assert_eq!(url, "https://example.com/feed");
                 assert_eq!(source, "Example");
                 assert_eq!(category, "tech");
+                assert_eq!(source_type, SourceType::NewsSitemap);
             }
             _ => panic!("Wrong variant"),
         }
     }
 
+    #[test]
+    fn feed_filter_actions_deserialize() {
+        let action: AdminAction = serde_json::from_str(
+            r#"{"type":"set_feed_filters","feed_id":"yahoo-top","filters":{"exclude_keywords":["PR"],"include_patterns":[{"field":"url_path","pattern":"^/articles/"}],"min_description_len":40}}"#,
+        )
+        .unwrap();
+        match action {
+            AdminAction::SetFeedFilters { feed_id, filters } => {
+                assert_eq!(feed_id, "yahoo-top");
+                assert_eq!(filters.exclude_keywords, vec!["PR"]);
+                assert_eq!(filters.include_patterns[0].field, crate::filters::FilterField::UrlPath);
+                assert_eq!(filters.min_description_len, Some(40));
+                assert!(filters.include_keywords.is_empty());
+            }
+            _ => panic!("Wrong variant"),
+        }
+        let clear: AdminAction =
+            serde_json::from_str(r#"{"type":"clear_feed_filters","feed_id":"yahoo-top"}"#).unwrap();
+        assert!(matches!(clear, AdminAction::ClearFeedFilters { .. }));
+    }
+
     #[test]
     fn change_request_serialization() {
         let change = ChangeRequest {
@@ -312,6 +378,8 @@ mod tests {
                 url: "https://rss.itmedia.co.jp/rss/2.0/itmedia_all.xml".into(),
                 source: "ITmedia".into(),
                 category: "tech".into(),
+                source_type: SourceType::Rss,
+                scrape: None,
             }],
             preview_config: None,
             created_at: "2025-01-01T00:00:00Z".into(),
@@ -321,5 +389,10 @@ mod tests {
         assert_eq!(parsed.change_id, "test-123");
         assert_eq!(parsed.status, ChangeStatus::Preview);
         assert_eq!(parsed.actions.len(), 1);
+
+        // Actions saved before source types existed still load
+        let legacy: AdminAction =
+            serde_json::from_str(r#"{"type":"add_feed","url":"u","source":"s","category":"tech"}"#).unwrap();
+        assert!(matches!(legacy, AdminAction::AddFeed { source_type: SourceType::Rss, .. }));
     }
 }
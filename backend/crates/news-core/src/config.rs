@@ -1,5 +1,11 @@
 #[cfg(feature = "dynamo")]
 use crate::error::{AppError, Result};
+use crate::filters::FeedFilters;
+use crate::sources::{ScrapeRules, SourceType};
+#[cfg(feature = "dynamo")]
+use crate::store::{default_categories, CategoryRecord, CategoryStorage, FeatureStorage, FeedStorage};
+#[cfg(feature = "dynamo")]
+use async_trait::async_trait;
 #[cfg(feature = "dynamo")]
 use aws_sdk_dynamodb::types::AttributeValue;
 #[cfg(feature = "dynamo")]
@@ -20,6 +26,65 @@ pub struct DynamicFeed {
     pub enabled: bool,
     #[serde(default)]
     pub added_by: Option<String>,
+    /// Admin override of the adaptive poll interval, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval_secs: Option<u64>,
+    /// Set when the feed was disabled automatically after repeated fetch failures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantine_reason: Option<String>,
+    /// What may be done with this source's article pages.
+    #[serde(default)]
+    pub content_policy: ContentPolicy,
+    #[serde(default)]
+    pub source_type: SourceType,
+    /// Selectors for `scrape` sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrape: Option<ScrapeRules>,
+    /// Include/exclude rules applied to fetched items before they are stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filters: Option<FeedFilters>,
+}
+
+/// Per-source permission for reading publisher pages, set by admins (e.g. on legal request).
+/// Ordered from least to most restrictive.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentPolicy {
+    /// Pages may be fetched and their full text used for AI features.
+    #[default]
+    FullText,
+    /// Pages may be fetched for metadata (image, canonical URL, dates) only.
+    MetadataOnly,
+    /// Pages are never fetched; only what the feed itself contains is used.
+    NoFetch,
+}
+
+impl ContentPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FullText => "full_text",
+            Self::MetadataOnly => "metadata_only",
+            Self::NoFetch => "no_fetch",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "full_text" => Some(Self::FullText),
+            "metadata_only" => Some(Self::MetadataOnly),
+            "no_fetch" => Some(Self::NoFetch),
+            _ => None,
+        }
+    }
+
+    pub fn allows_page_fetch(&self) -> bool {
+        *self != Self::NoFetch
+    }
+
+    pub fn allows_full_text(&self) -> bool {
+        *self == Self::FullText
+    }
 }
 
 /// Feature flags stored in DynamoDB ConfigTable.
@@ -28,6 +93,22 @@ pub struct FeatureFlags {
     pub grouping_enabled: bool,
     pub grouping_threshold: f64,
     pub ogp_enrichment_enabled: bool,
+    #[serde(default = "default_true")]
+    pub auto_quarantine_enabled: bool,
+    /// Consecutive fetch failures before a feed is quarantined.
+    #[serde(default = "default_quarantine_after")]
+    pub quarantine_after_failures: u32,
+    /// Subscribe to WebSub hubs advertised by feeds (news-server only).
+    #[serde(default = "default_true")]
+    pub websub_enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_quarantine_after() -> u32 {
+    crate::health::DEFAULT_QUARANTINE_AFTER
 }
 
 impl Default for FeatureFlags {
@@ -36,6 +117,34 @@ impl Default for FeatureFlags {
             grouping_enabled: false,
             grouping_threshold: 0.3,
             ogp_enrichment_enabled: true,
+            auto_quarantine_enabled: true,
+            quarantine_after_failures: crate::health::DEFAULT_QUARANTINE_AFTER,
+            websub_enabled: true,
+        }
+    }
+}
+
+impl FeatureFlags {
+    /// Apply one stored feature row. `extra` holds the feature's settings, e.g.
+    /// `{"similarity_threshold": 0.5}`; unknown features are ignored.
+    pub fn set(&mut self, feature: &str, enabled: bool, extra: Option<&serde_json::Value>) {
+        let number = |key: &str| extra.and_then(|v| v.get(key)).and_then(|v| v.as_f64());
+        match feature {
+            "grouping" => {
+                self.grouping_enabled = enabled;
+                if let Some(t) = number("similarity_threshold") {
+                    self.grouping_threshold = t;
+                }
+            }
+            "ogp_enrichment" => self.ogp_enrichment_enabled = enabled,
+            "auto_quarantine" => {
+                self.auto_quarantine_enabled = enabled;
+                if let Some(n) = number("failure_threshold") {
+                    self.quarantine_after_failures = n as u32;
+                }
+            }
+            "websub" => self.websub_enabled = enabled,
+            _ => {}
         }
     }
 }
@@ -64,101 +173,51 @@ impl ConfigStore {
         Self { client, table_name }
     }
 
-    /// Get all enabled feeds from ConfigTable.
-    pub async fn get_enabled_feeds(&self) -> Result<Vec<DynamicFeed>> {
+    async fn query_prefix(&self, prefix: &str) -> Result<Vec<HashMap<String, AttributeValue>>> {
         let output = self
             .client
             .query()
             .table_name(&self.table_name)
             .key_condition_expression("pk = :pk AND begins_with(sk, :prefix)")
             .expression_attribute_values(":pk", AttributeValue::S(PK_CONFIG.into()))
-            .expression_attribute_values(":prefix", AttributeValue::S("FEEDS#".into()))
+            .expression_attribute_values(":prefix", AttributeValue::S(prefix.into()))
             .send()
             .await
             .map_err(|e| AppError::DynamoError(e.into_service_error().to_string()))?;
+        Ok(output.items.unwrap_or_default())
+    }
 
-        let items = output.items.unwrap_or_default();
-        let feeds: Vec<DynamicFeed> = items
+    /// Stored categories, writing the defaults first if none have been stored yet so
+    /// that edits apply to the full list.
+    async fn stored_categories(&self) -> Result<Vec<CategoryRecord>> {
+        let stored: Vec<CategoryRecord> = self
+            .query_prefix("CATEGORY#")
+            .await?
             .iter()
-            .filter_map(|item| item_to_feed(item))
-            .filter(|f| f.enabled)
+            .filter_map(item_to_category)
             .collect();
-
-        Ok(feeds)
-    }
-
-    /// Get all feeds (including disabled) from ConfigTable.
-    pub async fn get_all_feeds(&self) -> Result<Vec<DynamicFeed>> {
-        let output = self
-            .client
-            .query()
-            .table_name(&self.table_name)
-            .key_condition_expression("pk = :pk AND begins_with(sk, :prefix)")
-            .expression_attribute_values(":pk", AttributeValue::S(PK_CONFIG.into()))
-            .expression_attribute_values(":prefix", AttributeValue::S("FEEDS#".into()))
-            .send()
-            .await
-            .map_err(|e| AppError::DynamoError(e.into_service_error().to_string()))?;
-
-        let items = output.items.unwrap_or_default();
-        Ok(items.iter().filter_map(|item| item_to_feed(item)).collect())
-    }
-
-    /// Get feature flags from ConfigTable.
-    pub async fn get_feature_flags(&self) -> Result<FeatureFlags> {
-        let mut flags = FeatureFlags::default();
-
-        let output = self
-            .client
-            .query()
-            .table_name(&self.table_name)
-            .key_condition_expression("pk = :pk AND begins_with(sk, :prefix)")
-            .expression_attribute_values(":pk", AttributeValue::S(PK_CONFIG.into()))
-            .expression_attribute_values(":prefix", AttributeValue::S("FEATURE#".into()))
-            .send()
-            .await
-            .map_err(|e| AppError::DynamoError(e.into_service_error().to_string()))?;
-
-        for item in output.items.unwrap_or_default() {
-            let sk = match item.get("sk").and_then(|v| v.as_s().ok()) {
-                Some(s) => s.as_str(),
-                None => continue,
-            };
-            let enabled = item
-                .get("enabled")
-                .and_then(|v| v.as_bool().ok().copied())
-                .unwrap_or(false);
-
-            match sk {
-                "FEATURE#grouping" => {
-                    flags.grouping_enabled = enabled;
-                    if let Some(v) = item
-                        .get("similarity_threshold")
-                        .and_then(|v| v.as_n().ok())
-                        .and_then(|n| n.parse::<f64>().ok())
-                    {
-                        flags.grouping_threshold = v;
-                    }
-                }
-                "FEATURE#ogp_enrichment" => {
-                    flags.ogp_enrichment_enabled = enabled;
-                }
-                _ => {}
-            }
+        if !stored.is_empty() {
+            return Ok(stored);
         }
-
-        Ok(flags)
+        let defaults = default_categories();
+        for category in &defaults {
+            self.put_category(category).await?;
+        }
+        info!(count = defaults.len(), "Default categories stored");
+        Ok(defaults)
     }
+}
 
-    /// Get full service config.
-    pub async fn get_service_config(&self) -> Result<ServiceConfig> {
-        let feeds = self.get_all_feeds().await?;
-        let features = self.get_feature_flags().await?;
-        Ok(ServiceConfig { feeds, features })
+#[cfg(feature = "dynamo")]
+#[async_trait]
+impl FeedStorage for ConfigStore {
+    /// Includes disabled feeds.
+    async fn get_all_feeds(&self) -> Result<Vec<DynamicFeed>> {
+        let items = self.query_prefix("FEEDS#").await?;
+        Ok(items.iter().filter_map(item_to_feed).collect())
     }
 
-    /// Add or update a feed in ConfigTable.
-    pub async fn put_feed(&self, feed: &DynamicFeed) -> Result<()> {
+    async fn put_feed(&self, feed: &DynamicFeed) -> Result<()> {
         let mut item: HashMap<String, AttributeValue> = HashMap::new();
         item.insert("pk".into(), AttributeValue::S(PK_CONFIG.into()));
         item.insert(
@@ -176,6 +235,38 @@ impl ConfigStore {
         if let Some(ref added_by) = feed.added_by {
             item.insert("added_by".into(), AttributeValue::S(added_by.clone()));
         }
+        if let Some(secs) = feed.poll_interval_secs {
+            item.insert(
+                "poll_interval_secs".into(),
+                AttributeValue::N(secs.to_string()),
+            );
+        }
+        if let Some(ref reason) = feed.quarantine_reason {
+            item.insert(
+                "quarantine_reason".into(),
+                AttributeValue::S(reason.clone()),
+            );
+        }
+        item.insert(
+            "content_policy".into(),
+            AttributeValue::S(feed.content_policy.as_str().into()),
+        );
+        item.insert(
+            "source_type".into(),
+            AttributeValue::S(feed.source_type.as_str().into()),
+        );
+        if let Some(ref rules) = feed.scrape {
+            item.insert(
+                "scrape_rules".into(),
+                AttributeValue::S(serde_json::to_string(rules).map_err(AppError::SerdeError)?),
+            );
+        }
+        if let Some(ref filters) = feed.filters {
+            item.insert(
+                "filters".into(),
+                AttributeValue::S(serde_json::to_string(filters).map_err(AppError::SerdeError)?),
+            );
+        }
 
         self.client
             .put_item()
@@ -189,8 +280,7 @@ impl ConfigStore {
         Ok(())
     }
 
-    /// Delete a feed from ConfigTable.
-    pub async fn delete_feed(&self, feed_id: &str) -> Result<()> {
+    async fn delete_feed(&self, feed_id: &str) -> Result<()> {
         self.client
             .delete_item()
             .table_name(&self.table_name)
@@ -203,13 +293,45 @@ impl ConfigStore {
         info!(feed_id = %feed_id, "Feed deleted from config");
         Ok(())
     }
+}
+
+#[cfg(feature = "dynamo")]
+#[async_trait]
+impl FeatureStorage for ConfigStore {
+    async fn get_feature_flags(&self) -> Result<FeatureFlags> {
+        let mut flags = FeatureFlags::default();
+
+        let items = self.query_prefix("FEATURE#").await?;
 
-    /// Set a feature flag.
-    pub async fn set_feature_flag(
+        for item in items {
+            let Some(feature) = item
+                .get("sk")
+                .and_then(|v| v.as_s().ok())
+                .and_then(|sk| sk.strip_prefix("FEATURE#"))
+            else {
+                continue;
+            };
+            let enabled = item
+                .get("enabled")
+                .and_then(|v| v.as_bool().ok().copied())
+                .unwrap_or(false);
+            // The feature's settings are the item's other attributes
+            let extra: serde_json::Map<String, serde_json::Value> = item
+                .iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "pk" | "sk" | "enabled"))
+                .filter_map(|(key, value)| Some((key.clone(), attribute_to_json(value)?)))
+                .collect();
+            flags.set(feature, enabled, Some(&serde_json::Value::Object(extra)));
+        }
+
+        Ok(flags)
+    }
+
+    async fn set_feature_flag(
         &self,
         feature: &str,
         enabled: bool,
-        extra: Option<HashMap<String, AttributeValue>>,
+        extra: Option<&serde_json::Value>,
     ) -> Result<()> {
         let mut item: HashMap<String, AttributeValue> = HashMap::new();
         item.insert("pk".into(), AttributeValue::S(PK_CONFIG.into()));
@@ -219,8 +341,10 @@ impl ConfigStore {
         );
         item.insert("enabled".into(), AttributeValue::Bool(enabled));
 
-        if let Some(extra_attrs) = extra {
-            item.extend(extra_attrs);
+        if let Some(serde_json::Value::Object(extra)) = extra {
+            for (key, value) in extra {
+                item.insert(key.clone(), json_to_attribute(value));
+            }
         }
 
         self.client
@@ -236,6 +360,134 @@ impl ConfigStore {
     }
 }
 
+#[cfg(feature = "dynamo")]
+#[async_trait]
+impl CategoryStorage for ConfigStore {
+    /// The defaults until a category is stored.
+    async fn get_categories(&self) -> Result<Vec<CategoryRecord>> {
+        let mut categories: Vec<CategoryRecord> = self
+            .query_prefix("CATEGORY#")
+            .await?
+            .iter()
+            .filter_map(item_to_category)
+            .collect();
+        if categories.is_empty() {
+            return Ok(default_categories());
+        }
+        categories.sort_by(|a, b| a.sort_order.cmp(&b.sort_order).then_with(|| a.id.cmp(&b.id)));
+        Ok(categories)
+    }
+
+    async fn put_category(&self, category: &CategoryRecord) -> Result<()> {
+        let mut item: HashMap<String, AttributeValue> = HashMap::new();
+        item.insert("pk".into(), AttributeValue::S(PK_CONFIG.into()));
+        item.insert("sk".into(), AttributeValue::S(format!("CATEGORY#{}", category.id)));
+        item.insert("category_id".into(), AttributeValue::S(category.id.clone()));
+        item.insert("label_ja".into(), AttributeValue::S(category.label_ja.clone()));
+        item.insert("label_en".into(), AttributeValue::S(category.label_en.clone()));
+        item.insert("sort_order".into(), AttributeValue::N(category.sort_order.to_string()));
+        item.insert("visible".into(), AttributeValue::Bool(category.visible));
+
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(item))
+            .send()
+            .await
+            .map_err(|e| AppError::DynamoError(e.into_service_error().to_string()))?;
+
+        info!(id = %category.id, label_ja = %category.label_ja, "Category saved");
+        Ok(())
+    }
+
+    async fn rename_category(&self, id: &str, label_ja: &str) -> Result<()> {
+        let category = self
+            .stored_categories()
+            .await?
+            .into_iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("category {id}")))?;
+        self.put_category(&CategoryRecord {
+            label_ja: label_ja.to_string(),
+            ..category
+        })
+        .await
+    }
+
+    async fn delete_category(&self, id: &str) -> Result<()> {
+        self.stored_categories().await?;
+        self.client
+            .delete_item()
+            .table_name(&self.table_name)
+            .key("pk", AttributeValue::S(PK_CONFIG.into()))
+            .key("sk", AttributeValue::S(format!("CATEGORY#{}", id)))
+            .send()
+            .await
+            .map_err(|e| AppError::DynamoError(e.into_service_error().to_string()))?;
+
+        info!(id, "Category deleted");
+        Ok(())
+    }
+
+    async fn reorder_categories(&self, order: &[String]) -> Result<()> {
+        let categories = self.stored_categories().await?;
+        for (i, id) in order.iter().enumerate() {
+            if let Some(category) = categories.iter().find(|c| c.id == *id) {
+                self.put_category(&CategoryRecord {
+                    sort_order: i as i32,
+                    ..category.clone()
+                })
+                .await?;
+            }
+        }
+        info!(count = order.len(), "Categories reordered");
+        Ok(())
+    }
+}
+
+/// A feature setting written by [`json_to_attribute`], read back.
+#[cfg(feature = "dynamo")]
+fn attribute_to_json(value: &AttributeValue) -> Option<serde_json::Value> {
+    match value {
+        AttributeValue::N(n) => n.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(Into::into),
+        AttributeValue::Bool(b) => Some((*b).into()),
+        AttributeValue::S(s) => Some(s.clone().into()),
+        _ => None,
+    }
+}
+
+/// Feature settings are stored as top-level attributes, numbers as `N`.
+#[cfg(feature = "dynamo")]
+fn json_to_attribute(value: &serde_json::Value) -> AttributeValue {
+    match value {
+        serde_json::Value::Number(n) => AttributeValue::N(n.to_string()),
+        serde_json::Value::Bool(b) => AttributeValue::Bool(*b),
+        serde_json::Value::String(s) => AttributeValue::S(s.clone()),
+        other => AttributeValue::S(other.to_string()),
+    }
+}
+
+#[cfg(feature = "dynamo")]
+fn item_to_category(item: &HashMap<String, AttributeValue>) -> Option<CategoryRecord> {
+    Some(CategoryRecord {
+        id: item.get("category_id")?.as_s().ok()?.clone(),
+        label_ja: item.get("label_ja")?.as_s().ok()?.clone(),
+        label_en: item
+            .get("label_en")
+            .and_then(|v| v.as_s().ok().cloned())
+            .unwrap_or_default(),
+        sort_order: item
+            .get("sort_order")
+            .and_then(|v| v.as_n().ok())
+            .and_then(|n| n.parse().ok())
+            .unwrap_or_default(),
+        visible: item
+            .get("visible")
+            .and_then(|v| v.as_bool().ok().copied())
+            .unwrap_or(true),
+    })
+}
+
 #[cfg(feature = "dynamo")]
 fn item_to_feed(item: &HashMap<String, AttributeValue>) -> Option<DynamicFeed> {
     let feed_id = item.get("feed_id")?.as_s().ok()?.clone();
@@ -249,6 +501,31 @@ fn item_to_feed(item: &HashMap<String, AttributeValue>) -> Option<DynamicFeed> {
     let added_by = item
         .get("added_by")
         .and_then(|v| v.as_s().ok().cloned());
+    let poll_interval_secs = item
+        .get("poll_interval_secs")
+        .and_then(|v| v.as_n().ok())
+        .and_then(|n| n.parse::<u64>().ok());
+    let quarantine_reason = item
+        .get("quarantine_reason")
+        .and_then(|v| v.as_s().ok().cloned());
+    let content_policy = item
+        .get("content_policy")
+        .and_then(|v| v.as_s().ok())
+        .and_then(|s| ContentPolicy::from_str(s))
+        .unwrap_or_default();
+    let source_type = item
+        .get("source_type")
+        .and_then(|v| v.as_s().ok())
+        .and_then(|s| SourceType::from_str(s))
+        .unwrap_or_default();
+    let scrape = item
+        .get("scrape_rules")
+        .and_then(|v| v.as_s().ok())
+        .and_then(|s| serde_json::from_str(s).ok());
+    let filters = item
+        .get("filters")
+        .and_then(|v| v.as_s().ok())
+        .and_then(|s| serde_json::from_str(s).ok());
 
     Some(DynamicFeed {
         feed_id,
@@ -257,6 +534,12 @@ fn item_to_feed(item: &HashMap<String, AttributeValue>) -> Option<DynamicFeed> {
         category,
         enabled,
         added_by,
+        poll_interval_secs,
+        quarantine_reason,
+        content_policy,
+        source_type,
+        scrape,
+        filters,
     })
 }
 
@@ -270,6 +553,12 @@ mod tests {
         assert!(!flags.grouping_enabled);
         assert!(flags.ogp_enrichment_enabled);
         assert!((flags.grouping_threshold - 0.3).abs() < f64::EPSILON);
+        assert!(flags.auto_quarantine_enabled);
+        assert!(flags.websub_enabled);
+        assert_eq!(
+            flags.quarantine_after_failures,
+            crate::health::DEFAULT_QUARANTINE_AFTER
+        );
     }
 
     #[test]
@@ -281,12 +570,28 @@ mod tests {
             category: "tech".into(),
             enabled: true,
             added_by: Some("admin".into()),
+            poll_interval_secs: None,
+            quarantine_reason: None,
+            content_policy: ContentPolicy::MetadataOnly,
+            source_type: SourceType::JsonFeed,
+            scrape: None,
+            filters: None,
         };
         let json = serde_json::to_string(&feed).unwrap();
         let parsed: DynamicFeed = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.feed_id, "test-1");
         assert_eq!(parsed.source, "Example");
         assert!(parsed.enabled);
+        assert_eq!(parsed.content_policy, ContentPolicy::MetadataOnly);
+        assert_eq!(parsed.source_type, SourceType::JsonFeed);
+
+        // Feeds stored before the policy existed default to full text
+        let legacy: DynamicFeed = serde_json::from_str(
+            r#"{"feed_id":"x","url":"u","source":"s","category":"c","enabled":true}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.content_policy, ContentPolicy::FullText);
+        assert_eq!(legacy.source_type, SourceType::Rss);
     }
 
     #[test]
@@ -299,6 +604,12 @@ mod tests {
                 category: "general".into(),
                 enabled: true,
                 added_by: None,
+                poll_interval_secs: None,
+                quarantine_reason: None,
+                content_policy: ContentPolicy::default(),
+                source_type: SourceType::default(),
+                scrape: None,
+                filters: None,
             }],
             features: FeatureFlags::default(),
         };
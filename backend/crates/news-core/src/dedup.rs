@@ -1,3 +1,6 @@
+use crate::models::Article;
+use regex::Regex;
+use serde::Deserialize;
 use url::Url;
 use uuid::Uuid;
 
@@ -7,6 +10,16 @@ const URL_NAMESPACE: Uuid = Uuid::from_bytes([
     0xc8,
 ]);
 
+/// Namespace UUID for title+source fingerprints.
+const FINGERPRINT_NAMESPACE: Uuid = Uuid::from_bytes([
+    0x3f, 0x1c, 0x52, 0x0e, 0x8b, 0x4a, 0x4d, 0x27, 0x9a, 0x61, 0x2e, 0x05, 0xc7, 0x90, 0x1d,
+    0x6b,
+]);
+
+/// Articles with the same fingerprint published within this many hours of each other are
+/// treated as the same article.
+pub const FINGERPRINT_WINDOW_HOURS: i64 = 12;
+
 /// Tracking query parameters to strip before normalization.
 const TRACKING_PARAMS: &[&str] = &[
     "utm_source",
@@ -19,39 +32,282 @@ const TRACKING_PARAMS: &[&str] = &[
     "gclid",
     "mc_cid",
     "mc_eid",
+    "ocid",
+    "cmpid",
+    "amp",
+    "_amp",
+    "outputtype",
 ];
 
-/// Normalize a URL by removing tracking parameters and fragments,
-/// then generate a deterministic UUID v5 from the normalized URL.
+/// Subdomains that serve the same content as the bare host.
+const ALIAS_SUBDOMAINS: &[&str] = &["www.", "m.", "mobile.", "amp."];
+
+/// A per-domain URL rewrite rule, configured under `[[url_rules]]` in feeds.toml.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UrlRule {
+    /// Host the rule applies to (after built-in normalization); subdomains match too.
+    pub host: String,
+    /// Replace the host, e.g. a publisher-specific mobile host with the desktop one.
+    #[serde(default)]
+    pub canonical_host: Option<String>,
+    /// Extra query parameters to drop.
+    #[serde(default)]
+    pub strip_params: Vec<String>,
+    /// If set, drop every query parameter not listed.
+    #[serde(default)]
+    pub keep_params: Option<Vec<String>>,
+    /// Regex applied to the path, with `path_replacement` (`$1` etc.).
+    #[serde(default)]
+    pub path_pattern: Option<String>,
+    #[serde(default)]
+    pub path_replacement: Option<String>,
+}
+
+/// Rewrites URLs to a canonical form so the same article reached through AMP,
+/// mobile, `www.` or `http` variants gets one ID.
+#[derive(Debug, Clone, Default)]
+pub struct Canonicalizer {
+    rules: Vec<(UrlRule, Option<Regex>)>,
+}
+
+impl Canonicalizer {
+    /// Build with per-domain rules. Rules with an invalid `path_pattern` keep their other effects.
+    pub fn new(rules: Vec<UrlRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .map(|r| {
+                let re = r.path_pattern.as_deref().and_then(|p| Regex::new(p).ok());
+                (r, re)
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Canonical form of `raw`. Unparseable input is returned unchanged.
+    pub fn canonical_url(&self, raw: &str) -> String {
+        let Ok(mut parsed) = Url::parse(raw.trim()) else {
+            return raw.to_string();
+        };
+        if let Some(inner) = unwrap_amp_cache(&parsed) {
+            parsed = inner;
+        }
+        if parsed.scheme() == "http" {
+            let _ = parsed.set_scheme("https");
+        }
+        parsed.set_fragment(None);
+
+        if let Some(host) = parsed.host_str().map(str::to_string) {
+            let mut host = host.as_str();
+            for prefix in ALIAS_SUBDOMAINS {
+                if let Some(rest) = host.strip_prefix(prefix) {
+                    if rest.contains('.') {
+                        host = rest;
+                    }
+                }
+            }
+            let host = host.to_string();
+            let _ = parsed.set_host(Some(&host));
+        }
+
+        let mut path = strip_amp_path(parsed.path());
+        if path.len() > 1 && path.ends_with('/') {
+            path.pop();
+        }
+        parsed.set_path(&path);
+
+        let rule = parsed
+            .host_str()
+            .and_then(|h| {
+                self.rules
+                    .iter()
+                    .find(|(r, _)| h == r.host || h.ends_with(&format!(".{}", r.host)))
+            });
+
+        if let Some((rule, re)) = rule {
+            if let Some(ref host) = rule.canonical_host {
+                let _ = parsed.set_host(Some(host));
+            }
+            if let (Some(re), Some(rep)) = (re, rule.path_replacement.as_deref()) {
+                let rewritten = re.replace(parsed.path(), rep).into_owned();
+                parsed.set_path(&rewritten);
+            }
+        }
+
+        let mut params: Vec<(String, String)> = parsed
+            .query_pairs()
+            .filter(|(key, _)| {
+                let key = key.to_ascii_lowercase();
+                !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_str())
+            })
+            .filter(|(key, _)| match rule {
+                Some((r, _)) => {
+                    !r.strip_params.iter().any(|p| p == key)
+                        && r.keep_params.as_ref().is_none_or(|keep| keep.iter().any(|p| p == key))
+                }
+                None => true,
+            })
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        params.sort();
+
+        if params.is_empty() {
+            parsed.set_query(None);
+        } else {
+            let qs: Vec<String> = params.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            parsed.set_query(Some(&qs.join("&")));
+        }
+
+        parsed.to_string()
+    }
+
+    /// Deterministic article ID for the canonical form of `raw`.
+    pub fn article_id(&self, raw: &str) -> String {
+        Uuid::new_v5(&URL_NAMESPACE, self.canonical_url(raw).as_bytes()).to_string()
+    }
+
+    /// Recompute an article's ID from its canonical URL. The article keeps the URL the
+    /// feed gave, which is the one readers are sent to; the canonical form is a guess
+    /// and may not exist.
+    pub fn apply(&self, article: &mut Article) {
+        article.id = self.article_id(&article.url);
+    }
+}
+
+/// Generate a deterministic UUID v5 from the canonical URL, using only the built-in rules.
 pub fn article_id_from_url(raw_url: &str) -> String {
-    let normalized = normalize_url(raw_url);
-    Uuid::new_v5(&URL_NAMESPACE, normalized.as_bytes()).to_string()
+    Canonicalizer::default().article_id(raw_url)
 }
 
-/// Strip tracking params, fragments, and normalize scheme/host to lowercase.
-fn normalize_url(raw: &str) -> String {
-    let Ok(mut parsed) = Url::parse(raw) else {
-        return raw.to_string();
+/// The ID articles got before URLs were canonicalized: only fragments and the original
+/// tracking parameters were dropped. Stores use it to recognize articles saved under
+/// the old ID.
+pub fn legacy_article_id(raw_url: &str) -> String {
+    const LEGACY_TRACKING_PARAMS: &[&str] = &[
+        "utm_source",
+        "utm_medium",
+        "utm_campaign",
+        "utm_term",
+        "utm_content",
+        "ref",
+        "fbclid",
+        "gclid",
+        "mc_cid",
+        "mc_eid",
+    ];
+    let normalized = match Url::parse(raw_url) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+            let filtered: Vec<String> = parsed
+                .query_pairs()
+                .filter(|(key, _)| !LEGACY_TRACKING_PARAMS.contains(&key.as_ref()))
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect();
+            if filtered.is_empty() {
+                parsed.set_query(None);
+            } else {
+                parsed.set_query(Some(&filtered.join("&")));
+            }
+            parsed.to_string()
+        }
+        Err(_) => raw_url.to_string(),
     };
+    Uuid::new_v5(&URL_NAMESPACE, normalized.as_bytes()).to_string()
+}
 
-    // Remove fragment
-    parsed.set_fragment(None);
+/// `https://www-example-com.cdn.ampproject.org/c/s/www.example.com/a` → `https://www.example.com/a`
+fn unwrap_amp_cache(url: &Url) -> Option<Url> {
+    if !url.host_str()?.ends_with(".cdn.ampproject.org") {
+        return None;
+    }
+    let path = url.path();
+    let rest = path
+        .strip_prefix("/c/s/")
+        .or_else(|| path.strip_prefix("/v/s/"))
+        .or_else(|| path.strip_prefix("/c/"))
+        .or_else(|| path.strip_prefix("/v/"))?;
+    let mut inner = Url::parse(&format!("https://{}", rest)).ok()?;
+    inner.set_query(url.query());
+    Some(inner)
+}
 
-    // Filter out tracking query params
-    let filtered: Vec<(String, String)> = parsed
-        .query_pairs()
-        .filter(|(key, _)| !TRACKING_PARAMS.contains(&key.as_ref()))
-        .map(|(k, v)| (k.into_owned(), v.into_owned()))
-        .collect();
+/// Drop AMP markers from a path: `/amp/...`, `.../amp`, `.amp` and `.amp.html`.
+fn strip_amp_path(path: &str) -> String {
+    let mut segments: Vec<&str> = path.split('/').collect();
+    segments.retain(|s| *s != "amp");
+    let mut path = segments.join("/");
+    if path.is_empty() {
+        path.push('/');
+    }
+    if let Some(stem) = path.strip_suffix(".amp.html") {
+        path = format!("{}.html", stem);
+    } else if let Some(stem) = path.strip_suffix(".amp") {
+        path = stem.to_string();
+    }
+    path
+}
 
-    if filtered.is_empty() {
-        parsed.set_query(None);
-    } else {
-        let qs: Vec<String> = filtered.iter().map(|(k, v)| format!("{k}={v}")).collect();
-        parsed.set_query(Some(&qs.join("&")));
+/// Fingerprint of an article's normalized title and source, for spotting the same
+/// article published under unrelated URLs. Compare together with publish time
+/// (see [`FINGERPRINT_WINDOW_HOURS`]).
+pub fn fingerprint(title: &str, source: &str) -> String {
+    let key = format!("{}|{}", normalize_title(title, source), source.trim().to_lowercase());
+    Uuid::new_v5(&FINGERPRINT_NAMESPACE, key.as_bytes()).to_string()
+}
+
+/// Whether two articles with the same [`fingerprint`] are copies of one article rather
+/// than different articles that share a headline (recurring "速報" or "Daily briefing"
+/// items). The title alone isn't enough: their URLs must end in the same article number
+/// or slug, or their descriptions must agree.
+pub fn copies_agree(
+    a_url: &str,
+    a_description: Option<&str>,
+    b_url: &str,
+    b_description: Option<&str>,
+) -> bool {
+    if let (Some(a), Some(b)) = (url_article_key(a_url), url_article_key(b_url)) {
+        if a == b {
+            return true;
+        }
     }
+    let normalize = |d: Option<&str>| -> String {
+        d.unwrap_or("")
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(|c| c.to_lowercase())
+            .collect()
+    };
+    let (a, b) = (normalize(a_description), normalize(b_description));
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    // Feeds often truncate descriptions, so a long enough prefix agrees too
+    shorter.chars().count() >= 10 && longer.starts_with(&shorter)
+}
 
-    parsed.to_string()
+/// The last path segment of a URL if it looks like an article number or slug: at least
+/// six characters with a digit in it, ignoring extensions like `.html`.
+fn url_article_key(raw: &str) -> Option<String> {
+    let parsed = Url::parse(raw).ok()?;
+    let segment = parsed.path_segments()?.rev().find(|s| !s.is_empty())?;
+    let stem = segment.split('.').next().unwrap_or(segment);
+    (stem.len() >= 6 && stem.chars().any(|c| c.is_ascii_digit())).then(|| stem.to_lowercase())
+}
+
+/// Lowercase, drop a trailing " - Source"/"（Source）" attribution, and keep only
+/// letters and digits so punctuation and spacing differences don't matter.
+fn normalize_title(title: &str, source: &str) -> String {
+    let mut t = title.trim();
+    let source = source.trim();
+    if !source.is_empty() {
+        for (open, close) in [(" - ", ""), (" | ", ""), ("（", "）"), ("(", ")")] {
+            let suffix = format!("{}{}{}", open, source, close);
+            if let Some(stripped) = t.strip_suffix(&suffix) {
+                t = stripped.trim_end();
+            }
+        }
+    }
+    t.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
 }
 
 #[cfg(test)]
@@ -93,4 +349,144 @@ mod tests {
         let id2 = article_id_from_url("https://example.com/search?q=go");
         assert_ne!(id1, id2);
     }
+
+    #[test]
+    fn url_variants_share_an_id() {
+        let canonical = article_id_from_url("https://example.com/news/2024/story");
+        for variant in [
+            "http://example.com/news/2024/story",
+            "https://www.example.com/news/2024/story/",
+            "https://m.example.com/news/2024/story",
+            "https://example.com/amp/news/2024/story",
+            "https://example.com/news/2024/story/amp",
+            "https://example.com/news/2024/story?outputType=amp&utm_campaign=x",
+            "https://www-example-com.cdn.ampproject.org/c/s/www.example.com/news/2024/story",
+        ] {
+            assert_eq!(article_id_from_url(variant), canonical, "{}", variant);
+        }
+        assert_eq!(
+            Canonicalizer::default().canonical_url("https://example.com/a/story.amp.html"),
+            "https://example.com/a/story.html"
+        );
+    }
+
+    #[test]
+    fn query_order_does_not_matter() {
+        assert_eq!(
+            article_id_from_url("https://example.com/a?b=2&a=1"),
+            article_id_from_url("https://example.com/a?a=1&b=2")
+        );
+    }
+
+    #[test]
+    fn bare_two_label_host_keeps_subdomain_like_name() {
+        // "m.jp" style hosts are not stripped down to a TLD
+        assert_eq!(
+            Canonicalizer::default().canonical_url("https://www.jp/"),
+            "https://www.jp/"
+        );
+    }
+
+    #[test]
+    fn per_domain_rules() {
+        let canon = Canonicalizer::new(vec![
+            UrlRule {
+                host: "news.yahoo.co.jp".into(),
+                canonical_host: None,
+                strip_params: vec!["source".into()],
+                keep_params: None,
+                path_pattern: Some(r"^/pickup/(\d+)$".into()),
+                path_replacement: Some("/articles/p$1".into()),
+            },
+            UrlRule {
+                host: "sp.example.jp".into(),
+                canonical_host: Some("example.jp".into()),
+                strip_params: vec![],
+                keep_params: Some(vec!["id".into()]),
+                path_pattern: None,
+                path_replacement: None,
+            },
+        ]);
+        assert_eq!(
+            canon.canonical_url("https://news.yahoo.co.jp/pickup/6500000?source=rss"),
+            "https://news.yahoo.co.jp/articles/p6500000"
+        );
+        assert_eq!(
+            canon.canonical_url("http://sp.example.jp/view?id=42&from=top"),
+            "https://example.jp/view?id=42"
+        );
+    }
+
+    #[test]
+    fn apply_keeps_the_feed_url() {
+        let mut article = crate::models::Article {
+            id: String::new(),
+            category: crate::models::Category::General,
+            title: "Story".into(),
+            url: "http://www.example.com/amp/news/2024/story".into(),
+            description: None,
+            image_url: None,
+            source: "Example".into(),
+            published_at: chrono::Utc::now(),
+            fetched_at: chrono::Utc::now(),
+            group_id: None,
+            group_count: None,
+            metadata: None,
+            audio: None,
+            categories: Vec::new(),
+            lang: None,
+            translated_from: None,
+            updated_at: None,
+            headline_changed: false,
+        };
+        Canonicalizer::default().apply(&mut article);
+        assert_eq!(article.url, "http://www.example.com/amp/news/2024/story");
+        assert_eq!(article.id, article_id_from_url("https://example.com/news/2024/story"));
+    }
+
+    #[test]
+    fn legacy_ids_match_the_old_normalization() {
+        let url = "https://www.example.com/news/1?utm_source=rss&amp=1#top";
+        assert_eq!(legacy_article_id(url), legacy_article_id("https://www.example.com/news/1?amp=1"));
+        assert_ne!(legacy_article_id(url), article_id_from_url(url));
+        // Canonical URLs were already in their final form
+        let canonical = "https://example.com/news/1";
+        assert_eq!(legacy_article_id(canonical), article_id_from_url(canonical));
+    }
+
+    #[test]
+    fn copies_need_more_than_a_shared_title() {
+        // Recurring headline, different editions
+        assert!(!copies_agree(
+            "https://example.com/briefing/2024-06-01",
+            Some("Markets rally on rate hopes"),
+            "https://example.com/briefing/2024-06-02",
+            Some("Oil slips as supply grows"),
+        ));
+        assert!(!copies_agree("https://example.com/a", None, "https://example.com/b", None));
+        // Same article number on another host
+        assert!(copies_agree(
+            "https://news.example.jp/articles/20240601-123456",
+            None,
+            "https://partner.example.com/pickup/20240601-123456.html",
+            None,
+        ));
+        // Truncated copy of the same description
+        assert!(copies_agree(
+            "https://example.com/a",
+            Some("首相は記者会見で新たな経済対策を表明した。財源は"),
+            "https://other.example/b",
+            Some("首相は記者会見で新たな経済対策を表明した…"),
+        ));
+    }
+
+    #[test]
+    fn fingerprint_ignores_attribution_and_punctuation() {
+        let a = fingerprint("首相が会見、経済対策を表明 - NHK", "NHK");
+        let b = fingerprint("首相が会見 経済対策を表明", "NHK");
+        let c = fingerprint("首相が会見、経済対策を表明（NHK）", "NHK");
+        assert_eq!(a, b);
+        assert_eq!(a, c);
+        assert_ne!(a, fingerprint("首相が会見、経済対策を表明", "毎日新聞"));
+    }
 }
@@ -0,0 +1,254 @@
+use crate::error::{AppError, Result};
+use crate::feeds::MAX_FEED_BYTES;
+use crate::ogp;
+use crate::sources::{ScrapeRules, SourceType};
+use chrono::{DateTime, Utc};
+use scraper::{Html, Selector};
+use serde::Serialize;
+use std::fmt;
+use url::Url;
+
+/// Paths tried on the site's origin when a page doesn't advertise its feeds.
+pub const COMMON_FEED_PATHS: &[&str] = &[
+    "/feed",
+    "/rss",
+    "/rss.xml",
+    "/feed.xml",
+    "/atom.xml",
+    "/index.xml",
+    "/index.rdf",
+    "/feed.json",
+];
+/// Upper bound on candidate URLs fetched per discovery.
+const MAX_CANDIDATES: usize = 16;
+
+/// A URL that was fetched and parsed successfully as a source.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedCandidate {
+    pub url: String,
+    pub source_type: SourceType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub item_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_item_at: Option<DateTime<Utc>>,
+}
+
+/// Why a URL was refused as a feed, with any feeds discovered from it instead.
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidFeed {
+    pub reason: String,
+    pub candidates: Vec<FeedCandidate>,
+}
+
+impl fmt::Display for InvalidFeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a usable feed: {}", self.reason)?;
+        if !self.candidates.is_empty() {
+            let urls: Vec<&str> = self.candidates.iter().map(|c| c.url.as_str()).collect();
+            write!(f, "; feeds found on the page: {}", urls.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// `<link rel="alternate">` feeds advertised by an HTML page, resolved against `base_url`.
+pub fn alternate_links(html: &str, base_url: &str) -> Vec<(String, SourceType)> {
+    let Ok(base) = Url::parse(base_url) else {
+        return Vec::new();
+    };
+    let doc = Html::parse_document(html);
+    let sel = Selector::parse(r#"link[rel~="alternate"][href][type]"#).unwrap();
+    doc.select(&sel)
+        .filter_map(|el| {
+            let v = el.value();
+            let source_type = match v.attr("type")?.trim().to_ascii_lowercase().as_str() {
+                "application/rss+xml" | "application/atom+xml" | "application/rdf+xml" => SourceType::Rss,
+                "application/feed+json" | "application/json" => SourceType::JsonFeed,
+                _ => return None,
+            };
+            let url = base.join(v.attr("href")?.trim()).ok()?;
+            Some((url.to_string(), source_type))
+        })
+        .collect()
+}
+
+/// The body at `url`, cut off after `max_bytes`.
+async fn fetch_body(client: &reqwest::Client, url: &str, max_bytes: usize) -> Result<Vec<u8>> {
+    let mut response = client.get(url).send().await?.error_for_status()?;
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let room = max_bytes - body.len();
+        body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        if body.len() >= max_bytes {
+            break;
+        }
+    }
+    Ok(body)
+}
+
+fn candidate(url: &str, source_type: SourceType, body: &[u8], scrape: Option<&ScrapeRules>) -> Result<FeedCandidate> {
+    let parsed = source_type.parse(body, url, scrape, None)?;
+    Ok(FeedCandidate {
+        url: url.to_string(),
+        source_type,
+        title: parsed.title,
+        item_count: parsed.entries.len(),
+        latest_item_at: parsed.entries.iter().filter_map(|e| e.published_at).max(),
+    })
+}
+
+/// Parse `body` as any feed format, JSON Feed first since feed-rs would accept it too.
+fn sniff(url: &str, body: &[u8]) -> Option<FeedCandidate> {
+    [SourceType::JsonFeed, SourceType::Rss]
+        .into_iter()
+        .find_map(|t| candidate(url, t, body, None).ok())
+}
+
+/// Feeds reachable from `page_url`: the URL itself if it is a feed, else the page's
+/// alternate links and the common paths on its origin, each fetched and parsed.
+pub async fn discover_feeds(client: &reqwest::Client, page_url: &str) -> Result<Vec<FeedCandidate>> {
+    let base = Url::parse(page_url).map_err(|e| AppError::ConfigError(format!("Invalid URL: {e}")))?;
+    let body = fetch_body(client, page_url, MAX_FEED_BYTES).await?;
+    if let Some(c) = sniff(page_url, &body) {
+        return Ok(vec![c]);
+    }
+
+    let html = ogp::decode_html(&body, None);
+    let mut urls: Vec<String> = alternate_links(&html, page_url)
+        .into_iter()
+        .map(|(url, _)| url)
+        .collect();
+    for path in COMMON_FEED_PATHS {
+        if let Ok(url) = base.join(path) {
+            urls.push(url.to_string());
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    urls.retain(|u| u != page_url && seen.insert(u.clone()));
+    urls.truncate(MAX_CANDIDATES);
+
+    let probes = urls.iter().map(|url| async move {
+        let body = fetch_body(client, url, MAX_FEED_BYTES).await.ok()?;
+        sniff(url, &body)
+    });
+    Ok(futures::future::join_all(probes).await.into_iter().flatten().collect())
+}
+
+/// Check that `url` parses as `source_type` before it's stored. For feed types, a page
+/// that isn't a feed is searched for feeds so the caller can suggest them.
+pub async fn validate_feed(
+    client: &reqwest::Client,
+    url: &str,
+    source_type: SourceType,
+    scrape: Option<&ScrapeRules>,
+) -> std::result::Result<FeedCandidate, InvalidFeed> {
+    let invalid = |reason: String| InvalidFeed {
+        reason,
+        candidates: Vec::new(),
+    };
+    source_type.check_rules(scrape).map_err(|e| invalid(e.to_string()))?;
+    let body = fetch_body(client, url, MAX_FEED_BYTES)
+        .await
+        .map_err(|e| invalid(e.to_string()))?;
+    let error = match candidate(url, source_type, &body, scrape) {
+        // A listing page whose selectors match nothing is as broken as one that fails
+        Ok(c) if source_type == SourceType::Scrape && c.item_count == 0 => {
+            return Err(invalid("scrape rules matched no items".into()));
+        }
+        Ok(c) => return Ok(c),
+        Err(e) => e,
+    };
+    let candidates = if matches!(source_type, SourceType::Rss | SourceType::JsonFeed) {
+        discover_feeds(client, url).await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    Err(InvalidFeed {
+        reason: error.to_string(),
+        candidates,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{Reply, TestServer};
+
+    #[test]
+    fn finds_alternate_feed_links() {
+        let html = r#"<html><head>
+            <link rel="alternate" type="application/rss+xml" title="RSS" href="/rss/index.xml">
+            <link rel="alternate" type="application/atom+xml" href="https://example.com/atom">
+            <link rel="alternate" type="application/feed+json" href="feed.json">
+            <link rel="alternate" hreflang="en" href="/en/">
+            <link rel="stylesheet" type="text/css" href="/style.css">
+        </head></html>"#;
+        let links = alternate_links(html, "https://example.com/blog/");
+        assert_eq!(
+            links,
+            vec![
+                ("https://example.com/rss/index.xml".to_string(), SourceType::Rss),
+                ("https://example.com/atom".to_string(), SourceType::Rss),
+                ("https://example.com/blog/feed.json".to_string(), SourceType::JsonFeed),
+            ]
+        );
+    }
+
+    #[test]
+    fn sniff_labels_json_feed_and_rss() {
+        let json = br#"{"version":"https://jsonfeed.org/version/1.1","title":"J","items":[{"url":"https://e.com/1","date_published":"2024-05-01T00:00:00Z"}]}"#;
+        let c = sniff("https://e.com/feed.json", json).unwrap();
+        assert_eq!(c.source_type, SourceType::JsonFeed);
+        assert_eq!(c.title.as_deref(), Some("J"));
+        assert_eq!(c.item_count, 1);
+        assert!(c.latest_item_at.is_some());
+
+        let rss = br#"<rss version="2.0"><channel><title>R</title><item><link>https://e.com/2</link></item></channel></rss>"#;
+        let c = sniff("https://e.com/rss", rss).unwrap();
+        assert_eq!(c.source_type, SourceType::Rss);
+        assert_eq!(c.title.as_deref(), Some("R"));
+
+        assert!(sniff("https://e.com/", b"<html><body>Home</body></html>").is_none());
+    }
+
+    #[test]
+    fn invalid_feed_lists_candidates() {
+        let err = InvalidFeed {
+            reason: "unable to parse feed".into(),
+            candidates: vec![FeedCandidate {
+                url: "https://e.com/rss".into(),
+                source_type: SourceType::Rss,
+                title: None,
+                item_count: 3,
+                latest_item_at: None,
+            }],
+        };
+        assert_eq!(
+            err.to_string(),
+            "not a usable feed: unable to parse feed; feeds found on the page: https://e.com/rss"
+        );
+    }
+
+    #[tokio::test]
+    async fn discovers_advertised_feed_and_caps_bodies() {
+        let rss = r#"<rss version="2.0"><channel><title>Blog</title><item><link>https://e.com/1</link></item></channel></rss>"#;
+        let server = TestServer::start(vec![
+            (
+                "/",
+                Reply::ok("text/html", r#"<html><head><link rel="alternate" type="application/rss+xml" href="/blog.rss"></head></html>"#),
+            ),
+            ("/blog.rss", Reply::ok("application/rss+xml", rss)),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+
+        let found = discover_feeds(&client, &server.url("/")).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].url, server.url("/blog.rss"));
+        assert_eq!(found[0].title.as_deref(), Some("Blog"));
+
+        let body = fetch_body(&client, &server.url("/blog.rss"), 16).await.unwrap();
+        assert_eq!(body, &rss.as_bytes()[..16]);
+    }
+}
@@ -1,5 +1,8 @@
+use crate::dedup;
 use crate::error::{AppError, Result};
-use crate::models::{Article, Category};
+use crate::models::{Article, Category, PageMetadata};
+use crate::store::ArticleStorage;
+use async_trait::async_trait;
 use aws_sdk_dynamodb::types::AttributeValue;
 use aws_sdk_dynamodb::Client;
 use chrono::{DateTime, Duration, Utc};
@@ -30,8 +33,37 @@ impl ArticleStore {
         )
     }
 
-    /// Put an article with idempotent write (attribute_not_exists).
+    /// Whether the article is already stored under the ID it had before IDs came from
+    /// canonical URLs. Those items expire after [`TTL_DAYS`], after which this only
+    /// costs a read for URLs whose ID changed.
+    async fn stored_under_legacy_id(&self, article: &Article) -> Result<bool> {
+        let legacy_id = dedup::legacy_article_id(&article.url);
+        if legacy_id == article.id {
+            return Ok(false);
+        }
+        let legacy = Article {
+            id: legacy_id,
+            ..article.clone()
+        };
+        let item = self
+            .client
+            .get_item()
+            .table_name(&self.table_name)
+            .key("category", AttributeValue::S(article.category.to_string()))
+            .key("sk", AttributeValue::S(Self::sort_key(&legacy)))
+            .projection_expression("sk")
+            .send()
+            .await
+            .map_err(|e| AppError::DynamoError(e.into_service_error().to_string()))?;
+        Ok(item.item.is_some())
+    }
+
+    /// Put an article with idempotent write (attribute_not_exists). Articles already
+    /// stored under their legacy ID are skipped.
     pub async fn put_article(&self, article: &Article) -> Result<bool> {
+        if self.stored_under_legacy_id(article).await? {
+            return Ok(false);
+        }
         let sk = Self::sort_key(article);
         let ttl = (Utc::now() + Duration::days(TTL_DAYS)).timestamp();
 
@@ -59,6 +91,20 @@ impl ArticleStore {
         if let Some(ref img) = article.image_url {
             item.insert("image_url".into(), AttributeValue::S(img.clone()));
         }
+        if let Some(json) = article.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok()) {
+            item.insert("metadata".into(), AttributeValue::S(json));
+        }
+        if let Some(json) = article.audio.as_ref().and_then(|a| serde_json::to_string(a).ok()) {
+            item.insert("audio".into(), AttributeValue::S(json));
+        }
+        if let Some(ref lang) = article.lang {
+            item.insert("lang".into(), AttributeValue::S(lang.clone()));
+        }
+        if !article.categories.is_empty() {
+            if let Ok(json) = serde_json::to_string(&article.categories) {
+                item.insert("categories".into(), AttributeValue::S(json));
+            }
+        }
 
         let result = self
             .client
@@ -82,20 +128,6 @@ impl ArticleStore {
         }
     }
 
-    /// Batch put articles, returning count of newly inserted.
-    pub async fn put_articles(&self, articles: &[Article]) -> Result<usize> {
-        let mut inserted = 0;
-        for article in articles {
-            match self.put_article(article).await {
-                Ok(true) => inserted += 1,
-                Ok(false) => {} // duplicate
-                Err(e) => warn!(article_id = %article.id, error = %e, "Failed to put article"),
-            }
-        }
-        info!(total = articles.len(), inserted, "Batch put complete");
-        Ok(inserted)
-    }
-
     /// Update the image_url of an article.
     pub async fn update_image_url(
         &self,
@@ -115,12 +147,57 @@ impl ArticleStore {
             .map_err(|e| AppError::DynamoError(e.into_service_error().to_string()))?;
         Ok(())
     }
+}
+
+#[async_trait]
+impl ArticleStorage for ArticleStore {
+    async fn put_articles(&self, articles: &[Article]) -> Result<usize> {
+        let mut inserted = 0;
+        for article in articles {
+            match self.put_article(article).await {
+                Ok(true) => inserted += 1,
+                Ok(false) => {} // duplicate
+                Err(e) => warn!(article_id = %article.id, error = %e, "Failed to put article"),
+            }
+        }
+        info!(total = articles.len(), inserted, "Batch put complete");
+        Ok(inserted)
+    }
+
+    async fn update_page_metadata(&self, article: &Article, metadata: &PageMetadata) -> Result<()> {
+        let json = serde_json::to_string(metadata).map_err(|e| AppError::ParseError(e.to_string()))?;
+        let mut update = self
+            .client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("category", AttributeValue::S(article.category.to_string()))
+            .key("sk", AttributeValue::S(Self::sort_key(article)))
+            .expression_attribute_values(":meta", AttributeValue::S(json));
+        let mut set = vec!["metadata = :meta".to_string()];
+        if let Some(ref img) = metadata.image_url {
+            set.push("image_url = if_not_exists(image_url, :img)".into());
+            update = update.expression_attribute_values(":img", AttributeValue::S(img.clone()));
+        }
+        if let Some(ref desc) = metadata.description {
+            set.push("description = if_not_exists(description, :desc)".into());
+            update = update.expression_attribute_values(":desc", AttributeValue::S(desc.clone()));
+        }
+        update
+            .update_expression(format!("SET {}", set.join(", ")))
+            .send()
+            .await
+            .map_err(|e| AppError::DynamoError(e.into_service_error().to_string()))?;
+        Ok(())
+    }
 
-    /// Query articles by category, newest first, with cursor-based pagination.
-    pub async fn query_articles(
+    /// The cursor is the base64 `last_evaluated_key` of the previous page. The table has
+    /// no translations, so `lang` keeps articles in that language or of unknown
+    /// language; it is a filter applied after `limit`, so a page can come back short.
+    async fn query_articles(
         &self,
         category: Option<&Category>,
-        limit: i32,
+        lang: Option<&str>,
+        limit: usize,
         cursor: Option<&str>,
     ) -> Result<(Vec<Article>, Option<String>)> {
         let (pk_name, pk_value) = match category {
@@ -142,11 +219,16 @@ impl ArticleStore {
             .expression_attribute_names("#pk", pk_name)
             .expression_attribute_values(":pk", AttributeValue::S(pk_value))
             .scan_index_forward(false) // newest first
-            .limit(limit);
+            .limit(limit as i32);
 
         if let Some(idx) = index_name {
             query = query.index_name(idx);
         }
+        if let Some(lang) = lang {
+            query = query
+                .filter_expression("attribute_not_exists(lang) OR lang = :lang")
+                .expression_attribute_values(":lang", AttributeValue::S(lang.to_string()));
+        }
 
         if let Some(cursor_str) = cursor {
             if let Some(start_key) = decode_cursor(cursor_str) {
@@ -193,6 +275,20 @@ fn item_to_article(item: &HashMap<String, AttributeValue>) -> Option<Article> {
         .get("description")
         .and_then(|v| v.as_s().ok().cloned());
     let image_url = item.get("image_url").and_then(|v| v.as_s().ok().cloned());
+    let metadata = item
+        .get("metadata")
+        .and_then(|v| v.as_s().ok())
+        .and_then(|json| serde_json::from_str(json).ok());
+    let audio = item
+        .get("audio")
+        .and_then(|v| v.as_s().ok())
+        .and_then(|json| serde_json::from_str(json).ok());
+    let categories = item
+        .get("categories")
+        .and_then(|v| v.as_s().ok())
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+    let lang = item.get("lang").and_then(|v| v.as_s().ok().cloned());
 
     Some(Article {
         id,
@@ -206,6 +302,13 @@ fn item_to_article(item: &HashMap<String, AttributeValue>) -> Option<Article> {
         fetched_at,
         group_id: None,
         group_count: None,
+        metadata,
+        audio,
+        categories,
+        lang,
+        translated_from: None,
+        updated_at: None,
+        headline_changed: false,
     })
 }
 
@@ -14,6 +14,12 @@ pub enum AppError {
     #[error("Database error: {0}")]
     DbError(String),
 
+    #[error("Fetch not allowed: {0}")]
+    Disallowed(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Config error: {0}")]
     ConfigError(String),
 
@@ -1,21 +1,147 @@
-use crate::dedup::article_id_from_url;
+use crate::categorize;
+use crate::dedup::{article_id_from_url, UrlRule};
 use crate::error::{AppError, Result};
+use crate::filters::{CompiledFilters, FeedFilters};
+use crate::health::{FetchErrorClass, ITEM_COUNT_HISTORY};
+use crate::lang;
 use crate::models::{Article, Category};
+use crate::politeness::{PoliteClient, PoliteResponse};
+use crate::schedule;
+use crate::sources::{ParsedSource, ScrapeRules, SourceType};
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
 use tracing::{info, warn};
 
+/// Weight of the newest sample in the exponential moving average of fetch latency.
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+/// Largest feed or listing page read; anything beyond is cut off.
+pub const MAX_FEED_BYTES: usize = 10 * 1024 * 1024;
+
 /// Feed configuration loaded from feeds.toml.
 #[derive(Debug, Deserialize, Clone)]
 pub struct FeedConfig {
+    /// ID of the backing `DynamicFeed`, if any. Fetch state is only persisted for feeds with an ID.
+    #[serde(default)]
+    pub feed_id: Option<String>,
     pub url: String,
     pub source: String,
     pub category: String,
+    /// Admin override of the adaptive poll interval, in seconds.
+    #[serde(default)]
+    pub poll_interval_secs: Option<u64>,
+    #[serde(default)]
+    pub source_type: SourceType,
+    /// Selectors for `scrape` sources.
+    #[serde(default)]
+    pub scrape: Option<ScrapeRules>,
+    /// Include/exclude rules applied to fetched items.
+    #[serde(default)]
+    pub filters: Option<FeedFilters>,
+}
+
+/// Per-feed HTTP caching validators and health counters, kept between fetch cycles.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FetchState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// HTTP status of the last response (None if the request never got one).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_latency_ms: Option<f64>,
+    /// When the scheduler should poll this feed next.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_poll_at: Option<DateTime<Utc>>,
+    /// Observed median gap between published items, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_interval_secs: Option<u64>,
+    /// Publisher's `<ttl>`/`sy:updatePeriod` hint, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_hint_secs: Option<u64>,
+    /// Class and message of the last failure; cleared on success.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error_class: Option<FetchErrorClass>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Item counts of the most recent parsed fetches, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_item_counts: Vec<u32>,
+    /// Publish time of the newest item seen.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_item_at: Option<DateTime<Utc>>,
+    /// WebSub hub the feed advertised on its last parse.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub websub_hub: Option<String>,
+    /// Topic URL to subscribe to at the hub: the feed's `rel="self"` link, else its URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub websub_topic: Option<String>,
+    /// Items the feed's filters dropped from the last parsed fetch.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub last_filtered_count: u32,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+impl FetchState {
+    /// True if the last fetch returned 304 Not Modified.
+    pub fn not_modified(&self) -> bool {
+        self.last_status == Some(StatusCode::NOT_MODIFIED.as_u16())
+    }
+
+    /// Record a successful (2xx or 304) response and its latency.
+    pub fn record_success(&mut self, status: u16, latency_ms: f64, at: DateTime<Utc>) {
+        self.last_status = Some(status);
+        self.last_success_at = Some(at);
+        self.consecutive_failures = 0;
+        self.last_error_class = None;
+        self.last_error = None;
+        self.record_latency(latency_ms);
+    }
+
+    /// Record a failed fetch. `status` is None for transport errors (DNS, timeout, ...).
+    pub fn record_failure(&mut self, status: Option<u16>, error: &AppError) {
+        self.last_status = status;
+        self.last_error_class = Some(FetchErrorClass::classify(error));
+        self.last_error = Some(error.to_string());
+        self.consecutive_failures += 1;
+    }
+
+    /// Record how many items a parsed fetch returned and the newest publish time.
+    pub fn record_items(&mut self, count: usize, latest: Option<DateTime<Utc>>) {
+        self.recent_item_counts.push(count as u32);
+        if self.recent_item_counts.len() > ITEM_COUNT_HISTORY {
+            let excess = self.recent_item_counts.len() - ITEM_COUNT_HISTORY;
+            self.recent_item_counts.drain(..excess);
+        }
+        if latest > self.latest_item_at {
+            self.latest_item_at = latest;
+        }
+    }
+
+    fn record_latency(&mut self, latency_ms: f64) {
+        self.avg_latency_ms = Some(match self.avg_latency_ms {
+            Some(avg) => avg + LATENCY_EWMA_ALPHA * (latency_ms - avg),
+            None => latency_ms,
+        });
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct FeedsConfig {
     pub feeds: Vec<FeedConfig>,
+    /// Per-domain URL canonicalization rules (`[[url_rules]]`).
+    #[serde(default)]
+    pub url_rules: Vec<UrlRule>,
 }
 
 impl FeedsConfig {
@@ -24,86 +150,206 @@ impl FeedsConfig {
     }
 }
 
-/// Fetch and parse a single RSS/Atom feed into articles.
-pub async fn fetch_feed(client: &reqwest::Client, feed: &FeedConfig) -> Result<Vec<Article>> {
-    let category = Category::from_str(&feed.category)
-        .ok_or_else(|| AppError::ConfigError(format!("Unknown category: {}", feed.category)))?;
+/// Fetch a single source and parse it according to its `source_type` into articles.
+///
+/// Sends `If-None-Match`/`If-Modified-Since` from `state` and updates it with the
+/// response. A 304 Not Modified yields an empty list without parsing. Items the
+/// feed's filters reject are dropped and counted in `state`. Scrape sources read
+/// ordinary publisher pages, so they go through the client's robots.txt and rate
+/// limit checks; feeds are offered for polling and are fetched directly.
+pub async fn fetch_feed(
+    client: &PoliteClient,
+    feed: &FeedConfig,
+    state: &mut FetchState,
+) -> Result<Vec<Article>> {
+    let category = feed_category(feed)?;
+    let filters = compile_filters(feed);
 
     info!(url = %feed.url, source = %feed.source, "Fetching feed");
 
-    let response = client.get(&feed.url).send().await?;
-    let bytes = response.bytes().await?;
+    let mut headers = HeaderMap::new();
+    let validators = [(IF_NONE_MATCH, &state.etag), (IF_MODIFIED_SINCE, &state.last_modified)];
+    for (name, value) in validators {
+        if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(name, value);
+        }
+    }
 
-    let parsed =
-        feed_rs::parser::parse(&bytes[..]).map_err(|e| AppError::ParseError(e.to_string()))?;
+    let started = Instant::now();
+    let sent = if feed.source_type == SourceType::Scrape {
+        client.get_with_headers(&feed.url, headers).await
+    } else {
+        client
+            .http()
+            .get(&feed.url)
+            .headers(headers)
+            .send()
+            .await
+            .map(PoliteResponse::from)
+            .map_err(AppError::from)
+    };
+    let response = match sent {
+        Ok(r) => r,
+        Err(e) => {
+            let status = match &e {
+                AppError::FetchError(inner) => inner.status().map(|s| s.as_u16()),
+                _ => None,
+            };
+            state.record_failure(status, &e);
+            return Err(e);
+        }
+    };
 
-    let now = Utc::now();
-    let mut articles = Vec::new();
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        state.record_success(status.as_u16(), elapsed_ms(started), Utc::now());
+        info!(url = %feed.url, "Feed not modified");
+        return Ok(Vec::new());
+    }
+    if let Err(e) = response.error_for_status_ref() {
+        state.record_failure(Some(status.as_u16()), &e);
+        return Err(e);
+    }
 
-    for entry in parsed.entries {
-        let Some(link) = entry.links.first().map(|l| l.href.clone()) else {
-            continue;
-        };
+    let etag = header_string(response.headers(), ETAG);
+    let last_modified = header_string(response.headers(), LAST_MODIFIED);
+    let content_type = response.content_type();
 
-        let title = entry
-            .title
-            .map(|t| t.content)
-            .unwrap_or_else(|| "(no title)".into());
-
-        let published_at: DateTime<Utc> = entry
-            .published
-            .or(entry.updated)
-            .unwrap_or(now);
-
-        let description = entry
-            .summary
-            .map(|s| s.content)
-            .or_else(|| {
-                entry
-                    .content
-                    .and_then(|c| c.body)
-            });
-
-        // Try to extract image from media content or content
-        let image_url = entry
-            .media
-            .first()
-            .and_then(|m| m.content.first())
-            .and_then(|c| c.url.as_ref())
-            .map(|u| u.to_string());
-
-        let id = article_id_from_url(&link);
-
-        articles.push(Article {
-            id,
-            category: category.clone(),
-            title,
-            url: link,
-            description,
-            image_url,
-            source: feed.source.clone(),
-            published_at,
-            fetched_at: now,
-            group_id: None,
-            group_count: None,
-        });
+    let bytes = match response.bytes(MAX_FEED_BYTES).await {
+        Ok(b) => b,
+        Err(e) => {
+            state.record_failure(Some(status.as_u16()), &e);
+            return Err(e);
+        }
+    };
+    let latency_ms = elapsed_ms(started);
+
+    let parsed = match feed
+        .source_type
+        .parse(&bytes, &feed.url, feed.scrape.as_ref(), content_type.as_deref())
+    {
+        Ok(p) => p,
+        Err(e) => {
+            state.record_failure(Some(status.as_u16()), &e);
+            return Err(e);
+        }
+    };
+
+    let now = Utc::now();
+
+    // Only remember validators once the body parsed, so a broken response is refetched in full.
+    state.etag = etag;
+    state.last_modified = last_modified;
+    state.record_success(status.as_u16(), latency_ms, now);
+    state.update_hint_secs = parsed.update_hint_secs;
+    let published: Vec<DateTime<Utc>> = parsed
+        .entries
+        .iter()
+        .filter_map(|e| e.published_at)
+        .collect();
+    if let Some(cadence) = schedule::publish_cadence(&published) {
+        state.publish_interval_secs = Some(cadence);
     }
+    state.record_items(parsed.entries.len(), published.iter().max().copied());
+    state.websub_topic = parsed.hub.as_ref().map(|_| parsed.self_url.clone().unwrap_or_else(|| feed.url.clone()));
+    state.websub_hub = parsed.hub.clone();
+
+    let mut articles = source_articles(feed, &category, parsed, now);
+    let filtered = filters.map_or(0, |f| f.apply(&mut articles));
+    state.last_filtered_count = filtered as u32;
 
     info!(
         url = %feed.url,
         count = articles.len(),
+        filtered,
         "Parsed feed"
     );
 
     Ok(articles)
 }
 
-/// Fetch all configured feeds concurrently.
-pub async fn fetch_all_feeds(client: &reqwest::Client, config: &FeedsConfig) -> Vec<Article> {
-    let futures: Vec<_> = config
+fn feed_category(feed: &FeedConfig) -> Result<Category> {
+    Category::from_str(&feed.category)
+        .ok_or_else(|| AppError::ConfigError(format!("Unknown category: {}", feed.category)))
+}
+
+/// Filters are validated when saved; one that still fails to compile is skipped
+/// rather than failing every fetch of the feed.
+fn compile_filters(feed: &FeedConfig) -> Option<CompiledFilters> {
+    feed.filters.as_ref().map(FeedFilters::compile_skipping_invalid)
+}
+
+fn source_articles(feed: &FeedConfig, category: &Category, parsed: ParsedSource, now: DateTime<Utc>) -> Vec<Article> {
+    let declared = parsed.language;
+    parsed
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let lang = lang::article_language(
+                entry.title.as_deref().unwrap_or_default(),
+                entry.description.as_deref(),
+                declared.as_deref(),
+            );
+            let mut article = Article {
+                id: article_id_from_url(&entry.url),
+                category: category.clone(),
+                title: entry.title.unwrap_or_else(|| "(no title)".into()),
+                url: entry.url,
+                description: entry.description,
+                image_url: entry.image_url,
+                source: feed.source.clone(),
+                published_at: entry.published_at.unwrap_or(now),
+                fetched_at: now,
+                group_id: None,
+                group_count: None,
+                metadata: None,
+                audio: entry.audio,
+                categories: Vec::new(),
+                lang,
+                translated_from: None,
+                updated_at: None,
+                headline_changed: false,
+            };
+            article.categories = categorize::for_article(&article, None);
+            article
+        })
+        .collect()
+}
+
+/// Parse a body the feed's publisher sent us directly (e.g. a WebSub push) into articles,
+/// the same way `fetch_feed` would.
+pub fn parse_feed_body(feed: &FeedConfig, body: &[u8]) -> Result<Vec<Article>> {
+    let category = feed_category(feed)?;
+    let filters = compile_filters(feed);
+    let parsed = feed.source_type.parse(body, &feed.url, feed.scrape.as_ref(), None)?;
+    let mut articles = source_articles(feed, &category, parsed, Utc::now());
+    if let Some(filters) = filters {
+        let filtered = filters.apply(&mut articles);
+        if filtered > 0 {
+            info!(url = %feed.url, filtered, "Filtered pushed items");
+        }
+    }
+    Ok(articles)
+}
+
+/// Fetch all configured feeds concurrently, without conditional requests.
+pub async fn fetch_all_feeds(client: &PoliteClient, config: &FeedsConfig) -> Vec<Article> {
+    let mut feeds: Vec<(FeedConfig, FetchState)> = config
         .feeds
         .iter()
-        .map(|feed| fetch_feed(client, feed))
+        .map(|feed| (feed.clone(), FetchState::default()))
+        .collect();
+    fetch_feeds_with_state(client, &mut feeds).await
+}
+
+/// Fetch feeds concurrently, sending conditional requests and updating each feed's state in place.
+pub async fn fetch_feeds_with_state(
+    client: &PoliteClient,
+    feeds: &mut [(FeedConfig, FetchState)],
+) -> Vec<Article> {
+    let futures: Vec<_> = feeds
+        .iter_mut()
+        .map(|(feed, state)| fetch_feed(client, feed, state))
         .collect();
 
     let results = futures::future::join_all(futures).await;
@@ -112,16 +358,32 @@ pub async fn fetch_all_feeds(client: &reqwest::Client, config: &FeedsConfig) ->
     for result in results {
         match result {
             Ok(articles) => all_articles.extend(articles),
-            Err(e) => warn!(error = %e, "Failed to fetch feed, skipping"),
+            Err(e) => warn!(
+                error = %e,
+                class = FetchErrorClass::classify(&e).as_str(),
+                "Failed to fetch feed, skipping"
+            ),
         }
     }
 
     all_articles
 }
 
+fn header_string(headers: &reqwest::header::HeaderMap, name: reqwest::header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+}
+
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_server::{Reply, TestServer};
 
     const SAMPLE_TOML: &str = r#"
 [[feeds]]
@@ -141,6 +403,29 @@ category = "general"
         assert_eq!(config.feeds.len(), 2);
         assert_eq!(config.feeds[0].source, "Hacker News");
         assert_eq!(config.feeds[1].category, "general");
+        assert_eq!(config.feeds[0].source_type, SourceType::Rss);
+        assert!(config.url_rules.is_empty());
+    }
+
+    #[test]
+    fn parse_url_rules() {
+        let toml = format!(
+            "{}\n[[url_rules]]\nhost = \"news.yahoo.co.jp\"\nstrip_params = [\"source\"]\n",
+            SAMPLE_TOML
+        );
+        let config = FeedsConfig::from_toml(&toml).unwrap();
+        assert_eq!(config.url_rules.len(), 1);
+        assert_eq!(config.url_rules[0].strip_params, vec!["source"]);
+    }
+
+    #[test]
+    fn parse_source_type() {
+        let toml = format!(
+            "{}\n[[feeds]]\nurl = \"https://example.co.jp/sitemap-news.xml\"\nsource = \"Example\"\ncategory = \"general\"\nsource_type = \"news_sitemap\"\n",
+            SAMPLE_TOML
+        );
+        let config = FeedsConfig::from_toml(&toml).unwrap();
+        assert_eq!(config.feeds[2].source_type, SourceType::NewsSitemap);
     }
 
     #[test]
@@ -148,4 +433,119 @@ category = "general"
         let result = FeedsConfig::from_toml("not valid toml {{{}}}");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn fetch_state_tracks_failures_and_resets_on_success() {
+        let mut state = FetchState::default();
+        state.record_failure(None, &AppError::ConfigError("unreachable".into()));
+        state.record_failure(Some(503), &AppError::ParseError("bad xml".into()));
+        assert_eq!(state.consecutive_failures, 2);
+        assert_eq!(state.last_status, Some(503));
+        assert_eq!(state.last_error_class, Some(FetchErrorClass::Parse));
+        assert!(state.last_success_at.is_none());
+
+        state.record_success(304, 100.0, Utc::now());
+        assert_eq!(state.consecutive_failures, 0);
+        assert!(state.last_error_class.is_none());
+        assert!(state.not_modified());
+        assert!(state.last_success_at.is_some());
+    }
+
+    #[test]
+    fn fetch_state_latency_is_moving_average() {
+        let mut state = FetchState::default();
+        state.record_success(200, 100.0, Utc::now());
+        assert_eq!(state.avg_latency_ms, Some(100.0));
+        state.record_success(200, 200.0, Utc::now());
+        let avg = state.avg_latency_ms.unwrap();
+        assert!((avg - 120.0).abs() < 1e-9, "unexpected average: {}", avg);
+    }
+
+    #[test]
+    fn fetch_state_keeps_bounded_item_history() {
+        let mut state = FetchState::default();
+        let newest = Utc::now();
+        for i in 0..(ITEM_COUNT_HISTORY + 3) {
+            state.record_items(i, Some(newest - chrono::Duration::hours(i as i64)));
+        }
+        assert_eq!(state.recent_item_counts.len(), ITEM_COUNT_HISTORY);
+        assert_eq!(state.recent_item_counts.last(), Some(&(ITEM_COUNT_HISTORY as u32 + 2)));
+        assert_eq!(state.latest_item_at, Some(newest));
+    }
+
+    #[test]
+    fn pushed_items_are_filtered() {
+        let body = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Top Picks</title>
+<item><title>半導体工場を新設</title><link>https://example.com/articles/1</link></item>
+<item><title>【PR】お得なセール</title><link>https://example.com/articles/2</link></item>
+<item><title>首相が会見</title><link>https://example.com/pickup/3</link></item>
+</channel></rss>"#;
+        let feed = FeedConfig {
+            feed_id: Some("f1".into()),
+            url: "https://example.com/rss".into(),
+            source: "Example".into(),
+            category: "general".into(),
+            poll_interval_secs: None,
+            source_type: SourceType::Rss,
+            scrape: None,
+            filters: Some(FeedFilters {
+                exclude_keywords: vec!["【PR】".into()],
+                exclude_patterns: vec![crate::filters::FieldPattern {
+                    field: crate::filters::FilterField::UrlPath,
+                    pattern: "^/pickup/".into(),
+                }],
+                ..Default::default()
+            }),
+        };
+        let articles = parse_feed_body(&feed, body.as_bytes()).unwrap();
+        assert_eq!(articles.len(), 1);
+        assert_eq!(articles[0].title, "半導体工場を新設");
+    }
+
+    #[tokio::test]
+    async fn scrape_sources_follow_robots_txt_and_feeds_do_not() {
+        let rss = r#"<?xml version="1.0"?><rss version="2.0"><channel><title>T</title>
+<item><title>首相が会見</title><link>https://example.com/articles/1</link></item></channel></rss>"#;
+        let (listing, _, _) = encoding_rs::SHIFT_JIS.encode(r#"<a class="story" href="/articles/2">首相が会見</a>"#);
+        let server = TestServer::start(vec![
+            ("/robots.txt", Reply::ok("text/plain", "User-agent: *\nDisallow: /private/\n")),
+            ("/private/rss", Reply::ok("application/rss+xml", rss)),
+            ("/private/list", Reply::ok("text/html", listing.to_vec())),
+            ("/list", Reply::ok("text/html; charset=Shift_JIS", listing.to_vec())),
+        ])
+        .await;
+        let client = PoliteClient::new(reqwest::Client::new()).with_limits(std::time::Duration::ZERO, 2);
+        let rules = ScrapeRules {
+            item: "a.story".into(),
+            link: "a[href]".into(),
+            title: None,
+            date: None,
+            image: None,
+        };
+        let feed = |path: &str, source_type: SourceType| FeedConfig {
+            feed_id: None,
+            url: server.url(path),
+            source: "Example".into(),
+            category: "general".into(),
+            poll_interval_secs: None,
+            source_type,
+            scrape: (source_type == SourceType::Scrape).then(|| rules.clone()),
+            filters: None,
+        };
+
+        let mut state = FetchState::default();
+        let articles = fetch_feed(&client, &feed("/private/rss", SourceType::Rss), &mut state).await.unwrap();
+        assert_eq!(articles.len(), 1);
+
+        let mut state = FetchState::default();
+        let err = fetch_feed(&client, &feed("/private/list", SourceType::Scrape), &mut state).await.unwrap_err();
+        assert!(matches!(err, AppError::Disallowed(_)), "{err}");
+        assert_eq!(server.hits("/private/list"), 0);
+        assert_eq!(state.consecutive_failures, 1);
+
+        let mut state = FetchState::default();
+        let articles = fetch_feed(&client, &feed("/list", SourceType::Scrape), &mut state).await.unwrap();
+        assert_eq!(articles[0].title, "首相が会見");
+    }
 }
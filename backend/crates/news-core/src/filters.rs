@@ -0,0 +1,305 @@
+use crate::error::{AppError, Result};
+use crate::models::Article;
+use crate::text::contains_keyword;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Part of an article a filter pattern is matched against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterField {
+    #[default]
+    Title,
+    Description,
+    /// Path of the article URL, e.g. "/articles/sports/123".
+    UrlPath,
+}
+
+impl FilterField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Description => "description",
+            Self::UrlPath => "url_path",
+        }
+    }
+
+    fn text<'a>(&self, article: &'a Article) -> &'a str {
+        match self {
+            Self::Title => &article.title,
+            Self::Description => article.description.as_deref().unwrap_or_default(),
+            Self::UrlPath => url_path(&article.url),
+        }
+    }
+}
+
+/// A regular expression matched against one field of an article.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldPattern {
+    #[serde(default)]
+    pub field: FilterField,
+    pub pattern: String,
+}
+
+impl FieldPattern {
+    fn compile(&self) -> Result<(FilterField, Regex)> {
+        Regex::new(&self.pattern).map(|re| (self.field, re)).map_err(|e| {
+            AppError::ConfigError(format!("filter {} pattern {:?}: {e}", self.field.as_str(), self.pattern))
+        })
+    }
+}
+
+/// Per-feed rules deciding which items of a broad feed are kept.
+///
+/// An item is kept when it matches at least one include rule (or there are none),
+/// matches no exclude rule, and its description is at least `min_description_len`
+/// characters long. Keywords match the title and description case-insensitively; a
+/// keyword starting or ending in an ASCII letter or digit only matches there at a word
+/// boundary, so "PR" matches "【PR】" but not "April".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedFilters {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_patterns: Vec<FieldPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_patterns: Vec<FieldPattern>,
+    /// Items with a shorter (or no) description are dropped, in characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_description_len: Option<usize>,
+}
+
+impl FeedFilters {
+    /// Check every pattern compiles, so a bad rule is rejected before it's saved.
+    pub fn validate(&self) -> Result<()> {
+        self.compile().map(|_| ())
+    }
+
+    /// Compile the rules once for matching a whole fetch.
+    pub fn compile(&self) -> Result<CompiledFilters> {
+        let compile = |patterns: &[FieldPattern]| -> Result<Vec<_>> {
+            patterns.iter().map(FieldPattern::compile).collect()
+        };
+        Ok(self.compiled(compile(&self.include_patterns)?, compile(&self.exclude_patterns)?))
+    }
+
+    /// [`FeedFilters::compile`], leaving out (and logging) patterns that don't compile,
+    /// so a rule saved before validation existed doesn't stop the feed being fetched.
+    pub fn compile_skipping_invalid(&self) -> CompiledFilters {
+        let compile = |patterns: &[FieldPattern]| -> Vec<_> {
+            patterns
+                .iter()
+                .filter_map(|p| {
+                    p.compile()
+                        .inspect_err(|e| warn!(error = %e, "Skipping invalid feed filter"))
+                        .ok()
+                })
+                .collect()
+        };
+        self.compiled(compile(&self.include_patterns), compile(&self.exclude_patterns))
+    }
+
+    fn compiled(
+        &self,
+        include_patterns: Vec<(FilterField, Regex)>,
+        exclude_patterns: Vec<(FilterField, Regex)>,
+    ) -> CompiledFilters {
+        let lowercase = |keywords: &[String]| -> Vec<String> {
+            keywords
+                .iter()
+                .map(|k| k.trim().to_lowercase())
+                .filter(|k| !k.is_empty())
+                .collect()
+        };
+        CompiledFilters {
+            include_keywords: lowercase(&self.include_keywords),
+            exclude_keywords: lowercase(&self.exclude_keywords),
+            include_patterns,
+            exclude_patterns,
+            min_description_len: self.min_description_len,
+        }
+    }
+}
+
+/// [`FeedFilters`] with patterns compiled and keywords lowercased.
+#[derive(Debug, Clone)]
+pub struct CompiledFilters {
+    include_keywords: Vec<String>,
+    exclude_keywords: Vec<String>,
+    include_patterns: Vec<(FilterField, Regex)>,
+    exclude_patterns: Vec<(FilterField, Regex)>,
+    min_description_len: Option<usize>,
+}
+
+impl CompiledFilters {
+    /// Whether `article` passes the feed's rules.
+    pub fn keep(&self, article: &Article) -> bool {
+        if let Some(min) = self.min_description_len {
+            let len = article.description.as_deref().map_or(0, |d| d.trim().chars().count());
+            if len < min {
+                return false;
+            }
+        }
+        let text = match article.description {
+            Some(ref d) => format!("{}\n{}", article.title, d).to_lowercase(),
+            None => article.title.to_lowercase(),
+        };
+        let matches_pattern = |(field, re): &(FilterField, Regex)| re.is_match(field.text(article));
+
+        if self.exclude_keywords.iter().any(|k| contains_keyword(&text, k))
+            || self.exclude_patterns.iter().any(matches_pattern)
+        {
+            return false;
+        }
+        if self.include_keywords.is_empty() && self.include_patterns.is_empty() {
+            return true;
+        }
+        self.include_keywords.iter().any(|k| contains_keyword(&text, k))
+            || self.include_patterns.iter().any(matches_pattern)
+    }
+
+    /// Drop the articles that don't pass, returning how many were dropped.
+    pub fn apply(&self, articles: &mut Vec<Article>) -> usize {
+        let before = articles.len();
+        articles.retain(|a| self.keep(a));
+        before - articles.len()
+    }
+}
+
+fn url_path(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = rest.find('/').map_or("", |i| &rest[i..]);
+    path.split(['?', '#']).next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Category;
+    use chrono::Utc;
+
+    fn article(title: &str, url: &str, description: Option<&str>) -> Article {
+        Article {
+            id: "a1".into(),
+            category: Category::General,
+            title: title.into(),
+            url: url.into(),
+            description: description.map(String::from),
+            image_url: None,
+            source: "Yahoo!ニュース".into(),
+            published_at: Utc::now(),
+            fetched_at: Utc::now(),
+            group_id: None,
+            group_count: None,
+            metadata: None,
+            audio: None,
+            categories: Vec::new(),
+            lang: None,
+            translated_from: None,
+            updated_at: None,
+            headline_changed: false,
+        }
+    }
+
+    fn pattern(field: FilterField, pattern: &str) -> FieldPattern {
+        FieldPattern {
+            field,
+            pattern: pattern.into(),
+        }
+    }
+
+    #[test]
+    fn no_rules_keep_everything() {
+        let filters = FeedFilters::default().compile().unwrap();
+        assert!(filters.keep(&article("首相が会見", "https://example.com/a", None)));
+    }
+
+    #[test]
+    fn exclude_keywords_and_patterns_drop_items() {
+        let filters = FeedFilters {
+            exclude_keywords: vec!["PR".into()],
+            exclude_patterns: vec![pattern(FilterField::UrlPath, "^/pickup/")],
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(!filters.keep(&article("【pr】お得なセール", "https://example.com/a", None)));
+        assert!(!filters.keep(&article("首相が会見", "https://news.yahoo.co.jp/pickup/123?src=rss", None)));
+        assert!(filters.keep(&article("首相が会見", "https://news.yahoo.co.jp/articles/123", None)));
+    }
+
+    #[test]
+    fn ascii_keywords_match_whole_words() {
+        let filters = FeedFilters {
+            exclude_keywords: vec!["PR".into()],
+            include_keywords: vec!["AI".into(), "半導体".into()],
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(filters.keep(&article("AI policy: Prime Minister speaks", "https://example.com/a", None)));
+        assert!(filters.keep(&article("AI chip price rises in April", "https://example.com/b", None)));
+        assert!(!filters.keep(&article("AI翻訳PR記事", "https://example.com/c", None)));
+        assert!(!filters.keep(&article("Sponsored: AI tools (PR)", "https://example.com/d", None)));
+        assert!(!filters.keep(&article("Airline news", "https://example.com/e", None)));
+        assert!(filters.keep(&article("次世代半導体の量産", "https://example.com/f", None)));
+    }
+
+    #[test]
+    fn include_rules_keep_only_matches() {
+        let filters = FeedFilters {
+            include_keywords: vec!["半導体".into()],
+            include_patterns: vec![pattern(FilterField::Description, r"(?i)\bchips?\b")],
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(filters.keep(&article("半導体工場を新設", "https://example.com/a", None)));
+        assert!(filters.keep(&article("New plant", "https://example.com/b", Some("Chip output rises"))));
+        assert!(!filters.keep(&article("首相が会見", "https://example.com/c", None)));
+    }
+
+    #[test]
+    fn short_descriptions_are_dropped() {
+        let mut articles = vec![
+            article("a", "https://example.com/a", None),
+            article("b", "https://example.com/b", Some("短い")),
+            article("c", "https://example.com/c", Some("十分な長さのある説明文です")),
+        ];
+        let filters = FeedFilters {
+            min_description_len: Some(10),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert_eq!(filters.apply(&mut articles), 2);
+        assert_eq!(articles[0].title, "c");
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let filters = FeedFilters {
+            include_patterns: vec![pattern(FilterField::Title, "(unclosed")],
+            ..Default::default()
+        };
+        assert!(filters.validate().is_err());
+
+        // Fetching skips just the bad pattern
+        let filters = FeedFilters {
+            exclude_patterns: vec![pattern(FilterField::Title, "(unclosed"), pattern(FilterField::Title, "^速報")],
+            ..Default::default()
+        }
+        .compile_skipping_invalid();
+        assert!(!filters.keep(&article("速報 地震", "https://example.com/a", None)));
+        assert!(filters.keep(&article("(unclosed", "https://example.com/b", None)));
+    }
+
+    #[test]
+    fn url_paths() {
+        assert_eq!(url_path("https://example.com/a/b?x=1#top"), "/a/b");
+        assert_eq!(url_path("https://example.com"), "");
+    }
+}
@@ -1,4 +1,8 @@
-use std::collections::HashSet;
+use crate::models::Article;
+use std::collections::{HashMap, HashSet};
+
+/// How far back new articles are compared against when clustering stories at ingest.
+pub const STORY_WINDOW_HOURS: i64 = 48;
 
 /// Extract character trigrams from a string (works well with Japanese + English).
 pub fn trigrams(s: &str) -> HashSet<String> {
@@ -15,13 +19,15 @@ pub fn trigrams(s: &str) -> HashSet<String> {
 
 /// Jaccard similarity between two strings based on character trigrams.
 pub fn similarity(a: &str, b: &str) -> f64 {
-    let ta = trigrams(a);
-    let tb = trigrams(b);
+    jaccard(&trigrams(a), &trigrams(b))
+}
+
+fn jaccard(ta: &HashSet<String>, tb: &HashSet<String>) -> f64 {
     if ta.is_empty() && tb.is_empty() {
         return 1.0;
     }
-    let intersection = ta.intersection(&tb).count();
-    let union = ta.union(&tb).count();
+    let intersection = ta.intersection(tb).count();
+    let union = ta.union(tb).count();
     if union == 0 {
         return 0.0;
     }
@@ -35,21 +41,6 @@ pub fn group_articles(titles: &[&str], threshold: f64) -> Vec<Vec<usize>> {
     let n = titles.len();
     let mut parent: Vec<usize> = (0..n).collect();
 
-    fn find(parent: &mut [usize], i: usize) -> usize {
-        if parent[i] != i {
-            parent[i] = find(parent, parent[i]);
-        }
-        parent[i]
-    }
-
-    fn union(parent: &mut [usize], a: usize, b: usize) {
-        let ra = find(parent, a);
-        let rb = find(parent, b);
-        if ra != rb {
-            parent[rb] = ra;
-        }
-    }
-
     // Compare all pairs
     for i in 0..n {
         for j in (i + 1)..n {
@@ -59,17 +50,278 @@ pub fn group_articles(titles: &[&str], threshold: f64) -> Vec<Vec<usize>> {
         }
     }
 
-    // Collect groups
-    let mut groups: std::collections::HashMap<usize, Vec<usize>> =
-        std::collections::HashMap::new();
-    for i in 0..n {
-        let root = find(&mut parent, i);
-        groups.entry(root).or_default().push(i);
+    collect_groups(&mut parent)
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    if parent[i] != i {
+        parent[i] = find(parent, parent[i]);
     }
+    parent[i]
+}
 
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let ra = find(parent, a);
+    let rb = find(parent, b);
+    if ra != rb {
+        parent[rb] = ra;
+    }
+}
+
+fn collect_groups(parent: &mut [usize]) -> Vec<Vec<usize>> {
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..parent.len() {
+        let root = find(parent, i);
+        groups.entry(root).or_default().push(i);
+    }
     groups.into_values().collect()
 }
 
+/// Text used to compare two articles: the title, optionally followed by the start of
+/// the description. Descriptions are cut short so long bodies don't drown the title.
+pub fn story_text(title: &str, description: Option<&str>) -> String {
+    const DESCRIPTION_CHARS: usize = 200;
+    match description.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(d) => {
+            let mut text = String::with_capacity(title.len() + DESCRIPTION_CHARS * 3);
+            text.push_str(title);
+            text.push(' ');
+            text.extend(d.chars().take(DESCRIPTION_CHARS));
+            text
+        }
+        None => title.to_string(),
+    }
+}
+
+// --- MinHash / LSH ---
+
+/// Number of hash functions in a MinHash signature.
+pub const SIGNATURE_LEN: usize = 64;
+
+/// A MinHash signature of a text's trigram set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature(Vec<u64>);
+
+impl Signature {
+    /// Signature over [`trigrams`] of `text`.
+    pub fn of(text: &str) -> Self {
+        Self::from_trigrams(&trigrams(text))
+    }
+
+    pub fn from_trigrams(set: &HashSet<String>) -> Self {
+        let mut mins = vec![u64::MAX; SIGNATURE_LEN];
+        for gram in set {
+            let base = fnv1a(gram.as_bytes());
+            for (i, min) in mins.iter_mut().enumerate() {
+                let h = splitmix64(base ^ PERMUTATION_SEEDS[i]);
+                if h < *min {
+                    *min = h;
+                }
+            }
+        }
+        Self(mins)
+    }
+
+    /// Estimated Jaccard similarity: the fraction of matching slots.
+    pub fn similarity(&self, other: &Signature) -> f64 {
+        let same = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        same as f64 / SIGNATURE_LEN as f64
+    }
+}
+
+const PERMUTATION_SEEDS: [u64; SIGNATURE_LEN] = {
+    let mut seeds = [0u64; SIGNATURE_LEN];
+    let mut i = 0;
+    while i < SIGNATURE_LEN {
+        seeds[i] = splitmix64(0x9E37_79B9_7F4A_7C15 ^ i as u64);
+        i += 1;
+    }
+    seeds
+};
+
+const fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    h
+}
+
+/// Locality-sensitive hashing index over MinHash signatures.
+///
+/// Signatures are split into `bands` bands of `rows` slots; two texts become candidates
+/// when any band matches exactly. Candidates still need an exact similarity check.
+#[derive(Debug, Clone)]
+pub struct LshIndex {
+    bands: usize,
+    rows: usize,
+    buckets: HashMap<(usize, u64), Vec<usize>>,
+    len: usize,
+}
+
+impl LshIndex {
+    /// Pick a band layout that finds pairs at `threshold` with high probability.
+    ///
+    /// A pair with similarity `s` becomes a candidate with probability `1 - (1 - s^rows)^bands`;
+    /// the layout with the most rows (fewest false candidates) whose S-curve midpoint
+    /// `(1/bands)^(1/rows)` sits well below the threshold is used.
+    pub fn for_threshold(threshold: f64) -> Self {
+        let (bands, rows) = [(8, 8), (16, 4), (32, 2), (64, 1)]
+            .into_iter()
+            .find(|&(b, r)| (1.0 / b as f64).powf(1.0 / r as f64) <= threshold * 0.8)
+            .unwrap_or((64, 1));
+        Self::new(bands, rows)
+    }
+
+    pub fn new(bands: usize, rows: usize) -> Self {
+        assert!(bands * rows <= SIGNATURE_LEN, "LSH layout exceeds signature length");
+        Self {
+            bands,
+            rows,
+            buckets: HashMap::new(),
+            len: 0,
+        }
+    }
+
+    /// Add a signature and return its index.
+    pub fn insert(&mut self, sig: &Signature) -> usize {
+        let idx = self.len;
+        for band in 0..self.bands {
+            self.buckets
+                .entry((band, self.band_hash(sig, band)))
+                .or_default()
+                .push(idx);
+        }
+        self.len += 1;
+        idx
+    }
+
+    /// Indices of stored signatures sharing at least one band with `sig`, ascending.
+    pub fn candidates(&self, sig: &Signature) -> Vec<usize> {
+        let mut out: Vec<usize> = (0..self.bands)
+            .filter_map(|band| self.buckets.get(&(band, self.band_hash(sig, band))))
+            .flatten()
+            .copied()
+            .collect();
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    fn band_hash(&self, sig: &Signature, band: usize) -> u64 {
+        let slots = &sig.0[band * self.rows..(band + 1) * self.rows];
+        slots
+            .iter()
+            .fold(band as u64, |h, &v| splitmix64(h ^ v))
+    }
+}
+
+/// Like [`group_articles`], but only compares pairs that an LSH index proposes, so it
+/// scales to thousands of texts. Each proposed pair is confirmed with the exact trigram
+/// similarity; a pair the index misses (rare near the threshold) stays ungrouped.
+pub fn group_articles_lsh(texts: &[&str], threshold: f64) -> Vec<Vec<usize>> {
+    let sets: Vec<HashSet<String>> = texts.iter().map(|t| trigrams(t)).collect();
+    let mut index = LshIndex::for_threshold(threshold);
+    let mut parent: Vec<usize> = (0..texts.len()).collect();
+
+    for (i, set) in sets.iter().enumerate() {
+        let sig = Signature::from_trigrams(set);
+        for j in index.candidates(&sig) {
+            if jaccard(set, &sets[j]) >= threshold {
+                union(&mut parent, j, i);
+            }
+        }
+        index.insert(&sig);
+    }
+
+    collect_groups(&mut parent)
+}
+
+/// A recent article that a newly ingested article may join as part of the same story.
+#[derive(Debug, Clone)]
+pub struct StoryCandidate {
+    pub id: String,
+    pub group_id: Option<String>,
+    trigrams: HashSet<String>,
+}
+
+impl StoryCandidate {
+    /// Story the candidate belongs to, or the ID a new story seeded by it would get.
+    pub fn story_id(&self) -> &str {
+        self.group_id.as_deref().unwrap_or(&self.id)
+    }
+}
+
+/// Recent articles indexed for story matching at ingest.
+pub struct StoryIndex {
+    threshold: f64,
+    lsh: LshIndex,
+    candidates: Vec<StoryCandidate>,
+    by_id: HashMap<String, usize>,
+}
+
+impl StoryIndex {
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            lsh: LshIndex::for_threshold(threshold),
+            candidates: Vec::new(),
+            by_id: HashMap::new(),
+        }
+    }
+
+    /// Add an article by the text it should be matched on (see [`story_text`]).
+    pub fn insert(&mut self, id: String, text: &str, group_id: Option<String>) {
+        let set = trigrams(text);
+        let idx = self.lsh.insert(&Signature::from_trigrams(&set));
+        self.by_id.insert(id.clone(), idx);
+        self.candidates.push(StoryCandidate {
+            id,
+            group_id,
+            trigrams: set,
+        });
+    }
+
+    /// Most similar indexed article whose similarity is at least the threshold.
+    pub fn best_match(&self, text: &str) -> Option<&StoryCandidate> {
+        let set = trigrams(text);
+        self.lsh
+            .candidates(&Signature::from_trigrams(&set))
+            .into_iter()
+            .map(|i| (&self.candidates[i], jaccard(&set, &self.candidates[i].trigrams)))
+            .filter(|(_, s)| *s >= self.threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(c, _)| c)
+    }
+
+    /// Record that an indexed article now belongs to a story.
+    pub fn set_group(&mut self, id: &str, group_id: String) {
+        if let Some(&i) = self.by_id.get(id) {
+            self.candidates[i].group_id = Some(group_id);
+        }
+    }
+}
+
+/// Keep only the first (newest) article of each stored story, in order.
+pub fn collapse_stories(articles: Vec<Article>) -> Vec<Article> {
+    let mut seen = HashSet::new();
+    articles
+        .into_iter()
+        .filter(|a| match a.group_id {
+            Some(ref g) => seen.insert(g.clone()),
+            None => true,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,16 +372,13 @@ mod tests {
 
     #[test]
     fn group_articles_basic() {
-        let titles = vec![
+        let titles = [
             "東京都で新型コロナ100人確認",
             "東京都で新型コロナ150人確認",
             "サッカーW杯の結果速報",
             "プログラミング言語Rustの最新版",
         ];
-        let groups = group_articles(
-            &titles.iter().map(|s| *s).collect::<Vec<_>>(),
-            0.3,
-        );
+        let groups = group_articles(&titles, 0.3);
         // The two corona articles should be grouped together
         let has_corona_group = groups.iter().any(|g| g.contains(&0) && g.contains(&1));
         assert!(has_corona_group, "Similar articles should be grouped: {:?}", groups);
@@ -137,9 +386,147 @@ mod tests {
 
     #[test]
     fn group_articles_no_groups() {
-        let titles = vec!["aaa", "bbb", "ccc"];
-        let groups = group_articles(&titles.iter().map(|s| *s).collect::<Vec<_>>(), 0.5);
+        let titles = ["aaa", "bbb", "ccc"];
+        let groups = group_articles(&titles, 0.5);
         // All should be separate
         assert_eq!(groups.len(), 3);
     }
+
+    #[test]
+    fn story_index_picks_most_similar_above_threshold() {
+        let mut index = StoryIndex::new(0.3);
+        index.insert("a".into(), "東京都で新型コロナ100人確認", None);
+        index.insert("b".into(), "東京都で新型コロナ150人確認", Some("story-1".into()));
+        index.insert("c".into(), "サッカーW杯の結果速報", None);
+
+        let m = index.best_match("東京都で新型コロナ150人確認 都が発表").unwrap();
+        assert_eq!(m.id, "b");
+        assert_eq!(m.story_id(), "story-1");
+        assert!(index.best_match("プログラミング言語Rustの最新版").is_none());
+
+        index.set_group("c", "story-2".into());
+        assert_eq!(index.best_match("サッカーW杯の結果速報").unwrap().story_id(), "story-2");
+    }
+
+    /// Headlines with a few near-duplicate rewrites each, like several outlets covering one story.
+    fn synthetic_headlines(stories: usize) -> Vec<String> {
+        const SUBJECTS: [&str; 8] = ["政府", "日銀", "東京都", "トヨタ", "Apple", "NASA", "WHO", "楽天"];
+        const EVENTS: [&str; 8] = [
+            "新たな経済対策を発表",
+            "金利を据え置き",
+            "大規模な組織再編を検討",
+            "新製品を来月発売へ",
+            "探査機の打ち上げに成功",
+            "感染症の警戒レベルを引き上げ",
+            "過去最高益を記録",
+            "海外企業の買収で合意",
+        ];
+        let mut titles = Vec::new();
+        for n in 0..stories {
+            let base = format!(
+                "{}が{}、{}件目の報道",
+                SUBJECTS[n % SUBJECTS.len()],
+                EVENTS[(n / SUBJECTS.len()) % EVENTS.len()],
+                n
+            );
+            titles.push(base.clone());
+            titles.push(format!("{}（速報）", base));
+            titles.push(format!("【独自】{}", base));
+        }
+        titles
+    }
+
+    fn normalized(mut groups: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        for g in &mut groups {
+            g.sort_unstable();
+        }
+        groups.sort();
+        groups
+    }
+
+    #[test]
+    fn lsh_grouping_matches_exact_grouping() {
+        let titles = synthetic_headlines(40);
+        let refs: Vec<&str> = titles.iter().map(|s| s.as_str()).collect();
+        for threshold in [0.3, 0.5, 0.7] {
+            assert_eq!(
+                normalized(group_articles_lsh(&refs, threshold)),
+                normalized(group_articles(&refs, threshold)),
+                "threshold {}",
+                threshold
+            );
+        }
+    }
+
+    #[test]
+    fn minhash_estimate_tracks_exact_similarity() {
+        let pairs = [
+            ("東京都で新型コロナウイルスの感染者が100人確認", "東京都で新型コロナウイルスの感染者が150人確認"),
+            ("Rust 2024 edition is released", "The Rust 2024 edition has been released"),
+            ("サッカーW杯の結果速報", "プログラミング言語Rustの最新版"),
+        ];
+        for (a, b) in pairs {
+            let exact = similarity(a, b);
+            let estimate = Signature::of(a).similarity(&Signature::of(b));
+            assert!(
+                (exact - estimate).abs() < 0.2,
+                "{} vs {}: exact {} estimate {}",
+                a,
+                b,
+                exact,
+                estimate
+            );
+        }
+        assert_eq!(Signature::of("same text").similarity(&Signature::of("same text")), 1.0);
+    }
+
+    #[test]
+    fn lsh_layout_follows_threshold() {
+        let strict = LshIndex::for_threshold(0.7);
+        let loose = LshIndex::for_threshold(0.3);
+        assert!(strict.rows > loose.rows);
+    }
+
+    #[test]
+    fn story_text_appends_trimmed_description() {
+        assert_eq!(story_text("Title", None), "Title");
+        assert_eq!(story_text("Title", Some("  ")), "Title");
+        let long = "あ".repeat(500);
+        let text = story_text("Title", Some(&long));
+        assert_eq!(text.chars().count(), "Title ".len() + 200);
+    }
+
+    #[test]
+    fn collapse_keeps_first_of_each_story() {
+        use crate::models::Category;
+        use chrono::Utc;
+        let article = |id: &str, group: Option<&str>| Article {
+            id: id.into(),
+            category: Category::General,
+            title: id.into(),
+            url: String::new(),
+            description: None,
+            image_url: None,
+            source: String::new(),
+            published_at: Utc::now(),
+            fetched_at: Utc::now(),
+            group_id: group.map(String::from),
+            group_count: None,
+            metadata: None,
+            audio: None,
+            categories: Vec::new(),
+            lang: None,
+            translated_from: None,
+            updated_at: None,
+            headline_changed: false,
+        };
+        let collapsed = collapse_stories(vec![
+            article("1", Some("s")),
+            article("2", None),
+            article("3", Some("s")),
+            article("4", Some("t")),
+        ]);
+        let ids: Vec<_> = collapsed.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "4"]);
+    }
 }
@@ -0,0 +1,328 @@
+use crate::config::DynamicFeed;
+use crate::error::AppError;
+use crate::feeds::FetchState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::error::Error as _;
+
+/// Consecutive failed fetches after which a feed is quarantined, unless overridden.
+pub const DEFAULT_QUARANTINE_AFTER: u32 = 8;
+/// How many recent item counts are kept per feed for the trend.
+pub const ITEM_COUNT_HISTORY: usize = 10;
+/// A feed whose newest item is older than this is reported as stale.
+const STALE_AFTER_HOURS: i64 = 72;
+
+/// Coarse class of the last fetch error, for the health report.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchErrorClass {
+    Dns,
+    Connect,
+    Timeout,
+    HttpStatus,
+    Parse,
+    Other,
+}
+
+impl FetchErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dns => "dns",
+            Self::Connect => "connect",
+            Self::Timeout => "timeout",
+            Self::HttpStatus => "http_status",
+            Self::Parse => "parse",
+            Self::Other => "other",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "dns" => Some(Self::Dns),
+            "connect" => Some(Self::Connect),
+            "timeout" => Some(Self::Timeout),
+            "http_status" => Some(Self::HttpStatus),
+            "parse" => Some(Self::Parse),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    /// Classify an error returned by `fetch_feed`.
+    pub fn classify(err: &AppError) -> Self {
+        match err {
+            AppError::ParseError(_) => Self::Parse,
+            AppError::FetchError(e) if e.is_status() => Self::HttpStatus,
+            AppError::FetchError(e) if e.is_timeout() => Self::Timeout,
+            AppError::FetchError(e) if e.is_connect() => {
+                if is_dns_failure(e) {
+                    Self::Dns
+                } else {
+                    Self::Connect
+                }
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+/// reqwest doesn't expose resolver failures directly, so look through the source chain.
+fn is_dns_failure(err: &reqwest::Error) -> bool {
+    let mut source = err.source();
+    while let Some(e) = source {
+        let msg = e.to_string().to_lowercase();
+        if msg.contains("dns") || msg.contains("lookup address") || msg.contains("name or service not known") {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+/// Direction of a feed's item count over recent fetches.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemTrend {
+    Growing,
+    Steady,
+    Shrinking,
+    /// The latest fetch parsed but returned no items.
+    Empty,
+    Unknown,
+}
+
+/// Compare the latest item count with the average of the earlier ones (oldest first).
+pub fn item_trend(counts: &[u32]) -> ItemTrend {
+    let Some((&latest, earlier)) = counts.split_last() else {
+        return ItemTrend::Unknown;
+    };
+    if latest == 0 {
+        return ItemTrend::Empty;
+    }
+    if earlier.is_empty() {
+        return ItemTrend::Unknown;
+    }
+    let avg = earlier.iter().map(|&c| c as f64).sum::<f64>() / earlier.len() as f64;
+    let latest = latest as f64;
+    if latest > avg * 1.25 {
+        ItemTrend::Growing
+    } else if latest < avg * 0.75 {
+        ItemTrend::Shrinking
+    } else {
+        ItemTrend::Steady
+    }
+}
+
+/// Overall health of a feed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    /// Last fetch failed but the feed hasn't been quarantined yet.
+    Failing,
+    /// Fetches succeed but nothing new has been published for a while.
+    Stale,
+    Quarantined,
+    Disabled,
+    /// Never fetched yet.
+    Unknown,
+}
+
+/// Health report entry for `GET /api/admin/feeds/health`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedHealth {
+    pub feed_id: String,
+    pub source: String,
+    pub url: String,
+    pub enabled: bool,
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quarantine_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_class: Option<FetchErrorClass>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_latency_ms: Option<f64>,
+    pub item_trend: ItemTrend,
+    pub recent_item_counts: Vec<u32>,
+    /// Items the feed's filters dropped from the last parsed fetch.
+    pub filtered_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_item_at: Option<DateTime<Utc>>,
+    /// Age of the newest item, in hours.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freshness_hours: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_poll_at: Option<DateTime<Utc>>,
+}
+
+/// Build the health report entry for a feed from its stored fetch state.
+pub fn feed_health(feed: &DynamicFeed, state: Option<&FetchState>, now: DateTime<Utc>) -> FeedHealth {
+    let default_state = FetchState::default();
+    let state = state.unwrap_or(&default_state);
+
+    let freshness_hours = state
+        .latest_item_at
+        .map(|t| (now - t).num_minutes() as f64 / 60.0);
+
+    let status = if feed.quarantine_reason.is_some() {
+        HealthStatus::Quarantined
+    } else if !feed.enabled {
+        HealthStatus::Disabled
+    } else if state.consecutive_failures > 0 {
+        HealthStatus::Failing
+    } else if state.last_success_at.is_none() {
+        HealthStatus::Unknown
+    } else if freshness_hours.is_none_or(|h| h > STALE_AFTER_HOURS as f64) {
+        HealthStatus::Stale
+    } else {
+        HealthStatus::Healthy
+    };
+
+    FeedHealth {
+        feed_id: feed.feed_id.clone(),
+        source: feed.source.clone(),
+        url: feed.url.clone(),
+        enabled: feed.enabled,
+        status,
+        quarantine_reason: feed.quarantine_reason.clone(),
+        last_success_at: state.last_success_at,
+        last_status: state.last_status,
+        error_class: state.last_error_class,
+        last_error: state.last_error.clone(),
+        consecutive_failures: state.consecutive_failures,
+        avg_latency_ms: state.avg_latency_ms,
+        item_trend: item_trend(&state.recent_item_counts),
+        recent_item_counts: state.recent_item_counts.clone(),
+        filtered_count: state.last_filtered_count,
+        latest_item_at: state.latest_item_at,
+        freshness_hours,
+        next_poll_at: state.next_poll_at,
+    }
+}
+
+/// True once a feed has failed `threshold` fetches in a row.
+pub fn should_quarantine(state: &FetchState, threshold: u32) -> bool {
+    threshold > 0 && state.consecutive_failures >= threshold
+}
+
+/// Human-readable reason recorded on a quarantined feed.
+pub fn quarantine_reason(state: &FetchState, now: DateTime<Utc>) -> String {
+    let class = state
+        .last_error_class
+        .map(|c| c.as_str())
+        .unwrap_or("unknown");
+    let mut reason = format!(
+        "{} consecutive failures ({}",
+        state.consecutive_failures, class
+    );
+    if let Some(status) = state.last_status {
+        reason.push_str(&format!(" {}", status));
+    }
+    reason.push_str(&format!("), quarantined at {}", now.to_rfc3339()));
+    reason
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn feed() -> DynamicFeed {
+        DynamicFeed {
+            feed_id: "f1".into(),
+            url: "https://example.com/rss".into(),
+            source: "Example".into(),
+            category: "tech".into(),
+            enabled: true,
+            added_by: None,
+            poll_interval_secs: None,
+            quarantine_reason: None,
+            content_policy: Default::default(),
+            source_type: Default::default(),
+            scrape: None,
+            filters: None,
+        }
+    }
+
+    #[test]
+    fn error_class_round_trip() {
+        for class in [
+            FetchErrorClass::Dns,
+            FetchErrorClass::Connect,
+            FetchErrorClass::Timeout,
+            FetchErrorClass::HttpStatus,
+            FetchErrorClass::Parse,
+            FetchErrorClass::Other,
+        ] {
+            assert_eq!(FetchErrorClass::from_str(class.as_str()), Some(class));
+        }
+        assert_eq!(
+            FetchErrorClass::classify(&AppError::ParseError("bad xml".into())),
+            FetchErrorClass::Parse
+        );
+    }
+
+    #[test]
+    fn trend_compares_latest_with_history() {
+        assert_eq!(item_trend(&[]), ItemTrend::Unknown);
+        assert_eq!(item_trend(&[20]), ItemTrend::Unknown);
+        assert_eq!(item_trend(&[20, 20, 0]), ItemTrend::Empty);
+        assert_eq!(item_trend(&[20, 20, 21]), ItemTrend::Steady);
+        assert_eq!(item_trend(&[20, 20, 40]), ItemTrend::Growing);
+        assert_eq!(item_trend(&[20, 20, 5]), ItemTrend::Shrinking);
+    }
+
+    #[test]
+    fn status_reflects_state() {
+        let now = Utc::now();
+        assert_eq!(feed_health(&feed(), None, now).status, HealthStatus::Unknown);
+
+        let mut state = FetchState {
+            last_success_at: Some(now),
+            latest_item_at: Some(now - Duration::hours(1)),
+            ..Default::default()
+        };
+        assert_eq!(feed_health(&feed(), Some(&state), now).status, HealthStatus::Healthy);
+
+        state.latest_item_at = Some(now - Duration::days(5));
+        assert_eq!(feed_health(&feed(), Some(&state), now).status, HealthStatus::Stale);
+
+        state.consecutive_failures = 2;
+        assert_eq!(feed_health(&feed(), Some(&state), now).status, HealthStatus::Failing);
+
+        let quarantined = DynamicFeed {
+            enabled: false,
+            quarantine_reason: Some("8 consecutive failures".into()),
+            ..feed()
+        };
+        assert_eq!(
+            feed_health(&quarantined, Some(&state), now).status,
+            HealthStatus::Quarantined
+        );
+    }
+
+    #[test]
+    fn quarantine_after_threshold() {
+        let mut state = FetchState {
+            consecutive_failures: DEFAULT_QUARANTINE_AFTER - 1,
+            last_status: Some(404),
+            last_error_class: Some(FetchErrorClass::HttpStatus),
+            ..Default::default()
+        };
+        assert!(!should_quarantine(&state, DEFAULT_QUARANTINE_AFTER));
+        state.consecutive_failures += 1;
+        assert!(should_quarantine(&state, DEFAULT_QUARANTINE_AFTER));
+        assert!(!should_quarantine(&state, 0));
+
+        let reason = quarantine_reason(&state, Utc::now());
+        assert!(reason.starts_with("8 consecutive failures (http_status 404)"), "{}", reason);
+    }
+}
@@ -0,0 +1,113 @@
+use whatlang::{Lang, Script};
+
+/// Confidence whatlang needs before a Latin-script guess is trusted. Headlines are too
+/// short for it to tell Latin-script languages apart reliably, while non-Latin scripts
+/// (kana, Hangul, Cyrillic, ...) identify the language by themselves.
+const MIN_LATIN_CONFIDENCE: f64 = 0.5;
+
+/// Declared languages we believe for Latin-script text whatlang isn't sure about.
+const LATIN_LANGUAGES: &[&str] = &["en", "fr", "de", "es", "pt", "it", "nl", "id", "tr", "pl", "vi"];
+
+/// ISO 639-1 code for the languages we serve or commonly ingest.
+fn iso639_1(lang: Lang) -> Option<&'static str> {
+    Some(match lang {
+        Lang::Jpn => "ja",
+        Lang::Eng => "en",
+        Lang::Kor => "ko",
+        Lang::Cmn => "zh",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Spa => "es",
+        Lang::Por => "pt",
+        Lang::Ita => "it",
+        Lang::Nld => "nl",
+        Lang::Rus => "ru",
+        Lang::Ukr => "uk",
+        Lang::Ara => "ar",
+        Lang::Hin => "hi",
+        Lang::Tha => "th",
+        Lang::Vie => "vi",
+        Lang::Ind => "id",
+        Lang::Tur => "tr",
+        Lang::Pol => "pl",
+        _ => return None,
+    })
+}
+
+/// Primary subtag of a declared language tag, e.g. "ja" for "ja-JP" or "en_US".
+pub fn normalize(tag: &str) -> Option<String> {
+    let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+    let valid = (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_lowercase());
+    valid.then_some(primary)
+}
+
+/// An article's language as an ISO 639-1 code, detected from its title and description
+/// and the language its feed declares, if any.
+///
+/// Unconfident Latin-script guesses go to the declared language when it is written in
+/// Latin script, and otherwise to English: a Japanese feed's headline in Latin letters
+/// ("iOS 18.2") is far more often English than anything else in our pool.
+pub fn article_language(title: &str, description: Option<&str>, declared: Option<&str>) -> Option<String> {
+    let text = match description {
+        Some(d) => format!("{title}\n{d}"),
+        None => title.to_string(),
+    };
+    let declared = declared.and_then(normalize);
+    let Some(info) = whatlang::detect(&text) else {
+        return declared;
+    };
+    if info.script() == Script::Latin && info.confidence() < MIN_LATIN_CONFIDENCE {
+        let latin = declared.filter(|l| LATIN_LANGUAGES.contains(&l.as_str()));
+        return Some(latin.unwrap_or_else(|| "en".into()));
+    }
+    iso639_1(info.lang()).map(String::from).or(declared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(title: &str) -> Option<String> {
+        article_language(title, None, None)
+    }
+
+    #[test]
+    fn detects_japanese_headlines_written_mostly_in_kanji() {
+        assert_eq!(detect("首相が衆院解散を表明").as_deref(), Some("ja"));
+        assert_eq!(detect("日銀、追加利上げを決定 物価見通し引き上げ").as_deref(), Some("ja"));
+    }
+
+    #[test]
+    fn detects_other_scripts_and_confident_latin_languages() {
+        assert_eq!(detect("서울 아파트 가격 상승세 계속").as_deref(), Some("ko"));
+        assert_eq!(
+            detect("Le gouvernement annonce une réforme des retraites pour l'année prochaine").as_deref(),
+            Some("fr")
+        );
+    }
+
+    #[test]
+    fn short_latin_headlines_default_to_english() {
+        assert_eq!(detect("Apple unveils new MacBook Pro with M4 chip").as_deref(), Some("en"));
+        assert_eq!(detect("OpenAI releases GPT-5").as_deref(), Some("en"));
+        // A Japanese feed's Latin-script headline is still not Japanese
+        assert_eq!(article_language("iOS 18.2", None, Some("ja-JP")).as_deref(), Some("en"));
+        // ...but a German feed's is German
+        assert_eq!(article_language("Bundesregierung plant neue Steuer", None, Some("de")).as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn falls_back_to_declared_language_without_text() {
+        assert_eq!(article_language("12345", None, Some("ja")).as_deref(), Some("ja"));
+        assert_eq!(article_language("12345", None, None), None);
+    }
+
+    #[test]
+    fn normalizes_language_tags() {
+        assert_eq!(normalize("ja-JP"), Some("ja".into()));
+        assert_eq!(normalize("en_US"), Some("en".into()));
+        assert_eq!(normalize(" EN "), Some("en".into()));
+        assert_eq!(normalize(""), None);
+        assert_eq!(normalize("english"), None);
+    }
+}
@@ -1,13 +1,27 @@
+pub mod categorize;
 pub mod changes;
 pub mod config;
 pub mod dedup;
+pub mod discovery;
 #[cfg(feature = "dynamo")]
 pub mod dynamo;
 pub mod error;
 pub mod feeds;
+pub mod filters;
 pub mod grouping;
+pub mod health;
+pub mod lang;
 pub mod models;
 pub mod ogp;
+pub mod opml;
+pub mod politeness;
+pub mod schedule;
+pub mod search;
+pub mod sources;
+pub mod store;
+pub mod text;
+#[cfg(test)]
+mod test_server;
 
 pub use error::{AppError, Result};
-pub use models::{Article, ArticlesResponse, Category, CategoryInfo};
+pub use models::{Article, ArticlesResponse, AudioEnclosure, Category, CategoryInfo, PageMetadata};
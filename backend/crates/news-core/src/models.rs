@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use crate::categorize::CategoryLabel;
 use serde::{Deserialize, Serialize};
 
 /// Article categories matching DynamoDB partition keys.
@@ -27,6 +28,7 @@ impl Category {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "general" => Some(Self::General),
@@ -77,6 +79,99 @@ pub struct Article {
     pub group_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub group_count: Option<u32>,
+    /// Page metadata read from the article itself, filled in by OGP enrichment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PageMetadata>,
+    /// Audio the feed attached to the item, e.g. a podcast episode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<AudioEnclosure>,
+    /// Every category the article is filed under, most confident first. The feed's
+    /// `category` is always among them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<CategoryLabel>,
+    /// ISO 639-1 language code detected at ingest, e.g. "ja" or "en".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// Set when the title and description are a machine translation into `lang`: the
+    /// language they were translated from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translated_from: Option<String>,
+    /// When the publisher last changed the title, description or image after we
+    /// first stored the article.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Set once the headline differs from the one first published.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub headline_changed: bool,
+}
+
+/// One version of an article's title, description and image as its publisher had it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArticleRevision {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    /// When we first saw this version.
+    pub seen_at: DateTime<Utc>,
+    /// When the next version replaced it; `None` for the current version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaced_at: Option<DateTime<Utc>>,
+}
+
+/// An `<enclosure>`, `media:content` or JSON Feed attachment with audio.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AudioEnclosure {
+    pub url: String,
+    /// e.g. "audio/mpeg".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+    /// `itunes:episode` number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub episode: Option<u32>,
+}
+
+/// Metadata from an article page's `og:*`, `twitter:*` and JSON-LD `NewsArticle` markup.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+    /// `og:type`, e.g. "article".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub og_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favicon_url: Option<String>,
+    /// `twitter:card`, e.g. "summary_large_image".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub twitter_card: Option<String>,
+    /// `twitter:site` handle of the publisher.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub twitter_site: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<DateTime<Utc>>,
+    /// Publisher's section, e.g. "経済" or "Science".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// The page is behind a paywall, so only the feed teaser is usable.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paywalled: bool,
 }
 
 /// Paginated response for article listing.
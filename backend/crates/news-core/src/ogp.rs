@@ -1,63 +1,219 @@
-use tracing::warn;
+use crate::error::AppError;
+use crate::models::PageMetadata;
+use crate::politeness::{detect_paywall, PageDirectives, PoliteClient};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use encoding_rs::{Encoding, EUC_JP, SHIFT_JIS};
+use regex::Regex;
+use ego_tree::NodeId;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use tracing::{info, warn};
+use url::Url;
 
-/// Extract article body text from HTML (strips scripts/styles, extracts p/h/li text).
-/// Returns up to 3000 chars of meaningful content.
-pub fn extract_article_text(html: &str) -> String {
-    // Remove <script>...</script> and <style>...</style> blocks
-    let re_script = regex::Regex::new(r"(?is)<script[^>]*>.*?</script>").unwrap();
-    let re_style = regex::Regex::new(r"(?is)<style[^>]*>.*?</style>").unwrap();
-    let cleaned = re_script.replace_all(html, "");
-    let cleaned = re_style.replace_all(&cleaned, "");
-
-    // Extract text from <p>, <h1>-<h6>, <li> tags
-    let re_tags = regex::Regex::new(r"(?is)<(?:p|h[1-6]|li)[^>]*>(.*?)</(?:p|h[1-6]|li)>").unwrap();
-    let re_html_tag = regex::Regex::new(r"<[^>]+>").unwrap();
-
-    let mut texts = Vec::new();
-    let mut total_len = 0;
-    for cap in re_tags.captures_iter(&cleaned) {
-        let inner = cap.get(1).map(|m| m.as_str()).unwrap_or("");
-        let text = re_html_tag.replace_all(inner, "");
-        let text = text.trim();
-        if text.is_empty() || text.len() < 5 {
-            continue;
+/// Characters of article text kept for summaries, Q&A and podcast scripts.
+pub const MAX_ARTICLE_CHARS: usize = 3000;
+/// Most of a page that is downloaded for content extraction.
+const MAX_PAGE_BYTES: usize = 1024 * 1024;
+/// Most of a page that is downloaded for its metadata. Covers the <head> and the
+/// JSON-LD most pages put near the top of <body>.
+const MAX_METADATA_BYTES: usize = 64 * 1024;
+
+/// Elements that never contain article text.
+const SKIP_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "footer", "aside", "form", "iframe", "svg",
+    "button", "select", "textarea", "head",
+];
+/// Elements whose text is emitted as one paragraph.
+const BLOCK_TAGS: &[&str] = &["p", "h1", "h2", "h3", "h4", "h5", "h6", "li", "blockquote", "pre"];
+/// Paragraphs shorter than this (in chars) don't count towards a container's score.
+const MIN_SCORED_CHARS: usize = 25;
+
+/// class/id hints for boilerplate (navigation, sharing widgets, related links, ads).
+static UNLIKELY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)comment|footer|\bfoot\b|\bnav|menu|sidebar|\bside\b|share|social|\bsns\b|related|recommend|ranking|banner|breadcrumb|promo|advert|\bads?\b|\bad-|popup|masthead|pager|pagination|copyright|\btags?\b",
+    )
+    .unwrap()
+});
+/// class/id hints for the article body, including common Japanese CMS names (honbun, kiji).
+static POSITIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)article|body|content|entry|main|post|story|text|honbun|kiji").unwrap()
+});
+static BYLINE_HINT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)byline|author|writer").unwrap());
+static DATE_HINT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)date|time|update|publish").unwrap());
+static JA_DATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\d{4})年\s*(\d{1,2})月\s*(\d{1,2})日(?:[^\d]{0,6}(\d{1,2})[時:](\d{1,2}))?").unwrap()
+});
+static META_CHARSET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)(?:<meta[^>]+charset|<\?xml[^>]+encoding)\s*=\s*["']?\s*([a-z0-9_\-]+)"#).unwrap()
+});
+
+/// Article content and metadata extracted from a page.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractedArticle {
+    pub title: Option<String>,
+    /// Main body text, one paragraph per line, at most [`MAX_ARTICLE_CHARS`].
+    pub text: String,
+    pub byline: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub lead_image: Option<String>,
+    /// Opt-outs found on the page; only set by [`fetch_article`].
+    pub directives: PageDirectives,
+    pub paywalled: bool,
+}
+
+/// Decode a page body to text. The charset comes from a BOM, the `Content-Type` header or
+/// `<meta charset>` (the `<?xml encoding>` declaration for XML), in that order. Undeclared pages that aren't valid UTF-8 are tried as
+/// Shift_JIS and EUC-JP before falling back to lossy UTF-8.
+pub fn decode_html(bytes: &[u8], content_type: Option<&str>) -> String {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding.decode(bytes).0.into_owned();
+    }
+
+    let header_label = content_type.and_then(|ct| {
+        ct.split(';')
+            .filter_map(|part| part.trim().strip_prefix("charset="))
+            .next()
+            .map(|l| l.trim_matches(|c| c == '"' || c == '\'').to_string())
+    });
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]);
+    let meta_label = META_CHARSET
+        .captures(&head)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string());
+    let declared = header_label
+        .iter()
+        .chain(meta_label.iter())
+        .find_map(|label| Encoding::for_label(label.as_bytes()));
+    if let Some(encoding) = declared {
+        return encoding.decode_without_bom_handling(bytes).0.into_owned();
+    }
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+    for encoding in [SHIFT_JIS, EUC_JP] {
+        if let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+            return text.into_owned();
         }
-        let decoded = text
-            .replace("&amp;", "&")
-            .replace("&lt;", "<")
-            .replace("&gt;", ">")
-            .replace("&quot;", "\"")
-            .replace("&#39;", "'")
-            .replace("&nbsp;", " ");
-        let decoded = decoded.trim().to_string();
-        if decoded.is_empty() {
+    }
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Extract the main article from a page: the container whose paragraphs score highest on
+/// text length and punctuation, discounted by link density, plus title, byline, publish
+/// date and lead image. `base_url` resolves relative image URLs.
+pub fn extract_article(html: &str, base_url: &str) -> ExtractedArticle {
+    let doc = Html::parse_document(html);
+    let base = Url::parse(base_url).ok();
+
+    let kept = Kept::collect(doc.root_element());
+
+    // Candidates in the order they were first scored, so ties go the same way every time
+    let mut candidates: Vec<ElementRef> = Vec::new();
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+    for el in kept.order.iter().filter(|e| matches!(e.value().name(), "p" | "pre" | "blockquote")) {
+        let text = kept.text(*el);
+        let len = text.chars().count();
+        if len < MIN_SCORED_CHARS {
             continue;
         }
-        total_len += decoded.len();
-        texts.push(decoded);
-        if total_len >= 3000 {
-            break;
+        let commas = text.chars().filter(|c| matches!(c, ',' | '、' | '，' | '。')).count();
+        let score = 1.0 + commas as f64 + (len as f64 / 100.0).min(3.0);
+
+        let ancestors = el.ancestors().filter_map(ElementRef::wrap).take(2);
+        for (level, ancestor) in ancestors.enumerate() {
+            let share = if level == 0 { score } else { score / 2.0 };
+            *scores.entry(ancestor.id()).or_insert_with(|| {
+                candidates.push(ancestor);
+                initial_score(ancestor)
+            }) += share;
+        }
+    }
+    for (id, score) in scores.iter_mut() {
+        *score *= 1.0 - kept.stats.get(id).map_or(0.0, NodeStats::link_density);
+    }
+
+    let top = candidates
+        .iter()
+        .map(|el| (*el, scores[&el.id()]))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    let (top, top_score) = match top {
+        Some(t) => t,
+        None => {
+            let body = Selector::parse("body").unwrap();
+            (doc.select(&body).next().unwrap_or(doc.root_element()), 0.0)
+        }
+    };
+
+    // Readability's sibling step: pull in neighbouring blocks that also look like content
+    let threshold = (top_score * 0.2).max(10.0);
+    let mut sections = Vec::new();
+    match top.parent().filter(|_| top_score > 0.0) {
+        Some(parent) => {
+            for sibling in parent.children().filter_map(ElementRef::wrap) {
+                if !kept.contains(sibling) {
+                    continue;
+                }
+                let include = sibling == top
+                    || scores.get(&sibling.id()).is_some_and(|s| *s >= threshold)
+                    || (sibling.value().name() == "p" && kept.is_content_paragraph(sibling));
+                if include {
+                    sections.push(sibling);
+                }
+            }
         }
+        None => sections.push(top),
     }
 
-    let mut result = texts.join("\n");
-    if result.len() > 3000 {
-        // Truncate at char boundary
-        let mut end = 3000;
-        while end > 0 && !result.is_char_boundary(end) {
-            end -= 1;
+    let mut paragraphs: Vec<String> = Vec::new();
+    for section in sections {
+        for el in kept.outermost_blocks(section) {
+            let text = kept.text(el);
+            if text.chars().count() < 5 || kept.link_density(el) > 0.5 {
+                continue;
+            }
+            if paragraphs.last().map(String::as_str) != Some(text) {
+                paragraphs.push(text.to_string());
+            }
         }
-        result.truncate(end);
     }
-    result
+
+    let meta = metadata_from_doc(&doc, base.as_ref());
+    ExtractedArticle {
+        title: meta.title.or_else(|| extract_title(&doc)),
+        text: truncate_chars(&paragraphs.join("\n"), MAX_ARTICLE_CHARS),
+        byline: meta.author.or_else(|| extract_byline(&doc)),
+        published_at: meta.published_at.or_else(|| extract_published_at(&doc)),
+        lead_image: meta.image_url.or_else(|| first_image(top, base.as_ref())),
+        ..Default::default()
+    }
+}
+
+/// Extract article body text from HTML, dropping navigation, sidebars and footers.
+/// Returns up to [`MAX_ARTICLE_CHARS`] characters, one paragraph per line.
+pub fn extract_article_text(html: &str) -> String {
+    extract_article(html, "").text
 }
 
-/// Fetch article content from a URL. Returns None on failure or empty content.
-pub async fn fetch_article_content(client: &reqwest::Client, url: &str) -> Option<String> {
-    let response = match client.get(url).send().await {
+/// Fetch a publisher page through the politeness layer and decode it.
+/// Returns the HTML and the page's opt-out directives, or None on any failure.
+async fn fetch_html(
+    client: &PoliteClient,
+    url: &str,
+    max_bytes: usize,
+) -> Option<(String, PageDirectives)> {
+    let response = match client.get(url).await {
         Ok(r) => r,
+        Err(AppError::Disallowed(reason)) => {
+            info!(url = %url, reason = %reason, "Skipping page fetch");
+            return None;
+        }
         Err(e) => {
-            warn!(url = %url, error = %e, "Failed to fetch article content");
+            warn!(url = %url, error = %e, "Failed to fetch page");
             return None;
         }
     };
@@ -66,19 +222,363 @@ pub async fn fetch_article_content(client: &reqwest::Client, url: &str) -> Optio
         return None;
     }
 
-    // Read up to 256KB
-    let bytes = match response.bytes().await {
+    let header = |name: reqwest::header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    let content_type = header(reqwest::header::CONTENT_TYPE);
+    let robots_tag = header(reqwest::header::HeaderName::from_static("x-robots-tag"));
+    let bytes = match response.bytes(max_bytes).await {
         Ok(b) => b,
         Err(_) => return None,
     };
+    let html = decode_html(&bytes, content_type.as_deref());
+    let directives = PageDirectives::from_page(&html, robots_tag.as_deref());
+    Some((html, directives))
+}
+
+/// Fetch and extract an article. Returns None on failure.
+pub async fn fetch_article(client: &PoliteClient, url: &str) -> Option<ExtractedArticle> {
+    let (html, directives) = fetch_html(client, url, MAX_PAGE_BYTES).await?;
+    Some(ExtractedArticle {
+        directives,
+        paywalled: detect_paywall(&html),
+        ..extract_article(&html, url)
+    })
+}
 
-    let html = String::from_utf8_lossy(&bytes[..bytes.len().min(262144)]);
-    let text = extract_article_text(&html);
-    if text.is_empty() {
+/// Fetch article content from a URL for AI features. Returns None on failure, empty
+/// content, a paywall, or when the publisher opts out with noarchive/nosnippet/noai.
+pub async fn fetch_article_content(client: &PoliteClient, url: &str) -> Option<String> {
+    let article = fetch_article(client, url).await?;
+    if article.paywalled || !article.directives.allows_full_text() {
+        info!(url = %url, paywalled = article.paywalled, "Full text not usable");
+        return None;
+    }
+    if article.text.is_empty() {
         None
     } else {
-        Some(text)
+        Some(article.text)
+    }
+}
+
+/// Text measurements of a kept element.
+#[derive(Debug, Default)]
+struct NodeStats {
+    /// Non-whitespace characters of text outside skipped subtrees.
+    chars: usize,
+    /// Of those, the characters inside links.
+    linked: usize,
+    /// Whitespace-collapsed text, kept for block elements only.
+    text: Option<String>,
+}
+
+impl NodeStats {
+    /// Share of the element's text that sits inside links.
+    fn link_density(&self) -> f64 {
+        if self.chars == 0 {
+            return 0.0;
+        }
+        (self.linked as f64 / self.chars as f64).min(1.0)
+    }
+}
+
+/// The elements of a page outside skipped or boilerplate subtrees, each measured once.
+struct Kept<'a> {
+    /// Depth-first, in document order.
+    order: Vec<ElementRef<'a>>,
+    stats: HashMap<NodeId, NodeStats>,
+}
+
+impl<'a> Kept<'a> {
+    fn collect(root: ElementRef<'a>) -> Self {
+        let mut kept = Kept {
+            order: Vec::new(),
+            stats: HashMap::new(),
+        };
+        kept.measure(root);
+        kept
+    }
+
+    /// Record `el` and its kept descendants, returning its (chars, linked) counts.
+    fn measure(&mut self, el: ElementRef<'a>) -> (usize, usize) {
+        if is_removed(el) {
+            return (0, 0);
+        }
+        self.order.push(el);
+        let (mut chars, mut linked) = (0, 0);
+        for child in el.children() {
+            match child.value() {
+                Node::Text(t) => chars += t.chars().filter(|c| !c.is_whitespace()).count(),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        let (c, l) = self.measure(child);
+                        chars += c;
+                        linked += l;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if el.value().name() == "a" {
+            linked = chars;
+        }
+        let text = BLOCK_TAGS.contains(&el.value().name()).then(|| inner_text(el));
+        self.stats.insert(el.id(), NodeStats { chars, linked, text });
+        (chars, linked)
+    }
+
+    fn contains(&self, el: ElementRef) -> bool {
+        self.stats.contains_key(&el.id())
+    }
+
+    /// Text of a kept block element; empty for anything else.
+    fn text(&self, el: ElementRef) -> &str {
+        self.stats.get(&el.id()).and_then(|s| s.text.as_deref()).unwrap_or_default()
+    }
+
+    fn link_density(&self, el: ElementRef) -> f64 {
+        self.stats.get(&el.id()).map_or(0.0, NodeStats::link_density)
+    }
+
+    fn is_content_paragraph(&self, el: ElementRef) -> bool {
+        let text = self.text(el);
+        let len = text.chars().count();
+        let density = self.link_density(el);
+        (len >= 80 && density < 0.25)
+            || (len > 0 && density == 0.0 && (text.contains('。') || text.contains(". ")))
+    }
+
+    /// Kept block elements under `root` (or `root` itself) that are not nested in another block.
+    fn outermost_blocks(&self, root: ElementRef<'a>) -> Vec<ElementRef<'a>> {
+        if BLOCK_TAGS.contains(&root.value().name()) {
+            return vec![root];
+        }
+        let mut out = Vec::new();
+        for child in root.children().filter_map(ElementRef::wrap) {
+            if self.contains(child) {
+                out.extend(self.outermost_blocks(child));
+            }
+        }
+        out
+    }
+}
+
+fn is_removed(el: ElementRef) -> bool {
+    let v = el.value();
+    let name = v.name();
+    if SKIP_TAGS.contains(&name) || v.attr("hidden").is_some() || v.attr("aria-hidden") == Some("true") {
+        return true;
+    }
+    if matches!(name, "html" | "body" | "article" | "main") {
+        return false;
+    }
+    let hints = class_and_id(el);
+    UNLIKELY.is_match(&hints) && !POSITIVE.is_match(&hints)
+}
+
+fn class_and_id(el: ElementRef) -> String {
+    let v = el.value();
+    format!("{} {}", v.attr("class").unwrap_or(""), v.attr("id").unwrap_or(""))
+}
+
+fn initial_score(el: ElementRef) -> f64 {
+    let tag = match el.value().name() {
+        "article" => 10.0,
+        "div" | "section" | "main" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "address" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    let v = el.value();
+    let weight: f64 = [v.attr("class"), v.attr("id")]
+        .into_iter()
+        .flatten()
+        .map(|hint| {
+            let mut w = 0.0;
+            if UNLIKELY.is_match(hint) {
+                w -= 25.0;
+            }
+            if POSITIVE.is_match(hint) {
+                w += 25.0;
+            }
+            w
+        })
+        .sum();
+    tag + weight
+}
+
+/// Text of an element with skipped subtrees removed and whitespace collapsed.
+fn inner_text(el: ElementRef) -> String {
+    fn walk(el: ElementRef, out: &mut String) {
+        for child in el.children() {
+            match child.value() {
+                Node::Text(t) => out.push_str(t),
+                Node::Element(e) if e.name() == "br" => out.push(' '),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        if !is_removed(child) {
+                            walk(child, out);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut raw = String::new();
+    walk(el, &mut raw);
+    raw.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text.to_string(),
+    }
+}
+
+fn meta_content(doc: &Html, selector: &str) -> Option<String> {
+    let sel = Selector::parse(selector).ok()?;
+    doc.select(&sel)
+        .filter_map(|m| m.value().attr("content"))
+        .map(|c| c.trim().to_string())
+        .find(|c| !c.is_empty())
+}
+
+fn extract_title(doc: &Html) -> Option<String> {
+    meta_content(doc, r#"meta[property="og:title"]"#).or_else(|| {
+        ["title", "h1"].iter().find_map(|tag| {
+            let sel = Selector::parse(tag).unwrap();
+            doc.select(&sel)
+                .map(inner_text)
+                .find(|t| !t.is_empty())
+        })
+    })
+}
+
+fn extract_byline(doc: &Html) -> Option<String> {
+    let from_meta = [r#"meta[name="author"]"#, r#"meta[property="article:author"]"#]
+        .iter()
+        .filter_map(|sel| meta_content(doc, sel))
+        .find(|c| !c.starts_with("http"));
+    if from_meta.is_some() {
+        return from_meta.map(|b| clean_byline(&b));
+    }
+
+    let linked = Selector::parse(r#"[rel~="author"], [itemprop~="author"]"#).unwrap();
+    let all = Selector::parse("[class], [id]").unwrap();
+    let hinted = |el: &ElementRef| BYLINE_HINT.is_match(&class_and_id(*el));
+    // The innermost hinted element, so a "byline" wrapper doesn't drag in the date next to it
+    let innermost = |el: &ElementRef| {
+        !el.descendants()
+            .skip(1)
+            .filter_map(ElementRef::wrap)
+            .any(|d| hinted(&d))
+    };
+    doc.select(&linked)
+        .chain(doc.select(&all).filter(hinted).filter(innermost))
+        .map(|el| clean_byline(&inner_text(el)))
+        .find(|b| (2..=100).contains(&b.chars().count()))
+}
+
+fn clean_byline(raw: &str) -> String {
+    let b = raw.trim().trim_matches(|c| matches!(c, '[' | ']' | '［' | '］'));
+    let b = ["By ", "by ", "BY ", "文：", "文・", "文/"]
+        .iter()
+        .find_map(|prefix| b.strip_prefix(prefix))
+        .unwrap_or(b);
+    b.trim().to_string()
+}
+
+fn extract_published_at(doc: &Html) -> Option<DateTime<Utc>> {
+    let metas = [
+        r#"meta[property="article:published_time"]"#,
+        r#"meta[itemprop="datePublished"]"#,
+        r#"meta[name="pubdate"]"#,
+        r#"meta[name="publishdate"]"#,
+        r#"meta[name="date"]"#,
+        r#"meta[name="DC.date.issued"]"#,
+    ];
+    if let Some(t) = metas
+        .iter()
+        .filter_map(|sel| meta_content(doc, sel))
+        .find_map(|c| parse_date(&c))
+    {
+        return Some(t);
+    }
+
+    let attrs = Selector::parse(r#"[itemprop="datePublished"], time[datetime]"#).unwrap();
+    let all = Selector::parse("[class], [id]").unwrap();
+    doc.select(&attrs)
+        .filter_map(|el| el.value().attr("datetime").or(el.value().attr("content")).map(str::to_string))
+        .chain(
+            doc.select(&all)
+                .filter(|el| DATE_HINT.is_match(&class_and_id(*el)))
+                .map(inner_text),
+        )
+        .find_map(|s| parse_date(&s))
+}
+
+/// Parse the date formats seen on news pages. Times without an offset are taken as UTC,
+/// except Japanese `年月日` dates, which are JST.
+pub fn parse_date(raw: &str) -> Option<DateTime<Utc>> {
+    let s = raw.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&Utc));
+    }
+    if let Ok(t) = DateTime::parse_from_rfc2822(s) {
+        return Some(t.with_timezone(&Utc));
     }
+    for fmt in ["%Y-%m-%dT%H:%M:%S%z", "%Y-%m-%dT%H:%M%z", "%Y-%m-%d %H:%M:%S%z"] {
+        if let Ok(t) = DateTime::parse_from_str(s, fmt) {
+            return Some(t.with_timezone(&Utc));
+        }
+    }
+    for fmt in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y/%m/%d %H:%M"] {
+        if let Ok(t) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Some(t.and_utc());
+        }
+    }
+    for fmt in ["%Y-%m-%d", "%Y/%m/%d"] {
+        if let Ok(d) = NaiveDate::parse_from_str(s, fmt) {
+            return d.and_hms_opt(0, 0, 0).map(|t| t.and_utc());
+        }
+    }
+
+    let caps = JA_DATE.captures(s)?;
+    let num = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<u32>().ok());
+    let date = NaiveDate::from_ymd_opt(num(1)? as i32, num(2)?, num(3)?)?;
+    let time = date.and_hms_opt(num(4).unwrap_or(0), num(5).unwrap_or(0), 0)?;
+    let jst = FixedOffset::east_opt(9 * 3600)?;
+    Some(time.and_local_timezone(jst).single()?.with_timezone(&Utc))
+}
+
+fn first_image(root: ElementRef, base: Option<&Url>) -> Option<String> {
+    let img = Selector::parse("img").unwrap();
+    root.select(&img).find_map(|el| {
+        let v = el.value();
+        let too_small = ["width", "height"]
+            .iter()
+            .filter_map(|a| v.attr(a)?.parse::<u32>().ok())
+            .any(|px| px < 100);
+        if too_small {
+            return None;
+        }
+        let src = v.attr("data-src").or(v.attr("src"))?.trim();
+        if src.starts_with("data:") {
+            return None;
+        }
+        let resolved = match base {
+            Some(base) => base.join(src).ok()?,
+            None => Url::parse(src).ok()?,
+        };
+        Some(resolved.to_string())
+    })
 }
 
 /// Extract og:image URL from HTML content using regex (lightweight, no scraper crate).
@@ -106,28 +606,252 @@ pub fn extract_og_image(html: &str) -> Option<String> {
     None
 }
 
-/// Fetch og:image from a URL. Returns None on any failure.
-pub async fn fetch_og_image(client: &reqwest::Client, url: &str) -> Option<String> {
-    let response = match client.get(url).send().await {
-        Ok(r) => r,
-        Err(e) => {
-            warn!(url = %url, error = %e, "Failed to fetch page for OGP");
-            return None;
+/// Extract the page's canonical URL from `<link rel="canonical">`, falling back to `og:url`.
+/// Relative canonicals are resolved against `base`.
+pub fn extract_canonical_url(html: &str, base: &str) -> Option<String> {
+    let patterns = [
+        r#"(?i)<link[^>]+rel\s*=\s*["']canonical["'][^>]+href\s*=\s*["']([^"']+)["']"#,
+        r#"(?i)<link[^>]+href\s*=\s*["']([^"']+)["'][^>]+rel\s*=\s*["']canonical["']"#,
+        r#"<meta[^>]+property\s*=\s*["']og:url["'][^>]+content\s*=\s*["']([^"']+)["']"#,
+        r#"<meta[^>]+content\s*=\s*["']([^"']+)["'][^>]+property\s*=\s*["']og:url["']"#,
+    ];
+    let base = Url::parse(base).ok();
+    for pattern in &patterns {
+        let Ok(re) = regex::Regex::new(pattern) else {
+            continue;
+        };
+        let Some(found) = re.captures(html).and_then(|c| c.get(1)) else {
+            continue;
+        };
+        let href = found.as_str().trim().replace("&amp;", "&");
+        let resolved = match &base {
+            Some(base) => base.join(&href).ok(),
+            None => Url::parse(&href).ok(),
+        };
+        if let Some(u) = resolved.filter(|u| matches!(u.scheme(), "http" | "https")) {
+            return Some(u.to_string());
         }
+    }
+    None
+}
+
+/// Extract `og:*`, `twitter:*`, JSON-LD `NewsArticle`, favicon and canonical URL from a page.
+/// JSON-LD wins over Open Graph, which wins over Twitter cards. `base_url` resolves relative URLs.
+pub fn extract_page_metadata(html: &str, base_url: &str) -> PageMetadata {
+    let doc = Html::parse_document(html);
+    metadata_from_doc(&doc, Url::parse(base_url).ok().as_ref())
+}
+
+fn metadata_from_doc(doc: &Html, base: Option<&Url>) -> PageMetadata {
+    let og = |prop: &str| meta_content(doc, &format!(r#"meta[property="{prop}"]"#));
+    let twitter = |name: &str| {
+        meta_content(doc, &format!(r#"meta[name="{name}"]"#))
+            .or_else(|| meta_content(doc, &format!(r#"meta[property="{name}"]"#)))
+    };
+    let resolve = |raw: String| -> Option<String> {
+        let resolved = match base {
+            Some(base) => base.join(raw.trim()).ok()?,
+            None => Url::parse(raw.trim()).ok()?,
+        };
+        matches!(resolved.scheme(), "http" | "https").then(|| resolved.to_string())
     };
+    let ld = json_ld_article(doc).unwrap_or_default();
 
-    if !response.status().is_success() {
-        return None;
-    }
+    let favicon = Selector::parse(r#"link[rel][href]"#).unwrap();
+    let favicon_url = doc
+        .select(&favicon)
+        .filter(|l| {
+            let rel = l.value().attr("rel").unwrap_or("").to_ascii_lowercase();
+            rel.split_whitespace().any(|r| r == "icon") || rel == "apple-touch-icon"
+        })
+        .filter_map(|l| l.value().attr("href").map(str::to_string))
+        .find_map(resolve)
+        .or_else(|| base.and_then(|b| b.join("/favicon.ico").ok()).map(|u| u.to_string()));
 
-    // Only read first 64KB to find og:image (it's in <head>)
-    let bytes = match response.bytes().await {
-        Ok(b) => b,
-        Err(_) => return None,
+    let canonical = Selector::parse(r#"link[rel="canonical"][href]"#).unwrap();
+    let canonical_url = doc
+        .select(&canonical)
+        .filter_map(|l| l.value().attr("href").map(str::to_string))
+        .find_map(resolve)
+        .or_else(|| og("og:url").and_then(resolve));
+
+    let keywords = if ld.keywords.is_empty() {
+        meta_content(doc, r#"meta[name="keywords"]"#)
+            .map(|k| split_keywords(&k))
+            .unwrap_or_default()
+    } else {
+        ld.keywords
     };
 
-    let html = String::from_utf8_lossy(&bytes[..bytes.len().min(65536)]);
-    extract_og_image(&html)
+    PageMetadata {
+        title: ld.headline.or_else(|| og("og:title")).or_else(|| twitter("twitter:title")),
+        description: og("og:description")
+            .or(ld.description)
+            .or_else(|| twitter("twitter:description"))
+            .or_else(|| meta_content(doc, r#"meta[name="description"]"#)),
+        image_url: ld
+            .image
+            .into_iter()
+            .chain(og("og:image"))
+            .chain(og("og:image:secure_url"))
+            .chain(twitter("twitter:image"))
+            .chain(twitter("twitter:image:src"))
+            .find_map(resolve),
+        site_name: og("og:site_name").or(ld.publisher),
+        og_type: og("og:type"),
+        canonical_url,
+        favicon_url,
+        twitter_card: twitter("twitter:card"),
+        twitter_site: twitter("twitter:site"),
+        author: ld
+            .author
+            .or_else(|| meta_content(doc, r#"meta[name="author"]"#))
+            .or_else(|| og("article:author"))
+            .filter(|a| !a.starts_with("http"))
+            .or_else(|| twitter("twitter:creator")),
+        published_at: ld
+            .date_published
+            .as_deref()
+            .and_then(parse_date)
+            .or_else(|| og("article:published_time").as_deref().and_then(parse_date)),
+        modified_at: ld
+            .date_modified
+            .as_deref()
+            .and_then(parse_date)
+            .or_else(|| og("article:modified_time").as_deref().and_then(parse_date)),
+        section: ld.section.or_else(|| og("article:section")),
+        keywords,
+        paywalled: false,
+    }
+}
+
+/// Fields of a JSON-LD `NewsArticle` (or `Article`, `BlogPosting`, ...) block.
+#[derive(Debug, Default)]
+struct JsonLdArticle {
+    headline: Option<String>,
+    description: Option<String>,
+    author: Option<String>,
+    publisher: Option<String>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    section: Option<String>,
+    keywords: Vec<String>,
+    image: Option<String>,
+}
+
+const ARTICLE_TYPES: &[&str] = &[
+    "NewsArticle",
+    "Article",
+    "ReportageNewsArticle",
+    "AnalysisNewsArticle",
+    "BlogPosting",
+    "Report",
+];
+
+fn json_ld_article(doc: &Html) -> Option<JsonLdArticle> {
+    let scripts = Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
+    doc.select(&scripts)
+        .filter_map(|s| serde_json::from_str::<serde_json::Value>(&s.text().collect::<String>()).ok())
+        .find_map(|v| find_article_node(&v).map(json_ld_fields))
+}
+
+/// Depth-first search through arrays and `@graph` for a node with an article `@type`.
+fn find_article_node(v: &serde_json::Value) -> Option<&serde_json::Value> {
+    match v {
+        serde_json::Value::Array(items) => items.iter().find_map(find_article_node),
+        serde_json::Value::Object(map) => {
+            let is_article = match map.get("@type") {
+                Some(serde_json::Value::String(t)) => ARTICLE_TYPES.contains(&t.as_str()),
+                Some(serde_json::Value::Array(ts)) => ts
+                    .iter()
+                    .any(|t| t.as_str().is_some_and(|t| ARTICLE_TYPES.contains(&t))),
+                _ => false,
+            };
+            if is_article {
+                Some(v)
+            } else {
+                map.get("@graph").and_then(find_article_node)
+            }
+        }
+        _ => None,
+    }
+}
+
+fn json_ld_fields(node: &serde_json::Value) -> JsonLdArticle {
+    let text = |key: &str| json_ld_text(node.get(key)?);
+    JsonLdArticle {
+        headline: text("headline").or_else(|| text("name")),
+        description: text("description"),
+        author: node.get("author").and_then(json_ld_names),
+        publisher: node.get("publisher").and_then(json_ld_names),
+        date_published: text("datePublished"),
+        date_modified: text("dateModified"),
+        section: node.get("articleSection").and_then(json_ld_names),
+        keywords: match node.get("keywords") {
+            Some(serde_json::Value::String(k)) => split_keywords(k),
+            Some(serde_json::Value::Array(ks)) => ks
+                .iter()
+                .filter_map(json_ld_text)
+                .flat_map(|k| split_keywords(&k))
+                .collect(),
+            _ => Vec::new(),
+        },
+        image: node.get("image").and_then(json_ld_url),
+    }
+}
+
+/// A non-empty string, or the `name` of an object.
+fn json_ld_text(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        serde_json::Value::Object(map) => map.get("name").and_then(json_ld_text),
+        _ => None,
+    }
+}
+
+/// One name, or several joined with ", " (multiple authors or sections).
+fn json_ld_names(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::Array(items) => {
+            let names: Vec<String> = items.iter().filter_map(json_ld_text).collect();
+            (!names.is_empty()).then(|| names.join(", "))
+        }
+        other => json_ld_text(other),
+    }
+}
+
+/// `image` may be a URL, an `ImageObject`, or a list of either.
+fn json_ld_url(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Object(map) => map.get("url").and_then(json_ld_url),
+        serde_json::Value::Array(items) => items.iter().find_map(json_ld_url),
+        _ => None,
+    }
+}
+
+fn split_keywords(raw: &str) -> Vec<String> {
+    raw.split([',', '、', '，'])
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+/// Fetch a page once and extract its metadata. A `nosnippet` page's description is dropped.
+/// Returns None on any failure.
+pub async fn fetch_page_metadata(client: &PoliteClient, url: &str) -> Option<PageMetadata> {
+    let (html, directives) = fetch_html(client, url, MAX_METADATA_BYTES).await?;
+    let mut metadata = extract_page_metadata(&html, url);
+    if directives.nosnippet {
+        metadata.description = None;
+    }
+    metadata.paywalled = detect_paywall(&html);
+    Some(metadata)
+}
+
+/// Fetch og:image from a URL. Returns None on any failure.
+pub async fn fetch_og_image(client: &PoliteClient, url: &str) -> Option<String> {
+    fetch_page_metadata(client, url).await?.image_url
 }
 
 #[cfg(test)]
@@ -219,4 +943,187 @@ mod tests {
         let text = extract_article_text(&html);
         assert!(text.len() <= 3000);
     }
+
+    #[test]
+    fn canonical_from_link_or_og_url() {
+        let html = r#"<link rel="canonical" href="https://example.com/news/1">
+        <meta property="og:url" content="https://example.com/other">"#;
+        assert_eq!(
+            extract_canonical_url(html, "https://example.com/amp/news/1"),
+            Some("https://example.com/news/1".into())
+        );
+
+        let html = r#"<meta content="https://example.com/news/2" property="og:url" />"#;
+        assert_eq!(
+            extract_canonical_url(html, "https://m.example.com/news/2"),
+            Some("https://example.com/news/2".into())
+        );
+    }
+
+    #[test]
+    fn relative_canonical_is_resolved() {
+        let html = r#"<link href="/news/3?a=1&amp;b=2" rel="canonical">"#;
+        assert_eq!(
+            extract_canonical_url(html, "https://example.com/amp/news/3"),
+            Some("https://example.com/news/3?a=1&b=2".into())
+        );
+        assert_eq!(extract_canonical_url("<html></html>", "https://example.com/"), None);
+    }
+
+    fn fixture(name: &str, url: &str, content_type: Option<&str>) -> ExtractedArticle {
+        let path = format!("{}/fixtures/articles/{}", env!("CARGO_MANIFEST_DIR"), name);
+        let bytes = std::fs::read(&path).unwrap();
+        extract_article(&decode_html(&bytes, content_type), url)
+    }
+
+    #[test]
+    fn fixture_nhk() {
+        let a = fixture(
+            "nhk.html",
+            "https://www3.nhk.or.jp/news/html/20241122/k10014646531000.html",
+            Some("text/html"),
+        );
+        assert_eq!(a.title.as_deref(), Some("政府 経済対策を閣議決定 物価高への対応柱に"));
+        assert!(a.text.contains("電気・ガス料金の補助を来年1月から3月まで再開"), "{}", a.text);
+        assert!(a.text.contains("臨時国会に提出する方針です。"), "{}", a.text);
+        for boilerplate in ["アクセスランキング", "関連ニュース", "Copyright", "ポスト", "経済\n"] {
+            assert!(!a.text.contains(boilerplate), "{boilerplate} in {}", a.text);
+        }
+        assert_eq!(a.published_at, Some("2024-11-22T03:05:00Z".parse().unwrap()));
+        assert!(a.lead_image.unwrap().ends_with("_01_02.jpg"));
+    }
+
+    #[test]
+    fn fixture_itmedia_shift_jis() {
+        let a = fixture(
+            "itmedia.html",
+            "https://www.itmedia.co.jp/news/articles/2405/10/news150.html",
+            None,
+        );
+        assert!(a.text.starts_with("調査会社のMM総研は5月10日"), "{}", a.text);
+        // Numeric entity and &amp; decoded
+        assert!(a.text.contains("化粧品のように"), "{}", a.text);
+        assert!(a.text.contains("押し上げる」&と分析"), "{}", a.text);
+        assert!(!a.text.contains("関連記事"));
+        assert!(!a.text.contains("NEWS トップに戻る"));
+        assert_eq!(a.byline.as_deref(), Some("井上輝一，ITmedia"));
+        assert_eq!(a.published_at, Some("2024-05-10T03:00:00Z".parse().unwrap()));
+        assert_eq!(
+            a.lead_image.as_deref(),
+            Some("https://image.itmedia.co.jp/news/articles/2405/10/yu_mm1.jpg")
+        );
+    }
+
+    #[test]
+    fn fixture_ars_technica() {
+        let a = fixture(
+            "ars_technica.html",
+            "https://arstechnica.com/space/2024/10/europa-clipper-launch/",
+            Some("text/html; charset=UTF-8"),
+        );
+        assert_eq!(
+            a.title.as_deref(),
+            Some("NASA\u{2019}s Europa Clipper is on its way to Jupiter\u{2019}s icy moon")
+        );
+        assert!(a.text.contains("A SpaceX Falcon Heavy rocket launched NASA\u{2019}s Europa Clipper"));
+        assert!(a.text.contains("in 2030 & make 49 close flybys"));
+        assert!(a.text.contains("\u{201c}We\u{2019}re not going to find life,\u{201d}"));
+        for boilerplate in ["Advertisement", "Reader comments", "Most Read", "Further reading", "Share on", "Cond"] {
+            assert!(!a.text.contains(boilerplate), "{boilerplate} in {}", a.text);
+        }
+        assert_eq!(a.byline.as_deref(), Some("Stephen Clark"));
+        assert_eq!(a.published_at, Some("2024-10-14T16:35:12Z".parse().unwrap()));
+    }
+
+    #[test]
+    fn charset_detection() {
+        let (sjis, _, _) = SHIFT_JIS.encode("<p>日本語のテキストです。</p>");
+        assert_eq!(decode_html(&sjis, None), "<p>日本語のテキストです。</p>");
+        let (euc, _, _) = EUC_JP.encode("<p>東京都</p>");
+        assert_eq!(decode_html(&euc, Some("text/html; charset=EUC-JP")), "<p>東京都</p>");
+        let page = r#"<meta http-equiv="Content-Type" content="text/html; charset=EUC-JP"><p>陸上</p>"#;
+        let (euc_page, _, _) = EUC_JP.encode(page);
+        assert_eq!(decode_html(&euc_page, None), page);
+        assert_eq!(decode_html("<p>é</p>".as_bytes(), None), "<p>é</p>");
+    }
+
+    #[test]
+    fn parses_common_date_formats() {
+        let expected: DateTime<Utc> = "2024-03-01T00:30:00Z".parse().unwrap();
+        for raw in [
+            "2024-03-01T09:30:00+09:00",
+            "2024-03-01T09:30:00+0900",
+            "Fri, 01 Mar 2024 00:30:00 GMT",
+            "2024-03-01 00:30:00",
+            "2024年3月1日 9時30分",
+            "2024年03月01日 09:30 更新",
+        ] {
+            assert_eq!(parse_date(raw), Some(expected), "{raw}");
+        }
+        assert_eq!(
+            parse_date("2024/03/01"),
+            Some("2024-03-01T00:00:00Z".parse().unwrap())
+        );
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn page_metadata_prefers_json_ld() {
+        let html = r#"<html><head>
+        <meta property="og:title" content="OG title">
+        <meta property="og:description" content="OG description">
+        <meta property="og:image" content="https://example.com/og.jpg">
+        <meta property="og:site_name" content="Example News">
+        <meta property="og:type" content="article">
+        <meta name="twitter:card" content="summary_large_image">
+        <meta name="twitter:site" content="@example">
+        <meta name="twitter:image" content="https://example.com/tw.jpg">
+        <link rel="icon" href="/static/icon.png">
+        <link rel="canonical" href="https://example.com/news/1">
+        <script type="application/ld+json">
+        {"@context": "https://schema.org", "@graph": [
+          {"@type": "WebSite", "name": "Example"},
+          {"@type": ["NewsArticle"], "headline": "LD headline",
+           "author": [{"@type": "Person", "name": "Alice"}, {"@type": "Person", "name": "Bob"}],
+           "publisher": {"@type": "Organization", "name": "Example Inc."},
+           "datePublished": "2024-06-01T09:00:00+09:00",
+           "dateModified": "2024-06-01T10:00:00+09:00",
+           "articleSection": "経済", "keywords": ["金利", "日銀、為替"],
+           "image": {"@type": "ImageObject", "url": "https://example.com/ld.jpg"}}
+        ]}
+        </script>
+        </head><body></body></html>"#;
+        let m = extract_page_metadata(html, "https://example.com/amp/news/1");
+        assert_eq!(m.title.as_deref(), Some("LD headline"));
+        assert_eq!(m.description.as_deref(), Some("OG description"));
+        assert_eq!(m.image_url.as_deref(), Some("https://example.com/ld.jpg"));
+        assert_eq!(m.site_name.as_deref(), Some("Example News"));
+        assert_eq!(m.og_type.as_deref(), Some("article"));
+        assert_eq!(m.canonical_url.as_deref(), Some("https://example.com/news/1"));
+        assert_eq!(m.favicon_url.as_deref(), Some("https://example.com/static/icon.png"));
+        assert_eq!(m.twitter_card.as_deref(), Some("summary_large_image"));
+        assert_eq!(m.twitter_site.as_deref(), Some("@example"));
+        assert_eq!(m.author.as_deref(), Some("Alice, Bob"));
+        assert_eq!(m.published_at, Some("2024-06-01T00:00:00Z".parse().unwrap()));
+        assert_eq!(m.modified_at, Some("2024-06-01T01:00:00Z".parse().unwrap()));
+        assert_eq!(m.section.as_deref(), Some("経済"));
+        assert_eq!(m.keywords, vec!["金利", "日銀", "為替"]);
+    }
+
+    #[test]
+    fn page_metadata_falls_back_to_twitter_and_defaults() {
+        let html = r#"<head>
+        <meta name="twitter:title" content="Card title">
+        <meta name="twitter:image" content="/img/card.png">
+        <meta name="keywords" content="a, b">
+        <meta property="og:url" content="https://example.com/story">
+        </head>"#;
+        let m = extract_page_metadata(html, "https://example.com/story?utm_source=x");
+        assert_eq!(m.title.as_deref(), Some("Card title"));
+        assert_eq!(m.image_url.as_deref(), Some("https://example.com/img/card.png"));
+        assert_eq!(m.canonical_url.as_deref(), Some("https://example.com/story"));
+        assert_eq!(m.favicon_url.as_deref(), Some("https://example.com/favicon.ico"));
+        assert_eq!(m.keywords, vec!["a", "b"]);
+        assert_eq!(m.published_at, None);
+    }
 }
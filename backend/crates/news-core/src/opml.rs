@@ -0,0 +1,453 @@
+use crate::config::{ContentPolicy, DynamicFeed};
+use crate::error::{AppError, Result};
+use crate::models::CategoryInfo;
+use crate::sources::{ScrapeRules, SourceType};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Namespace of the outline attributes we add for settings OPML has no place for.
+pub const OPML_NS: &str = "https://news.xyz/ns/opml";
+
+/// A feed outline read from an OPML document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpmlFeed {
+    pub url: String,
+    pub source: String,
+    /// Category as written in the file: the outline's `category` attribute, else the
+    /// enclosing group outline's text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub enabled: bool,
+    /// From our `sourceType` attribute; plain OPML feeds are RSS/Atom.
+    pub source_type: SourceType,
+    /// From our `scrapeRules` attribute (JSON).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scrape: Option<ScrapeRules>,
+    /// From our `contentPolicy` attribute.
+    pub content_policy: ContentPolicy,
+    /// From our `pollIntervalSecs` attribute.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poll_interval_secs: Option<u64>,
+}
+
+/// Render feeds as OPML 2.0, one group outline per category in `categories` order.
+/// Feeds in categories not listed come last, grouped under their raw category id.
+/// Settings other readers don't know are written as attributes in [`OPML_NS`].
+pub fn to_opml(feeds: &[DynamicFeed], categories: &[CategoryInfo], title: &str) -> String {
+    let mut by_category: HashMap<&str, Vec<&DynamicFeed>> = HashMap::new();
+    for feed in feeds {
+        by_category.entry(feed.category.as_str()).or_default().push(feed);
+    }
+
+    let mut groups: Vec<(&str, &str)> = categories
+        .iter()
+        .map(|c| (c.id.as_str(), c.label_ja.as_str()))
+        .collect();
+    let mut extra: Vec<&str> = by_category
+        .keys()
+        .copied()
+        .filter(|id| !categories.iter().any(|c| c.id == *id))
+        .collect();
+    extra.sort_unstable();
+    groups.extend(extra.into_iter().map(|id| (id, id)));
+
+    let mut out = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\" xmlns:news=\"{OPML_NS}\">\n");
+    out.push_str(&format!("  <head>\n    <title>{}</title>\n", escape(title)));
+    out.push_str(&format!(
+        "    <dateCreated>{}</dateCreated>\n  </head>\n  <body>\n",
+        chrono::Utc::now().to_rfc2822()
+    ));
+    for (id, label) in groups {
+        let Some(mut group) = by_category.remove(id) else {
+            continue;
+        };
+        group.sort_by(|a, b| a.source.cmp(&b.source).then(a.url.cmp(&b.url)));
+        out.push_str(&format!(
+            "    <outline text=\"{}\" title=\"{}\">\n",
+            escape(label),
+            escape(label)
+        ));
+        for feed in group {
+            // Our own attributes, so non-RSS sources survive a round trip
+            let mut extra = String::new();
+            if feed.source_type != SourceType::Rss {
+                extra.push_str(&format!(" news:sourceType=\"{}\"", feed.source_type.as_str()));
+            }
+            if let Some(json) = feed.scrape.as_ref().and_then(|r| serde_json::to_string(r).ok()) {
+                extra.push_str(&format!(" news:scrapeRules=\"{}\"", escape(&json)));
+            }
+            if feed.content_policy != ContentPolicy::default() {
+                extra.push_str(&format!(" news:contentPolicy=\"{}\"", feed.content_policy.as_str()));
+            }
+            if let Some(secs) = feed.poll_interval_secs {
+                extra.push_str(&format!(" news:pollIntervalSecs=\"{secs}\""));
+            }
+            if !feed.enabled {
+                extra.push_str(" isDisabled=\"true\"");
+            }
+            out.push_str(&format!(
+                "      <outline type=\"rss\" text=\"{src}\" title=\"{src}\" xmlUrl=\"{url}\" category=\"/{cat}\"{extra}/>\n",
+                src = escape(&feed.source),
+                url = escape(&feed.url),
+                cat = escape(&feed.category),
+            ));
+        }
+        out.push_str("    </outline>\n");
+    }
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+/// Parse the feed outlines (those with an `xmlUrl`) of an OPML document, at any depth.
+pub fn parse_opml(xml: &str) -> Result<Vec<OpmlFeed>> {
+    let mut reader = Reader::from_str(xml);
+    // Text of each open non-feed outline, innermost last
+    let mut groups: Vec<Option<String>> = Vec::new();
+    let mut feeds = Vec::new();
+    let mut saw_opml = false;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| AppError::ParseError(format!("OPML: {e}")))?;
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let empty = matches!(event, Event::Empty(_));
+                match e.local_name().as_ref() {
+                    b"opml" => saw_opml = true,
+                    b"outline" => {
+                        let attrs = outline_attrs(e)?;
+                        let text = attrs
+                            .get("text")
+                            .or_else(|| attrs.get("title"))
+                            .map(|t| t.trim().to_string())
+                            .filter(|t| !t.is_empty());
+                        if let Some(url) = attrs.get("xmlUrl").map(|u| u.trim()).filter(|u| !u.is_empty()) {
+                            let category = attrs
+                                .get("category")
+                                .and_then(|c| c.split(',').next())
+                                .map(|c| c.trim().trim_matches('/').to_string())
+                                .filter(|c| !c.is_empty())
+                                .or_else(|| groups.iter().rev().flatten().next().cloned());
+                            feeds.push(OpmlFeed {
+                                url: url.to_string(),
+                                source: text.clone().unwrap_or_else(|| url.to_string()),
+                                category,
+                                enabled: attrs.get("isDisabled").is_none_or(|v| v != "true"),
+                                source_type: attrs
+                                    .get("sourceType")
+                                    .and_then(|t| SourceType::from_str(t))
+                                    .unwrap_or_default(),
+                                scrape: attrs
+                                    .get("scrapeRules")
+                                    .and_then(|json| serde_json::from_str(json).ok()),
+                                content_policy: attrs
+                                    .get("contentPolicy")
+                                    .and_then(|p| ContentPolicy::from_str(p))
+                                    .unwrap_or_default(),
+                                poll_interval_secs: attrs
+                                    .get("pollIntervalSecs")
+                                    .and_then(|s| s.trim().parse().ok()),
+                            });
+                        }
+                        if !empty {
+                            // A feed outline with children isn't a group for them
+                            groups.push(if attrs.contains_key("xmlUrl") { None } else { text });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(ref e) if e.local_name().as_ref() == b"outline" => {
+                groups.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !saw_opml {
+        return Err(AppError::ParseError("OPML: missing <opml> root".into()));
+    }
+    Ok(feeds)
+}
+
+fn outline_attrs(e: &BytesStart) -> Result<HashMap<String, String>> {
+    let mut attrs = HashMap::new();
+    for attr in e.attributes() {
+        let attr = attr.map_err(|e| AppError::ParseError(format!("OPML attribute: {e}")))?;
+        let value = attr
+            .normalized_value(XmlVersion::Implicit1_0)
+            .map_err(|e| AppError::ParseError(format!("OPML attribute: {e}")))?;
+        attrs.insert(
+            String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned(),
+            value.into_owned(),
+        );
+    }
+    Ok(attrs)
+}
+
+/// Feed URL reduced to what identifies the feed: scheme and `www.` dropped, host
+/// lowercased, no fragment or trailing slash.
+pub fn normalize_feed_url(url: &str) -> String {
+    let trimmed = url.trim();
+    let Ok(mut parsed) = url::Url::parse(trimmed) else {
+        return trimmed.to_lowercase();
+    };
+    parsed.set_fragment(None);
+    let host = parsed.host_str().unwrap_or("").to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let port = parsed.port().map(|p| format!(":{p}")).unwrap_or_default();
+    let path = parsed.path().trim_end_matches('/');
+    let query = parsed.query().map(|q| format!("?{q}")).unwrap_or_default();
+    format!("{host}{port}{path}{query}")
+}
+
+/// A feed in the import that is already present.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportDuplicate {
+    pub url: String,
+    pub source: String,
+    /// The existing feed, or None when the duplicate is earlier in the same file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub existing_feed_id: Option<String>,
+}
+
+/// What an OPML import would do, computed before anything is written.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportPlan {
+    /// New feeds, with their category resolved to a known category id.
+    pub to_add: Vec<OpmlFeed>,
+    pub duplicates: Vec<ImportDuplicate>,
+    /// Feeds skipped because their category isn't configured.
+    pub unknown_category: Vec<OpmlFeed>,
+    /// Distinct unknown category names, for the report.
+    pub unknown_categories: Vec<String>,
+    /// Outlines whose `xmlUrl` isn't an http(s) URL.
+    pub invalid_urls: Vec<String>,
+}
+
+impl ImportPlan {
+    /// Feeds to store for `to_add`, with fresh feed IDs.
+    pub fn new_feeds(&self, added_by: &str) -> Vec<DynamicFeed> {
+        self.to_add
+            .iter()
+            .map(|f| DynamicFeed {
+                feed_id: format!(
+                    "feed-{}",
+                    uuid::Uuid::new_v4().to_string().split('-').next().unwrap_or("x")
+                ),
+                url: f.url.clone(),
+                source: f.source.clone(),
+                category: f.category.clone().unwrap_or_default(),
+                enabled: f.enabled,
+                added_by: Some(added_by.into()),
+                poll_interval_secs: f.poll_interval_secs,
+                quarantine_reason: None,
+                content_policy: f.content_policy,
+                source_type: f.source_type,
+                scrape: f.scrape.clone(),
+                filters: None,
+            })
+            .collect()
+    }
+}
+
+/// Match imported feeds against the existing ones and the configured categories.
+///
+/// A category matches by id or label, case-insensitively. Feeds without any category
+/// take `default_category` when given.
+pub fn plan_import(
+    imported: Vec<OpmlFeed>,
+    existing: &[DynamicFeed],
+    categories: &[CategoryInfo],
+    default_category: Option<&str>,
+) -> ImportPlan {
+    let mut seen: HashMap<String, Option<String>> = existing
+        .iter()
+        .map(|f| (normalize_feed_url(&f.url), Some(f.feed_id.clone())))
+        .collect();
+    let mut unknown: HashSet<String> = HashSet::new();
+    let mut plan = ImportPlan::default();
+
+    for mut feed in imported {
+        let valid = url::Url::parse(&feed.url)
+            .map(|u| matches!(u.scheme(), "http" | "https"))
+            .unwrap_or(false);
+        if !valid {
+            plan.invalid_urls.push(feed.url);
+            continue;
+        }
+        let key = normalize_feed_url(&feed.url);
+        if let Some(existing_feed_id) = seen.get(&key) {
+            plan.duplicates.push(ImportDuplicate {
+                url: feed.url,
+                source: feed.source,
+                existing_feed_id: existing_feed_id.clone(),
+            });
+            continue;
+        }
+
+        let wanted = feed.category.clone().or_else(|| default_category.map(String::from));
+        let resolved = wanted.as_deref().and_then(|name| {
+            categories
+                .iter()
+                .find(|c| {
+                    c.id.eq_ignore_ascii_case(name)
+                        || c.label.eq_ignore_ascii_case(name)
+                        || c.label_ja == name
+                })
+                .map(|c| c.id.clone())
+        });
+        match resolved {
+            Some(id) => {
+                seen.insert(key, None);
+                feed.category = Some(id);
+                plan.to_add.push(feed);
+            }
+            None => {
+                let name = wanted.unwrap_or_default();
+                if unknown.insert(name.clone()) {
+                    plan.unknown_categories.push(name);
+                }
+                plan.unknown_category.push(feed);
+            }
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(id: &str, url: &str, source: &str, category: &str) -> DynamicFeed {
+        DynamicFeed {
+            feed_id: id.into(),
+            url: url.into(),
+            source: source.into(),
+            category: category.into(),
+            enabled: true,
+            added_by: None,
+            poll_interval_secs: None,
+            quarantine_reason: None,
+            content_policy: Default::default(),
+            source_type: Default::default(),
+            scrape: None,
+            filters: None,
+        }
+    }
+
+    fn opml_feed(url: &str, source: &str, category: Option<&str>) -> OpmlFeed {
+        OpmlFeed {
+            url: url.into(),
+            source: source.into(),
+            category: category.map(String::from),
+            enabled: true,
+            source_type: SourceType::Rss,
+            scrape: None,
+            content_policy: Default::default(),
+            poll_interval_secs: None,
+        }
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let mut disabled = feed("f2", "https://example.com/a&b.xml", "Ex <Two>", "business");
+        disabled.enabled = false;
+        disabled.content_policy = ContentPolicy::NoFetch;
+        disabled.poll_interval_secs = Some(1800);
+        disabled.source_type = SourceType::Scrape;
+        disabled.scrape = Some(ScrapeRules {
+            item: "li.news".into(),
+            link: "a[href^=\"/\"]".into(),
+            title: None,
+            date: Some("time".into()),
+            image: None,
+        });
+        let feeds = vec![
+            feed("f1", "https://www3.nhk.or.jp/rss/news/cat0.xml", "NHK", "general"),
+            disabled,
+            feed("f3", "https://blog.example/feed", "Blog", "custom"),
+        ];
+        let xml = to_opml(&feeds, &CategoryInfo::all(), "Feeds");
+        let parsed = parse_opml(&xml).unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0].url, "https://www3.nhk.or.jp/rss/news/cat0.xml");
+        assert_eq!(parsed[0].category.as_deref(), Some("general"));
+        assert_eq!(parsed[1].url, "https://example.com/a&b.xml");
+        assert_eq!(parsed[1].source, "Ex <Two>");
+        assert!(!parsed[1].enabled);
+        assert_eq!(parsed[1].source_type, SourceType::Scrape);
+        assert_eq!(parsed[1].scrape, feeds[1].scrape);
+        assert_eq!(parsed[1].content_policy, ContentPolicy::NoFetch);
+        assert_eq!(parsed[1].poll_interval_secs, Some(1800));
+        assert_eq!(parsed[0].source_type, SourceType::Rss);
+        assert_eq!(parsed[0].content_policy, ContentPolicy::default());
+        assert_eq!(parsed[0].poll_interval_secs, None);
+        assert!(xml.contains(&format!("xmlns:news=\"{OPML_NS}\"")));
+        assert!(xml.contains(" news:contentPolicy=\"no_fetch\" news:pollIntervalSecs=\"1800\""));
+        // Unknown categories are exported after the configured ones
+        assert_eq!(parsed[2].category.as_deref(), Some("custom"));
+    }
+
+    #[test]
+    fn category_comes_from_enclosing_group() {
+        let xml = r#"<?xml version="1.0"?>
+        <opml version="2.0"><head><title>x</title></head><body>
+          <outline text="Tech">
+            <outline text="Sub">
+              <outline type="rss" text="Ars" xmlUrl="https://feeds.arstechnica.com/arstechnica/index"/>
+            </outline>
+          </outline>
+          <outline title="Loose" xmlUrl="http://loose.example/rss"/>
+        </body></opml>"#;
+        let parsed = parse_opml(xml).unwrap();
+        assert_eq!(parsed[0].category.as_deref(), Some("Sub"));
+        assert_eq!(parsed[0].source, "Ars");
+        assert_eq!(parsed[1].category, None);
+        assert_eq!(parsed[1].source, "Loose");
+        assert!(parse_opml("<rss></rss>").is_err());
+    }
+
+    #[test]
+    fn normalized_urls_ignore_scheme_www_and_slash() {
+        assert_eq!(
+            normalize_feed_url("http://WWW.Example.com/feed/#top"),
+            normalize_feed_url("https://example.com/feed")
+        );
+        assert_ne!(
+            normalize_feed_url("https://example.com/feed?cat=1"),
+            normalize_feed_url("https://example.com/feed?cat=2")
+        );
+    }
+
+    #[test]
+    fn plan_reports_duplicates_and_unknown_categories() {
+        let existing = vec![feed("f1", "https://example.com/rss", "Example", "tech")];
+        let imported = vec![
+            opml_feed("http://www.example.com/rss/", "Dup", Some("tech")),
+            opml_feed("https://new.example/rss", "New", Some("テクノロジー")),
+            opml_feed("https://new.example/rss", "Again", Some("tech")),
+            opml_feed("https://other.example/rss", "Other", Some("Gardening")),
+            opml_feed("https://nocat.example/rss", "NoCat", None),
+            opml_feed("javascript:alert(1)", "Bad", None),
+        ];
+        let plan = plan_import(imported, &existing, &CategoryInfo::all(), Some("general"));
+        let added: Vec<_> = plan.to_add.iter().map(|f| (f.source.as_str(), f.category.as_deref())).collect();
+        assert_eq!(added, vec![("New", Some("tech")), ("NoCat", Some("general"))]);
+        assert_eq!(plan.duplicates.len(), 2);
+        assert_eq!(plan.duplicates[0].existing_feed_id.as_deref(), Some("f1"));
+        assert_eq!(plan.duplicates[1].existing_feed_id, None);
+        assert_eq!(plan.unknown_categories, vec!["Gardening"]);
+        assert_eq!(plan.invalid_urls, vec!["javascript:alert(1)"]);
+
+        let new = plan.new_feeds("opml-import");
+        assert_eq!(new[0].url, "https://new.example/rss");
+        assert_eq!(new[0].category, "tech");
+        assert_ne!(new[0].feed_id, new[1].feed_id);
+    }
+}
@@ -0,0 +1,653 @@
+use crate::error::{AppError, Result};
+use regex::Regex;
+use scraper::{Html, Selector};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+use tracing::{info, warn};
+use url::Url;
+
+/// User agent sent with every publisher page request. The product token is what
+/// robots.txt groups are matched against.
+pub const USER_AGENT: &str = "NewsAggregator/1.0";
+/// Minimum gap between two requests to the same host.
+pub const DEFAULT_HOST_DELAY: Duration = Duration::from_secs(1);
+/// Requests in flight per host at most.
+pub const DEFAULT_HOST_CONCURRENCY: usize = 2;
+/// How long a fetched robots.txt is trusted.
+const ROBOTS_TTL: Duration = Duration::from_secs(24 * 3600);
+/// How long a host is held off after its robots.txt failed with a server error, 429 or
+/// timeout, before robots.txt is tried again.
+const ROBOTS_RETRY_TTL: Duration = Duration::from_secs(10 * 60);
+/// Largest robots.txt read; anything beyond is ignored.
+const MAX_ROBOTS_BYTES: usize = 512 * 1024;
+/// A robots.txt `Crawl-delay` above this is capped rather than honored literally.
+const MAX_CRAWL_DELAY: Duration = Duration::from_secs(30);
+
+/// A class or id token naming a paywall container: the marker as the whole token or one
+/// `-`/`_`-separated part of it ("paywall", "c-paywall__body").
+static PAYWALL_HINT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:.*[-_])?(?:paywall|regwall|subscribers?-only|premium-article|article-locked)(?:[-_].*)?$|会員限定|有料会員").unwrap()
+});
+/// Parts of a class or id token that negate its paywall marker ("no-paywall", "paywall-free").
+const PAYWALL_NEGATIONS: &[&str] = &["no", "non", "not", "without", "free", "off", "disabled", "none"];
+
+/// One `User-agent` group of a robots.txt file while parsing.
+#[derive(Default)]
+struct RobotsGroup {
+    agents: Vec<String>,
+    robots: RobotsTxt,
+}
+
+/// Parsed robots.txt rules for one user agent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsTxt {
+    /// (allow, pattern), from the most specific group matching our agent.
+    rules: Vec<(bool, String)>,
+    pub crawl_delay: Option<Duration>,
+}
+
+impl RobotsTxt {
+    /// Everything allowed, used when a site has no robots.txt.
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Everything disallowed, used while a site's robots.txt is erroring.
+    pub fn disallow_all() -> Self {
+        Self {
+            rules: vec![(false, "/".into())],
+            crawl_delay: None,
+        }
+    }
+
+    /// Parse robots.txt and keep the group that best matches `user_agent`: the longest
+    /// `User-agent` value contained in its product token, else `*`.
+    pub fn parse(body: &str, user_agent: &str) -> Self {
+        let token = user_agent
+            .split('/')
+            .next()
+            .unwrap_or(user_agent)
+            .trim()
+            .to_ascii_lowercase();
+
+        let mut groups: Vec<RobotsGroup> = Vec::new();
+        let mut in_agents = false;
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+            match key.as_str() {
+                "user-agent" => {
+                    if !in_agents {
+                        groups.push(RobotsGroup::default());
+                        in_agents = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                "allow" | "disallow" => {
+                    in_agents = false;
+                    // An empty Disallow allows everything; it adds no rule
+                    if let Some(group) = groups.last_mut().filter(|_| !value.is_empty()) {
+                        group.robots.rules.push((key == "allow", value.to_string()));
+                    }
+                }
+                "crawl-delay" => {
+                    in_agents = false;
+                    if let (Some(group), Ok(secs)) = (groups.last_mut(), value.parse::<f64>()) {
+                        group.robots.crawl_delay = Some(Duration::from_secs_f64(secs.max(0.0)).min(MAX_CRAWL_DELAY));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let specific = groups
+            .iter()
+            .filter_map(|g| {
+                g.agents
+                    .iter()
+                    .filter(|a| *a != "*" && token.contains(a.as_str()))
+                    .map(|a| a.len())
+                    .max()
+                    .map(|len| (len, g))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, g)| g);
+        let chosen = specific.or_else(|| groups.iter().find(|g| g.agents.iter().any(|a| a == "*")));
+
+        chosen.map(|g| g.robots.clone()).unwrap_or_default()
+    }
+
+    /// Whether `path` (with query) may be fetched. The longest matching rule wins,
+    /// and Allow wins a tie.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| pattern_matches(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// robots.txt path matching: a prefix match where `*` matches any run of characters
+/// and a trailing `$` anchors the end.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let Some(rest) = path.strip_prefix(parts[0]) else {
+        return false;
+    };
+    let mut rest = rest;
+    for (i, part) in parts.iter().enumerate().skip(1) {
+        let last = i == parts.len() - 1;
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+/// Publisher opt-outs from `<meta name="robots">` and the `X-Robots-Tag` header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageDirectives {
+    pub noarchive: bool,
+    pub nosnippet: bool,
+    /// `noai`: the publisher opts out of AI use of the page.
+    pub noai: bool,
+}
+
+impl PageDirectives {
+    /// Parse page-level directives from the HTML meta tags and an optional `X-Robots-Tag` value.
+    pub fn from_page(html: &str, robots_header: Option<&str>) -> Self {
+        let mut directives = Self::default();
+        if let Some(header) = robots_header {
+            directives.apply(header);
+        }
+        let doc = Html::parse_document(html);
+        let meta = Selector::parse(r#"meta[name][content]"#).unwrap();
+        for el in doc.select(&meta) {
+            let name = el.value().attr("name").unwrap_or("").to_ascii_lowercase();
+            if matches!(name.as_str(), "robots" | "googlebot" | "bingbot") {
+                directives.apply(el.value().attr("content").unwrap_or(""));
+            }
+        }
+        directives
+    }
+
+    fn apply(&mut self, value: &str) {
+        for token in value.split(',') {
+            // X-Robots-Tag may scope a directive to a crawler: "googlebot: nosnippet"
+            let token = token.rsplit(':').next().unwrap_or("").trim().to_ascii_lowercase();
+            match token.as_str() {
+                "noarchive" => self.noarchive = true,
+                "nosnippet" => self.nosnippet = true,
+                "noai" => self.noai = true,
+                "none" => {
+                    self.noarchive = true;
+                    self.nosnippet = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether the page's full text may be kept and sent to AI features.
+    pub fn allows_full_text(&self) -> bool {
+        !(self.noarchive || self.nosnippet || self.noai)
+    }
+}
+
+/// Detect a paywalled page from JSON-LD `isAccessibleForFree`, `article:content_tier`,
+/// or paywall containers in the markup.
+pub fn detect_paywall(html: &str) -> bool {
+    let doc = Html::parse_document(html);
+
+    let ld = Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
+    let not_free = doc.select(&ld).any(|s| {
+        let text: String = s.text().collect();
+        serde_json::from_str::<serde_json::Value>(&text)
+            .map(|v| json_not_free(&v))
+            .unwrap_or(false)
+    });
+    if not_free {
+        return true;
+    }
+
+    let tier = Selector::parse(r#"meta[property="article:content_tier"]"#).unwrap();
+    if doc
+        .select(&tier)
+        .filter_map(|m| m.value().attr("content"))
+        .any(|c| matches!(c.trim().to_ascii_lowercase().as_str(), "locked" | "metered"))
+    {
+        return true;
+    }
+
+    let hinted = Selector::parse("body [class], body [id]").unwrap();
+    doc.select(&hinted).any(|el| {
+        let v = el.value();
+        !matches!(v.name(), "script" | "style" | "link")
+            && v.classes().chain(v.id()).any(is_paywall_token)
+    })
+}
+
+fn is_paywall_token(token: &str) -> bool {
+    PAYWALL_HINT.is_match(token)
+        && !token
+            .split(['-', '_'])
+            .any(|part| PAYWALL_NEGATIONS.contains(&part.to_ascii_lowercase().as_str()))
+}
+
+fn json_not_free(v: &serde_json::Value) -> bool {
+    match v {
+        serde_json::Value::Array(items) => items.iter().any(json_not_free),
+        serde_json::Value::Object(map) => {
+            let flag = match map.get("isAccessibleForFree") {
+                Some(serde_json::Value::Bool(b)) => Some(*b),
+                Some(serde_json::Value::String(s)) => Some(!s.eq_ignore_ascii_case("false")),
+                _ => None,
+            };
+            flag == Some(false) || map.get("@graph").is_some_and(json_not_free)
+        }
+        _ => false,
+    }
+}
+
+struct HostState {
+    /// Cached rules and when they expire.
+    robots: Option<(RobotsTxt, Instant)>,
+    /// Held while robots.txt is being fetched, so concurrent requests wait for that
+    /// fetch instead of each making their own.
+    robots_fetch: Arc<tokio::sync::Mutex<()>>,
+    next_slot: Instant,
+    permits: Arc<Semaphore>,
+}
+
+/// A publisher's response. It keeps the host's concurrency slot until the body has
+/// been read, so the per-host cap covers downloads and not just headers.
+pub struct PoliteResponse {
+    response: reqwest::Response,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// A response to a request made without the politeness checks, such as polling a
+/// feed the publisher offers for that purpose.
+impl From<reqwest::Response> for PoliteResponse {
+    fn from(response: reqwest::Response) -> Self {
+        Self {
+            response,
+            _permit: None,
+        }
+    }
+}
+
+impl PoliteResponse {
+    pub fn status(&self) -> reqwest::StatusCode {
+        self.response.status()
+    }
+
+    pub fn headers(&self) -> &reqwest::header::HeaderMap {
+        self.response.headers()
+    }
+
+    /// The response's `Content-Type`, for decoding its body.
+    pub fn content_type(&self) -> Option<String> {
+        self.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    }
+
+    /// An error if the status is a client or server error.
+    pub fn error_for_status_ref(&self) -> Result<()> {
+        self.response.error_for_status_ref()?;
+        Ok(())
+    }
+
+    /// Read the body, stopping after `max_bytes`, then release the host's slot.
+    pub async fn bytes(mut self, max_bytes: usize) -> Result<Vec<u8>> {
+        let mut body = Vec::new();
+        while let Some(chunk) = self.response.chunk().await? {
+            let room = max_bytes - body.len();
+            body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+            if body.len() >= max_bytes {
+                break;
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// HTTP client for publisher pages. Each request checks the host's robots.txt (cached
+/// for a day), waits its turn under the host's rate limit and concurrency cap, and
+/// identifies itself as [`USER_AGENT`]. Cheap to clone; clones share the host state.
+#[derive(Clone)]
+pub struct PoliteClient {
+    client: reqwest::Client,
+    user_agent: String,
+    host_delay: Duration,
+    host_concurrency: usize,
+    hosts: Arc<Mutex<HashMap<String, HostState>>>,
+}
+
+impl PoliteClient {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            user_agent: USER_AGENT.to_string(),
+            host_delay: DEFAULT_HOST_DELAY,
+            host_concurrency: DEFAULT_HOST_CONCURRENCY,
+            hosts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Override the per-host request gap and concurrency cap.
+    pub fn with_limits(mut self, host_delay: Duration, host_concurrency: usize) -> Self {
+        self.host_delay = host_delay;
+        self.host_concurrency = host_concurrency.max(1);
+        self
+    }
+
+    /// The underlying client, for requests that don't need the politeness checks.
+    pub fn http(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// GET a publisher page. Fails with [`AppError::Disallowed`] if robots.txt forbids it.
+    pub async fn get(&self, url: &str) -> Result<PoliteResponse> {
+        self.get_with_headers(url, reqwest::header::HeaderMap::new()).await
+    }
+
+    /// [`PoliteClient::get`] with extra request headers, e.g. conditional request validators.
+    pub async fn get_with_headers(&self, url: &str, headers: reqwest::header::HeaderMap) -> Result<PoliteResponse> {
+        let parsed = Url::parse(url).map_err(|e| AppError::ParseError(format!("{url}: {e}")))?;
+        let host = parsed.host_str().unwrap_or("").to_ascii_lowercase();
+
+        let robots = self.robots_for(&parsed, &host).await;
+        let mut path = parsed.path().to_string();
+        if let Some(q) = parsed.query() {
+            path.push('?');
+            path.push_str(q);
+        }
+        if !robots.is_allowed(&path) {
+            return Err(AppError::Disallowed(format!("robots.txt disallows {url}")));
+        }
+
+        let delay = robots.crawl_delay.unwrap_or_default().max(self.host_delay);
+        self.throttled(&host, delay, self.client.get(url).headers(headers)).await
+    }
+
+    /// Wait for a concurrency permit and the host's next request slot, then send. The
+    /// permit travels with the response until its body is read.
+    async fn throttled(
+        &self,
+        host: &str,
+        delay: Duration,
+        request: reqwest::RequestBuilder,
+    ) -> Result<PoliteResponse> {
+        let (slot, permits) = {
+            let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+            let state = self.host_state(&mut hosts, host);
+            let slot = state.next_slot.max(Instant::now());
+            state.next_slot = slot + delay;
+            (slot, state.permits.clone())
+        };
+        let permit = permits
+            .acquire_owned()
+            .await
+            .map_err(|e| AppError::ConfigError(e.to_string()))?;
+        tokio::time::sleep_until(slot).await;
+        let response = request
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .send()
+            .await?;
+        Ok(PoliteResponse {
+            response,
+            _permit: Some(permit),
+        })
+    }
+
+    fn host_state<'a>(
+        &self,
+        hosts: &'a mut HashMap<String, HostState>,
+        host: &str,
+    ) -> &'a mut HostState {
+        hosts.entry(host.to_string()).or_insert_with(|| HostState {
+            robots: None,
+            robots_fetch: Arc::new(tokio::sync::Mutex::new(())),
+            next_slot: Instant::now(),
+            permits: Arc::new(Semaphore::new(self.host_concurrency)),
+        })
+    }
+
+    /// Cached robots.txt for a host, fetching it when missing or expired. A missing
+    /// file (4xx) allows everything for [`ROBOTS_TTL`]; server errors, 429s and
+    /// timeouts disallow everything for [`ROBOTS_RETRY_TTL`], after which it is tried
+    /// again. Only one fetch per host is in flight; other requests wait for its result.
+    async fn robots_for(&self, url: &Url, host: &str) -> RobotsTxt {
+        if let Some(robots) = self.cached_robots(host) {
+            return robots;
+        }
+        let fetch_lock = {
+            let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+            self.host_state(&mut hosts, host).robots_fetch.clone()
+        };
+        let _fetching = fetch_lock.lock().await;
+        // Another request may have fetched it while we waited
+        if let Some(robots) = self.cached_robots(host) {
+            return robots;
+        }
+
+        let robots_url = format!("{}://{}/robots.txt", url.scheme(), url.authority());
+        let (robots, ttl) = match self
+            .throttled(host, self.host_delay, self.client.get(&robots_url))
+            .await
+        {
+            Ok(r) if r.status().is_success() => match r.bytes(MAX_ROBOTS_BYTES).await {
+                Ok(body) => (RobotsTxt::parse(&String::from_utf8_lossy(&body), &self.user_agent), ROBOTS_TTL),
+                Err(e) => {
+                    warn!(host, error = %e, "robots.txt read failed, holding off");
+                    (RobotsTxt::disallow_all(), ROBOTS_RETRY_TTL)
+                }
+            },
+            Ok(r) if r.status().is_client_error() && r.status().as_u16() != 429 => {
+                (RobotsTxt::allow_all(), ROBOTS_TTL)
+            }
+            Ok(r) => {
+                warn!(host, status = r.status().as_u16(), "robots.txt unavailable, holding off");
+                (RobotsTxt::disallow_all(), ROBOTS_RETRY_TTL)
+            }
+            Err(e) => {
+                warn!(host, error = %e, "robots.txt fetch failed, holding off");
+                (RobotsTxt::disallow_all(), ROBOTS_RETRY_TTL)
+            }
+        };
+        info!(host, rules = robots.rules.len(), ttl_secs = ttl.as_secs(), "robots.txt cached");
+
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        self.host_state(&mut hosts, host).robots = Some((robots.clone(), Instant::now() + ttl));
+        robots
+    }
+
+    fn cached_robots(&self, host: &str) -> Option<RobotsTxt> {
+        let hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let (robots, expires) = hosts.get(host)?.robots.as_ref()?;
+        (Instant::now() < *expires).then(|| robots.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{Reply, TestServer};
+
+    const ROBOTS: &str = "
+# Example
+User-agent: *
+Disallow: /private/
+Disallow: /*.pdf$
+Allow: /private/press/
+
+User-agent: Googlebot
+User-agent: NewsAggregator
+Disallow: /members/
+Allow: /members/free
+Crawl-delay: 5
+
+User-agent: BadBot
+Disallow: /
+";
+
+    #[test]
+    fn picks_the_matching_group() {
+        let ours = RobotsTxt::parse(ROBOTS, USER_AGENT);
+        assert_eq!(ours.crawl_delay, Some(Duration::from_secs(5)));
+        assert!(!ours.is_allowed("/members/article"));
+        assert!(ours.is_allowed("/members/free/article"));
+        // Only the most specific group applies, not `*` as well
+        assert!(ours.is_allowed("/private/x"));
+
+        let other = RobotsTxt::parse(ROBOTS, "SomeCrawler/2.0");
+        assert!(!other.is_allowed("/private/x"));
+        assert!(other.is_allowed("/private/press/release"));
+        assert!(!other.is_allowed("/files/report.pdf"));
+        assert!(other.is_allowed("/files/report.pdf?download=1"));
+        assert!(other.is_allowed("/news/1"));
+    }
+
+    #[test]
+    fn empty_and_missing_rules_allow() {
+        let robots = RobotsTxt::parse("User-agent: *\nDisallow:\n", USER_AGENT);
+        assert!(robots.is_allowed("/anything"));
+        assert!(RobotsTxt::parse("", USER_AGENT).is_allowed("/"));
+        assert!(!RobotsTxt::disallow_all().is_allowed("/news"));
+        assert!(RobotsTxt::disallow_all().is_allowed("/robots.txt"));
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(pattern_matches("/a/*/c", "/a/b/c/d"));
+        assert!(!pattern_matches("/a/*/c$", "/a/b/c/d"));
+        assert!(pattern_matches("/*?session=", "/news?session=1"));
+        assert!(!pattern_matches("/news", "/new"));
+    }
+
+    #[test]
+    fn directives_from_meta_and_header() {
+        let html = r#"<head><meta name="robots" content="index, noarchive"></head>"#;
+        let d = PageDirectives::from_page(html, None);
+        assert!(d.noarchive && !d.nosnippet);
+        assert!(!d.allows_full_text());
+
+        let d = PageDirectives::from_page("<p>hi</p>", Some("googlebot: nosnippet, noai"));
+        assert!(d.nosnippet && d.noai);
+
+        assert!(PageDirectives::from_page("<p>hi</p>", Some("noindex")).allows_full_text());
+    }
+
+    #[test]
+    fn paywall_markers() {
+        let ld = r#"<script type="application/ld+json">
+            {"@type": "NewsArticle", "isAccessibleForFree": "False",
+             "hasPart": {"@type": "WebPageElement", "isAccessibleForFree": "False", "cssSelector": ".x"}}
+            </script><p>teaser</p>"#;
+        assert!(detect_paywall(ld));
+        assert!(detect_paywall(
+            r#"<meta property="article:content_tier" content="locked"><p>x</p>"#
+        ));
+        assert!(detect_paywall(r#"<body><div class="article paywall">続きは有料</div></body>"#));
+        assert!(!detect_paywall(
+            r#"<script type="application/ld+json">{"@type":"NewsArticle","isAccessibleForFree":true}</script><p>free</p>"#
+        ));
+        assert!(!detect_paywall(r#"<body><div class="article-body">free</div></body>"#));
+        assert!(detect_paywall(r#"<body><section id="c-paywall__gate">x</section></body>"#));
+        for free in ["no-paywall", "paywall-free", "article paywall_disabled", "paywalled-preview-x nopaywall"] {
+            let html = format!(r#"<body><div class="{free}">free</div></body>"#);
+            assert!(!detect_paywall(&html), "{free}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limit_spaces_requests_per_host() {
+        let server = TestServer::start(vec![("/", Reply::ok("text/plain", "hi"))]).await;
+        let delay = Duration::from_millis(200);
+        let client = PoliteClient::new(reqwest::Client::new()).with_limits(delay, 1);
+        let started = Instant::now();
+        for _ in 0..3 {
+            let request = client.client.get(server.url("/"));
+            let _ = client.throttled("127.0.0.1", delay, request).await;
+        }
+        assert!(started.elapsed() >= delay * 2);
+        assert_eq!(server.hits("/"), 3);
+    }
+
+    #[tokio::test]
+    async fn robots_failures_are_retried_soon_and_fetched_once() {
+        let server = TestServer::start(vec![
+            ("/robots.txt", Reply::status(503)),
+            ("/news/1", Reply::ok("text/html", "<p>story</p>")),
+        ])
+        .await;
+        let client = PoliteClient::new(reqwest::Client::new()).with_limits(Duration::ZERO, 4);
+        let url = server.url("/news/1");
+        let results = futures::future::join_all((0..4).map(|_| client.get(&url))).await;
+        assert!(results.iter().all(|r| matches!(r, Err(AppError::Disallowed(_)))));
+        assert_eq!(server.hits("/robots.txt"), 1);
+
+        let host = Url::parse(&url).unwrap().host_str().unwrap().to_string();
+        let hosts = client.hosts.lock().unwrap();
+        let (_, expires) = hosts[&host].robots.as_ref().unwrap();
+        assert!(*expires <= Instant::now() + ROBOTS_RETRY_TTL);
+    }
+
+    #[tokio::test]
+    async fn host_slot_is_held_until_the_body_is_read() {
+        let slow = Reply {
+            body_delay: Duration::from_millis(300),
+            ..Reply::ok("text/html", "<p>slow</p>")
+        };
+        let server = TestServer::start(vec![("/robots.txt", Reply::status(404)), ("/slow", slow)]).await;
+        let client = PoliteClient::new(reqwest::Client::new()).with_limits(Duration::ZERO, 1);
+        let url = server.url("/slow");
+
+        let first = client.get(&url).await.unwrap();
+        let started = Instant::now();
+        let second = tokio::spawn({
+            let client = client.clone();
+            let url = url.clone();
+            async move { client.get(&url).await.map(|_| started.elapsed()) }
+        });
+        assert_eq!(first.bytes(1024).await.unwrap(), b"<p>slow</p>");
+        let waited = second.await.unwrap().unwrap();
+        assert!(waited >= Duration::from_millis(250), "second request started after {waited:?}");
+    }
+
+    #[tokio::test]
+    async fn bodies_are_capped() {
+        let server = TestServer::start(vec![
+            ("/robots.txt", Reply::status(404)),
+            ("/big", Reply::ok("text/html", vec![b'x'; 100_000])),
+        ])
+        .await;
+        let client = PoliteClient::new(reqwest::Client::new());
+        let body = client.get(&server.url("/big")).await.unwrap().bytes(1000).await.unwrap();
+        assert_eq!(body.len(), 1000);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::feeds::FetchState;
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Poll interval used when nothing is known about a feed yet.
+pub const DEFAULT_POLL_SECS: i64 = 600;
+/// Fastest a feed is ever polled, even for breaking-news sources.
+pub const MIN_POLL_SECS: i64 = 120;
+/// Slowest a healthy feed is polled.
+pub const MAX_POLL_SECS: i64 = 6 * 3600;
+/// Upper bound of the failure backoff.
+pub const MAX_BACKOFF_SECS: i64 = 24 * 3600;
+
+/// How many recent items are considered when estimating publish cadence.
+const CADENCE_SAMPLE: usize = 20;
+
+static UPDATE_PERIOD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<sy:updatePeriod>\s*(\w+)\s*</sy:updatePeriod>").unwrap());
+static UPDATE_FREQUENCY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<sy:updateFrequency>\s*(\d+)\s*</sy:updateFrequency>").unwrap());
+
+/// Compute how long to wait before polling a feed again.
+///
+/// An admin override sets the interval outright, down to [`MIN_POLL_SECS`]. Otherwise the
+/// feed is polled about twice per observed publish interval, never faster than the
+/// publisher's `<ttl>`/`sy:updatePeriod` hint, clamped to [`MIN_POLL_SECS`, `MAX_POLL_SECS`].
+/// Either way, consecutive failures double the interval each time up to
+/// [`MAX_BACKOFF_SECS`] (or the override, if that is longer).
+pub fn poll_interval(state: &FetchState, override_secs: Option<u64>) -> Duration {
+    let mut secs = match override_secs {
+        Some(secs) => (secs as i64).max(MIN_POLL_SECS),
+        None => {
+            let mut secs = state
+                .publish_interval_secs
+                .map(|s| s as i64 / 2)
+                .unwrap_or(DEFAULT_POLL_SECS);
+            if let Some(hint) = state.update_hint_secs {
+                secs = secs.max(hint as i64);
+            }
+            secs.clamp(MIN_POLL_SECS, MAX_POLL_SECS)
+        }
+    };
+
+    if state.consecutive_failures > 0 {
+        let exp = state.consecutive_failures.min(10);
+        secs = secs.saturating_mul(1 << exp).min(MAX_BACKOFF_SECS.max(secs));
+    }
+
+    Duration::seconds(secs)
+}
+
+/// Next time a feed should be polled, counted from `now`.
+pub fn next_poll_at(
+    state: &FetchState,
+    override_secs: Option<u64>,
+    now: DateTime<Utc>,
+) -> DateTime<Utc> {
+    now + poll_interval(state, override_secs)
+}
+
+/// True if the feed has never been scheduled or its poll time has passed.
+pub fn is_due(state: &FetchState, now: DateTime<Utc>) -> bool {
+    state.next_poll_at.is_none_or(|t| t <= now)
+}
+
+/// Median gap in seconds between the most recent item publish times.
+/// Returns None if there are fewer than two distinct timestamps.
+pub fn publish_cadence(published: &[DateTime<Utc>]) -> Option<u64> {
+    let mut times: Vec<i64> = published.iter().map(|t| t.timestamp()).collect();
+    times.sort_unstable_by(|a, b| b.cmp(a));
+    times.dedup();
+    times.truncate(CADENCE_SAMPLE);
+    if times.len() < 2 {
+        return None;
+    }
+
+    let mut gaps: Vec<i64> = times.windows(2).map(|w| w[0] - w[1]).collect();
+    gaps.sort_unstable();
+    Some(gaps[gaps.len() / 2] as u64)
+}
+
+/// Publisher update hint in seconds from RSS `<ttl>` (minutes) or the
+/// `sy:updatePeriod`/`sy:updateFrequency` syndication elements.
+pub fn update_hint_secs(ttl_minutes: Option<u32>, raw: &str) -> Option<u64> {
+    if let Some(ttl) = ttl_minutes.filter(|t| *t > 0) {
+        return Some(ttl as u64 * 60);
+    }
+
+    let period = UPDATE_PERIOD.captures(raw)?.get(1)?.as_str().to_lowercase();
+    let period_secs: u64 = match period.as_str() {
+        "hourly" => 3600,
+        "daily" => 86400,
+        "weekly" => 7 * 86400,
+        "monthly" => 30 * 86400,
+        "yearly" => 365 * 86400,
+        _ => return None,
+    };
+    let frequency = UPDATE_FREQUENCY
+        .captures(raw)
+        .and_then(|c| c.get(1))
+        .and_then(|m| m.as_str().parse::<u64>().ok())
+        .filter(|f| *f > 0)
+        .unwrap_or(1);
+
+    Some(period_secs / frequency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_feed_uses_default() {
+        let state = FetchState::default();
+        assert_eq!(poll_interval(&state, None).num_seconds(), DEFAULT_POLL_SECS);
+    }
+
+    #[test]
+    fn override_wins_but_respects_minimum() {
+        let state = FetchState {
+            publish_interval_secs: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(poll_interval(&state, Some(3600)).num_seconds(), 3600);
+        assert_eq!(poll_interval(&state, Some(10)).num_seconds(), MIN_POLL_SECS);
+    }
+
+    #[test]
+    fn cadence_drives_interval_within_bounds() {
+        let fast = FetchState {
+            publish_interval_secs: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(poll_interval(&fast, None).num_seconds(), MIN_POLL_SECS);
+
+        let slow = FetchState {
+            publish_interval_secs: Some(7 * 86400),
+            ..Default::default()
+        };
+        assert_eq!(poll_interval(&slow, None).num_seconds(), MAX_POLL_SECS);
+    }
+
+    #[test]
+    fn update_hint_is_a_floor() {
+        let state = FetchState {
+            publish_interval_secs: Some(600),
+            update_hint_secs: Some(1800),
+            ..Default::default()
+        };
+        assert_eq!(poll_interval(&state, None).num_seconds(), 1800);
+    }
+
+    #[test]
+    fn failures_back_off_exponentially() {
+        let mut state = FetchState {
+            consecutive_failures: 1,
+            ..Default::default()
+        };
+        assert_eq!(poll_interval(&state, None).num_seconds(), DEFAULT_POLL_SECS * 2);
+        state.consecutive_failures = 3;
+        assert_eq!(poll_interval(&state, None).num_seconds(), DEFAULT_POLL_SECS * 8);
+        state.consecutive_failures = 40;
+        assert_eq!(poll_interval(&state, None).num_seconds(), MAX_BACKOFF_SECS);
+    }
+
+    #[test]
+    fn failures_back_off_from_an_override() {
+        let mut state = FetchState {
+            consecutive_failures: 2,
+            ..Default::default()
+        };
+        assert_eq!(poll_interval(&state, Some(300)).num_seconds(), 1200);
+        state.consecutive_failures = 40;
+        assert_eq!(poll_interval(&state, Some(300)).num_seconds(), MAX_BACKOFF_SECS);
+        // An override beyond the backoff cap isn't shortened
+        assert_eq!(poll_interval(&state, Some(2 * 86400)).num_seconds(), 2 * 86400);
+    }
+
+    #[test]
+    fn due_when_unscheduled_or_past() {
+        let now = Utc::now();
+        let mut state = FetchState::default();
+        assert!(is_due(&state, now));
+        state.next_poll_at = Some(now - Duration::seconds(1));
+        assert!(is_due(&state, now));
+        state.next_poll_at = Some(now + Duration::seconds(60));
+        assert!(!is_due(&state, now));
+    }
+
+    #[test]
+    fn cadence_is_median_gap() {
+        let base = Utc::now();
+        let times = vec![
+            base,
+            base - Duration::minutes(10),
+            base - Duration::minutes(20),
+            base - Duration::minutes(30),
+            base - Duration::hours(10),
+        ];
+        assert_eq!(publish_cadence(&times), Some(600));
+        assert_eq!(publish_cadence(&[base]), None);
+        assert_eq!(publish_cadence(&[base, base]), None);
+    }
+
+    #[test]
+    fn update_hint_from_ttl_and_syndication() {
+        assert_eq!(update_hint_secs(Some(15), ""), Some(900));
+        let raw = "<channel><sy:updatePeriod>hourly</sy:updatePeriod><sy:updateFrequency>2</sy:updateFrequency></channel>";
+        assert_eq!(update_hint_secs(None, raw), Some(1800));
+        let raw = "<sy:updatePeriod> daily </sy:updatePeriod>";
+        assert_eq!(update_hint_secs(None, raw), Some(86400));
+        assert_eq!(update_hint_secs(None, "<rss></rss>"), None);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::models::Article;
+use quick_xml::escape::escape;
+use serde::Serialize;
+
+/// The FTS5 trigram tokenizer can only match terms of at least this many characters.
+pub const MIN_TRIGRAM_CHARS: usize = 3;
+/// Age in days at which an article's relevance score is halved.
+pub const RECENCY_HALF_LIFE_DAYS: f64 = 3.0;
+/// Markers around matches in a raw FTS5 snippet, turned into `<mark>` tags by
+/// [`snippet_html`] once the text around them is escaped.
+pub const SNIPPET_MARK_START: char = '\u{2}';
+pub const SNIPPET_MARK_END: char = '\u{3}';
+
+/// A search result with its ranking score and a highlighted excerpt.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub article: Article,
+    /// HTML: escaped article text with matches wrapped in `<mark>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    pub score: f64,
+}
+
+/// A user search query split into terms, with its FTS5 translation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    /// FTS5 MATCH expression, or None when some term is too short for the trigram index.
+    pub fts: Option<String>,
+    /// Terms (words or phrases) searched for.
+    pub terms: Vec<String>,
+    /// `terms` as alternatives, each a list of terms that must all appear: AND binds
+    /// tighter than OR, as in FTS5.
+    pub any_of: Vec<Vec<String>>,
+    /// Terms prefixed with `-` or `NOT` that must not appear.
+    pub excluded: Vec<String>,
+}
+
+impl ParsedQuery {
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Term(String),
+    Not(String),
+    Or,
+    And,
+}
+
+/// Parse a search box query. Supports `"exact phrases"`, `OR`, `AND`, and
+/// exclusion with `-term` or `NOT term`; adjacent terms are ANDed.
+///
+/// Every term is quoted in the FTS5 expression so punctuation in user input
+/// can't produce a syntax error. Exclusions are applied to the whole query.
+pub fn parse_query(input: &str) -> ParsedQuery {
+    let tokens = tokenize(input);
+
+    let mut parsed = ParsedQuery::default();
+    let mut expr = String::new();
+    let mut pending_op: Option<&str> = None;
+    for token in tokens {
+        match token {
+            Token::Term(t) => {
+                let op = pending_op.take();
+                match parsed.any_of.last_mut() {
+                    Some(clause) if op != Some("OR") => clause.push(t.clone()),
+                    _ => parsed.any_of.push(vec![t.clone()]),
+                }
+                if !expr.is_empty() {
+                    expr.push(' ');
+                    if let Some(op) = op {
+                        expr.push_str(op);
+                        expr.push(' ');
+                    }
+                }
+                expr.push_str(&quote(&t));
+                parsed.terms.push(t);
+            }
+            Token::Not(t) => parsed.excluded.push(t),
+            Token::Or => pending_op = Some("OR"),
+            Token::And => pending_op = Some("AND"),
+        }
+    }
+
+    let too_short = parsed
+        .terms
+        .iter()
+        .chain(&parsed.excluded)
+        .any(|t| t.chars().count() < MIN_TRIGRAM_CHARS);
+    if !parsed.terms.is_empty() && !too_short {
+        let mut fts = format!("({})", expr);
+        for t in &parsed.excluded {
+            fts.push_str(" NOT ");
+            fts.push_str(&quote(t));
+        }
+        parsed.fts = Some(fts);
+    }
+    parsed
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut negate_next = false;
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut negate = std::mem::take(&mut negate_next);
+        if c == '-' {
+            negate = true;
+            chars.next();
+        }
+
+        let text: String = if chars.peek() == Some(&'"') {
+            chars.next();
+            let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            phrase.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            if !negate {
+                match word.as_str() {
+                    "OR" => {
+                        tokens.push(Token::Or);
+                        continue;
+                    }
+                    "AND" => {
+                        tokens.push(Token::And);
+                        continue;
+                    }
+                    "NOT" => {
+                        negate_next = true;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            word
+        };
+
+        if text.is_empty() {
+            continue;
+        }
+        tokens.push(if negate { Token::Not(text) } else { Token::Term(text) });
+    }
+    tokens
+}
+
+fn quote(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+/// LIKE pattern matching `term` anywhere, escaping `%`, `_` and `\` (use with `ESCAPE '\'`).
+pub fn like_pattern(term: &str) -> String {
+    let mut pattern = String::with_capacity(term.len() + 2);
+    pattern.push('%');
+    for c in term.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// An FTS5 snippet marked with [`SNIPPET_MARK_START`] and [`SNIPPET_MARK_END`] as HTML:
+/// the text escaped, matches wrapped in `<mark>`.
+pub fn snippet_html(raw: &str) -> String {
+    escape(raw)
+        .replace(SNIPPET_MARK_START, "<mark>")
+        .replace(SNIPPET_MARK_END, "</mark>")
+}
+
+/// Excerpt of `text` around the first matching term as HTML: the text escaped, matches
+/// wrapped in `<mark>`.
+///
+/// Used when the FTS index can't produce a snippet (short terms). Matching is case-insensitive
+/// for ASCII only, which is all the LIKE fallback guarantees too.
+pub fn highlight(text: &str, terms: &[String], context_chars: usize) -> Option<String> {
+    let lower = text.to_ascii_lowercase();
+    let (start, term) = terms
+        .iter()
+        .filter(|t| !t.is_empty())
+        .filter_map(|t| lower.find(&t.to_ascii_lowercase()).map(|i| (i, t)))
+        .min_by_key(|(i, _)| *i)?;
+
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .nth(context_chars.saturating_sub(1))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let to = text[start..]
+        .char_indices()
+        .nth(term.chars().count() + context_chars)
+        .map(|(i, _)| start + i)
+        .unwrap_or(text.len());
+
+    let window = &text[from..to];
+    let window_lower = &lower[from..to];
+    let mut out = String::new();
+    if from > 0 {
+        out.push('…');
+    }
+    let mut pos = 0;
+    while pos < window.len() {
+        let next = terms
+            .iter()
+            .filter(|t| !t.is_empty())
+            .filter_map(|t| {
+                window_lower[pos..]
+                    .find(&t.to_ascii_lowercase())
+                    .map(|i| (pos + i, t.len()))
+            })
+            .min_by_key(|(i, _)| *i);
+        match next {
+            Some((i, len)) if i + len <= window.len() => {
+                out.push_str(&escape(&window[pos..i]));
+                out.push_str("<mark>");
+                out.push_str(&escape(&window[i..i + len]));
+                out.push_str("</mark>");
+                pos = i + len;
+            }
+            _ => {
+                out.push_str(&escape(&window[pos..]));
+                break;
+            }
+        }
+    }
+    if to < text.len() {
+        out.push('…');
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_words_are_anded_and_quoted() {
+        let q = parse_query("rust async");
+        assert_eq!(q.fts.as_deref(), Some(r#"("rust" "async")"#));
+        assert_eq!(q.terms, vec!["rust", "async"]);
+    }
+
+    #[test]
+    fn phrases_operators_and_exclusions() {
+        let q = parse_query(r#""open source" OR linux -windows NOT macos"#);
+        assert_eq!(
+            q.fts.as_deref(),
+            Some(r#"("open source" OR "linux") NOT "windows" NOT "macos""#)
+        );
+        assert_eq!(q.excluded, vec!["windows", "macos"]);
+        assert_eq!(q.any_of, vec![vec!["open source"], vec!["linux"]]);
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let q = parse_query("政治 経済 OR 野球 AND 速報");
+        assert!(q.fts.is_none());
+        assert_eq!(q.any_of, vec![vec!["政治", "経済"], vec!["野球", "速報"]]);
+        assert_eq!(parse_query("OR rust cargo").any_of, vec![vec!["rust", "cargo"]]);
+    }
+
+    #[test]
+    fn punctuation_cannot_break_fts_syntax() {
+        let q = parse_query(r#"c++ a"b* (foo)"#);
+        assert_eq!(q.fts.as_deref(), Some(r#"("c++" "a""b*" "(foo)")"#));
+        // The stray quote opens a phrase that runs to the end of input
+        let q = parse_query(r#"c++ "b* (foo)"#);
+        assert_eq!(q.fts.as_deref(), Some(r#"("c++" "b* (foo)")"#));
+    }
+
+    #[test]
+    fn short_terms_fall_back() {
+        let q = parse_query("政治 ニュース");
+        assert!(q.fts.is_none());
+        assert_eq!(q.terms, vec!["政治", "ニュース"]);
+        assert!(parse_query("政治家 ニュース").fts.is_some());
+    }
+
+    #[test]
+    fn leading_operator_is_ignored() {
+        assert_eq!(parse_query("OR rust cargo").fts.as_deref(), Some(r#"("rust" "cargo")"#));
+    }
+
+    #[test]
+    fn only_exclusions_is_empty() {
+        let q = parse_query("-sports OR AND");
+        assert!(q.is_empty());
+        assert!(q.fts.is_none());
+    }
+
+    #[test]
+    fn like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern("100%_a\\b"), r"%100\%\_a\\b%");
+    }
+
+    #[test]
+    fn highlight_marks_terms_with_context() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let s = highlight(text, &["fox".to_string()], 6).unwrap();
+        assert_eq!(s, "…brown <mark>fox</mark> jumps…");
+        let s = highlight("日本の政治と経済", &["政治".to_string()], 10).unwrap();
+        assert_eq!(s, "日本の<mark>政治</mark>と経済");
+        assert!(highlight(text, &["cat".to_string()], 6).is_none());
+    }
+
+    #[test]
+    fn snippets_escape_feed_text() {
+        let s = highlight("<img src=x onerror=alert(1)> fox & hound", &["fox".to_string()], 40).unwrap();
+        assert_eq!(s, "&lt;img src=x onerror=alert(1)&gt; <mark>fox</mark> &amp; hound");
+        let raw = format!("<b>{SNIPPET_MARK_START}rust{SNIPPET_MARK_END}</b>");
+        assert_eq!(snippet_html(&raw), "&lt;b&gt;<mark>rust</mark>&lt;/b&gt;");
+    }
+}
@@ -0,0 +1,778 @@
+use crate::error::{AppError, Result};
+use crate::models::AudioEnclosure;
+use crate::ogp::{self, parse_date};
+use crate::schedule;
+use chrono::{DateTime, Utc};
+use feed_rs::model::{MediaContent, MediaObject};
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use quick_xml::{Reader, XmlVersion};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use url::Url;
+
+/// Format a source publishes, which decides the parser `fetch_feed` uses.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SourceType {
+    /// RSS 0.9x/1.0/2.0 or Atom, via feed-rs.
+    #[default]
+    #[serde(rename = "rss")]
+    Rss,
+    /// JSON Feed 1.0/1.1.
+    #[serde(rename = "jsonfeed")]
+    JsonFeed,
+    /// A Google News sitemap (`<urlset>` with `news:news` entries).
+    #[serde(rename = "news_sitemap")]
+    NewsSitemap,
+    /// An HTML listing page read with the feed's [`ScrapeRules`].
+    #[serde(rename = "scrape")]
+    Scrape,
+}
+
+impl SourceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rss => "rss",
+            Self::JsonFeed => "jsonfeed",
+            Self::NewsSitemap => "news_sitemap",
+            Self::Scrape => "scrape",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "rss" => Some(Self::Rss),
+            "jsonfeed" => Some(Self::JsonFeed),
+            "news_sitemap" => Some(Self::NewsSitemap),
+            "scrape" => Some(Self::Scrape),
+            _ => None,
+        }
+    }
+
+    /// Check a source of this type has usable rules: scrape sources need valid selectors.
+    pub fn check_rules(&self, scrape: Option<&ScrapeRules>) -> Result<()> {
+        match (self, scrape) {
+            (Self::Scrape, Some(rules)) => rules.validate(),
+            (Self::Scrape, None) => Err(AppError::ConfigError("scrape source without scrape rules".into())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Parse a body fetched from `url`. Scrape sources need their rules. Scraped pages and
+    /// news sitemaps are decoded with the charset of `content_type`, the response's header.
+    pub fn parse(
+        &self,
+        body: &[u8],
+        url: &str,
+        scrape: Option<&ScrapeRules>,
+        content_type: Option<&str>,
+    ) -> Result<ParsedSource> {
+        match self {
+            Self::Rss => parse_rss(body),
+            Self::JsonFeed => parse_json_feed(body),
+            Self::NewsSitemap => parse_news_sitemap(body, content_type),
+            Self::Scrape => {
+                let rules = scrape
+                    .ok_or_else(|| AppError::ConfigError("scrape source without scrape rules".into()))?;
+                rules.parse(&ogp::decode_html(body, content_type), url)
+            }
+        }
+    }
+}
+
+/// CSS selectors that turn a listing page into items. `link`, `title`, `date` and
+/// `image` are evaluated within each `item` element.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrapeRules {
+    pub item: String,
+    /// Element carrying the article URL in `href`. When it matches nothing, the item itself is tried.
+    pub link: String,
+    /// Defaults to the link's text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Read from `datetime` or `content`, else the element text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// Read from `src`, `data-src` or `content`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+impl ScrapeRules {
+    /// Check every selector parses, so a bad rule is rejected before it's saved.
+    pub fn validate(&self) -> Result<()> {
+        self.selectors().map(|_| ())
+    }
+
+    fn selectors(&self) -> Result<[Option<Selector>; 5]> {
+        let parse = |name: &str, css: Option<&String>| -> Result<Option<Selector>> {
+            css.map(|css| {
+                Selector::parse(css)
+                    .map_err(|e| AppError::ConfigError(format!("scrape {name} selector {css:?}: {e}")))
+            })
+            .transpose()
+        };
+        Ok([
+            parse("item", Some(&self.item))?,
+            parse("link", Some(&self.link))?,
+            parse("title", self.title.as_ref())?,
+            parse("date", self.date.as_ref())?,
+            parse("image", self.image.as_ref())?,
+        ])
+    }
+
+    /// Extract the items of a listing page; relative URLs are resolved against `base_url`.
+    pub fn parse(&self, html: &str, base_url: &str) -> Result<ParsedSource> {
+        let [Some(item), Some(link), title, date, image] = self.selectors()? else {
+            unreachable!("item and link selectors are required");
+        };
+        let base = Url::parse(base_url).map_err(|e| AppError::ConfigError(format!("scrape URL: {e}")))?;
+        let resolve = |href: &str| base.join(href.trim()).ok().map(|u| u.to_string());
+        let doc = Html::parse_document(html);
+
+        let mut seen = HashSet::new();
+        let mut entries = Vec::new();
+        for el in doc.select(&item) {
+            let link_el = el
+                .select(&link)
+                .find(|a| a.value().attr("href").is_some())
+                .or_else(|| el.value().attr("href").map(|_| el));
+            let Some(url) = link_el.and_then(|a| resolve(a.value().attr("href")?)) else {
+                continue;
+            };
+            if !url.starts_with("http") || !seen.insert(url.clone()) {
+                continue;
+            }
+            let title = match &title {
+                Some(sel) => el.select(sel).next().map(element_text),
+                None => link_el.map(element_text),
+            }
+            .filter(|t| !t.is_empty());
+            let published_at = date.as_ref().and_then(|sel| {
+                let d = el.select(sel).next()?;
+                let v = d.value();
+                v.attr("datetime")
+                    .or(v.attr("content"))
+                    .map(String::from)
+                    .or_else(|| Some(element_text(d)))
+                    .and_then(|raw| parse_date(&raw))
+            });
+            let image_url = image.as_ref().and_then(|sel| {
+                let v = el.select(sel).next()?.value();
+                resolve(v.attr("src").or(v.attr("data-src")).or(v.attr("content"))?)
+            });
+            entries.push(SourceEntry {
+                url,
+                title,
+                description: None,
+                image_url,
+                published_at,
+                audio: None,
+            });
+        }
+        Ok(ParsedSource {
+            entries,
+            ..Default::default()
+        })
+    }
+}
+
+fn element_text(el: ElementRef) -> String {
+    el.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// One item of a source, before it becomes an `Article`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceEntry {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub audio: Option<AudioEnclosure>,
+}
+
+/// A parsed source body.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedSource {
+    /// The source's own title, where the format has one.
+    pub title: Option<String>,
+    pub entries: Vec<SourceEntry>,
+    /// Publisher's update hint in seconds, if the format has one.
+    pub update_hint_secs: Option<u64>,
+    /// WebSub hub advertised by the feed (`rel="hub"`, or `hubs` in JSON Feed).
+    pub hub: Option<String>,
+    /// The feed's own URL as it advertises it (`rel="self"`), the topic to subscribe to.
+    pub self_url: Option<String>,
+    /// Language the source declares for its items (`<language>`, `news:language`).
+    pub language: Option<String>,
+}
+
+fn parse_rss(body: &[u8]) -> Result<ParsedSource> {
+    let parsed = feed_rs::parser::parse(body).map_err(|e| AppError::ParseError(e.to_string()))?;
+    let update_hint_secs = schedule::update_hint_secs(parsed.ttl, &String::from_utf8_lossy(body));
+    // feed-rs doesn't read itunes:episode; entries come in document order
+    let episodes = itunes_episodes(body);
+    let entries = parsed
+        .entries
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            let url = entry.links.first()?.href.clone();
+            let audio = audio_enclosure(&entry.media).map(|audio| AudioEnclosure {
+                episode: episodes.get(i).copied().flatten(),
+                ..audio
+            });
+            Some(SourceEntry {
+                url,
+                title: entry.title.map(|t| t.content),
+                description: entry
+                    .summary
+                    .map(|s| s.content)
+                    .or_else(|| entry.content.and_then(|c| c.body)),
+                // Try to extract image from media content, skipping audio/video enclosures
+                image_url: entry
+                    .media
+                    .iter()
+                    .flat_map(|m| &m.content)
+                    .find(|c| !is_playable(c))
+                    .and_then(|c| c.url.as_ref())
+                    .map(|u| u.to_string()),
+                published_at: entry.published.or(entry.updated),
+                audio,
+            })
+        })
+        .collect();
+    let link = |rel: &str| {
+        parsed
+            .links
+            .iter()
+            .find(|l| l.rel.as_deref() == Some(rel))
+            .map(|l| l.href.clone())
+    };
+    Ok(ParsedSource {
+        hub: link("hub"),
+        self_url: link("self"),
+        title: parsed.title.map(|t| t.content),
+        language: parsed.language,
+        entries,
+        update_hint_secs,
+    })
+}
+
+fn mime_type(content: &MediaContent) -> Option<String> {
+    content.content_type.as_ref().map(|t| t.to_string())
+}
+
+fn is_playable(content: &MediaContent) -> bool {
+    mime_type(content).is_some_and(|t| t.starts_with("audio/") || t.starts_with("video/"))
+}
+
+/// The first audio `<enclosure>`/`media:content` of an item, with the item's
+/// `itunes:duration` when the content doesn't carry its own.
+fn audio_enclosure(media: &[MediaObject]) -> Option<AudioEnclosure> {
+    media.iter().find_map(|m| {
+        let content = m
+            .content
+            .iter()
+            .find(|c| mime_type(c).is_some_and(|t| t.starts_with("audio/")))?;
+        Some(AudioEnclosure {
+            url: content.url.as_ref()?.to_string(),
+            mime_type: mime_type(content),
+            duration_secs: content.duration.or(m.duration).map(|d| d.as_secs()),
+            episode: None,
+        })
+    })
+}
+
+/// `itunes:episode` of each RSS `<item>`, in document order.
+fn itunes_episodes(body: &[u8]) -> Vec<Option<u32>> {
+    let xml = String::from_utf8_lossy(body);
+    let mut reader = Reader::from_str(&xml);
+    let mut episodes = Vec::new();
+    let mut current: Option<Option<u32>> = None;
+    let mut in_episode = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match e.name().as_ref() {
+                b"item" => current = Some(None),
+                b"itunes:episode" => in_episode = current.is_some(),
+                _ => {}
+            },
+            Ok(Event::Text(t)) if in_episode => {
+                let text = t.xml_content(XmlVersion::Implicit1_0).unwrap_or_default();
+                current = Some(text.trim().parse().ok());
+                in_episode = false;
+            }
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"item" => episodes.extend(current.take()),
+                b"itunes:episode" => in_episode = false,
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    episodes
+}
+
+#[derive(Deserialize)]
+struct JsonFeed {
+    version: String,
+    title: Option<String>,
+    feed_url: Option<String>,
+    language: Option<String>,
+    #[serde(default)]
+    hubs: Vec<JsonFeedHub>,
+    #[serde(default)]
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Deserialize)]
+struct JsonFeedHub {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct JsonFeedItem {
+    url: Option<String>,
+    external_url: Option<String>,
+    title: Option<String>,
+    summary: Option<String>,
+    content_text: Option<String>,
+    content_html: Option<String>,
+    image: Option<String>,
+    banner_image: Option<String>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    #[serde(default)]
+    attachments: Vec<JsonFeedAttachment>,
+}
+
+#[derive(Deserialize)]
+struct JsonFeedAttachment {
+    url: String,
+    mime_type: String,
+    duration_in_seconds: Option<f64>,
+}
+
+fn parse_json_feed(body: &[u8]) -> Result<ParsedSource> {
+    let feed: JsonFeed = serde_json::from_slice(body)
+        .map_err(|e| AppError::ParseError(format!("JSON Feed: {e}")))?;
+    if !feed.version.starts_with("https://jsonfeed.org/version/") {
+        return Err(AppError::ParseError(format!("JSON Feed: unknown version {}", feed.version)));
+    }
+    let entries = feed
+        .items
+        .into_iter()
+        .filter_map(|item| {
+            let url = item.url.or(item.external_url)?;
+            Some(SourceEntry {
+                url,
+                title: item.title,
+                description: item.summary.or(item.content_text).or(item.content_html),
+                image_url: item.image.or(item.banner_image),
+                published_at: item
+                    .date_published
+                    .or(item.date_modified)
+                    .as_deref()
+                    .and_then(parse_date),
+                audio: item
+                    .attachments
+                    .into_iter()
+                    .find(|a| a.mime_type.starts_with("audio/"))
+                    .map(|a| AudioEnclosure {
+                        url: a.url,
+                        mime_type: Some(a.mime_type),
+                        duration_secs: a.duration_in_seconds.map(|d| d.round() as u64),
+                        episode: None,
+                    }),
+            })
+        })
+        .collect();
+    Ok(ParsedSource {
+        title: feed.title,
+        entries,
+        update_hint_secs: None,
+        hub: feed
+            .hubs
+            .into_iter()
+            .find(|h| h.kind.eq_ignore_ascii_case("websub"))
+            .map(|h| h.url),
+        self_url: feed.feed_url,
+        language: feed.language,
+    })
+}
+
+/// Element of a news sitemap `<url>` whose text is being read.
+#[derive(Clone, Copy, PartialEq)]
+enum SitemapField {
+    Loc,
+    Title,
+    PublicationDate,
+    ImageLoc,
+    Language,
+}
+
+fn parse_news_sitemap(body: &[u8], content_type: Option<&str>) -> Result<ParsedSource> {
+    let xml = ogp::decode_html(body, content_type);
+    let mut reader = Reader::from_str(&xml);
+    let err = |e: &dyn std::fmt::Display| AppError::ParseError(format!("News sitemap: {e}"));
+
+    let mut entries = Vec::new();
+    let mut saw_urlset = false;
+    let mut current: Option<SourceEntry> = None;
+    // Local names of the open elements
+    let mut path: Vec<String> = Vec::new();
+    let mut field: Option<SitemapField> = None;
+    let mut text = String::new();
+    // news:language is per entry, but always the publication's language in practice
+    let mut language: Option<String> = None;
+
+    loop {
+        match reader.read_event().map_err(|e| err(&e))? {
+            Event::Start(e) => {
+                let local = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                match local.as_str() {
+                    "sitemapindex" if path.is_empty() => {
+                        return Err(err(&"sitemap index; point the feed at a news sitemap"))
+                    }
+                    "urlset" if path.is_empty() => saw_urlset = true,
+                    "url" if current.is_none() => current = Some(SourceEntry::default()),
+                    _ => {}
+                }
+                field = match (path.last().map(String::as_str), local.as_str()) {
+                    (Some("url"), "loc") => Some(SitemapField::Loc),
+                    (Some("news"), "title") => Some(SitemapField::Title),
+                    (Some("news"), "publication_date") => Some(SitemapField::PublicationDate),
+                    (Some("image"), "loc") => Some(SitemapField::ImageLoc),
+                    (Some("publication"), "language") => Some(SitemapField::Language),
+                    _ => None,
+                };
+                text.clear();
+                path.push(local);
+            }
+            Event::Text(t) if field.is_some() => {
+                text.push_str(&t.xml_content(XmlVersion::Implicit1_0).map_err(|e| err(&e))?);
+            }
+            Event::CData(t) if field.is_some() => {
+                text.push_str(&t.decode().map_err(|e| err(&e))?);
+            }
+            Event::GeneralRef(r) if field.is_some() => {
+                if let Some(c) = r.resolve_char_ref().map_err(|e| err(&e))? {
+                    text.push(c);
+                } else if let Some(s) = resolve_predefined_entity(&r.decode().map_err(|e| err(&e))?) {
+                    text.push_str(s);
+                }
+            }
+            Event::End(e) => {
+                path.pop();
+                let value = text.trim().to_string();
+                if let (Some(f), Some(entry)) = (field.take(), current.as_mut()) {
+                    if !value.is_empty() {
+                        match f {
+                            SitemapField::Loc => entry.url = value,
+                            SitemapField::Title => entry.title = Some(value),
+                            SitemapField::PublicationDate => entry.published_at = parse_date(&value),
+                            SitemapField::ImageLoc => {
+                                entry.image_url.get_or_insert(value);
+                            }
+                            SitemapField::Language => {
+                                language.get_or_insert(value);
+                            }
+                        }
+                    }
+                }
+                if e.local_name().as_ref() == b"url" {
+                    // Plain sitemap entries without news:news carry no title; skip them
+                    if let Some(entry) = current.take().filter(|e| !e.url.is_empty() && e.title.is_some()) {
+                        entries.push(entry);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !saw_urlset {
+        return Err(err(&"missing <urlset> root"));
+    }
+    Ok(ParsedSource {
+        entries,
+        language,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_type_round_trip() {
+        for t in [SourceType::Rss, SourceType::JsonFeed, SourceType::NewsSitemap, SourceType::Scrape] {
+            assert_eq!(SourceType::from_str(t.as_str()), Some(t));
+            let json = serde_json::to_string(&t).unwrap();
+            assert_eq!(json, format!("\"{}\"", t.as_str()));
+        }
+    }
+
+    #[test]
+    fn parses_json_feed() {
+        let body = br#"{
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": "Example",
+            "items": [
+                {"id": "1", "url": "https://example.com/a", "title": "First",
+                 "content_html": "<p>Body</p>", "summary": "Short",
+                 "image": "https://example.com/a.jpg", "date_published": "2024-05-01T09:00:00+09:00"},
+                {"id": "2", "external_url": "https://other.example/b", "content_text": "Untitled post"},
+                {"id": "3", "content_text": "No link at all"}
+            ]
+        }"#;
+        let parsed = SourceType::JsonFeed.parse(body, "https://example.com/", None, None).unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+        let first = &parsed.entries[0];
+        assert_eq!(first.url, "https://example.com/a");
+        assert_eq!(first.title.as_deref(), Some("First"));
+        assert_eq!(first.description.as_deref(), Some("Short"));
+        assert_eq!(first.image_url.as_deref(), Some("https://example.com/a.jpg"));
+        assert_eq!(first.published_at.unwrap().to_rfc3339(), "2024-05-01T00:00:00+00:00");
+        assert_eq!(parsed.entries[1].url, "https://other.example/b");
+        assert_eq!(parsed.entries[1].title, None);
+
+        assert!(SourceType::JsonFeed.parse(br#"{"version": "1", "items": []}"#, "https://example.com/", None, None).is_err());
+    }
+
+    #[test]
+    fn parses_news_sitemap() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+        xmlns:news="http://www.google.com/schemas/sitemap-news/0.9"
+        xmlns:image="http://www.google.com/schemas/sitemap-image/1.1">
+  <url>
+    <loc>https://www.example.co.jp/articles/123</loc>
+    <news:news>
+      <news:publication><news:name>Example 新聞</news:name><news:language>ja</news:language></news:publication>
+      <news:publication_date>2024-05-01T12:30+09:00</news:publication_date>
+      <news:title>首相、&quot;新たな&quot;経済対策を発表 &amp; 会見</news:title>
+    </news:news>
+    <image:image><image:loc>https://img.example.co.jp/123.jpg</image:loc></image:image>
+  </url>
+  <url>
+    <loc>https://www.example.co.jp/articles/124</loc>
+    <news:news>
+      <news:publication_date>2024-05-01</news:publication_date>
+      <news:title><![CDATA[株価 <速報>]]></news:title>
+    </news:news>
+  </url>
+  <url><loc>https://www.example.co.jp/about</loc></url>
+</urlset>"#;
+        let parsed = SourceType::NewsSitemap.parse(body.as_bytes(), "https://example.com/", None, None).unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+        let first = &parsed.entries[0];
+        assert_eq!(first.url, "https://www.example.co.jp/articles/123");
+        assert_eq!(first.title.as_deref(), Some("首相、\"新たな\"経済対策を発表 & 会見"));
+        assert_eq!(first.image_url.as_deref(), Some("https://img.example.co.jp/123.jpg"));
+        assert_eq!(first.published_at.unwrap().to_rfc3339(), "2024-05-01T03:30:00+00:00");
+        assert_eq!(parsed.entries[1].title.as_deref(), Some("株価 <速報>"));
+        assert!(parsed.entries[1].published_at.is_some());
+        assert_eq!(parsed.language.as_deref(), Some("ja"));
+    }
+
+    #[test]
+    fn news_sitemap_is_decoded_with_its_declared_charset() {
+        let sitemap = |declaration: &str| {
+            let xml = format!(
+                r#"{declaration}<urlset xmlns:news="http://www.google.com/schemas/sitemap-news/0.9">
+<url><loc>https://www.example.co.jp/a/1</loc><news:news><news:title>日銀が金利据え置き</news:title></news:news></url>
+</urlset>"#
+            );
+            encoding_rs::SHIFT_JIS.encode(&xml).0.into_owned()
+        };
+        let title = |body: &[u8], content_type| {
+            let parsed = SourceType::NewsSitemap.parse(body, "https://example.com/", None, content_type).unwrap();
+            parsed.entries[0].title.clone()
+        };
+        let declared = sitemap(r#"<?xml version="1.0" encoding="Shift_JIS"?>"#);
+        assert_eq!(title(&declared, None).as_deref(), Some("日銀が金利据え置き"));
+        let undeclared = sitemap("");
+        assert_eq!(
+            title(&undeclared, Some("application/xml; charset=Shift_JIS")).as_deref(),
+            Some("日銀が金利据え置き")
+        );
+    }
+
+    #[test]
+    fn sitemap_index_and_other_xml_are_rejected() {
+        let index = br#"<sitemapindex><sitemap><loc>https://example.com/news.xml</loc></sitemap></sitemapindex>"#;
+        assert!(SourceType::NewsSitemap.parse(index, "https://example.com/", None, None).is_err());
+        assert!(SourceType::NewsSitemap.parse(b"<rss><channel/></rss>", "https://example.com/", None, None).is_err());
+    }
+
+    #[test]
+    fn rss_entries_keep_links_and_dates() {
+        let body = br#"<?xml version="1.0"?><rss version="2.0"><channel><title>t</title><ttl>30</ttl>
+            <item><title>One</title><link>https://example.com/1</link><description>d</description>
+            <pubDate>Wed, 01 May 2024 09:00:00 +0900</pubDate></item>
+            </channel></rss>"#;
+        let parsed = SourceType::Rss.parse(body, "https://example.com/", None, None).unwrap();
+        assert_eq!(parsed.update_hint_secs, Some(1800));
+        assert_eq!(parsed.entries[0].url, "https://example.com/1");
+        assert_eq!(parsed.entries[0].description.as_deref(), Some("d"));
+        assert_eq!(parsed.hub, None);
+    }
+
+    #[test]
+    fn podcast_enclosures_become_audio() {
+        let body = br#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd" xmlns:media="http://search.yahoo.com/mrss/">
+<channel><title>Cast</title>
+  <item><title>Ep 42</title><link>https://cast.example/42</link>
+    <enclosure url="https://cdn.example/42.mp3" length="1234" type="audio/mpeg"/>
+    <itunes:duration>1:02:03</itunes:duration><itunes:episode>42</itunes:episode></item>
+  <item><title>Bonus</title><link>https://cast.example/bonus</link>
+    <enclosure url="https://cdn.example/bonus.m4a" type="audio/x-m4a"/></item>
+  <item><title>Article</title><link>https://cast.example/post</link>
+    <media:content url="https://cdn.example/post.jpg" type="image/jpeg"/></item>
+</channel></rss>"#;
+        let parsed = SourceType::Rss.parse(body, "https://cast.example/rss", None, None).unwrap();
+        let audio = parsed.entries[0].audio.as_ref().unwrap();
+        assert_eq!(audio.url, "https://cdn.example/42.mp3");
+        assert_eq!(audio.mime_type.as_deref(), Some("audio/mpeg"));
+        assert_eq!(audio.duration_secs, Some(3723));
+        assert_eq!(audio.episode, Some(42));
+        // The enclosure isn't mistaken for an image
+        assert_eq!(parsed.entries[0].image_url, None);
+
+        let bonus = parsed.entries[1].audio.as_ref().unwrap();
+        assert_eq!(bonus.episode, None);
+        assert_eq!(bonus.duration_secs, None);
+
+        assert_eq!(parsed.entries[2].audio, None);
+        assert_eq!(parsed.entries[2].image_url.as_deref(), Some("https://cdn.example/post.jpg"));
+
+        let json = br#"{"version":"https://jsonfeed.org/version/1.1","items":[{"url":"https://cast.example/1",
+            "attachments":[{"url":"https://cdn.example/1.pdf","mime_type":"application/pdf"},
+                           {"url":"https://cdn.example/1.mp3","mime_type":"audio/mpeg","duration_in_seconds":95.6}]}]}"#;
+        let parsed = SourceType::JsonFeed.parse(json, "https://cast.example/feed.json", None, None).unwrap();
+        let audio = parsed.entries[0].audio.as_ref().unwrap();
+        assert_eq!(audio.url, "https://cdn.example/1.mp3");
+        assert_eq!(audio.duration_secs, Some(96));
+    }
+
+    #[test]
+    fn detects_websub_hub_and_self_links() {
+        let rss = br#"<?xml version="1.0"?><rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel>
+            <title>t</title><link>https://example.com/</link>
+            <atom:link rel="hub" href="https://pubsubhubbub.appspot.com/"/>
+            <atom:link rel="self" type="application/rss+xml" href="https://example.com/rss"/>
+            </channel></rss>"#;
+        let parsed = SourceType::Rss.parse(rss, "https://example.com/rss", None, None).unwrap();
+        assert_eq!(parsed.hub.as_deref(), Some("https://pubsubhubbub.appspot.com/"));
+        assert_eq!(parsed.self_url.as_deref(), Some("https://example.com/rss"));
+
+        let atom = br#"<feed xmlns="http://www.w3.org/2005/Atom"><title>a</title><id>urn:a</id>
+            <updated>2024-05-01T00:00:00Z</updated>
+            <link rel="hub" href="https://hub.example/"/><link rel="self" href="https://example.com/atom"/></feed>"#;
+        let parsed = SourceType::Rss.parse(atom, "https://example.com/atom", None, None).unwrap();
+        assert_eq!(parsed.hub.as_deref(), Some("https://hub.example/"));
+
+        let json = br#"{"version":"https://jsonfeed.org/version/1.1","feed_url":"https://example.com/feed.json",
+            "hubs":[{"type":"rssCloud","url":"https://cloud.example/"},{"type":"WebSub","url":"https://hub.example/"}],"items":[]}"#;
+        let parsed = SourceType::JsonFeed.parse(json, "https://example.com/feed.json", None, None).unwrap();
+        assert_eq!(parsed.hub.as_deref(), Some("https://hub.example/"));
+        assert_eq!(parsed.self_url.as_deref(), Some("https://example.com/feed.json"));
+    }
+
+    fn rules() -> ScrapeRules {
+        ScrapeRules {
+            item: "ul.news li".into(),
+            link: "a".into(),
+            title: Some(".headline".into()),
+            date: Some("time".into()),
+            image: Some("img".into()),
+        }
+    }
+
+    #[test]
+    fn scrapes_listing_page() {
+        let html = r#"<html><body>
+            <ul class="news">
+              <li><a href="/news/1"><span class="headline">  First
+                 story </span></a><time datetime="2024-05-01T09:00:00+09:00">5月1日</time>
+                 <img data-src="/img/1.jpg"></li>
+              <li><a href="https://other.example/2"><span class="headline">Second</span></a>
+                 <time>2024年5月2日 10:30</time></li>
+              <li><a href="/news/1"><span class="headline">Duplicate</span></a></li>
+              <li><span class="headline">No link</span></li>
+              <li><a href="javascript:void(0)"><span class="headline">Script</span></a></li>
+            </ul>
+            <ul class="other"><li><a href="/ad">Ad</a></li></ul>
+        </body></html>"#;
+        let parsed = rules().parse(html, "https://example.co.jp/list/").unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+        let first = &parsed.entries[0];
+        assert_eq!(first.url, "https://example.co.jp/news/1");
+        assert_eq!(first.title.as_deref(), Some("First story"));
+        assert_eq!(first.image_url.as_deref(), Some("https://example.co.jp/img/1.jpg"));
+        assert_eq!(first.published_at.unwrap().to_rfc3339(), "2024-05-01T00:00:00+00:00");
+        let second = &parsed.entries[1];
+        assert_eq!(second.url, "https://other.example/2");
+        assert_eq!(second.published_at.unwrap().to_rfc3339(), "2024-05-02T01:30:00+00:00");
+        assert_eq!(second.image_url, None);
+    }
+
+    #[test]
+    fn item_can_be_the_link_and_title_defaults_to_link_text() {
+        let rules = ScrapeRules {
+            item: "a.story".into(),
+            link: "a[href]".into(),
+            title: None,
+            date: None,
+            image: None,
+        };
+        let html = r#"<a class="story" href="/s/1">Story <b>one</b></a>"#;
+        let parsed = SourceType::Scrape
+            .parse(html.as_bytes(), "https://example.com/", Some(&rules), None)
+            .unwrap();
+        assert_eq!(parsed.entries[0].url, "https://example.com/s/1");
+        assert_eq!(parsed.entries[0].title.as_deref(), Some("Story one"));
+    }
+
+    #[test]
+    fn scraped_pages_use_the_header_charset() {
+        let (html, _, _) = encoding_rs::SHIFT_JIS.encode(r#"<a class="story" href="/s/1">首相が会見</a>"#);
+        let rules = ScrapeRules {
+            item: "a.story".into(),
+            link: "a[href]".into(),
+            title: None,
+            date: None,
+            image: None,
+        };
+        let parsed = SourceType::Scrape
+            .parse(&html, "https://example.com/", Some(&rules), Some("text/html; charset=Shift_JIS"))
+            .unwrap();
+        assert_eq!(parsed.entries[0].title.as_deref(), Some("首相が会見"));
+    }
+
+    #[test]
+    fn bad_or_missing_rules_are_config_errors() {
+        let mut bad = rules();
+        bad.date = Some("time[".into());
+        assert!(matches!(bad.validate(), Err(AppError::ConfigError(_))));
+        assert!(rules().validate().is_ok());
+        assert!(matches!(
+            SourceType::Scrape.parse(b"<html></html>", "https://example.com/", None, None),
+            Err(AppError::ConfigError(_))
+        ));
+        assert!(SourceType::Scrape.check_rules(None).is_err());
+        assert!(SourceType::Scrape.check_rules(Some(&bad)).is_err());
+        assert!(SourceType::Rss.check_rules(None).is_ok());
+    }
+}
@@ -0,0 +1,302 @@
+//! Storage traits shared by every backend: SQLite in news-server, DynamoDB in the
+//! Lambdas, and [`memory::MemoryStore`] for tests.
+//!
+//! Code written against these traits (e.g. [`apply_action`]) behaves the same on all
+//! of them. Backend-specific features like search and enrichment stay on the backends.
+
+pub mod memory;
+
+use crate::changes::{AdminAction, ChangeRequest, ChangeStatus};
+use crate::config::{DynamicFeed, FeatureFlags, ServiceConfig};
+use crate::error::{AppError, Result};
+use crate::models::{Article, Category, CategoryInfo, PageMetadata};
+use crate::opml;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+#[async_trait]
+pub trait ArticleStorage: Send + Sync {
+    /// Store articles, skipping ones already stored. Returns how many were new.
+    async fn put_articles(&self, articles: &[Article]) -> Result<usize>;
+
+    /// Newest first, optionally in one category and readable in `lang` (articles of
+    /// unknown language included). The cursor is opaque and only meaningful to the
+    /// backend that returned it.
+    async fn query_articles(
+        &self,
+        category: Option<&Category>,
+        lang: Option<&str>,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<Article>, Option<String>)>;
+
+    /// Store page metadata, filling in the image and description if the feed had none.
+    async fn update_page_metadata(&self, article: &Article, metadata: &PageMetadata) -> Result<()>;
+}
+
+#[async_trait]
+pub trait FeedStorage: Send + Sync {
+    async fn get_all_feeds(&self) -> Result<Vec<DynamicFeed>>;
+
+    async fn get_enabled_feeds(&self) -> Result<Vec<DynamicFeed>> {
+        let feeds = self.get_all_feeds().await?;
+        Ok(feeds.into_iter().filter(|f| f.enabled).collect())
+    }
+
+    async fn get_feed(&self, feed_id: &str) -> Result<Option<DynamicFeed>> {
+        let feeds = self.get_all_feeds().await?;
+        Ok(feeds.into_iter().find(|f| f.feed_id == feed_id))
+    }
+
+    /// Add a feed or replace the one with the same `feed_id`.
+    async fn put_feed(&self, feed: &DynamicFeed) -> Result<()>;
+
+    async fn delete_feed(&self, feed_id: &str) -> Result<()>;
+
+    /// Add the new feeds of an OPML import plan. Returns how many were added.
+    async fn import_feeds(&self, plan: &opml::ImportPlan, added_by: &str) -> Result<usize> {
+        let feeds = plan.new_feeds(added_by);
+        for feed in &feeds {
+            self.put_feed(feed).await?;
+        }
+        Ok(feeds.len())
+    }
+
+    /// Re-enable a quarantined feed. Backends that track fetch failures reset them too.
+    async fn release_quarantine(&self, feed_id: &str) -> Result<()> {
+        let feed = require_feed(self, feed_id).await?;
+        self.put_feed(&DynamicFeed {
+            enabled: true,
+            quarantine_reason: None,
+            ..feed
+        })
+        .await
+    }
+}
+
+#[async_trait]
+pub trait FeatureStorage: Send + Sync {
+    async fn get_feature_flags(&self) -> Result<FeatureFlags>;
+
+    /// Replace a feature's row. `extra` holds its settings, e.g.
+    /// `{"similarity_threshold": 0.5}`, and is dropped when `None`.
+    async fn set_feature_flag(
+        &self,
+        feature: &str,
+        enabled: bool,
+        extra: Option<&serde_json::Value>,
+    ) -> Result<()>;
+}
+
+#[async_trait]
+pub trait ChangeStorage: Send + Sync {
+    async fn create_change(&self, change: &ChangeRequest) -> Result<()>;
+
+    async fn get_change(&self, change_id: &str) -> Result<Option<ChangeRequest>>;
+
+    async fn update_change_status(&self, change_id: &str, status: ChangeStatus) -> Result<()>;
+
+    /// Most recent first.
+    async fn list_changes(&self, limit: usize) -> Result<Vec<ChangeRequest>>;
+}
+
+/// A configured article category.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryRecord {
+    pub id: String,
+    pub label_ja: String,
+    /// Empty for categories added from the admin chat, which only names them in Japanese.
+    pub label_en: String,
+    pub sort_order: i32,
+    pub visible: bool,
+}
+
+impl CategoryRecord {
+    pub fn info(&self) -> CategoryInfo {
+        CategoryInfo {
+            id: self.id.clone(),
+            label: if self.label_en.is_empty() { self.label_ja.clone() } else { self.label_en.clone() },
+            label_ja: self.label_ja.clone(),
+        }
+    }
+}
+
+/// The built-in categories, used until a backend has stored its own.
+pub fn default_categories() -> Vec<CategoryRecord> {
+    CategoryInfo::all()
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| CategoryRecord {
+            id: c.id,
+            label_ja: c.label_ja,
+            label_en: c.label,
+            sort_order: i as i32,
+            visible: true,
+        })
+        .collect()
+}
+
+#[async_trait]
+pub trait CategoryStorage: Send + Sync {
+    /// In display order.
+    async fn get_categories(&self) -> Result<Vec<CategoryRecord>>;
+
+    /// Add a category or replace the one with the same id.
+    async fn put_category(&self, category: &CategoryRecord) -> Result<()>;
+
+    /// Fails with [`AppError::NotFound`] for an unknown id.
+    async fn rename_category(&self, id: &str, label_ja: &str) -> Result<()>;
+
+    async fn delete_category(&self, id: &str) -> Result<()>;
+
+    /// Number the given ids in order. Ids not listed keep their position.
+    async fn reorder_categories(&self, order: &[String]) -> Result<()>;
+}
+
+/// Feeds and feature flags, as shown to the admin chat.
+pub async fn service_config<S>(store: &S) -> Result<ServiceConfig>
+where
+    S: FeedStorage + FeatureStorage + ?Sized,
+{
+    let feeds = store.get_all_feeds().await?;
+    let features = store.get_feature_flags().await?;
+    Ok(ServiceConfig { feeds, features })
+}
+
+/// Plan importing an OPML document: which of its feeds are new, duplicates, in an
+/// unknown category or invalid. Hidden categories count as known.
+pub async fn import_plan<S>(store: &S, xml: &str, default_category: Option<&str>) -> Result<opml::ImportPlan>
+where
+    S: FeedStorage + CategoryStorage + ?Sized,
+{
+    let imported = opml::parse_opml(xml)?;
+    let existing = store.get_all_feeds().await?;
+    let categories: Vec<CategoryInfo> = store.get_categories().await?.iter().map(CategoryRecord::info).collect();
+    Ok(opml::plan_import(imported, &existing, &categories, default_category))
+}
+
+/// Apply one admin chat action.
+///
+/// `AddFeed` URLs aren't fetched here; callers check them with
+/// [`crate::discovery::validate_feed`] first.
+pub async fn apply_action<S>(store: &S, action: &AdminAction) -> Result<()>
+where
+    S: FeedStorage + FeatureStorage + CategoryStorage + ?Sized,
+{
+    match action {
+        AdminAction::AddFeed {
+            url,
+            source,
+            category,
+            source_type,
+            scrape,
+        } => {
+            source_type.check_rules(scrape.as_ref())?;
+            let feed = DynamicFeed {
+                feed_id: format!(
+                    "feed-{}",
+                    uuid::Uuid::new_v4().to_string().split('-').next().unwrap_or("x")
+                ),
+                url: url.clone(),
+                source: source.clone(),
+                category: category.clone(),
+                enabled: true,
+                added_by: Some("admin-chat".into()),
+                poll_interval_secs: None,
+                quarantine_reason: None,
+                content_policy: Default::default(),
+                source_type: *source_type,
+                scrape: scrape.clone(),
+                filters: None,
+            };
+            store.put_feed(&feed).await
+        }
+        AdminAction::RemoveFeed { feed_id } => store.delete_feed(feed_id).await,
+        AdminAction::EnableFeed { feed_id } => {
+            let feed = require_feed(store, feed_id).await?;
+            if feed.quarantine_reason.is_some() {
+                return store.release_quarantine(feed_id).await;
+            }
+            store.put_feed(&DynamicFeed { enabled: true, ..feed }).await
+        }
+        AdminAction::DisableFeed { feed_id } => {
+            let feed = require_feed(store, feed_id).await?;
+            store.put_feed(&DynamicFeed { enabled: false, ..feed }).await
+        }
+        AdminAction::ReleaseQuarantine { feed_id } => store.release_quarantine(feed_id).await,
+        AdminAction::SetPollInterval { feed_id, seconds } => {
+            let feed = require_feed(store, feed_id).await?;
+            store
+                .put_feed(&DynamicFeed {
+                    poll_interval_secs: *seconds,
+                    ..feed
+                })
+                .await
+        }
+        AdminAction::SetContentPolicy { feed_id, policy } => {
+            let feed = require_feed(store, feed_id).await?;
+            store
+                .put_feed(&DynamicFeed {
+                    content_policy: *policy,
+                    ..feed
+                })
+                .await
+        }
+        AdminAction::SetFeedFilters { feed_id, filters } => {
+            filters.validate()?;
+            let feed = require_feed(store, feed_id).await?;
+            store
+                .put_feed(&DynamicFeed {
+                    filters: Some(filters.clone()),
+                    ..feed
+                })
+                .await
+        }
+        AdminAction::ClearFeedFilters { feed_id } => {
+            let feed = require_feed(store, feed_id).await?;
+            store.put_feed(&DynamicFeed { filters: None, ..feed }).await
+        }
+        AdminAction::ImportFeeds { opml, default_category } => {
+            let plan = import_plan(store, opml, default_category.as_deref()).await?;
+            let added = store.import_feeds(&plan, "admin-chat").await?;
+            info!(
+                added,
+                duplicates = plan.duplicates.len(),
+                unknown_category = plan.unknown_category.len(),
+                invalid = plan.invalid_urls.len(),
+                "OPML import"
+            );
+            Ok(())
+        }
+        AdminAction::ToggleFeature { feature, enabled } => {
+            store.set_feature_flag(feature, *enabled, None).await
+        }
+        AdminAction::SetGroupingThreshold { threshold } => {
+            let extra = serde_json::json!({"similarity_threshold": threshold});
+            store.set_feature_flag("grouping", true, Some(&extra)).await
+        }
+        AdminAction::AddCategory { id, label_ja } => {
+            let sort_order = store.get_categories().await?.len() as i32;
+            store
+                .put_category(&CategoryRecord {
+                    id: id.clone(),
+                    label_ja: label_ja.clone(),
+                    label_en: String::new(),
+                    sort_order,
+                    visible: true,
+                })
+                .await
+        }
+        AdminAction::RemoveCategory { id } => store.delete_category(id).await,
+        AdminAction::RenameCategory { id, label_ja } => store.rename_category(id, label_ja).await,
+        AdminAction::ReorderCategories { order } => store.reorder_categories(order).await,
+    }
+}
+
+async fn require_feed<S: FeedStorage + ?Sized>(store: &S, feed_id: &str) -> Result<DynamicFeed> {
+    store
+        .get_feed(feed_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("feed {feed_id}")))
+}
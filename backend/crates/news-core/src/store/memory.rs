@@ -0,0 +1,340 @@
+use super::{ArticleStorage, CategoryRecord, CategoryStorage, ChangeStorage, FeatureStorage, FeedStorage};
+use crate::changes::{ChangeRequest, ChangeStatus};
+use crate::config::{DynamicFeed, FeatureFlags};
+use crate::error::{AppError, Result};
+use crate::models::{Article, Category, PageMetadata};
+use async_trait::async_trait;
+use std::sync::{Mutex, MutexGuard};
+
+/// Every storage trait over plain in-process collections, for tests and local tools.
+/// Nothing is persisted.
+#[derive(Default)]
+pub struct MemoryStore {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    articles: Vec<Article>,
+    feeds: Vec<DynamicFeed>,
+    features: Vec<(String, bool, Option<serde_json::Value>)>,
+    changes: Vec<ChangeRequest>,
+    /// `None` until a category is written, while the defaults apply.
+    categories: Option<Vec<CategoryRecord>>,
+}
+
+impl Inner {
+    fn categories(&mut self) -> &mut Vec<CategoryRecord> {
+        self.categories.get_or_insert_with(super::default_categories)
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // A panicking test can poison the lock; the data is still usable.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl ArticleStorage for MemoryStore {
+    async fn put_articles(&self, articles: &[Article]) -> Result<usize> {
+        let mut inner = self.lock();
+        let mut inserted = 0;
+        for article in articles {
+            if !inner.articles.iter().any(|a| a.id == article.id) {
+                inner.articles.push(article.clone());
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// The cursor is the offset of the next page. There are no translations, so `lang`
+    /// keeps articles in that language or of unknown language.
+    async fn query_articles(
+        &self,
+        category: Option<&Category>,
+        lang: Option<&str>,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<Article>, Option<String>)> {
+        let inner = self.lock();
+        let mut articles: Vec<&Article> = inner
+            .articles
+            .iter()
+            .filter(|a| category.is_none_or(|c| a.category == *c))
+            .filter(|a| lang.is_none_or(|l| a.lang.as_deref().is_none_or(|al| al == l)))
+            .collect();
+        articles.sort_by(|a, b| b.published_at.cmp(&a.published_at).then_with(|| b.id.cmp(&a.id)));
+        let offset = cursor.and_then(|c| c.parse::<usize>().ok()).unwrap_or(0);
+        let page: Vec<Article> = articles.iter().skip(offset).take(limit).map(|a| (*a).clone()).collect();
+        let next = offset + page.len();
+        let next_cursor = (next < articles.len()).then(|| next.to_string());
+        Ok((page, next_cursor))
+    }
+
+    async fn update_page_metadata(&self, article: &Article, metadata: &PageMetadata) -> Result<()> {
+        let mut inner = self.lock();
+        let stored = inner
+            .articles
+            .iter_mut()
+            .find(|a| a.id == article.id)
+            .ok_or_else(|| AppError::NotFound(format!("article {}", article.id)))?;
+        if stored.image_url.is_none() {
+            stored.image_url = metadata.image_url.clone();
+        }
+        if stored.description.is_none() {
+            stored.description = metadata.description.clone();
+        }
+        stored.metadata = Some(metadata.clone());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FeedStorage for MemoryStore {
+    async fn get_all_feeds(&self) -> Result<Vec<DynamicFeed>> {
+        Ok(self.lock().feeds.clone())
+    }
+
+    async fn put_feed(&self, feed: &DynamicFeed) -> Result<()> {
+        let mut inner = self.lock();
+        match inner.feeds.iter_mut().find(|f| f.feed_id == feed.feed_id) {
+            Some(existing) => *existing = feed.clone(),
+            None => inner.feeds.push(feed.clone()),
+        }
+        Ok(())
+    }
+
+    async fn delete_feed(&self, feed_id: &str) -> Result<()> {
+        self.lock().feeds.retain(|f| f.feed_id != feed_id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FeatureStorage for MemoryStore {
+    async fn get_feature_flags(&self) -> Result<FeatureFlags> {
+        let mut flags = FeatureFlags::default();
+        for (feature, enabled, extra) in &self.lock().features {
+            flags.set(feature, *enabled, extra.as_ref());
+        }
+        Ok(flags)
+    }
+
+    async fn set_feature_flag(
+        &self,
+        feature: &str,
+        enabled: bool,
+        extra: Option<&serde_json::Value>,
+    ) -> Result<()> {
+        let mut inner = self.lock();
+        inner.features.retain(|(f, _, _)| f != feature);
+        inner.features.push((feature.to_string(), enabled, extra.cloned()));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ChangeStorage for MemoryStore {
+    async fn create_change(&self, change: &ChangeRequest) -> Result<()> {
+        self.lock().changes.push(change.clone());
+        Ok(())
+    }
+
+    async fn get_change(&self, change_id: &str) -> Result<Option<ChangeRequest>> {
+        Ok(self.lock().changes.iter().find(|c| c.change_id == change_id).cloned())
+    }
+
+    async fn update_change_status(&self, change_id: &str, status: ChangeStatus) -> Result<()> {
+        if let Some(change) = self.lock().changes.iter_mut().find(|c| c.change_id == change_id) {
+            change.status = status;
+        }
+        Ok(())
+    }
+
+    async fn list_changes(&self, limit: usize) -> Result<Vec<ChangeRequest>> {
+        let mut changes = self.lock().changes.clone();
+        changes.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        changes.truncate(limit);
+        Ok(changes)
+    }
+}
+
+#[async_trait]
+impl CategoryStorage for MemoryStore {
+    async fn get_categories(&self) -> Result<Vec<CategoryRecord>> {
+        let mut categories = self.lock().categories().clone();
+        categories.sort_by(|a, b| a.sort_order.cmp(&b.sort_order).then_with(|| a.id.cmp(&b.id)));
+        Ok(categories)
+    }
+
+    async fn put_category(&self, category: &CategoryRecord) -> Result<()> {
+        let mut inner = self.lock();
+        let categories = inner.categories();
+        match categories.iter_mut().find(|c| c.id == category.id) {
+            Some(existing) => *existing = category.clone(),
+            None => categories.push(category.clone()),
+        }
+        Ok(())
+    }
+
+    async fn rename_category(&self, id: &str, label_ja: &str) -> Result<()> {
+        let mut inner = self.lock();
+        let category = inner
+            .categories()
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("category {id}")))?;
+        category.label_ja = label_ja.to_string();
+        Ok(())
+    }
+
+    async fn delete_category(&self, id: &str) -> Result<()> {
+        self.lock().categories().retain(|c| c.id != id);
+        Ok(())
+    }
+
+    async fn reorder_categories(&self, order: &[String]) -> Result<()> {
+        let mut inner = self.lock();
+        let categories = inner.categories();
+        for (i, id) in order.iter().enumerate() {
+            if let Some(category) = categories.iter_mut().find(|c| c.id == *id) {
+                category.sort_order = i as i32;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changes::AdminAction;
+    use crate::filters::FeedFilters;
+    use crate::store::{apply_action, default_categories, service_config};
+    use chrono::{Duration, Utc};
+
+    fn article(id: &str, category: Category, hours_ago: i64) -> Article {
+        Article {
+            id: id.into(),
+            category,
+            title: format!("Story {id}"),
+            url: format!("https://example.com/{id}"),
+            description: None,
+            image_url: None,
+            source: "Example".into(),
+            published_at: Utc::now() - Duration::hours(hours_ago),
+            fetched_at: Utc::now(),
+            group_id: None,
+            group_count: None,
+            metadata: None,
+            audio: None,
+            categories: Vec::new(),
+            lang: None,
+            translated_from: None,
+            updated_at: None,
+            headline_changed: false,
+        }
+    }
+
+    fn feed(feed_id: &str) -> DynamicFeed {
+        DynamicFeed {
+            feed_id: feed_id.into(),
+            url: format!("https://example.com/{feed_id}.xml"),
+            source: "Example".into(),
+            category: "tech".into(),
+            enabled: true,
+            added_by: None,
+            poll_interval_secs: None,
+            quarantine_reason: Some("5 consecutive failures".into()),
+            content_policy: Default::default(),
+            source_type: Default::default(),
+            scrape: None,
+            filters: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn articles_page_newest_first() {
+        let store = MemoryStore::new();
+        let batch = [
+            article("a", Category::Tech, 3),
+            article("b", Category::General, 2),
+            article("c", Category::Tech, 1),
+        ];
+        assert_eq!(store.put_articles(&batch).await.unwrap(), 3);
+        assert_eq!(store.put_articles(&batch[..1]).await.unwrap(), 0);
+
+        let (page, cursor) = store.query_articles(None, None, 2, None).await.unwrap();
+        assert_eq!(page.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(), ["c", "b"]);
+        let (rest, cursor) = store.query_articles(None, None, 2, cursor.as_deref()).await.unwrap();
+        assert_eq!(rest[0].id, "a");
+        assert!(cursor.is_none());
+
+        let (tech, _) = store.query_articles(Some(&Category::Tech), None, 10, None).await.unwrap();
+        assert_eq!(tech.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn admin_actions_apply_to_feeds_and_features() {
+        let store = MemoryStore::new();
+        store.put_feed(&feed("f1")).await.unwrap();
+
+        let actions = [
+            AdminAction::DisableFeed { feed_id: "f1".into() },
+            AdminAction::EnableFeed { feed_id: "f1".into() },
+            AdminAction::SetFeedFilters {
+                feed_id: "f1".into(),
+                filters: FeedFilters {
+                    exclude_keywords: vec!["PR".into()],
+                    ..Default::default()
+                },
+            },
+            AdminAction::SetGroupingThreshold { threshold: 0.5 },
+        ];
+        for action in &actions {
+            apply_action(&store, action).await.unwrap();
+        }
+
+        let config = service_config(&store).await.unwrap();
+        let f1 = &config.feeds[0];
+        assert!(f1.enabled);
+        assert!(f1.quarantine_reason.is_none());
+        assert!(f1.filters.is_some());
+        assert!(config.features.grouping_enabled);
+        assert_eq!(config.features.grouping_threshold, 0.5);
+
+        let missing = apply_action(&store, &AdminAction::DisableFeed { feed_id: "nope".into() }).await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn category_actions_start_from_the_defaults() {
+        let store = MemoryStore::new();
+        let actions = [
+            AdminAction::AddCategory { id: "lifestyle".into(), label_ja: "ライフスタイル".into() },
+            AdminAction::RemoveCategory { id: "sports".into() },
+            AdminAction::RenameCategory { id: "tech".into(), label_ja: "IT・テック".into() },
+            AdminAction::ReorderCategories { order: vec!["lifestyle".into(), "tech".into(), "general".into()] },
+        ];
+        for action in &actions {
+            apply_action(&store, action).await.unwrap();
+        }
+
+        let categories = store.get_categories().await.unwrap();
+        assert_eq!(categories.len(), default_categories().len());
+        assert_eq!(categories[0].id, "lifestyle");
+        assert_eq!(categories[0].info().label, "ライフスタイル");
+        assert_eq!(categories[1].label_ja, "IT・テック");
+        assert!(!categories.iter().any(|c| c.id == "sports"));
+
+        let missing = apply_action(&store, &AdminAction::RenameCategory { id: "nope".into(), label_ja: "x".into() }).await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+    }
+}
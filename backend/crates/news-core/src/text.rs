@@ -0,0 +1,31 @@
+//! Text matching shared by keyword category rules and feed filters.
+
+/// Whether `keyword` occurs in `text`, with an ASCII letter or digit at either end of
+/// the keyword not continuing into one in the text: "ai" doesn't match "said", but CJK
+/// neighbours count as boundaries ("生成aiの規制"). Callers lowercase both sides.
+pub fn contains_keyword(text: &str, keyword: &str) -> bool {
+    let word_char = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    let check_start = word_char(keyword.chars().next());
+    let check_end = word_char(keyword.chars().next_back());
+    text.match_indices(keyword).any(|(i, _)| {
+        let joined_before = check_start && word_char(text[..i].chars().next_back());
+        let joined_after = check_end && word_char(text[i + keyword.len()..].chars().next());
+        !(joined_before || joined_after)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_ends_match_at_word_boundaries() {
+        assert!(contains_keyword("new ai model", "ai"));
+        assert!(contains_keyword("生成aiの規制", "ai"));
+        assert!(!contains_keyword("officials said", "ai"));
+        assert!(!contains_keyword("spacex", "space"));
+        assert!(contains_keyword("2026年のw杯", "w杯"));
+        assert!(!contains_keyword("ew杯", "w杯"));
+        assert!(contains_keyword("半導体メーカー", "半導体"));
+    }
+}
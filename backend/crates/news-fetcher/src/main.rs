@@ -1,9 +1,13 @@
 use lambda_runtime::{service_fn, Error, LambdaEvent};
 use news_core::config::{ConfigStore, FeatureFlags};
+use news_core::dedup::{Canonicalizer, UrlRule};
 use news_core::dynamo::ArticleStore;
+use news_core::store::{ArticleStorage, FeatureStorage, FeedStorage};
 use news_core::feeds::{fetch_all_feeds, FeedConfig, FeedsConfig};
 use news_core::ogp;
+use news_core::politeness::PoliteClient;
 use serde_json::Value;
+use std::collections::HashSet;
 use tracing::info;
 
 /// Embedded feeds configuration (compiled into binary) — used as fallback.
@@ -17,9 +21,14 @@ async fn load_feeds(config_store: &ConfigStore) -> Vec<FeedConfig> {
             feeds
                 .into_iter()
                 .map(|f| FeedConfig {
+                    feed_id: Some(f.feed_id),
                     url: f.url,
                     source: f.source,
                     category: f.category,
+                    poll_interval_secs: f.poll_interval_secs,
+                    source_type: f.source_type,
+                    scrape: f.scrape,
+                    filters: f.filters,
                 })
                 .collect()
         }
@@ -40,6 +49,12 @@ fn fallback_feeds() -> Vec<FeedConfig> {
         .unwrap_or_default()
 }
 
+fn url_rules() -> Vec<UrlRule> {
+    FeedsConfig::from_toml(FEEDS_TOML)
+        .map(|c| c.url_rules)
+        .unwrap_or_default()
+}
+
 /// Load feature flags, returning defaults on failure.
 async fn load_feature_flags(config_store: &ConfigStore) -> FeatureFlags {
     config_store
@@ -69,8 +84,16 @@ async fn handler(_event: LambdaEvent<Value>) -> Result<Value, Error> {
     let feeds = load_feeds(&config_store).await;
     let features = load_feature_flags(&config_store).await;
 
-    let feeds_config = FeedsConfig { feeds };
-    let articles = fetch_all_feeds(&http_client, &feeds_config).await;
+    let feeds_config = FeedsConfig {
+        feeds,
+        url_rules: url_rules(),
+    };
+    let polite_client = PoliteClient::new(http_client.clone());
+    let mut articles = fetch_all_feeds(&polite_client, &feeds_config).await;
+    let canonicalizer = Canonicalizer::new(feeds_config.url_rules);
+    for article in &mut articles {
+        canonicalizer.apply(article);
+    }
     info!(total_articles = articles.len(), "Fetched all feeds");
 
     let inserted = store.put_articles(&articles).await?;
@@ -78,19 +101,28 @@ async fn handler(_event: LambdaEvent<Value>) -> Result<Value, Error> {
     // OGP enrichment: if enabled, enrich articles without image_url (max 20 per cycle)
     let mut ogp_count = 0;
     if features.ogp_enrichment_enabled {
+        let no_fetch: HashSet<String> = config_store
+            .get_all_feeds()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|f| !f.content_policy.allows_page_fetch())
+            .map(|f| f.source)
+            .collect();
         let no_image: Vec<_> = articles
             .iter()
-            .filter(|a| a.image_url.is_none())
+            .filter(|a| a.image_url.is_none() && !no_fetch.contains(&a.source))
             .take(20)
             .collect();
 
         for article in &no_image {
-            if let Some(img_url) = ogp::fetch_og_image(&http_client, &article.url).await {
-                let sk = format!("{}#{}", article.published_at.to_rfc3339(), article.id);
+            if let Some(metadata) = ogp::fetch_page_metadata(&polite_client, &article.url).await {
+                let has_image = metadata.image_url.is_some();
                 if store
-                    .update_image_url(article.category.as_str(), &sk, &img_url)
+                    .update_page_metadata(article, &metadata)
                     .await
                     .is_ok()
+                    && has_image
                 {
                     ogp_count += 1;
                 }
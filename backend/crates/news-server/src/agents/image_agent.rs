@@ -58,6 +58,7 @@ struct ReplicateResponse {
     error: Option<String>,
 }
 
+#[allow(dead_code)]
 const FLUX_SCHNELL_VERSION: &str = "f2e15a9d0e1c4e8c91f3d63f5f3b3e3f2e15a9d0e1c4e8c91f3d63f5f3b3e3f"; // Flux Schnell
 
 /// Generate an AI image for an article using DALL-E 3 or Flux (fallback).
@@ -262,7 +263,7 @@ async fn generate_flux_image(
 
         // Get prediction status
         let get_response = client
-            .get(&format!(
+            .get(format!(
                 "https://api.replicate.com/v1/predictions/{}",
                 prediction.id
             ))
@@ -65,6 +65,7 @@ where
 ///
 /// # Returns
 /// Ok(current_usage) if under limit, Err(message) if over limit
+#[allow(dead_code)]
 pub async fn check_rate_limit(
     db: &crate::db::Db,
     device_id: &str,
@@ -86,6 +87,7 @@ pub async fn check_rate_limit(
 }
 
 /// Increment usage counter for a feature.
+#[allow(dead_code)]
 pub async fn increment_usage(
     db: &crate::db::Db,
     device_id: &str,
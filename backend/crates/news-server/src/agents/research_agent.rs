@@ -137,9 +137,7 @@ async fn call_claude_research(
     article: &Article,
 ) -> Result<ResearchEnrichmentData, String> {
     let description = article
-        .description
-        .as_ref()
-        .map(|d| d.as_str())
+        .description.as_deref()
         .unwrap_or("");
 
     let prompt = format!(
@@ -240,9 +238,7 @@ async fn generate_visualization(
     }
 
     let description = article
-        .description
-        .as_ref()
-        .map(|d| d.as_str())
+        .description.as_deref()
         .unwrap_or("");
 
     // Check if article likely contains numerical data
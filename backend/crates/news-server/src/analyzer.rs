@@ -1,4 +1,4 @@
-/**
+/*
  * AI Article Analyzer - Background task
  *
  * Runs every 10 minutes to analyze articles using ChatWeb.ai
@@ -7,6 +7,7 @@
 
 use crate::chatweb::ChatWebClient;
 use crate::routes::AppState;
+use news_core::models::Article;
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::{error, info, warn};
@@ -71,7 +72,7 @@ pub async fn run(state: Arc<AppState>) {
             .map(|a| {
                 (
                     a.title.clone(),
-                    a.description.clone().unwrap_or_default(),
+                    description_with_metadata(a),
                     a.url.clone(),
                 )
             })
@@ -141,3 +142,18 @@ pub async fn run(state: Arc<AppState>) {
         );
     }
 }
+
+/// Article description plus the publisher's section and keywords from page metadata,
+/// which give the categorizer more to go on than a one-line feed summary.
+fn description_with_metadata(article: &Article) -> String {
+    let mut description = article.description.clone().unwrap_or_default();
+    if let Some(ref meta) = article.metadata {
+        if let Some(ref section) = meta.section {
+            description.push_str(&format!("\nSection: {}", section));
+        }
+        if !meta.keywords.is_empty() {
+            description.push_str(&format!("\nKeywords: {}", meta.keywords.join(", ")));
+        }
+    }
+    description
+}
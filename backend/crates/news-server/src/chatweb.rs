@@ -1,4 +1,4 @@
-/**
+/*
  * ChatWeb.ai API client
  *
  * Provides access to chatweb.ai's AI chat and analysis capabilities.
@@ -22,6 +22,7 @@ struct ChatRequest {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct ChatResponse {
     response: String,
     #[serde(default)]
@@ -37,11 +37,18 @@ const SYSTEM_PROMPT: &str = r#"あなたはHyperNewsの管理AIです。ユー
 
 ## アクション一覧（typeフィールドで識別、フラット構造）
 
-- `{"type":"add_feed","url":"...","source":"...","category":"general|tech|business|entertainment|sports|science"}`
+- `{"type":"add_feed","url":"...","source":"...","category":"general|tech|business|entertainment|sports|science","source_type":"rss|jsonfeed|news_sitemap|scrape"}`（source_typeは省略時rss。JSON Feedやニュースサイトマップのみ提供するサイトで指定）
+- フィードがないサイトは `{"type":"add_feed","url":"一覧ページURL","source":"...","category":"...","source_type":"scrape","scrape":{"item":"CSSセレクタ","link":"a","title":"...","date":"time","image":"img"}}`（item・link必須、他は省略可）
 - `{"type":"remove_feed","feed_id":"..."}`
 - `{"type":"enable_feed","feed_id":"..."}`
 - `{"type":"disable_feed","feed_id":"..."}`
-- `{"type":"toggle_feature","feature":"grouping|ogp_enrichment","enabled":true|false}`
+- `{"type":"set_poll_interval","feed_id":"...","seconds":300}`（secondsをnullにすると自動調整に戻す）
+- `{"type":"release_quarantine","feed_id":"..."}`
+- `{"type":"import_feeds","opml":"<opml>...</opml>","default_category":"general"}`（ユーザーが貼り付けたOPMLをそのまま入れる。重複URLと未知のカテゴリはスキップ）
+- `{"type":"set_content_policy","feed_id":"...","policy":"full_text|metadata_only|no_fetch"}`（metadata_onlyは本文をAIに使わない、no_fetchは記事ページを一切取得しない）
+- `{"type":"set_feed_filters","feed_id":"...","filters":{"include_keywords":["..."],"exclude_keywords":["..."],"include_patterns":[{"field":"title|description|url_path","pattern":"正規表現"}],"exclude_patterns":[...],"min_description_len":40}}`（各項目は省略可。設定済みのルールは置き換わる）
+- `{"type":"clear_feed_filters","feed_id":"..."}`
+- `{"type":"toggle_feature","feature":"grouping|ogp_enrichment|websub","enabled":true|false}`
 - `{"type":"set_grouping_threshold","threshold":0.3}`
 - `{"type":"add_category","id":"lifestyle","label_ja":"ライフスタイル"}`
 - `{"type":"remove_category","id":"sports"}`
@@ -57,6 +64,10 @@ const SYSTEM_PROMPT: &str = r#"あなたはHyperNewsの管理AIです。ユー
 - 「スポーツを消して」→ remove_categoryでカテゴリ削除
 - 「テクノロジーをIT・テックに変更して」→ rename_categoryで名前変更
 - 「テクノロジーを一番前にして」→ reorder_categoriesで並び替え
+- 「止まっているフィードは？」「隔離されたフィードを教えて」→ 現在の設定でquarantine_reasonがあるフィードをinterpretationで一覧にし、actionsは空にする
+- 「〇〇の隔離を解除して」→ release_quarantineで再有効化
+- 「〇〇の本文を使わないで」→ set_content_policyでmetadata_only、「〇〇のページを取得しないで」→ no_fetch
+- 「〇〇からは××の記事だけ取り込んで」→ set_feed_filtersでinclude_keywords、「〇〇のPR記事を除外して」→ exclude_keywordsやexclude_patterns、「説明が短い記事はいらない」→ min_description_len
 - 不明確なコマンドにはconfidence 0.5以下で説明のみ返す
 
 ## 出力フォーマット（厳密にこの形式のJSONのみ出力。コードブロック不要）
@@ -278,6 +289,7 @@ pub async fn transform_question_to_positive(
     Ok(transformed)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn answer_question(
     client: &reqwest::Client,
     api_key: &str,
@@ -585,6 +597,91 @@ pub async fn generate_murmur(
     Ok(text.trim().to_string())
 }
 
+// --- Translation ---
+
+#[derive(Debug, Deserialize)]
+pub struct TranslatedArticle {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+/// 記事のタイトル・概要・AI要約を target_lang（"ja" / "en" など）に翻訳
+pub async fn translate_article(
+    client: &reqwest::Client,
+    api_key: &str,
+    title: &str,
+    description: Option<&str>,
+    summary: Option<&str>,
+    target_lang: &str,
+) -> Result<TranslatedArticle, String> {
+    let language = match target_lang {
+        "ja" => "日本語",
+        "en" => "英語",
+        other => other,
+    };
+    let source = serde_json::json!({
+        "title": title,
+        "description": description,
+        "summary": summary,
+    });
+    let prompt = format!(
+        "以下のニュース記事のtitle・description・summaryを{}に翻訳してください。\n\n\
+        ルール:\n\
+        - 報道機関の見出し・記事として自然な文体で訳す\n\
+        - 固有名詞は訳先の言語で一般的な表記にする\n\
+        - nullの項目はnullのまま返す。内容を足したり要約したりしない\n\
+        - JSON出力のみ: {{\"title\":\"...\",\"description\":\"...\",\"summary\":\"...\"}}\n\n\
+        ## 記事\n{}",
+        language, source
+    );
+
+    let request = ClaudeRequest {
+        model: "claude-haiku-4-5-20251001".into(),
+        max_tokens: 1024,
+        messages: vec![ClaudeMessage {
+            role: "user".into(),
+            content: prompt,
+        }],
+    };
+
+    info!(title = %title, target_lang, "Translating article");
+
+    let response = client
+        .post("https://api.anthropic.com/v1/messages")
+        .header("x-api-key", api_key)
+        .header("anthropic-version", "2023-06-01")
+        .header("content-type", "application/json")
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Claude API request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        warn!(status = %status, body = %body, "Claude API error (translate)");
+        return Err(format!("Claude API error: {} - {}", status, body));
+    }
+
+    let claude_response: ClaudeResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Claude response: {}", e))?;
+
+    let text = claude_response
+        .content
+        .first()
+        .and_then(|b| b.text.as_ref())
+        .ok_or_else(|| "Empty response from Claude".to_string())?;
+
+    let clean = text.trim().trim_start_matches("```json").trim_start_matches("```").trim_end_matches("```").trim();
+    serde_json::from_str(clean)
+        .map_err(|e| format!("Failed to parse translation: {} — raw: {}", e, text))
+}
+
 // --- Smart News Classification & Action Plans ---
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1,18 +1,101 @@
+use crate::translation::{self, ArticleTranslation};
+use crate::websub::{HubSubscription, SubscriptionStatus};
 use chrono::{DateTime, Utc};
+use news_core::categorize::{self, CategoryDisagreement, CategoryDisagreementCount, CategoryLabel};
 use news_core::changes::{AdminAction, ChangeRequest, ChangeStatus};
-use news_core::config::{DynamicFeed, FeatureFlags, ServiceConfig};
-use news_core::models::{Article, Category};
-use rusqlite::{params, Connection};
-use std::sync::Mutex;
-use tracing::info;
-
+use news_core::config::{ContentPolicy, DynamicFeed, FeatureFlags, ServiceConfig};
+use news_core::dedup;
+use news_core::feeds::FetchState;
+use news_core::grouping;
+use news_core::health::FetchErrorClass;
+use news_core::lang;
+use news_core::models::{Article, ArticleRevision, AudioEnclosure, Category, PageMetadata};
+use news_core::opml::ImportPlan;
+use news_core::search::{self, SearchHit};
+use news_core::sources::SourceType;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use tracing::{info, warn};
+
+mod archive;
+pub mod migrations;
+mod storage;
+
+pub use archive::Retention;
+
+/// Read-only connections opened by [`Db::open`].
+pub const DEFAULT_READ_CONNECTIONS: usize = 4;
+
+/// Most articles [`Db::insert_articles`] writes per transaction.
+const ARTICLE_CHUNK: usize = 50;
+
+/// Earlier versions kept per article; older ones are dropped as new ones come in.
+const MAX_REVISIONS: i64 = 20;
+
+/// SQLite storage: one connection that does every write, plus a pool of read-only
+/// connections so API reads never queue behind the fetcher or the background agents.
+/// In WAL mode readers see the last committed state while a write is in progress.
 pub struct Db {
-    conn: Mutex<Connection>,
+    writer: Mutex<Connection>,
+    readers: ReadPool,
+}
+
+/// Fixed set of read-only connections, handed out to whichever is free.
+struct ReadPool {
+    conns: Vec<Mutex<Connection>>,
+    next: AtomicUsize,
+}
+
+impl ReadPool {
+    fn open(path: &str, size: usize) -> Result<Self, String> {
+        let conns = (0..size)
+            .map(|_| {
+                let conn = Connection::open_with_flags(
+                    path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY
+                        | OpenFlags::SQLITE_OPEN_NO_MUTEX
+                        | OpenFlags::SQLITE_OPEN_URI,
+                )
+                .map_err(|e| format!("SQLite open reader: {e}"))?;
+                conn.execute_batch("PRAGMA busy_timeout=5000; PRAGMA query_only=ON;")
+                    .map_err(|e| format!("SQLite reader pragma: {e}"))?;
+                Ok(Mutex::new(conn))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self {
+            conns,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// A free connection if there is one, else wait for the next in turn. `None` when
+    /// the pool is empty.
+    fn get(&self) -> Option<Result<MutexGuard<'_, Connection>, String>> {
+        if self.conns.is_empty() {
+            return None;
+        }
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let n = self.conns.len();
+        for i in 0..n {
+            if let Ok(conn) = self.conns[(start + i) % n].try_lock() {
+                return Some(Ok(conn));
+            }
+        }
+        Some(self.conns[start % n].lock().map_err(|e| e.to_string()))
+    }
 }
 
 impl Db {
     pub fn open(path: &str) -> Result<Self, String> {
-        let conn = Connection::open(path).map_err(|e| format!("SQLite open: {e}"))?;
+        Self::open_with_readers(path, DEFAULT_READ_CONNECTIONS)
+    }
+
+    /// Open with `readers` read-only connections. An in-memory database can't be
+    /// shared between connections, so it gets none and reads go through the writer.
+    pub fn open_with_readers(path: &str, readers: usize) -> Result<Self, String> {
+        let mut conn = Connection::open(path).map_err(|e| format!("SQLite open: {e}"))?;
         conn.execute_batch(
             "PRAGMA journal_mode=WAL;
              PRAGMA busy_timeout=5000;
@@ -21,214 +104,183 @@ impl Db {
         )
         .map_err(|e| format!("SQLite pragma: {e}"))?;
 
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS articles (
-                id TEXT PRIMARY KEY,
-                category TEXT NOT NULL,
-                title TEXT NOT NULL,
-                url TEXT NOT NULL,
-                description TEXT,
-                image_url TEXT,
-                source TEXT NOT NULL,
-                published_at TEXT NOT NULL,
-                fetched_at TEXT NOT NULL,
-                group_id TEXT,
-                group_count INTEGER,
-                view_count INTEGER NOT NULL DEFAULT 0,
-                click_count INTEGER NOT NULL DEFAULT 0,
-                enrichment_status TEXT,
-                enriched_at TEXT,
-                popularity_score REAL NOT NULL DEFAULT 0.0,
-                ai_summary TEXT,
-                ai_keywords TEXT,
-                ai_sentiment TEXT,
-                ai_importance REAL,
-                ai_category TEXT,
-                analyzed_at TEXT
-            );
-            CREATE INDEX IF NOT EXISTS idx_articles_cat_pub
-                ON articles(category, published_at DESC);
-            CREATE INDEX IF NOT EXISTS idx_articles_pub
-                ON articles(published_at DESC);
-            CREATE INDEX IF NOT EXISTS idx_articles_popularity
-                ON articles(popularity_score DESC, published_at DESC);
-            CREATE INDEX IF NOT EXISTS idx_articles_enrichment_status
-                ON articles(enrichment_status);
-
-            CREATE TABLE IF NOT EXISTS feeds (
-                feed_id TEXT PRIMARY KEY,
-                url TEXT NOT NULL,
-                source TEXT NOT NULL,
-                category TEXT NOT NULL,
-                enabled INTEGER NOT NULL DEFAULT 1,
-                added_by TEXT
-            );
-
-            CREATE TABLE IF NOT EXISTS features (
-                feature TEXT PRIMARY KEY,
-                enabled INTEGER NOT NULL DEFAULT 0,
-                extra_json TEXT
-            );
-
-            CREATE TABLE IF NOT EXISTS changes (
-                change_id TEXT PRIMARY KEY,
-                status TEXT NOT NULL,
-                command_text TEXT NOT NULL,
-                interpretation TEXT NOT NULL DEFAULT '',
-                actions_json TEXT NOT NULL DEFAULT '[]',
-                created_at TEXT NOT NULL
-            );
-
-            CREATE TABLE IF NOT EXISTS categories (
-                id TEXT PRIMARY KEY,
-                label_ja TEXT NOT NULL,
-                label_en TEXT NOT NULL DEFAULT '',
-                sort_order INTEGER NOT NULL DEFAULT 0,
-                visible INTEGER NOT NULL DEFAULT 1
-            );
-
-            CREATE TABLE IF NOT EXISTS subscriptions (
-                api_token TEXT PRIMARY KEY,
-                stripe_customer_id TEXT NOT NULL,
-                stripe_subscription_id TEXT NOT NULL UNIQUE,
-                status TEXT NOT NULL DEFAULT 'active',
-                current_period_end TEXT NOT NULL,
-                created_at TEXT NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_subs_stripe_sub_id
-                ON subscriptions(stripe_subscription_id);
-            CREATE INDEX IF NOT EXISTS idx_subs_stripe_cust_id
-                ON subscriptions(stripe_customer_id);
-
-            CREATE TABLE IF NOT EXISTS usage_limits (
-                device_id TEXT NOT NULL,
-                feature TEXT NOT NULL,
-                used_date TEXT NOT NULL,
-                count INTEGER NOT NULL DEFAULT 0,
-                PRIMARY KEY (device_id, feature, used_date)
-            );
-
-            CREATE TABLE IF NOT EXISTS ai_cache (
-                cache_key TEXT PRIMARY KEY,
-                endpoint TEXT NOT NULL,
-                response_json TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                expires_at TEXT NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_ai_cache_expires
-                ON ai_cache(expires_at);
-
-            CREATE TABLE IF NOT EXISTS users (
-                id TEXT PRIMARY KEY,
-                email TEXT NOT NULL UNIQUE,
-                name TEXT NOT NULL DEFAULT '',
-                picture_url TEXT,
-                google_id TEXT NOT NULL UNIQUE,
-                auth_token TEXT NOT NULL UNIQUE,
-                device_id TEXT,
-                konami_claimed INTEGER NOT NULL DEFAULT 0,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_users_auth_token ON users(auth_token);
-
-            CREATE TABLE IF NOT EXISTS enrichments (
-                enrichment_id TEXT PRIMARY KEY,
-                article_id TEXT NOT NULL,
-                agent_type TEXT NOT NULL,
-                content_type TEXT NOT NULL,
-                data_json TEXT NOT NULL,
-                status TEXT NOT NULL DEFAULT 'pending',
-                error_message TEXT,
-                created_at TEXT NOT NULL,
-                completed_at TEXT,
-                FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
-            );
-            CREATE INDEX IF NOT EXISTS idx_enrichments_article
-                ON enrichments(article_id, status);",
-        )
-        .map_err(|e| format!("SQLite schema: {e}"))?;
-
-        // Migration: Add AI analysis columns if they don't exist
-        let column_check: Result<i64, _> = conn.query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('articles') WHERE name='ai_summary'",
-            [],
-            |row| row.get(0),
-        );
-
-        if let Ok(0) = column_check {
-            info!("Running migration: Adding AI analysis columns to articles table");
-            conn.execute_batch(
-                "ALTER TABLE articles ADD COLUMN ai_summary TEXT;
-                 ALTER TABLE articles ADD COLUMN ai_keywords TEXT;
-                 ALTER TABLE articles ADD COLUMN ai_sentiment TEXT;
-                 ALTER TABLE articles ADD COLUMN ai_importance REAL;
-                 ALTER TABLE articles ADD COLUMN ai_category TEXT;
-                 ALTER TABLE articles ADD COLUMN analyzed_at TEXT;"
-            )
-            .map_err(|e| format!("Migration failed: {e}"))?;
-            info!("Migration complete: AI analysis columns added");
-        }
+        migrations::migrate(&mut conn)?;
 
-        info!(path, "SQLite database opened");
+        let readers = if path == ":memory:" { 0 } else { readers };
+        let readers = ReadPool::open(path, readers)?;
+        info!(path, readers = readers.conns.len(), "SQLite database opened");
         Ok(Self {
-            conn: Mutex::new(conn),
+            writer: Mutex::new(conn),
+            readers,
         })
     }
 
+    /// The write connection. Hold it only for as long as the write takes.
+    fn write(&self) -> Result<MutexGuard<'_, Connection>, String> {
+        self.writer.lock().map_err(|e| e.to_string())
+    }
+
+    /// A read-only connection from the pool, or the writer if there is no pool.
+    fn read(&self) -> Result<MutexGuard<'_, Connection>, String> {
+        self.readers.get().unwrap_or_else(|| self.write())
+    }
+
     // --- Articles ---
 
-    pub fn insert_article(&self, article: &Article) -> Result<bool, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        let result = conn.execute(
-            "INSERT OR IGNORE INTO articles
-                (id, category, title, url, description, image_url, source, published_at, fetched_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-            params![
-                article.id,
-                article.category.as_str(),
-                article.title,
-                article.url,
-                article.description,
-                article.image_url,
-                article.source,
-                article.published_at.to_rfc3339(),
-                article.fetched_at.to_rfc3339(),
-            ],
-        );
-        match result {
-            Ok(n) => Ok(n > 0),
-            Err(e) => Err(format!("Insert article: {e}")),
+    /// Insert articles, skipping ones already stored (see [`insert_article`]). Returns
+    /// the IDs that were new.
+    ///
+    /// Articles are committed [`ARTICLE_CHUNK`] at a time, releasing the writer between
+    /// chunks so view counts and other small writes don't wait for a whole fetch cycle.
+    /// Each article gets its own savepoint: one that fails is logged and skipped
+    /// without losing the rest.
+    pub fn insert_articles(&self, articles: &[Article]) -> Result<Vec<String>, String> {
+        let mut inserted = Vec::new();
+        for chunk in articles.chunks(ARTICLE_CHUNK) {
+            let mut conn = self.write()?;
+            let mut tx = conn.transaction().map_err(|e| e.to_string())?;
+            let mut chunk_inserted = Vec::new();
+            for a in chunk {
+                let sp = tx.savepoint().map_err(|e| e.to_string())?;
+                match insert_article(&sp, a) {
+                    Ok(new) => {
+                        sp.commit().map_err(|e| format!("Release savepoint: {e}"))?;
+                        if new {
+                            chunk_inserted.push(a.id.clone());
+                        }
+                    }
+                    // Dropping the savepoint rolls back just this article
+                    Err(e) => warn!(error = %e, article_id = %a.id, url = %a.url, "Skipping article that failed to store"),
+                }
+            }
+            tx.commit().map_err(|e| format!("Commit articles: {e}"))?;
+            drop(conn);
+            inserted.extend(chunk_inserted);
+            // The lock isn't fair; give writers queued behind this chunk their turn
+            std::thread::yield_now();
         }
+        Ok(inserted)
     }
 
-    pub fn insert_articles(&self, articles: &[Article]) -> Result<usize, String> {
-        let mut inserted = 0;
-        for a in articles {
-            if self.insert_article(a)? {
-                inserted += 1;
+    /// Attach newly ingested articles to stories: each joins the story of the most
+    /// similar recent article (by [`grouping::story_text`]), or seeds a new one with that article.
+    /// The story ID is the ID of the article that started it, so it never changes.
+    /// Returns how many articles were assigned to a story.
+    pub fn cluster_articles(&self, ids: &[String], threshold: f64) -> Result<usize, String> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.write()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+        let since = (Utc::now() - chrono::Duration::hours(grouping::STORY_WINDOW_HOURS)).to_rfc3339();
+        let new_ids: std::collections::HashSet<&str> = ids.iter().map(|s| s.as_str()).collect();
+        let mut index = grouping::StoryIndex::new(threshold);
+        let mut new_articles = Vec::new();
+        {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT id, title, description, group_id FROM articles
+                     WHERE published_at >= ?1
+                     ORDER BY published_at ASC",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![since], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                })
+                .map_err(|e| e.to_string())?;
+            for (id, title, description, group_id) in rows.flatten() {
+                let text = grouping::story_text(&title, description.as_deref());
+                if new_ids.contains(id.as_str()) {
+                    new_articles.push((id, text));
+                } else {
+                    index.insert(id, &text, group_id);
+                }
             }
         }
-        Ok(inserted)
+
+        let mut touched = std::collections::HashSet::new();
+        let mut assigned = 0;
+        for (id, text) in new_articles {
+            let story = index
+                .best_match(&text)
+                .map(|c| (c.id.clone(), c.group_id.is_none(), c.story_id().to_string()));
+            let group_id = match story {
+                Some((seed_id, seed_ungrouped, story_id)) => {
+                    if seed_ungrouped {
+                        tx.execute(
+                            "UPDATE articles SET group_id = ?1 WHERE id = ?2",
+                            params![story_id, seed_id],
+                        )
+                        .map_err(|e| format!("Cluster seed: {e}"))?;
+                        index.set_group(&seed_id, story_id.clone());
+                    }
+                    tx.execute(
+                        "UPDATE articles SET group_id = ?1 WHERE id = ?2",
+                        params![story_id, id],
+                    )
+                    .map_err(|e| format!("Cluster article: {e}"))?;
+                    assigned += 1;
+                    touched.insert(story_id.clone());
+                    Some(story_id)
+                }
+                None => None,
+            };
+            // Later articles in the same batch can join this one's story
+            index.insert(id, &text, group_id);
+        }
+
+        for story_id in &touched {
+            tx.execute(
+                "UPDATE articles
+                 SET group_count = (SELECT COUNT(*) FROM articles WHERE group_id = ?1)
+                 WHERE group_id = ?1",
+                params![story_id],
+            )
+            .map_err(|e| format!("Story count: {e}"))?;
+        }
+        tx.commit().map_err(|e| format!("Commit clusters: {e}"))?;
+        Ok(assigned)
     }
 
-    pub fn update_image_url(&self, article_id: &str, image_url: &str) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        conn.execute(
-            "UPDATE articles SET image_url = ?1 WHERE id = ?2",
-            params![image_url, article_id],
-        )
-        .map_err(|e| format!("Update image: {e}"))?;
-        Ok(())
+    /// All articles in a story, oldest first.
+    pub fn get_story(&self, group_id: &str) -> Result<Vec<Article>, String> {
+        let conn = self.read()?;
+        let mut stmt = conn
+            .prepare(
+                &format!(
+                    "SELECT {ARTICLE_COLUMNS}
+                     FROM articles WHERE group_id = ?1
+                     ORDER BY published_at ASC, id ASC"
+                ),
+            )
+            .map_err(|e| e.to_string())?;
+        let articles = stmt
+            .query_map(params![group_id], row_to_article)
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(articles)
     }
 
+    /// Newest articles first, optionally only those filed under `category` (by any of
+    /// their assigned categories, not just the feed's) and readable in `lang`: written
+    /// in it, or translated into it, in which case the translation is served. Articles
+    /// whose language couldn't be detected are included whatever `lang` is.
     pub fn query_articles(
         &self,
         category: Option<&Category>,
+        lang: Option<&str>,
         limit: i64,
         cursor: Option<&str>,
     ) -> Result<(Vec<Article>, Option<String>), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
 
         let (cursor_pub, cursor_id) = match cursor {
             Some(c) => decode_cursor(c).unwrap_or((String::new(), String::new())),
@@ -240,7 +292,13 @@ impl Db {
         // Build SQL dynamically to avoid borrow issues
         let mut conditions = Vec::new();
         if category.is_some() {
-            conditions.push("category = :cat");
+            conditions.push("id IN (SELECT article_id FROM article_categories WHERE category = :cat)");
+        }
+        if lang.is_some() {
+            conditions.push(
+                "(lang = :lang OR lang IS NULL
+                  OR id IN (SELECT article_id FROM article_translations WHERE lang = :lang))",
+            );
         }
         if has_cursor {
             conditions.push("(published_at < :cpub OR (published_at = :cpub AND id < :cid))");
@@ -253,8 +311,7 @@ impl Db {
         };
 
         let sql = format!(
-            "SELECT id, category, title, url, description, image_url, source,
-                    published_at, fetched_at, group_id, group_count
+            "SELECT {ARTICLE_COLUMNS}
              FROM articles {}
              ORDER BY published_at DESC, id DESC
              LIMIT :lim",
@@ -273,6 +330,11 @@ impl Db {
             param_values.push(Box::new(cat.clone()));
             idx += 1;
         }
+        if let Some(lang) = lang {
+            param_names.push(":lang");
+            param_values.push(Box::new(lang.to_string()));
+            idx += 1;
+        }
         if has_cursor {
             param_names.push(":cpub");
             param_values.push(Box::new(cursor_pub.clone()));
@@ -297,50 +359,27 @@ impl Db {
 
         let next_cursor = if articles.len() as i64 > limit {
             articles.truncate(limit as usize);
-            articles.last().map(|a| encode_cursor(a))
+            articles.last().map(encode_cursor)
         } else {
             None
         };
+        if let Some(lang) = lang {
+            apply_translations(&conn, &mut articles, lang)?;
+        }
 
         Ok((articles, next_cursor))
     }
 
-    pub fn articles_without_image(&self, limit: i64) -> Result<Vec<Article>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, category, title, url, description, image_url, source,
-                        published_at, fetched_at, group_id, group_count
-                 FROM articles WHERE image_url IS NULL
-                 ORDER BY published_at DESC LIMIT ?1",
-            )
-            .map_err(|e| e.to_string())?;
-        let articles = stmt
-            .query_map(params![limit], row_to_article)
-            .map_err(|e| e.to_string())?
-            .filter_map(|r| r.ok())
-            .collect();
-        Ok(articles)
-    }
-
-    pub fn delete_old_articles(&self, before: &DateTime<Utc>) -> Result<usize, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        let deleted = conn
-            .execute(
-                "DELETE FROM articles WHERE published_at < ?1",
-                params![before.to_rfc3339()],
-            )
-            .map_err(|e| format!("Delete old: {e}"))?;
-        Ok(deleted)
-    }
-
+    /// Look up an article by ID; IDs of merged duplicates resolve to the kept article.
     pub fn get_article_by_id(&self, id: &str) -> Result<Option<Article>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
+        let id = resolve_alias(&conn, id)?.unwrap_or_else(|| id.to_string());
         let mut stmt = conn
             .prepare(
-                "SELECT id, category, title, url, description, image_url, source,
-                        published_at, fetched_at, group_id, group_count
-                 FROM articles WHERE id = ?1",
+                &format!(
+                    "SELECT {ARTICLE_COLUMNS}
+                     FROM articles WHERE id = ?1"
+                ),
             )
             .map_err(|e| e.to_string())?;
         let mut rows = stmt
@@ -353,214 +392,802 @@ impl Db {
         }
     }
 
-    // --- Search ---
-
-    pub fn search_articles(&self, query: &str, limit: i64) -> Result<Vec<Article>, String> {
-        let search = format!("%{}%", query);
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    /// Every version of an article's title, description and image, oldest first and
+    /// ending with the current one. `None` if there is no such article.
+    pub fn get_article_revisions(&self, id: &str) -> Result<Option<Vec<ArticleRevision>>, String> {
+        let conn = self.read()?;
+        let id = resolve_alias(&conn, id)?.unwrap_or_else(|| id.to_string());
+        let current = conn
+            .query_row(
+                "SELECT title, description, image_url, COALESCE(updated_at, fetched_at)
+                 FROM articles WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(ArticleRevision {
+                        title: row.get(0)?,
+                        description: row.get(1)?,
+                        image_url: row.get(2)?,
+                        seen_at: row.get::<_, String>(3)?.parse().unwrap_or_default(),
+                        replaced_at: None,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| format!("Get article revisions: {e}"))?;
+        let Some(current) = current else {
+            return Ok(None);
+        };
         let mut stmt = conn
             .prepare(
-                "SELECT id, category, title, url, description, image_url, source,
-                        published_at, fetched_at, group_id, group_count
-                 FROM articles
-                 WHERE title LIKE ?1 OR description LIKE ?1
-                 ORDER BY published_at DESC
-                 LIMIT ?2",
+                "SELECT title, description, image_url, seen_at, replaced_at
+                 FROM article_revisions WHERE article_id = ?1 ORDER BY id ASC",
             )
             .map_err(|e| e.to_string())?;
-        let articles = stmt
-            .query_map(params![search, limit], row_to_article)
-            .map_err(|e| e.to_string())?
-            .filter_map(|r| r.ok())
-            .collect();
-        Ok(articles)
-    }
-
-    // --- Feeds ---
-
-    pub fn get_enabled_feeds(&self) -> Result<Vec<DynamicFeed>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        let mut stmt = conn
-            .prepare("SELECT feed_id, url, source, category, enabled, added_by FROM feeds WHERE enabled = 1")
-            .map_err(|e| e.to_string())?;
-        let feeds = stmt
-            .query_map([], |row| {
-                Ok(DynamicFeed {
-                    feed_id: row.get(0)?,
-                    url: row.get(1)?,
-                    source: row.get(2)?,
-                    category: row.get(3)?,
-                    enabled: row.get::<_, i32>(4)? != 0,
-                    added_by: row.get(5)?,
+        let mut revisions: Vec<ArticleRevision> = stmt
+            .query_map(params![id], |row| {
+                Ok(ArticleRevision {
+                    title: row.get(0)?,
+                    description: row.get(1)?,
+                    image_url: row.get(2)?,
+                    seen_at: row.get::<_, String>(3)?.parse().unwrap_or_default(),
+                    replaced_at: row.get::<_, String>(4)?.parse().ok(),
                 })
             })
-            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Get article revisions: {e}"))?
             .filter_map(|r| r.ok())
             .collect();
-        Ok(feeds)
+        revisions.push(current);
+        Ok(Some(revisions))
     }
 
-    pub fn get_all_feeds(&self) -> Result<Vec<DynamicFeed>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    /// Recent articles still missing an image, or whose page hasn't been read for
+    /// metadata and a canonical URL yet.
+    pub fn articles_needing_page_metadata(
+        &self,
+        limit: i64,
+        since: &DateTime<Utc>,
+    ) -> Result<Vec<Article>, String> {
+        let conn = self.read()?;
         let mut stmt = conn
-            .prepare("SELECT feed_id, url, source, category, enabled, added_by FROM feeds")
+            .prepare(
+                &format!(
+                    "SELECT {ARTICLE_COLUMNS}
+                     FROM articles
+                     WHERE (image_url IS NULL
+                            OR ((canonical_url IS NULL OR page_metadata IS NULL) AND published_at >= ?2))
+                       AND NOT EXISTS (SELECT 1 FROM page_fetch_failures f
+                                       WHERE f.article_id = articles.id AND f.retry_at > ?3)
+                     ORDER BY published_at DESC LIMIT ?1"
+                ),
+            )
             .map_err(|e| e.to_string())?;
-        let feeds = stmt
-            .query_map([], |row| {
-                Ok(DynamicFeed {
-                    feed_id: row.get(0)?,
-                    url: row.get(1)?,
-                    source: row.get(2)?,
-                    category: row.get(3)?,
-                    enabled: row.get::<_, i32>(4)? != 0,
-                    added_by: row.get(5)?,
-                })
-            })
+        let articles = stmt
+            .query_map(params![limit, since.to_rfc3339(), Utc::now().to_rfc3339()], row_to_article)
             .map_err(|e| e.to_string())?
             .filter_map(|r| r.ok())
             .collect();
-        Ok(feeds)
+        Ok(articles)
     }
 
-    pub fn put_feed(&self, feed: &DynamicFeed) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    /// Store metadata read from an article's page. It fills a missing description or image,
+    /// replaces the publish time when the feed gave none (ingest then uses the fetch
+    /// time for both), and its section and keywords count towards the article's categories.
+    pub fn update_page_metadata(&self, article_id: &str, metadata: &PageMetadata) -> Result<(), String> {
+        let json = serde_json::to_string(metadata).map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         conn.execute(
-            "INSERT OR REPLACE INTO feeds (feed_id, url, source, category, enabled, added_by)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "UPDATE articles SET
+                page_metadata = ?1,
+                description = COALESCE(NULLIF(description, ''), ?2),
+                image_url = COALESCE(image_url, ?3),
+                published_at = CASE WHEN ?4 IS NOT NULL AND published_at = fetched_at
+                                    THEN ?4 ELSE published_at END
+             WHERE id = ?5",
             params![
-                feed.feed_id,
-                feed.url,
-                feed.source,
-                feed.category,
-                feed.enabled as i32,
-                feed.added_by,
+                json,
+                metadata.description,
+                metadata.image_url,
+                metadata.published_at.map(|t| t.to_rfc3339()),
+                article_id,
             ],
         )
-        .map_err(|e| format!("Put feed: {e}"))?;
-        info!(feed_id = %feed.feed_id, source = %feed.source, "Feed saved");
-        Ok(())
-    }
-
-    pub fn delete_feed(&self, feed_id: &str) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        conn.execute("DELETE FROM feeds WHERE feed_id = ?1", params![feed_id])
-            .map_err(|e| format!("Delete feed: {e}"))?;
-        info!(feed_id, "Feed deleted");
+        .map_err(|e| format!("Update page metadata: {e}"))?;
+        refile_article(&conn, article_id)?;
+        conn.execute("DELETE FROM page_fetch_failures WHERE article_id = ?1", params![article_id])
+            .map_err(|e| format!("Clear page fetch failures: {e}"))?;
         Ok(())
     }
 
-    pub fn feed_count(&self) -> Result<i64, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        conn.query_row("SELECT COUNT(*) FROM feeds", [], |row| row.get(0))
-            .map_err(|e| format!("Feed count: {e}"))
+    /// Record that an article's page couldn't be fetched. The article is left out of
+    /// [`Db::articles_needing_page_metadata`] for `backoff` doubled on each further
+    /// failure, up to `max_backoff`. Returns how many times in a row it has failed.
+    pub fn record_page_fetch_failure(
+        &self,
+        article_id: &str,
+        backoff: chrono::Duration,
+        max_backoff: chrono::Duration,
+    ) -> Result<u32, String> {
+        let mut conn = self.write()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let failures: u32 = tx
+            .query_row(
+                "SELECT failures FROM page_fetch_failures WHERE article_id = ?1",
+                params![article_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Page fetch failures: {e}"))?
+            .unwrap_or(0)
+            + 1;
+        let retry_at = Utc::now() + doubled_backoff(failures, backoff, max_backoff);
+        tx.execute(
+            "INSERT OR REPLACE INTO page_fetch_failures (article_id, failures, retry_at) VALUES (?1, ?2, ?3)",
+            params![article_id, failures, retry_at.to_rfc3339()],
+        )
+        .map_err(|e| format!("Record page fetch failure: {e}"))?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(failures)
     }
 
-    // --- Features ---
-
-    pub fn get_feature_flags(&self) -> Result<FeatureFlags, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        let mut flags = FeatureFlags::default();
-
-        let mut stmt = conn
-            .prepare("SELECT feature, enabled, extra_json FROM features")
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map([], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, i32>(1)? != 0,
-                    row.get::<_, Option<String>>(2)?,
-                ))
-            })
-            .map_err(|e| e.to_string())?;
-
-        for row in rows.flatten() {
-            let (feature, enabled, extra) = row;
-            match feature.as_str() {
-                "grouping" => {
-                    flags.grouping_enabled = enabled;
-                    if let Some(ref json) = extra {
-                        if let Ok(v) = serde_json::from_str::<serde_json::Value>(json) {
-                            if let Some(t) = v.get("similarity_threshold").and_then(|t| t.as_f64())
-                            {
-                                flags.grouping_threshold = t;
-                            }
-                        }
-                    }
-                }
-                "ogp_enrichment" => {
-                    flags.ogp_enrichment_enabled = enabled;
+    /// Record the canonical URL found on an article's page. If another stored article
+    /// already has the canonical ID, this one is merged into it and that ID is returned;
+    /// otherwise the canonical ID becomes an alias so feeds linking to it later are skipped.
+    pub fn record_canonical(
+        &self,
+        id: &str,
+        canonical_url: &str,
+        canonical_id: &str,
+    ) -> Result<Option<String>, String> {
+        if canonical_id != id {
+            let target = {
+                let conn = self.write()?;
+                match resolve_alias(&conn, canonical_id)? {
+                    Some(target) => Some(target),
+                    None => conn
+                        .query_row(
+                            "SELECT id FROM articles WHERE id = ?1",
+                            params![canonical_id],
+                            |row| row.get::<_, String>(0),
+                        )
+                        .ok(),
                 }
-                _ => {}
+            };
+            if let Some(keep) = target.filter(|t| t != id) {
+                self.merge_article_into(id, &keep)?;
+                return Ok(Some(keep));
             }
         }
-        Ok(flags)
-    }
 
-    pub fn get_service_config(&self) -> Result<ServiceConfig, String> {
-        let feeds = self.get_all_feeds()?;
-        let features = self.get_feature_flags()?;
-        Ok(ServiceConfig { feeds, features })
+        let conn = self.write()?;
+        conn.execute(
+            "UPDATE articles SET canonical_url = ?1 WHERE id = ?2",
+            params![canonical_url, id],
+        )
+        .map_err(|e| format!("Update canonical: {e}"))?;
+        if canonical_id != id {
+            conn.execute(
+                "INSERT OR IGNORE INTO article_aliases (alias_id, article_id, created_at)
+                 VALUES (?1, ?2, ?3)",
+                params![canonical_id, id, Utc::now().to_rfc3339()],
+            )
+            .map_err(|e| format!("Insert alias: {e}"))?;
+        }
+        Ok(None)
     }
 
-    pub fn set_feature_flag(
-        &self,
-        feature: &str,
-        enabled: bool,
-        extra_json: Option<&str>,
-    ) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        conn.execute(
-            "INSERT OR REPLACE INTO features (feature, enabled, extra_json) VALUES (?1, ?2, ?3)",
-            params![feature, enabled as i32, extra_json],
+    /// Merge a duplicate article into `keep_id`: counters are summed, a missing image or
+    /// description is filled in, enrichments and aliases move over, and the duplicate's ID
+    /// becomes an alias so links to it keep working.
+    pub fn merge_article_into(&self, dup_id: &str, keep_id: &str) -> Result<(), String> {
+        let mut conn = self.write()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE articles SET
+                view_count = view_count + COALESCE((SELECT view_count FROM articles WHERE id = ?1), 0),
+                click_count = click_count + COALESCE((SELECT click_count FROM articles WHERE id = ?1), 0),
+                image_url = COALESCE(image_url, (SELECT image_url FROM articles WHERE id = ?1)),
+                description = COALESCE(description, (SELECT description FROM articles WHERE id = ?1))
+             WHERE id = ?2",
+            params![dup_id, keep_id],
         )
-        .map_err(|e| format!("Set feature: {e}"))?;
-        info!(feature, enabled, "Feature flag updated");
+        .map_err(|e| format!("Merge article: {e}"))?;
+        tx.execute(
+            "UPDATE articles SET popularity_score = view_count * 0.7 + click_count * 0.3 WHERE id = ?1",
+            params![keep_id],
+        )
+        .map_err(|e| format!("Update popularity: {e}"))?;
+        tx.execute(
+            "UPDATE enrichments SET article_id = ?2 WHERE article_id = ?1",
+            params![dup_id, keep_id],
+        )
+        .map_err(|e| format!("Move enrichments: {e}"))?;
+        tx.execute(
+            "UPDATE article_aliases SET article_id = ?2 WHERE article_id = ?1",
+            params![dup_id, keep_id],
+        )
+        .map_err(|e| format!("Move aliases: {e}"))?;
+        tx.execute("DELETE FROM articles WHERE id = ?1", params![dup_id])
+            .map_err(|e| format!("Delete duplicate: {e}"))?;
+        tx.execute(
+            "INSERT OR REPLACE INTO article_aliases (alias_id, article_id, created_at)
+             VALUES (?1, ?2, ?3)",
+            params![dup_id, keep_id, Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| format!("Insert alias: {e}"))?;
+        tx.commit().map_err(|e| e.to_string())?;
+        info!(duplicate = dup_id, kept = keep_id, "Merged duplicate article");
         Ok(())
     }
 
-    // --- Categories ---
+    // --- Search ---
 
-    pub fn category_count(&self) -> Result<i64, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        conn.query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0))
-            .map_err(|e| format!("Category count: {e}"))
+    /// Full-text search ranked by bm25 relevance (title weighted over description)
+    /// decayed by article age, with highlighted snippets and cursor pagination.
+    ///
+    /// Queries with terms shorter than the trigram length fall back to LIKE matching,
+    /// ranked by recency alone.
+    pub fn search_articles(
+        &self,
+        query: &str,
+        category: Option<&Category>,
+        lang: Option<&str>,
+        limit: i64,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<SearchHit>, Option<String>), String> {
+        self.search(query, category, lang, limit, cursor, false)
     }
 
-    pub fn seed_default_categories(&self) -> Result<(), String> {
-        let defaults = [
-            ("general", "総合", "General", 0),
-            ("tech", "テクノロジー", "Technology", 1),
-            ("business", "ビジネス", "Business", 2),
-            ("entertainment", "エンタメ", "Entertainment", 3),
-            ("sports", "スポーツ", "Sports", 4),
-            ("science", "サイエンス", "Science", 5),
-            ("podcast", "ポッドキャスト", "Podcast", 6),
-        ];
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        for (id, ja, en, order) in defaults {
-            conn.execute(
-                "INSERT OR IGNORE INTO categories (id, label_ja, label_en, sort_order, visible) VALUES (?1, ?2, ?3, ?4, 1)",
-                params![id, ja, en, order],
-            ).map_err(|e| format!("Seed category: {e}"))?;
-        }
-        info!("Default categories seeded");
-        Ok(())
+    /// [`Db::search_articles`] over archived articles instead of live ones.
+    pub fn search_archive(
+        &self,
+        query: &str,
+        category: Option<&Category>,
+        lang: Option<&str>,
+        limit: i64,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<SearchHit>, Option<String>), String> {
+        self.search(query, category, lang, limit, cursor, true)
     }
 
-    pub fn ensure_all_categories_visible(&self) -> Result<usize, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        let updated = conn
-            .execute("UPDATE categories SET visible = 1 WHERE visible = 0", [])
-            .map_err(|e| format!("Ensure visible: {e}"))?;
-        if updated > 0 {
-            info!(updated, "Made hidden categories visible");
-        }
-        Ok(updated)
+    fn search(
+        &self,
+        query: &str,
+        category: Option<&Category>,
+        lang: Option<&str>,
+        limit: i64,
+        cursor: Option<&str>,
+        archived: bool,
+    ) -> Result<(Vec<SearchHit>, Option<String>), String> {
+        let (table, fts_table) = if archived {
+            ("archived_articles", "archived_articles_fts")
+        } else {
+            ("articles", "articles_fts")
+        };
+        let parsed = search::parse_query(query);
+        if parsed.is_empty() {
+            return Ok((Vec::new(), None));
+        }
+
+        let (now, after) = match cursor.and_then(decode_search_cursor) {
+            Some((now, score, id)) => (now, Some((score, id))),
+            None => (Utc::now().to_rfc3339(), None),
+        };
+
+        let mut param_names: Vec<String> = vec![":now".into(), ":half".into()];
+        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> =
+            vec![Box::new(now.clone()), Box::new(search::RECENCY_HALF_LIFE_DAYS)];
+        let recency =
+            "(1.0 + MAX(0.0, julianday(:now) - julianday(a.published_at)) / :half)";
+
+        let mut conditions = Vec::new();
+        let (score_expr, snippet_expr, from) = match parsed.fts {
+            Some(ref fts) => {
+                conditions.push(format!("{fts_table} MATCH :q"));
+                param_names.push(":q".into());
+                param_values.push(Box::new(fts.clone()));
+                (
+                    format!("-bm25({fts_table}, 10.0, 1.0) / {recency}"),
+                    format!(
+                        "snippet({fts_table}, -1, '{}', '{}', '…', 24)",
+                        search::SNIPPET_MARK_START,
+                        search::SNIPPET_MARK_END
+                    ),
+                    format!("{fts_table} JOIN {table} a ON a.rowid = {fts_table}.rowid"),
+                )
+            }
+            None => {
+                let mut alternatives = Vec::new();
+                for (i, clause) in parsed.any_of.iter().enumerate() {
+                    let mut all = Vec::new();
+                    for (j, term) in clause.iter().enumerate() {
+                        all.push(format!(
+                            "(a.title LIKE :t{i}_{j} ESCAPE '\\' OR a.description LIKE :t{i}_{j} ESCAPE '\\')"
+                        ));
+                        param_names.push(format!(":t{i}_{j}"));
+                        param_values.push(Box::new(search::like_pattern(term)));
+                    }
+                    alternatives.push(format!("({})", all.join(" AND ")));
+                }
+                conditions.push(format!("({})", alternatives.join(" OR ")));
+                for (i, term) in parsed.excluded.iter().enumerate() {
+                    conditions.push(format!(
+                        "NOT (a.title LIKE :x{i} ESCAPE '\\' OR IFNULL(a.description, '') LIKE :x{i} ESCAPE '\\')"
+                    ));
+                    param_names.push(format!(":x{i}"));
+                    param_values.push(Box::new(search::like_pattern(term)));
+                }
+                (format!("1.0 / {recency}"), "NULL".into(), format!("{table} a"))
+            }
+        };
+        if let Some(cat) = category {
+            conditions.push(if archived {
+                "EXISTS (SELECT 1 FROM json_each(a.category_ids) WHERE value = :cat)".into()
+            } else {
+                "a.id IN (SELECT article_id FROM article_categories WHERE category = :cat)".into()
+            });
+            param_names.push(":cat".into());
+            param_values.push(Box::new(cat.as_str().to_string()));
+        }
+        if let Some(lang) = lang {
+            conditions.push(if archived {
+                "(a.lang = :lang OR a.lang IS NULL)".into()
+            } else {
+                "(a.lang = :lang OR a.lang IS NULL
+                  OR a.id IN (SELECT article_id FROM article_translations WHERE lang = :lang))"
+                    .into()
+            });
+            param_names.push(":lang".into());
+            param_values.push(Box::new(lang.to_string()));
+        }
+
+        let page_filter = if let Some((score, id)) = after {
+            param_names.push(":cscore".into());
+            param_values.push(Box::new(score));
+            param_names.push(":cid".into());
+            param_values.push(Box::new(id));
+            "WHERE score < :cscore OR (score = :cscore AND id < :cid)"
+        } else {
+            ""
+        };
+        param_names.push(":lim".into());
+        param_values.push(Box::new(limit + 1));
+
+        // Archived rows keep the whole article compressed in `data`
+        let columns = if archived {
+            "a.id, a.data".to_string()
+        } else {
+            ARTICLE_COLUMNS
+                .split(", ")
+                .map(|c| format!("a.{c}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let sql = format!(
+            "WITH hits AS (
+                SELECT {columns},
+                       {snippet_expr} AS snippet, {score_expr} AS score
+                FROM {from}
+                WHERE {}
+             )
+             SELECT * FROM hits {page_filter}
+             ORDER BY score DESC, id DESC
+             LIMIT :lim",
+            conditions.join(" AND ")
+        );
+
+        let conn = self.read()?;
+        let mut stmt = conn.prepare(&sql).map_err(|e| format!("Search: {e}"))?;
+        let params: Vec<(&str, &dyn rusqlite::types::ToSql)> = param_names
+            .iter()
+            .zip(param_values.iter())
+            .map(|(name, val)| (name.as_str(), val.as_ref()))
+            .collect();
+        let mut hits: Vec<SearchHit> = if archived {
+            stmt.query_map(params.as_slice(), |row| {
+                Ok((row.get::<_, Vec<u8>>(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(|e| format!("Search: {e}"))?
+            .filter_map(|r| r.ok())
+            .filter_map(|(data, snippet, score)| {
+                let article = archive::decode(&data).ok()?.article;
+                Some(SearchHit { article, snippet, score })
+            })
+            .collect()
+        } else {
+            stmt.query_map(params.as_slice(), |row| {
+                Ok(SearchHit {
+                    article: row_to_article(row)?,
+                    snippet: row.get(ARTICLE_COLUMN_COUNT)?,
+                    score: row.get(ARTICLE_COLUMN_COUNT + 1)?,
+                })
+            })
+            .map_err(|e| format!("Search: {e}"))?
+            .filter_map(|r| r.ok())
+            .collect()
+        };
+
+        if let Some(lang) = lang.filter(|_| !archived) {
+            apply_translations(&conn, hits.iter_mut().map(|h| &mut h.article), lang)?;
+        }
+        if parsed.fts.is_some() {
+            for hit in &mut hits {
+                hit.snippet = hit.snippet.as_deref().map(search::snippet_html);
+            }
+        } else {
+            for hit in &mut hits {
+                let text = hit.article.description.as_deref().unwrap_or(&hit.article.title);
+                hit.snippet = search::highlight(text, &parsed.terms, 24)
+                    .or_else(|| search::highlight(&hit.article.title, &parsed.terms, 24));
+            }
+        }
+
+        let next_cursor = if hits.len() as i64 > limit {
+            hits.truncate(limit as usize);
+            hits.last().map(|h| encode_search_cursor(&now, h))
+        } else {
+            None
+        };
+        Ok((hits, next_cursor))
+    }
+
+    // --- Feeds ---
+
+    pub fn get_enabled_feeds(&self) -> Result<Vec<DynamicFeed>, String> {
+        let conn = self.read()?;
+        let mut stmt = conn
+            .prepare("SELECT feed_id, url, source, category, enabled, added_by, poll_interval_secs, quarantine_reason, content_policy, source_type, scrape_rules, filters FROM feeds WHERE enabled = 1")
+            .map_err(|e| e.to_string())?;
+        let feeds = stmt
+            .query_map([], row_to_feed)
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(feeds)
+    }
+
+    pub fn get_all_feeds(&self) -> Result<Vec<DynamicFeed>, String> {
+        let conn = self.read()?;
+        let mut stmt = conn
+            .prepare("SELECT feed_id, url, source, category, enabled, added_by, poll_interval_secs, quarantine_reason, content_policy, source_type, scrape_rules, filters FROM feeds")
+            .map_err(|e| e.to_string())?;
+        let feeds = stmt
+            .query_map([], row_to_feed)
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(feeds)
+    }
+
+    pub fn put_feed(&self, feed: &DynamicFeed) -> Result<(), String> {
+        let conn = self.write()?;
+        put_feed(&conn, feed)?;
+        info!(feed_id = %feed.feed_id, source = %feed.source, "Feed saved");
+        Ok(())
+    }
+
+    /// Add the new feeds of an OPML import plan, all or none. Returns how many were added.
+    pub fn write_import(&self, plan: &ImportPlan, added_by: &str) -> Result<usize, String> {
+        let feeds = plan.new_feeds(added_by);
+        let mut conn = self.write()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        for feed in &feeds {
+            put_feed(&tx, feed)?;
+        }
+        tx.commit().map_err(|e| format!("Commit import: {e}"))?;
+        info!(count = feeds.len(), "OPML import saved");
+        Ok(feeds.len())
+    }
+
+    /// Content policy for an article's page: the most restrictive policy of the feeds
+    /// with the article's source. URLs that aren't stored articles get `NoFetch`, so a
+    /// caller-supplied URL is never fetched.
+    pub fn content_policy_for_url(&self, url: &str) -> Result<ContentPolicy, String> {
+        let conn = self.read()?;
+        let source: Option<String> = conn
+            .query_row(
+                "SELECT source FROM articles WHERE url = ?1 OR canonical_url = ?1 LIMIT 1",
+                params![url],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Content policy: {e}"))?;
+        let Some(source) = source else {
+            return Ok(ContentPolicy::NoFetch);
+        };
+        let mut stmt = conn
+            .prepare("SELECT content_policy FROM feeds WHERE source = ?1")
+            .map_err(|e| format!("Content policy: {e}"))?;
+        let policy = stmt
+            .query_map(params![source], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Content policy: {e}"))?
+            .filter_map(|r| r.ok())
+            .filter_map(|p| ContentPolicy::from_str(&p))
+            .max();
+        Ok(policy.unwrap_or_default())
+    }
+
+    pub fn delete_feed(&self, feed_id: &str) -> Result<(), String> {
+        let conn = self.write()?;
+        conn.execute("DELETE FROM feeds WHERE feed_id = ?1", params![feed_id])
+            .map_err(|e| format!("Delete feed: {e}"))?;
+        conn.execute("DELETE FROM feed_fetch_state WHERE feed_id = ?1", params![feed_id])
+            .map_err(|e| format!("Delete feed fetch state: {e}"))?;
+        info!(feed_id, "Feed deleted");
+        Ok(())
+    }
+
+    pub fn feed_count(&self) -> Result<i64, String> {
+        let conn = self.read()?;
+        conn.query_row("SELECT COUNT(*) FROM feeds", [], |row| row.get(0))
+            .map_err(|e| format!("Feed count: {e}"))
+    }
+
+    // --- Feed Fetch State ---
+
+    /// Load the fetch state of every feed, keyed by feed_id.
+    pub fn get_fetch_states(&self) -> Result<HashMap<String, FetchState>, String> {
+        let conn = self.read()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT feed_id, etag, last_modified, last_status, last_success_at,
+                        consecutive_failures, avg_latency_ms, next_poll_at,
+                        publish_interval_secs, update_hint_secs, last_error_class,
+                        last_error, recent_item_counts, latest_item_at,
+                        websub_hub, websub_topic, last_filtered_count
+                 FROM feed_fetch_state",
+            )
+            .map_err(|e| e.to_string())?;
+        let states = stmt
+            .query_map([], |row| {
+                let last_success_at: Option<String> = row.get(4)?;
+                let next_poll_at: Option<String> = row.get(7)?;
+                let last_error_class: Option<String> = row.get(10)?;
+                let recent_item_counts: Option<String> = row.get(12)?;
+                let latest_item_at: Option<String> = row.get(13)?;
+                Ok((
+                    row.get::<_, String>(0)?,
+                    FetchState {
+                        etag: row.get(1)?,
+                        last_modified: row.get(2)?,
+                        last_status: row.get(3)?,
+                        last_success_at: last_success_at.and_then(|s| s.parse().ok()),
+                        consecutive_failures: row.get(5)?,
+                        avg_latency_ms: row.get(6)?,
+                        next_poll_at: next_poll_at.and_then(|s| s.parse().ok()),
+                        publish_interval_secs: row.get(8)?,
+                        update_hint_secs: row.get(9)?,
+                        last_error_class: last_error_class
+                            .and_then(|s| FetchErrorClass::from_str(&s)),
+                        last_error: row.get(11)?,
+                        recent_item_counts: recent_item_counts
+                            .and_then(|s| serde_json::from_str(&s).ok())
+                            .unwrap_or_default(),
+                        latest_item_at: latest_item_at.and_then(|s| s.parse().ok()),
+                        websub_hub: row.get(14)?,
+                        websub_topic: row.get(15)?,
+                        last_filtered_count: row.get(16)?,
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(states)
+    }
+
+    /// Persist fetch state for a batch of feeds in one transaction.
+    pub fn put_fetch_states(&self, states: &[(String, FetchState)]) -> Result<(), String> {
+        let mut conn = self.write()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let now = Utc::now().to_rfc3339();
+        for (feed_id, state) in states {
+            tx.execute(
+                "INSERT OR REPLACE INTO feed_fetch_state
+                    (feed_id, etag, last_modified, last_status, last_success_at,
+                     consecutive_failures, avg_latency_ms, next_poll_at,
+                     publish_interval_secs, update_hint_secs, last_error_class,
+                     last_error, recent_item_counts, latest_item_at, websub_hub,
+                     websub_topic, last_filtered_count, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+                params![
+                    feed_id,
+                    state.etag,
+                    state.last_modified,
+                    state.last_status,
+                    state.last_success_at.map(|t| t.to_rfc3339()),
+                    state.consecutive_failures,
+                    state.avg_latency_ms,
+                    state.next_poll_at.map(|t| t.to_rfc3339()),
+                    state.publish_interval_secs,
+                    state.update_hint_secs,
+                    state.last_error_class.map(|c| c.as_str()),
+                    state.last_error,
+                    serde_json::to_string(&state.recent_item_counts).ok(),
+                    state.latest_item_at.map(|t| t.to_rfc3339()),
+                    state.websub_hub,
+                    state.websub_topic,
+                    state.last_filtered_count,
+                    now,
+                ],
+            )
+            .map_err(|e| format!("Put fetch state: {e}"))?;
+        }
+        tx.commit().map_err(|e| format!("Commit fetch state: {e}"))?;
+        Ok(())
+    }
+
+    /// Disable a feed that keeps failing and record why.
+    pub fn quarantine_feed(&self, feed_id: &str, reason: &str) -> Result<(), String> {
+        let conn = self.write()?;
+        conn.execute(
+            "UPDATE feeds SET enabled = 0, quarantine_reason = ?2 WHERE feed_id = ?1",
+            params![feed_id, reason],
+        )
+        .map_err(|e| format!("Quarantine feed: {e}"))?;
+        info!(feed_id, reason, "Feed quarantined");
+        Ok(())
+    }
+
+    /// Re-enable a quarantined feed, clear its failure count and poll it on the next tick.
+    pub fn release_quarantine(&self, feed_id: &str) -> Result<(), String> {
+        let conn = self.write()?;
+        let updated = conn
+            .execute(
+                "UPDATE feeds SET enabled = 1, quarantine_reason = NULL WHERE feed_id = ?1",
+                params![feed_id],
+            )
+            .map_err(|e| format!("Release quarantine: {e}"))?;
+        if updated == 0 {
+            return Err(format!("Feed not found: {}", feed_id));
+        }
+        conn.execute(
+            "UPDATE feed_fetch_state SET consecutive_failures = 0, next_poll_at = NULL WHERE feed_id = ?1",
+            params![feed_id],
+        )
+        .map_err(|e| format!("Reset fetch failures: {e}"))?;
+        info!(feed_id, "Feed quarantine released");
+        Ok(())
+    }
+
+    // --- WebSub Subscriptions ---
+
+    pub fn get_websub_subscription(&self, feed_id: &str) -> Result<Option<HubSubscription>, String> {
+        let conn = self.read()?;
+        conn.query_row(
+            &format!("SELECT {WEBSUB_COLUMNS} FROM websub_subscriptions WHERE feed_id = ?1"),
+            params![feed_id],
+            row_to_websub,
+        )
+        .optional()
+        .map_err(|e| format!("Get WebSub subscription: {e}"))
+    }
+
+    pub fn get_websub_subscriptions(&self) -> Result<Vec<HubSubscription>, String> {
+        let conn = self.read()?;
+        let mut stmt = conn
+            .prepare(&format!("SELECT {WEBSUB_COLUMNS} FROM websub_subscriptions ORDER BY feed_id"))
+            .map_err(|e| e.to_string())?;
+        let subs = stmt
+            .query_map([], row_to_websub)
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(subs)
+    }
+
+    pub fn put_websub_subscription(&self, sub: &HubSubscription) -> Result<(), String> {
+        let conn = self.write()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO websub_subscriptions
+                (feed_id, hub, topic, secret, status, lease_expires_at, requested_at, last_push_at, last_error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                sub.feed_id,
+                sub.hub,
+                sub.topic,
+                sub.secret,
+                sub.status.as_str(),
+                sub.lease_expires_at.map(|t| t.to_rfc3339()),
+                sub.requested_at.to_rfc3339(),
+                sub.last_push_at.map(|t| t.to_rfc3339()),
+                sub.last_error,
+            ],
+        )
+        .map_err(|e| format!("Put WebSub subscription: {e}"))?;
+        Ok(())
+    }
+
+    // --- Features ---
+
+    pub fn get_feature_flags(&self) -> Result<FeatureFlags, String> {
+        let conn = self.read()?;
+        let mut flags = FeatureFlags::default();
+
+        let mut stmt = conn
+            .prepare("SELECT feature, enabled, extra_json FROM features")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i32>(1)? != 0,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        for (feature, enabled, extra) in rows.flatten() {
+            let extra = extra.and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+            flags.set(&feature, enabled, extra.as_ref());
+        }
+        Ok(flags)
+    }
+
+    pub fn get_service_config(&self) -> Result<ServiceConfig, String> {
+        let feeds = self.get_all_feeds()?;
+        let features = self.get_feature_flags()?;
+        Ok(ServiceConfig { feeds, features })
+    }
+
+    pub fn set_feature_flag(
+        &self,
+        feature: &str,
+        enabled: bool,
+        extra_json: Option<&str>,
+    ) -> Result<(), String> {
+        let conn = self.write()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO features (feature, enabled, extra_json) VALUES (?1, ?2, ?3)",
+            params![feature, enabled as i32, extra_json],
+        )
+        .map_err(|e| format!("Set feature: {e}"))?;
+        info!(feature, enabled, "Feature flag updated");
+        Ok(())
+    }
+
+    // --- Categories ---
+
+    pub fn category_count(&self) -> Result<i64, String> {
+        let conn = self.read()?;
+        conn.query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0))
+            .map_err(|e| format!("Category count: {e}"))
+    }
+
+    pub fn seed_default_categories(&self) -> Result<(), String> {
+        let defaults = [
+            ("general", "総合", "General", 0),
+            ("tech", "テクノロジー", "Technology", 1),
+            ("business", "ビジネス", "Business", 2),
+            ("entertainment", "エンタメ", "Entertainment", 3),
+            ("sports", "スポーツ", "Sports", 4),
+            ("science", "サイエンス", "Science", 5),
+            ("podcast", "ポッドキャスト", "Podcast", 6),
+        ];
+        let conn = self.write()?;
+        for (id, ja, en, order) in defaults {
+            conn.execute(
+                "INSERT OR IGNORE INTO categories (id, label_ja, label_en, sort_order, visible) VALUES (?1, ?2, ?3, ?4, 1)",
+                params![id, ja, en, order],
+            ).map_err(|e| format!("Seed category: {e}"))?;
+        }
+        info!("Default categories seeded");
+        Ok(())
+    }
+
+    pub fn ensure_all_categories_visible(&self) -> Result<usize, String> {
+        let conn = self.write()?;
+        let updated = conn
+            .execute("UPDATE categories SET visible = 1 WHERE visible = 0", [])
+            .map_err(|e| format!("Ensure visible: {e}"))?;
+        if updated > 0 {
+            info!(updated, "Made hidden categories visible");
+        }
+        Ok(updated)
     }
 
+    #[allow(clippy::type_complexity)]
     pub fn get_categories(&self) -> Result<Vec<(String, String, String, i32, bool)>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare("SELECT id, label_ja, label_en, sort_order, visible FROM categories ORDER BY sort_order ASC, id ASC")
             .map_err(|e| e.to_string())?;
@@ -581,7 +1208,7 @@ impl Db {
     }
 
     pub fn put_category(&self, id: &str, label_ja: &str, label_en: &str, sort_order: i32) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         conn.execute(
             "INSERT OR REPLACE INTO categories (id, label_ja, label_en, sort_order, visible) VALUES (?1, ?2, ?3, ?4, 1)",
             params![id, label_ja, label_en, sort_order],
@@ -591,7 +1218,7 @@ impl Db {
     }
 
     pub fn rename_category(&self, id: &str, label_ja: &str) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         let affected = conn.execute(
             "UPDATE categories SET label_ja = ?1 WHERE id = ?2",
             params![label_ja, id],
@@ -604,7 +1231,7 @@ impl Db {
     }
 
     pub fn delete_category(&self, id: &str) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         conn.execute("DELETE FROM categories WHERE id = ?1", params![id])
             .map_err(|e| format!("Delete category: {e}"))?;
         info!(id, "Category deleted");
@@ -612,7 +1239,7 @@ impl Db {
     }
 
     pub fn reorder_categories(&self, order: &[String]) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         for (i, id) in order.iter().enumerate() {
             conn.execute(
                 "UPDATE categories SET sort_order = ?1 WHERE id = ?2",
@@ -628,7 +1255,7 @@ impl Db {
     pub fn create_change(&self, change: &ChangeRequest) -> Result<(), String> {
         let actions_json =
             serde_json::to_string(&change.actions).map_err(|e| format!("Serialize actions: {e}"))?;
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         conn.execute(
             "INSERT INTO changes (change_id, status, command_text, interpretation, actions_json, created_at)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
@@ -647,7 +1274,7 @@ impl Db {
     }
 
     pub fn get_change(&self, change_id: &str) -> Result<Option<ChangeRequest>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
                 "SELECT change_id, status, command_text, interpretation, actions_json, created_at
@@ -693,7 +1320,7 @@ impl Db {
         change_id: &str,
         status: ChangeStatus,
     ) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         conn.execute(
             "UPDATE changes SET status = ?1 WHERE change_id = ?2",
             params![status.as_str(), change_id],
@@ -712,7 +1339,7 @@ impl Db {
         stripe_subscription_id: &str,
         current_period_end: &str,
     ) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         conn.execute(
             "INSERT OR REPLACE INTO subscriptions
                 (api_token, stripe_customer_id, stripe_subscription_id, status, current_period_end, created_at)
@@ -734,7 +1361,7 @@ impl Db {
         &self,
         api_token: &str,
     ) -> Result<Option<(String, String, String, String)>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
                 "SELECT stripe_customer_id, stripe_subscription_id, status, current_period_end
@@ -759,7 +1386,7 @@ impl Db {
         &self,
         stripe_subscription_id: &str,
     ) -> Result<Option<(String, String, String)>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
                 "SELECT api_token, status, current_period_end
@@ -783,7 +1410,7 @@ impl Db {
         &self,
         stripe_customer_id: &str,
     ) -> Result<Option<(String, String, String, String)>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
                 "SELECT api_token, stripe_subscription_id, status, current_period_end
@@ -809,7 +1436,7 @@ impl Db {
         status: &str,
         current_period_end: Option<&str>,
     ) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         if let Some(period_end) = current_period_end {
             conn.execute(
                 "UPDATE subscriptions SET status = ?1, current_period_end = ?2 WHERE stripe_subscription_id = ?3",
@@ -830,7 +1457,7 @@ impl Db {
 
     pub fn increment_usage(&self, device_id: &str, feature: &str) -> Result<i64, String> {
         let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         conn.execute(
             "INSERT INTO usage_limits (device_id, feature, used_date, count)
              VALUES (?1, ?2, ?3, 1)
@@ -851,7 +1478,7 @@ impl Db {
 
     pub fn get_usage(&self, device_id: &str, feature: &str) -> Result<i64, String> {
         let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let count = conn
             .query_row(
                 "SELECT count FROM usage_limits WHERE device_id = ?1 AND feature = ?2 AND used_date = ?3",
@@ -864,7 +1491,7 @@ impl Db {
 
     pub fn get_all_usage(&self, device_id: &str) -> Result<Vec<(String, i64)>, String> {
         let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
                 "SELECT feature, count FROM usage_limits WHERE device_id = ?1 AND used_date = ?2",
@@ -884,7 +1511,7 @@ impl Db {
         let cutoff = (chrono::Utc::now() - chrono::Duration::days(days_to_keep))
             .format("%Y-%m-%d")
             .to_string();
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         let deleted = conn
             .execute(
                 "DELETE FROM usage_limits WHERE used_date < ?1",
@@ -895,7 +1522,7 @@ impl Db {
     }
 
     pub fn list_changes(&self, limit: i64) -> Result<Vec<ChangeRequest>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
                 "SELECT change_id, status, command_text, interpretation, actions_json, created_at
@@ -940,18 +1567,26 @@ impl Db {
 
     // --- Top Articles per Category (for TTS pre-cache) ---
 
+    /// The newest `per_category` articles filed under each category, podcasts aside.
+    /// An article filed under several categories is listed once.
     pub fn top_articles_per_category(&self, per_category: i64) -> Result<Vec<Article>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
-                "SELECT id, category, title, url, description, image_url, source,
-                        published_at, fetched_at, group_id, group_count
-                 FROM (
-                     SELECT *, ROW_NUMBER() OVER (PARTITION BY category ORDER BY published_at DESC) AS rn
+                &format!(
+                    "SELECT {ARTICLE_COLUMNS}
                      FROM articles
-                     WHERE category != 'podcast'
-                 )
-                 WHERE rn <= ?1",
+                     WHERE id IN (
+                         SELECT article_id FROM (
+                             SELECT c.article_id,
+                                    ROW_NUMBER() OVER (PARTITION BY c.category ORDER BY a.published_at DESC) AS rn
+                             FROM article_categories c JOIN articles a ON a.id = c.article_id
+                             WHERE c.category != 'podcast' AND a.category != 'podcast'
+                         )
+                         WHERE rn <= ?1
+                     )
+                     ORDER BY published_at DESC"
+                ),
             )
             .map_err(|e| e.to_string())?;
         let articles = stmt
@@ -965,7 +1600,7 @@ impl Db {
     // --- AI Cache ---
 
     pub fn get_cache(&self, cache_key: &str) -> Result<Option<String>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let now = chrono::Utc::now().to_rfc3339();
         let mut stmt = conn
             .prepare(
@@ -985,7 +1620,7 @@ impl Db {
         response_json: &str,
         ttl_secs: i64,
     ) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         let now = chrono::Utc::now();
         let expires = now + chrono::Duration::seconds(ttl_secs);
         conn.execute(
@@ -1004,7 +1639,7 @@ impl Db {
     }
 
     pub fn cleanup_expired_cache(&self) -> Result<usize, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         let now = chrono::Utc::now().to_rfc3339();
         let deleted = conn
             .execute("DELETE FROM ai_cache WHERE expires_at < ?1", params![now])
@@ -1023,7 +1658,7 @@ impl Db {
         picture_url: Option<&str>,
         device_id: Option<&str>,
     ) -> Result<(String, String, bool), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         let now = chrono::Utc::now().to_rfc3339();
 
         // Check if user already exists
@@ -1060,11 +1695,12 @@ impl Db {
     }
 
     /// Get a user by their auth token. Returns (user_id, email, name, picture_url, device_id, konami_claimed).
+    #[allow(clippy::type_complexity)]
     pub fn get_user_by_auth_token(
         &self,
         auth_token: &str,
     ) -> Result<Option<(String, String, String, Option<String>, Option<String>, bool)>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let result = conn
             .query_row(
                 "SELECT id, email, name, picture_url, device_id, konami_claimed FROM users WHERE auth_token = ?1",
@@ -1086,7 +1722,7 @@ impl Db {
 
     /// Claim the konami code bonus for a user. Returns true if successfully claimed, false if already used.
     pub fn claim_konami(&self, user_id: &str) -> Result<bool, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         let now = chrono::Utc::now().to_rfc3339();
         let affected = conn
             .execute(
@@ -1104,7 +1740,7 @@ impl Db {
 
     /// Increment view count for an article and update popularity score.
     pub fn increment_view_count(&self, article_id: &str) -> Result<i64, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         conn.execute(
             "UPDATE articles SET view_count = view_count + 1 WHERE id = ?1",
             params![article_id],
@@ -1130,7 +1766,7 @@ impl Db {
 
     /// Increment click count for an article and update popularity score.
     pub fn increment_click_count(&self, article_id: &str) -> Result<i64, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         conn.execute(
             "UPDATE articles SET click_count = click_count + 1 WHERE id = ?1",
             params![article_id],
@@ -1157,7 +1793,7 @@ impl Db {
     /// Get popular articles by percentile range (e.g., top 10-20%).
     /// Returns articles with popularity_score in the specified percentile range, ordered by score DESC.
     pub fn get_popular_articles(&self, min_percentile: f64, max_percentile: f64, limit: i64) -> Result<Vec<Article>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
 
         // Get total article count
         let total: i64 = conn
@@ -1174,12 +1810,13 @@ impl Db {
 
         let mut stmt = conn
             .prepare(
-                "SELECT id, category, title, url, description, image_url, source,
-                        published_at, fetched_at, group_id, group_count
-                 FROM articles
-                 WHERE popularity_score > 0
-                 ORDER BY popularity_score DESC, published_at DESC
-                 LIMIT ?1 OFFSET ?2",
+                &format!(
+                    "SELECT {ARTICLE_COLUMNS}
+                     FROM articles
+                     WHERE popularity_score > 0
+                     ORDER BY popularity_score DESC, published_at DESC
+                     LIMIT ?1 OFFSET ?2"
+                ),
             )
             .map_err(|e| e.to_string())?;
 
@@ -1193,7 +1830,7 @@ impl Db {
 
     /// Update enrichment status for an article.
     pub fn update_enrichment_status(&self, article_id: &str, status: &str) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         let now = chrono::Utc::now().to_rfc3339();
         conn.execute(
             "UPDATE articles SET enrichment_status = ?1, enriched_at = ?2 WHERE id = ?3",
@@ -1212,7 +1849,7 @@ impl Db {
         content_type: &str,
         data_json: &str,
     ) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         let now = chrono::Utc::now().to_rfc3339();
         conn.execute(
             "INSERT INTO enrichments (enrichment_id, article_id, agent_type, content_type, data_json, status, created_at)
@@ -1232,7 +1869,7 @@ impl Db {
         data_json: Option<&str>,
         error_message: Option<&str>,
     ) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
         let now = chrono::Utc::now().to_rfc3339();
 
         if let Some(data) = data_json {
@@ -1251,8 +1888,9 @@ impl Db {
     }
 
     /// Get all enrichments for an article.
+    #[allow(clippy::type_complexity)]
     pub fn get_enrichments(&self, article_id: &str) -> Result<Vec<(String, String, String, String, String)>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
                 "SELECT enrichment_id, agent_type, content_type, data_json, status
@@ -1279,9 +1917,10 @@ impl Db {
     }
 
     /// Degrade images for old unpopular articles (older than hours_old, below median popularity).
+    /// The URL is kept in `original_image_url` so the archived copy still has it.
     pub fn degrade_old_unpopular_images(&self, hours_old: i64) -> Result<usize, String> {
         let cutoff = (chrono::Utc::now() - chrono::Duration::hours(hours_old)).to_rfc3339();
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
 
         // Get median popularity score for old articles
         let median_score: f64 = conn
@@ -1299,7 +1938,7 @@ impl Db {
         let degraded = conn
             .execute(
                 "UPDATE articles
-                 SET image_url = NULL
+                 SET original_image_url = COALESCE(original_image_url, image_url), image_url = NULL
                  WHERE published_at < ?1
                  AND popularity_score < ?2
                  AND popularity_score > 0
@@ -1311,47 +1950,18 @@ impl Db {
         Ok(degraded)
     }
 
-    /// Delete bottom 80% of articles older than days_old (keep top 20% by popularity).
-    pub fn cleanup_old_articles_bottom_80(&self, days_old: i64) -> Result<usize, String> {
-        let cutoff = (chrono::Utc::now() - chrono::Duration::days(days_old)).to_rfc3339();
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
-
-        // Get 20th percentile popularity score for old articles
-        let percentile_20_score: f64 = conn
-            .query_row(
-                "SELECT popularity_score FROM articles
-                 WHERE published_at < ?1
-                 ORDER BY popularity_score DESC
-                 LIMIT 1 OFFSET (SELECT COUNT(*) * 20 / 100 FROM articles WHERE published_at < ?1)",
-                params![cutoff],
-                |row| row.get(0),
-            )
-            .unwrap_or(0.0);
-
-        // Delete bottom 80% (below 20th percentile)
-        let deleted = conn
-            .execute(
-                "DELETE FROM articles
-                 WHERE published_at < ?1
-                 AND popularity_score < ?2",
-                params![cutoff, percentile_20_score],
-            )
-            .map_err(|e| format!("Delete old articles: {}", e))?;
-
-        Ok(deleted)
-    }
-
     /// Get articles pending enrichment.
     pub fn get_pending_enrichment_articles(&self, limit: i64) -> Result<Vec<Article>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
-                "SELECT id, category, title, url, description, image_url, source,
-                        published_at, fetched_at, group_id, group_count
-                 FROM articles
-                 WHERE enrichment_status = 'pending'
-                 ORDER BY popularity_score DESC, published_at DESC
-                 LIMIT ?1",
+                &format!(
+                    "SELECT {ARTICLE_COLUMNS}
+                     FROM articles
+                     WHERE enrichment_status = 'pending'
+                     ORDER BY popularity_score DESC, published_at DESC
+                     LIMIT ?1"
+                ),
             )
             .map_err(|e| e.to_string())?;
 
@@ -1363,62 +1973,193 @@ impl Db {
         Ok(articles)
     }
 
-    /// Get fresh articles within specified time window (in minutes).
+    /// Get fresh articles within specified time window (in minutes), optionally only
+    /// those filed under `category` and readable in `lang`, as in [`Db::query_articles`].
     pub fn get_fresh_articles(
         &self,
         category: Option<&Category>,
+        lang: Option<&str>,
         minutes: i64,
         limit: i64,
     ) -> Result<Vec<Article>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let cutoff = (chrono::Utc::now() - chrono::Duration::minutes(minutes))
             .to_rfc3339();
 
-        let sql = if category.is_some() {
-            "SELECT id, category, title, url, description, image_url, source,
-                    published_at, fetched_at, group_id, group_count
-             FROM articles
-             WHERE category = ?1 AND published_at >= ?2
-             ORDER BY published_at DESC
-             LIMIT ?3"
-        } else {
-            "SELECT id, category, title, url, description, image_url, source,
-                    published_at, fetched_at, group_id, group_count
-             FROM articles
-             WHERE published_at >= ?1
-             ORDER BY published_at DESC
-             LIMIT ?2"
-        };
-
-        let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                &format!(
+                    "SELECT {ARTICLE_COLUMNS}
+                     FROM articles
+                     WHERE published_at >= :cutoff
+                       AND (:cat IS NULL
+                            OR id IN (SELECT article_id FROM article_categories WHERE category = :cat))
+                       AND (:lang IS NULL OR lang IS NULL OR lang = :lang
+                            OR id IN (SELECT article_id FROM article_translations WHERE lang = :lang))
+                     ORDER BY published_at DESC
+                     LIMIT :lim"
+                ),
+            )
+            .map_err(|e| e.to_string())?;
 
-        let articles = if let Some(cat) = category {
-            stmt.query_map(params![cat.as_str(), cutoff, limit], row_to_article)
-        } else {
-            stmt.query_map(params![cutoff, limit], row_to_article)
+        let mut articles: Vec<Article> = stmt
+            .query_map(
+                rusqlite::named_params! {
+                    ":cutoff": cutoff,
+                    ":cat": category.map(|c| c.as_str()),
+                    ":lang": lang,
+                    ":lim": limit,
+                },
+                row_to_article,
+            )
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        if let Some(lang) = lang {
+            apply_translations(&conn, &mut articles, lang)?;
         }
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
 
         Ok(articles)
     }
 
+    // --- Translations ---
+
+    pub fn get_translation(&self, article_id: &str, lang: &str) -> Result<Option<ArticleTranslation>, String> {
+        let conn = self.read()?;
+        conn.query_row(
+            &format!(
+                "SELECT {TRANSLATION_COLUMNS} FROM article_translations
+                 WHERE article_id = ?1 AND lang = ?2"
+            ),
+            params![article_id, lang],
+            row_to_translation,
+        )
+        .optional()
+        .map_err(|e| format!("Get translation: {e}"))
+    }
+
+    pub fn put_translation(&self, t: &ArticleTranslation) -> Result<(), String> {
+        let conn = self.write()?;
+        conn.execute(
+            &format!(
+                "INSERT OR REPLACE INTO article_translations ({TRANSLATION_COLUMNS})
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
+            ),
+            params![
+                t.article_id,
+                t.lang,
+                t.source_lang,
+                t.title,
+                t.description,
+                t.summary,
+                t.created_at.to_rfc3339(),
+            ],
+        )
+        .map_err(|e| format!("Put translation: {e}"))?;
+        conn.execute(
+            "DELETE FROM translation_failures WHERE article_id = ?1 AND lang = ?2",
+            params![t.article_id, t.lang],
+        )
+        .map_err(|e| format!("Clear translation failures: {e}"))?;
+        Ok(())
+    }
+
+    /// Record a failed background translation. The article is left out of
+    /// [`Db::articles_to_translate`] for `backoff` doubled on each further failure,
+    /// up to `max_backoff`. Returns how many times in a row it has failed.
+    pub fn record_translation_failure(
+        &self,
+        article_id: &str,
+        lang: &str,
+        error: &str,
+        backoff: chrono::Duration,
+        max_backoff: chrono::Duration,
+    ) -> Result<u32, String> {
+        let mut conn = self.write()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let failures: u32 = tx
+            .query_row(
+                "SELECT failures FROM translation_failures WHERE article_id = ?1 AND lang = ?2",
+                params![article_id, lang],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Translation failures: {e}"))?
+            .unwrap_or(0)
+            + 1;
+        let wait = doubled_backoff(failures, backoff, max_backoff);
+        tx.execute(
+            "INSERT OR REPLACE INTO translation_failures (article_id, lang, failures, last_error, retry_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![article_id, lang, failures, error, (Utc::now() + wait).to_rfc3339()],
+        )
+        .map_err(|e| format!("Record translation failure: {e}"))?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(failures)
+    }
+
+    /// Most popular articles published since `since` that aren't in `lang` and haven't
+    /// been translated into it, or were translated before they had an AI summary and
+    /// now have one. Articles whose last translation failed wait out their backoff.
+    pub fn articles_to_translate(
+        &self,
+        lang: &str,
+        since: &DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Article>, String> {
+        let conn = self.read()?;
+        let mut stmt = conn
+            .prepare(
+                &format!(
+                    "SELECT {ARTICLE_COLUMNS}
+                     FROM articles
+                     WHERE lang IS NOT NULL AND lang != ?1 AND published_at >= ?2
+                       AND NOT EXISTS (SELECT 1 FROM article_translations t
+                                       WHERE t.article_id = articles.id AND t.lang = ?1
+                                         AND (t.summary IS NOT NULL OR articles.ai_summary IS NULL))
+                       AND NOT EXISTS (SELECT 1 FROM translation_failures f
+                                       WHERE f.article_id = articles.id AND f.lang = ?1 AND f.retry_at > ?4)
+                     ORDER BY popularity_score DESC, published_at DESC
+                     LIMIT ?3"
+                ),
+            )
+            .map_err(|e| e.to_string())?;
+        let articles = stmt
+            .query_map(params![lang, since.to_rfc3339(), limit, Utc::now().to_rfc3339()], row_to_article)
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(articles)
+    }
+
+    pub fn get_ai_summary(&self, article_id: &str) -> Result<Option<String>, String> {
+        let conn = self.read()?;
+        conn.query_row(
+            "SELECT ai_summary FROM articles WHERE id = ?1",
+            params![article_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map(Option::flatten)
+        .map_err(|e| format!("Get AI summary: {e}"))
+    }
+
     // --- AI Analysis ---
 
     /// Get articles that need AI analysis (not yet analyzed)
     pub fn get_articles_for_analysis(&self, limit: i64) -> Result<Vec<Article>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
         let mut stmt = conn
             .prepare(
-                "SELECT id, category, title, url, description, image_url, source,
-                        published_at, fetched_at, group_id, group_count
-                 FROM articles
-                 WHERE analyzed_at IS NULL
-                   AND description IS NOT NULL
-                   AND length(description) > 10
-                 ORDER BY published_at DESC
-                 LIMIT ?1",
+                &format!(
+                    "SELECT {ARTICLE_COLUMNS}
+                     FROM articles
+                     WHERE analyzed_at IS NULL
+                       AND description IS NOT NULL
+                       AND length(description) > 10
+                     ORDER BY published_at DESC
+                     LIMIT ?1"
+                ),
             )
             .map_err(|e| e.to_string())?;
 
@@ -1441,7 +2182,7 @@ impl Db {
         importance: f32,
         category: &str,
     ) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.write()?;
 
         let keywords_json = serde_json::to_string(keywords)
             .map_err(|e| format!("Failed to serialize keywords: {}", e))?;
@@ -1469,12 +2210,77 @@ impl Db {
         )
         .map_err(|e| format!("Failed to update analysis: {}", e))?;
 
+        // Re-file the article now that the analyzer has an opinion on where it belongs
+        refile_article(&conn, article_id)?;
+
         Ok(())
     }
 
+    /// Analyzed articles whose AI category isn't their feed's category, newest first,
+    /// with how often each (feed category, AI category) pair occurs overall.
+    pub fn category_disagreements(
+        &self,
+        limit: i64,
+    ) -> Result<(Vec<CategoryDisagreementCount>, Vec<CategoryDisagreement>), String> {
+        let conn = self.read()?;
+        // "other" is the analyzer declining to pick a category, not disagreeing
+        let disagrees = "ai_category IS NOT NULL AND ai_category != 'other'
+                         AND lower(ai_category) != category";
+
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT category, lower(ai_category), COUNT(*) AS n FROM articles
+                 WHERE {disagrees}
+                 GROUP BY category, lower(ai_category)
+                 ORDER BY n DESC, category, lower(ai_category)"
+            ))
+            .map_err(|e| e.to_string())?;
+        let counts = stmt
+            .query_map([], |row| {
+                Ok(CategoryDisagreementCount {
+                    feed_category: row.get(0)?,
+                    ai_category: row.get(1)?,
+                    count: row.get(2)?,
+                })
+            })
+            .map_err(|e| format!("Disagreement counts: {e}"))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id, title, source, category, lower(ai_category), category_labels, analyzed_at
+                 FROM articles
+                 WHERE {disagrees}
+                 ORDER BY analyzed_at DESC, id DESC
+                 LIMIT ?1"
+            ))
+            .map_err(|e| e.to_string())?;
+        let articles = stmt
+            .query_map(params![limit], |row| {
+                Ok(CategoryDisagreement {
+                    article_id: row.get(0)?,
+                    title: row.get(1)?,
+                    source: row.get(2)?,
+                    feed_category: row.get(3)?,
+                    ai_category: row.get(4)?,
+                    categories: row
+                        .get::<_, Option<String>>(5)?
+                        .and_then(|json| serde_json::from_str(&json).ok())
+                        .unwrap_or_default(),
+                    analyzed_at: row.get(6)?,
+                })
+            })
+            .map_err(|e| format!("Disagreements: {e}"))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok((counts, articles))
+    }
+
     /// Get analysis statistics
     pub fn get_analysis_stats(&self) -> Result<(i64, i64), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let conn = self.read()?;
 
         let total: i64 = conn
             .query_row("SELECT COUNT(*) FROM articles", [], |row| row.get(0))
@@ -1492,6 +2298,33 @@ impl Db {
     }
 }
 
+/// Wait before retrying after the `failures`th failure in a row: `backoff`, doubled for
+/// each failure after the first, capped at `max_backoff`.
+fn doubled_backoff(failures: u32, backoff: chrono::Duration, max_backoff: chrono::Duration) -> chrono::Duration {
+    (1..failures)
+        .try_fold(backoff, |d, _| d.checked_add(&d))
+        .map_or(max_backoff, |d| d.min(max_backoff))
+}
+
+/// The `articles` columns [`row_to_article`] reads, in order.
+const ARTICLE_COLUMNS: &str = "id, category, title, url, description, image_url, source, \
+    published_at, fetched_at, group_id, group_count, page_metadata, \
+    audio_url, audio_mime_type, audio_duration_secs, audio_episode, category_labels, lang, \
+    updated_at, headline_changed";
+
+/// How many [`ARTICLE_COLUMNS`] there are: the index of the first column selected after them.
+const ARTICLE_COLUMN_COUNT: usize = {
+    let bytes = ARTICLE_COLUMNS.as_bytes();
+    let (mut count, mut i) = (1, 0);
+    while i < bytes.len() {
+        if bytes[i] == b',' {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+};
+
 fn row_to_article(row: &rusqlite::Row) -> rusqlite::Result<Article> {
     let cat_str: String = row.get(1)?;
     let category = Category::from_str(&cat_str).unwrap_or(Category::General);
@@ -1512,6 +2345,406 @@ fn row_to_article(row: &rusqlite::Row) -> rusqlite::Result<Article> {
         fetched_at,
         group_id: row.get(9)?,
         group_count: row.get(10)?,
+        metadata: row
+            .get::<_, Option<String>>(11)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        audio: match row.get::<_, Option<String>>(12)? {
+            Some(url) => Some(AudioEnclosure {
+                url,
+                mime_type: row.get(13)?,
+                duration_secs: row.get(14)?,
+                episode: row.get(15)?,
+            }),
+            None => None,
+        },
+        categories: row
+            .get::<_, Option<String>>(16)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        lang: row.get(17)?,
+        translated_from: None,
+        updated_at: row
+            .get::<_, Option<String>>(18)?
+            .and_then(|s| s.parse().ok()),
+        headline_changed: row.get::<_, Option<bool>>(19)?.unwrap_or(false),
+    })
+}
+
+/// Insert an article unless it is already stored. Returns false for known IDs
+/// (recording a revision if the publisher edited the article), for URLs previously
+/// merged into another article, and for re-published copies (same title fingerprint
+/// and source within [`dedup::FINGERPRINT_WINDOW_HOURS`], and URLs or descriptions
+/// that agree per [`dedup::copies_agree`]), which instead fill in the existing
+/// article's missing image or description.
+fn insert_article(conn: &Connection, article: &Article) -> Result<bool, String> {
+    if resolve_alias(conn, &article.id)?.is_some() || archive::is_archived(conn, &article.id)? {
+        return Ok(false);
+    }
+
+    let fingerprint = dedup::fingerprint(&article.title, &article.source);
+    let window = chrono::Duration::hours(dedup::FINGERPRINT_WINDOW_HOURS);
+    let existing: Option<String> = {
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, url, description FROM articles
+                 WHERE fingerprint = ?1 AND id != ?2 AND published_at BETWEEN ?3 AND ?4
+                 ORDER BY published_at ASC",
+            )
+            .map_err(|e| format!("Fingerprint lookup: {e}"))?;
+        let found = stmt
+            .query_map(
+                params![
+                    fingerprint,
+                    article.id,
+                    (article.published_at - window).to_rfc3339(),
+                    (article.published_at + window).to_rfc3339(),
+                ],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?)),
+            )
+            .map_err(|e| format!("Fingerprint lookup: {e}"))?
+            .filter_map(|r| r.ok())
+            .find(|(_, url, description)| {
+                dedup::copies_agree(url, description.as_deref(), &article.url, article.description.as_deref())
+            });
+        found.map(|(id, _, _)| id)
+    };
+    if let Some(keep_id) = existing {
+        conn.execute(
+            "UPDATE articles SET
+                image_url = COALESCE(image_url, ?1),
+                description = COALESCE(description, ?2)
+             WHERE id = ?3",
+            params![article.image_url, article.description, keep_id],
+        )
+        .map_err(|e| format!("Merge duplicate: {e}"))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO article_aliases (alias_id, article_id, created_at)
+             VALUES (?1, ?2, ?3)",
+            params![article.id, keep_id, Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| format!("Insert alias: {e}"))?;
+        return Ok(false);
+    }
+
+    let audio = article.audio.as_ref();
+    let lang = article
+        .lang
+        .clone()
+        .or_else(|| lang::article_language(&article.title, article.description.as_deref(), None));
+    let result = conn.execute(
+        "INSERT OR IGNORE INTO articles
+            (id, category, title, url, description, image_url, source, published_at, fetched_at,
+             fingerprint, audio_url, audio_mime_type, audio_duration_secs, audio_episode, lang)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            article.id,
+            article.category.as_str(),
+            article.title,
+            article.url,
+            article.description,
+            article.image_url,
+            article.source,
+            article.published_at.to_rfc3339(),
+            article.fetched_at.to_rfc3339(),
+            fingerprint,
+            audio.map(|a| &a.url),
+            audio.and_then(|a| a.mime_type.as_ref()),
+            audio.and_then(|a| a.duration_secs),
+            audio.and_then(|a| a.episode),
+            lang,
+        ],
+    );
+    match result {
+        Ok(0) => {
+            revise_article(conn, article)?;
+            Ok(false)
+        }
+        Ok(_) => {
+            let labels = if article.categories.is_empty() {
+                categorize::for_article(article, None)
+            } else {
+                article.categories.clone()
+            };
+            write_categories(conn, &article.id, &labels)?;
+            Ok(true)
+        }
+        Err(e) => Err(format!("Insert article: {e}")),
+    }
+}
+
+/// Store a fetched copy of an already stored article as its new version if the
+/// publisher changed its title, description or image, keeping the previous version in
+/// `article_revisions` (at most [`MAX_REVISIONS`] of them). A description or image the
+/// feed no longer carries is not a change. The language and categories are worked out
+/// again from the new text. Returns whether the article changed.
+fn revise_article(conn: &Connection, article: &Article) -> Result<bool, String> {
+    type Stored = (String, Option<String>, Option<String>, bool, String);
+    // A degraded image counts as the article's image, so the feed still carrying it
+    // is not a revision
+    let stored: Option<Stored> = conn
+        .query_row(
+            "SELECT title, description, COALESCE(original_image_url, image_url),
+                    image_url IS NULL AND original_image_url IS NOT NULL,
+                    COALESCE(updated_at, fetched_at)
+             FROM articles WHERE id = ?1",
+            params![article.id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Load article for revision: {e}"))?;
+    let Some((title, description, image_url, degraded, seen_at)) = stored else {
+        return Ok(false);
+    };
+
+    let revised = |new: Option<&str>, old: Option<&str>| -> Option<String> {
+        let new = new.map(str::trim).filter(|n| !n.is_empty())?;
+        (Some(new) != old.map(str::trim)).then(|| new.to_string())
+    };
+    let new_title = revised(Some(&article.title), Some(&title));
+    let new_description = revised(article.description.as_deref(), description.as_deref());
+    let new_image = revised(article.image_url.as_deref(), image_url.as_deref());
+    if new_title.is_none() && new_description.is_none() && new_image.is_none() {
+        return Ok(false);
+    }
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO article_revisions (article_id, title, description, image_url, seen_at, replaced_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![article.id, title, description, image_url, seen_at, now],
+    )
+    .map_err(|e| format!("Insert revision: {e}"))?;
+    conn.execute(
+        "DELETE FROM article_revisions WHERE article_id = ?1 AND id NOT IN
+            (SELECT id FROM article_revisions WHERE article_id = ?1 ORDER BY id DESC LIMIT ?2)",
+        params![article.id, MAX_REVISIONS],
+    )
+    .map_err(|e| format!("Trim revisions: {e}"))?;
+    let headline_changed = new_title.is_some();
+    let title = new_title.unwrap_or(title);
+    let description = new_description.clone().or(description);
+    let lang = article
+        .lang
+        .clone()
+        .or_else(|| lang::article_language(&title, description.as_deref(), None));
+    // A degraded article stays degraded; a new image replaces the one kept aside
+    let image = new_image.clone().or(image_url);
+    let (live_image, kept_image) = if degraded { (None, image) } else { (image, None) };
+    conn.execute(
+        "UPDATE articles SET
+            title = ?2,
+            description = ?3,
+            image_url = ?4,
+            original_image_url = COALESCE(?5, original_image_url),
+            fingerprint = ?6,
+            updated_at = ?7,
+            headline_changed = headline_changed OR ?8,
+            lang = COALESCE(?9, lang)
+         WHERE id = ?1",
+        params![
+            article.id,
+            title,
+            description,
+            live_image,
+            kept_image,
+            dedup::fingerprint(&title, &article.source),
+            now,
+            headline_changed,
+            lang,
+        ],
+    )
+    .map_err(|e| format!("Revise article: {e}"))?;
+    if article.categories.is_empty() {
+        refile_article(conn, &article.id)?;
+    } else {
+        write_categories(conn, &article.id, &article.categories)?;
+    }
+    if headline_changed || new_description.is_some() {
+        // Translations of the old text would now be wrong; they are redone on demand
+        conn.execute(
+            "DELETE FROM article_translations WHERE article_id = ?1",
+            params![article.id],
+        )
+        .map_err(|e| format!("Clear stale translations: {e}"))?;
+    }
+    info!(
+        article_id = %article.id,
+        headline_changed,
+        description_changed = new_description.is_some(),
+        image_changed = new_image.is_some(),
+        "Article revised"
+    );
+    Ok(true)
+}
+
+/// File a stored article under the categories its current text, page metadata and AI
+/// category call for.
+fn refile_article(conn: &Connection, article_id: &str) -> Result<(), String> {
+    let stored = conn
+        .query_row(
+            &format!(
+                "SELECT {ARTICLE_COLUMNS}, ai_category
+                 FROM articles WHERE id = ?1"
+            ),
+            params![article_id],
+            |row| Ok((row_to_article(row)?, row.get::<_, Option<String>>(ARTICLE_COLUMN_COUNT)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Load article to categorize: {e}"))?;
+    if let Some((article, ai)) = stored {
+        let ai = ai.as_deref().and_then(categorize::ai_category);
+        write_categories(conn, article_id, &categorize::for_article(&article, ai.as_ref()))?;
+    }
+    Ok(())
+}
+
+/// Replace the categories an article is filed under.
+fn write_categories(conn: &Connection, article_id: &str, labels: &[CategoryLabel]) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM article_categories WHERE article_id = ?1",
+        params![article_id],
+    )
+    .map_err(|e| format!("Clear categories: {e}"))?;
+    let mut stmt = conn
+        .prepare(
+            "INSERT INTO article_categories (article_id, category, confidence, sources)
+             VALUES (?1, ?2, ?3, ?4)",
+        )
+        .map_err(|e| e.to_string())?;
+    for label in labels {
+        let sources: Vec<&str> = label.sources.iter().map(|s| s.as_str()).collect();
+        stmt.execute(params![
+            article_id,
+            label.category.as_str(),
+            label.confidence,
+            sources.join(","),
+        ])
+        .map_err(|e| format!("Insert category: {e}"))?;
+    }
+    let json = serde_json::to_string(labels).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE articles SET category_labels = ?1 WHERE id = ?2",
+        params![json, article_id],
+    )
+    .map_err(|e| format!("Store categories: {e}"))?;
+    Ok(())
+}
+
+fn put_feed(conn: &Connection, feed: &DynamicFeed) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO feeds (feed_id, url, source, category, enabled, added_by, poll_interval_secs, quarantine_reason, content_policy, source_type, scrape_rules, filters)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            feed.feed_id,
+            feed.url,
+            feed.source,
+            feed.category,
+            feed.enabled as i32,
+            feed.added_by,
+            feed.poll_interval_secs,
+            feed.quarantine_reason,
+            feed.content_policy.as_str(),
+            feed.source_type.as_str(),
+            feed.scrape.as_ref().and_then(|r| serde_json::to_string(r).ok()),
+            feed.filters.as_ref().and_then(|f| serde_json::to_string(f).ok()),
+        ],
+    )
+    .map_err(|e| format!("Put feed: {e}"))?;
+    Ok(())
+}
+
+fn row_to_feed(row: &rusqlite::Row) -> rusqlite::Result<DynamicFeed> {
+    Ok(DynamicFeed {
+        feed_id: row.get(0)?,
+        url: row.get(1)?,
+        source: row.get(2)?,
+        category: row.get(3)?,
+        enabled: row.get::<_, i32>(4)? != 0,
+        added_by: row.get(5)?,
+        poll_interval_secs: row.get(6)?,
+        quarantine_reason: row.get(7)?,
+        content_policy: ContentPolicy::from_str(&row.get::<_, String>(8)?).unwrap_or_default(),
+        source_type: SourceType::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
+        scrape: row
+            .get::<_, Option<String>>(10)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        filters: row
+            .get::<_, Option<String>>(11)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+    })
+}
+
+/// Article ID a merged duplicate's ID now points to, if any.
+fn resolve_alias(conn: &Connection, id: &str) -> Result<Option<String>, String> {
+    match conn.query_row(
+        "SELECT article_id FROM article_aliases WHERE alias_id = ?1",
+        params![id],
+        |row| row.get(0),
+    ) {
+        Ok(target) => Ok(Some(target)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Alias lookup: {e}")),
+    }
+}
+
+const TRANSLATION_COLUMNS: &str =
+    "article_id, lang, source_lang, title, description, summary, created_at";
+
+fn row_to_translation(row: &rusqlite::Row) -> rusqlite::Result<ArticleTranslation> {
+    let created_at: String = row.get(6)?;
+    Ok(ArticleTranslation {
+        article_id: row.get(0)?,
+        lang: row.get(1)?,
+        source_lang: row.get(2)?,
+        title: row.get(3)?,
+        description: row.get(4)?,
+        summary: row.get(5)?,
+        created_at: created_at.parse().unwrap_or_default(),
+    })
+}
+
+/// Serve articles that aren't in `lang` in their stored translation into it, if any.
+fn apply_translations<'a>(
+    conn: &Connection,
+    articles: impl IntoIterator<Item = &'a mut Article>,
+    lang: &str,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {TRANSLATION_COLUMNS} FROM article_translations
+             WHERE article_id = ?1 AND lang = ?2"
+        ))
+        .map_err(|e| e.to_string())?;
+    for article in articles.into_iter().filter(|a| translation::needs_translation(a, lang)) {
+        let found = stmt
+            .query_row(params![article.id, lang], row_to_translation)
+            .optional()
+            .map_err(|e| format!("Load translation: {e}"))?;
+        if let Some(t) = found {
+            t.apply(article);
+        }
+    }
+    Ok(())
+}
+
+const WEBSUB_COLUMNS: &str =
+    "feed_id, hub, topic, secret, status, lease_expires_at, requested_at, last_push_at, last_error";
+
+fn row_to_websub(row: &rusqlite::Row) -> rusqlite::Result<HubSubscription> {
+    let status: String = row.get(4)?;
+    let lease_expires_at: Option<String> = row.get(5)?;
+    let requested_at: String = row.get(6)?;
+    let last_push_at: Option<String> = row.get(7)?;
+    Ok(HubSubscription {
+        feed_id: row.get(0)?,
+        hub: row.get(1)?,
+        topic: row.get(2)?,
+        secret: row.get(3)?,
+        status: SubscriptionStatus::from_str(&status).unwrap_or(SubscriptionStatus::Failed),
+        lease_expires_at: lease_expires_at.and_then(|s| s.parse().ok()),
+        requested_at: requested_at.parse().unwrap_or_else(|_| Utc::now()),
+        last_push_at: last_push_at.and_then(|s| s.parse().ok()),
+        last_error: row.get(8)?,
     })
 }
 
@@ -1524,6 +2757,30 @@ fn encode_cursor(article: &Article) -> String {
     base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json.to_string().as_bytes())
 }
 
+/// Search cursors pin the reference time so scores stay comparable across pages.
+fn encode_search_cursor(now: &str, hit: &SearchHit) -> String {
+    use base64::Engine;
+    let json = serde_json::json!({
+        "t": now,
+        // serde_json doesn't round-trip every f64, and the next page compares against it exactly
+        "s": hit.score.to_bits(),
+        "i": hit.article.id,
+    });
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json.to_string().as_bytes())
+}
+
+fn decode_search_cursor(cursor: &str) -> Option<(String, f64, String)> {
+    use base64::Engine;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()?;
+    let v: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    let t = v.get("t")?.as_str()?.to_string();
+    let s = f64::from_bits(v.get("s")?.as_u64()?);
+    let i = v.get("i")?.as_str()?.to_string();
+    Some((t, s, i))
+}
+
 fn decode_cursor(cursor: &str) -> Option<(String, String)> {
     use base64::Engine;
     let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
@@ -1534,3 +2791,972 @@ fn decode_cursor(cursor: &str) -> Option<(String, String)> {
     let i = v.get("i")?.as_str()?.to_string();
     Some((p, i))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use news_core::sources::ScrapeRules;
+    use news_core::filters::FeedFilters;
+
+    fn article(id: &str, category: Category, title: &str, description: &str, age_hours: i64) -> Article {
+        let published_at = Utc::now() - Duration::hours(age_hours);
+        Article {
+            id: id.into(),
+            category,
+            title: title.into(),
+            url: format!("https://example.com/{id}"),
+            description: Some(description.into()),
+            image_url: None,
+            source: "Example".into(),
+            published_at,
+            fetched_at: published_at,
+            group_id: None,
+            group_count: None,
+            metadata: None,
+            audio: None,
+            categories: Vec::new(),
+            lang: None,
+            translated_from: None,
+            updated_at: None,
+            headline_changed: false,
+        }
+    }
+
+    /// Insert one article; whether it was new.
+    fn insert_one(db: &Db, article: &Article) -> Result<bool, String> {
+        Ok(!db.insert_articles(std::slice::from_ref(article))?.is_empty())
+    }
+
+    fn seeded_db() -> Db {
+        let db = Db::open(":memory:").unwrap();
+        db.insert_articles(&[
+            article("a1", Category::Tech, "Rust 2024 edition released", "The Rust team ships a new edition", 1),
+            article("a2", Category::Tech, "Go generics one year later", "A retrospective; no Rust here", 2),
+            article("a3", Category::General, "政府が新しい経済対策を発表", "政府は経済対策を閣議決定した", 3),
+            article("a4", Category::Business, "Rust adoption in banking", "Banks evaluate Rust for safety", 200),
+        ])
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn search_ranks_by_relevance_and_recency() {
+        let db = seeded_db();
+        let (hits, _) = db.search_articles("rust", None, None, 10, None).unwrap();
+        let ids: Vec<_> = hits.iter().map(|h| h.article.id.as_str()).collect();
+        assert_eq!(ids.first(), Some(&"a1"));
+        assert!(ids.contains(&"a4"));
+        assert!(hits[0].snippet.as_deref().unwrap_or("").contains("<mark>"));
+
+        // Feed text comes back escaped around the marks
+        insert_one(&db, &article("x1", Category::Tech, "Exploit <script>alert(1)</script>", "", 1)).unwrap();
+        let (hits, _) = db.search_articles("exploit", None, None, 10, None).unwrap();
+        let snippet = hits[0].snippet.as_deref().unwrap();
+        assert!(snippet.starts_with("<mark>Exploit</mark> &lt;script&gt;alert(1)"));
+        assert!(!snippet.contains("<script"));
+
+        let (hits, _) = db.search_articles("rust -banking", None, None, 10, None).unwrap();
+        assert!(hits.iter().all(|h| h.article.id != "a4"));
+
+        let (hits, _) = db.search_articles("rust", Some(&Category::Business), None, 10, None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].article.id, "a4");
+    }
+
+    #[test]
+    fn search_handles_japanese_and_short_terms() {
+        let db = seeded_db();
+        let (hits, _) = db.search_articles("経済対策", None, None, 10, None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].article.id, "a3");
+
+        // Two-character terms are below the trigram length and use the LIKE fallback
+        let (hits, _) = db.search_articles("政府", None, None, 10, None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].snippet.as_deref().unwrap_or("").contains("<mark>政府</mark>"));
+
+        // The fallback keeps the query's OR and AND
+        let (hits, _) = db.search_articles("政府 OR Go", None, None, 10, None).unwrap();
+        let mut ids: Vec<_> = hits.iter().map(|h| h.article.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["a2", "a3"]);
+        let (hits, _) = db.search_articles("政府 Go", None, None, 10, None).unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn search_paginates_with_cursor() {
+        let db = seeded_db();
+        let (first, cursor) = db.search_articles("rust", None, None, 1, None).unwrap();
+        assert_eq!(first.len(), 1);
+        let cursor = cursor.expect("more results");
+        let (rest, _) = db.search_articles("rust", None, None, 10, Some(&cursor)).unwrap();
+        assert!(!rest.is_empty());
+        assert!(rest.iter().all(|h| h.article.id != first[0].article.id));
+    }
+
+    #[test]
+    fn search_cursor_pages_through_every_hit_once() {
+        let db = seeded_db();
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = db.search_articles("rust", None, None, 1, cursor.as_deref()).unwrap();
+            seen.extend(page.into_iter().map(|h| h.article.id));
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, vec!["a1", "a2", "a4"]);
+    }
+
+    #[test]
+    fn clustering_assigns_stable_story_ids() {
+        let db = Db::open(":memory:").unwrap();
+        let first = db
+            .insert_articles(&[
+                article("c1", Category::General, "東京都で新型コロナ100人確認", "", 3),
+                article("c2", Category::Sports, "サッカーW杯の結果速報", "", 3),
+            ])
+            .unwrap();
+        assert_eq!(db.cluster_articles(&first, 0.3).unwrap(), 0);
+
+        let second = db
+            .insert_articles(&[
+                article("c3", Category::General, "東京都で新型コロナ150人確認", "", 2),
+                article("c4", Category::General, "東京都で新型コロナ150人確認 都が発表", "", 1),
+            ])
+            .unwrap();
+        assert_eq!(db.cluster_articles(&second, 0.3).unwrap(), 2);
+
+        let story = db.get_story("c1").unwrap();
+        let ids: Vec<_> = story.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["c1", "c3", "c4"]);
+        assert!(story.iter().all(|a| a.group_count == Some(3)));
+        assert!(db.get_story("c2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn clustering_compares_descriptions_too() {
+        let db = Db::open(":memory:").unwrap();
+        let description = "日銀は金融政策決定会合で政策金利を0.25%に引き上げることを決めた";
+        let first = db
+            .insert_articles(&[article("s1", Category::Business, "日銀が追加利上げ", description, 2)])
+            .unwrap();
+        db.cluster_articles(&first, 0.3).unwrap();
+        let second = db
+            .insert_articles(&[article("s2", Category::Business, "政策金利0.25%に", description, 1)])
+            .unwrap();
+        assert_eq!(db.cluster_articles(&second, 0.3).unwrap(), 1);
+        assert_eq!(db.get_story("s1").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn republished_copy_merges_into_original() {
+        let db = Db::open(":memory:").unwrap();
+        let mut original = article("d1", Category::General, "首相が会見、経済対策を表明", "", 2);
+        original.url = "https://example.com/news/20240601-0042".into();
+        original.description = None;
+        assert!(insert_one(&db, &original).unwrap());
+
+        // Same article number under another path
+        let mut copy = article("d2", Category::General, "首相が会見 経済対策を表明 - Example", "詳細", 1);
+        copy.url = "https://example.com/pickup/20240601-0042.html".into();
+        copy.image_url = Some("https://example.com/d2.jpg".into());
+        assert!(!insert_one(&db, &copy).unwrap());
+
+        let kept = db.get_article_by_id("d2").unwrap().unwrap();
+        assert_eq!(kept.id, "d1");
+        assert_eq!(kept.description.as_deref(), Some("詳細"));
+        assert_eq!(kept.image_url.as_deref(), Some("https://example.com/d2.jpg"));
+
+        // Same headline outside the window is a new article
+        let later = article("d3", Category::General, "首相が会見、経済対策を表明", "", 2 + 48);
+        assert!(insert_one(&db, &later).unwrap());
+
+        // A recurring headline with different content is a different article
+        let morning = article("d4", Category::General, "Daily briefing", "Markets rally on rate hopes", 6);
+        let evening = article("d5", Category::General, "Daily briefing", "Oil slips as supply grows", 1);
+        assert!(insert_one(&db, &morning).unwrap());
+        assert!(insert_one(&db, &evening).unwrap());
+    }
+
+    #[test]
+    fn canonical_url_merges_duplicates() {
+        let db = Db::open(":memory:").unwrap();
+        db.insert_articles(&[
+            article("c1", Category::Tech, "Canonical story", "desc", 2),
+            article("c2", Category::Tech, "Canonical story (AMP)", "desc", 1),
+        ])
+        .unwrap();
+        db.increment_view_count("c2").unwrap();
+
+        assert_eq!(db.record_canonical("c1", "https://example.com/c1", "c1").unwrap(), None);
+        assert_eq!(
+            db.record_canonical("c2", "https://example.com/c1", "c1").unwrap(),
+            Some("c1".to_string())
+        );
+        let kept = db.get_article_by_id("c2").unwrap().unwrap();
+        assert_eq!(kept.id, "c1");
+        // The merged URL's ID is skipped if a feed lists it again
+        assert!(!insert_one(&db, &article("c2", Category::Tech, "Canonical story (AMP)", "desc", 1)).unwrap());
+
+        // A canonical nobody has stored yet becomes an alias of the article
+        insert_one(&db, &article("c3", Category::Tech, "Another story", "desc", 1)).unwrap();
+        assert_eq!(db.record_canonical("c3", "https://example.com/x", "x").unwrap(), None);
+        assert!(!insert_one(&db, &article("x", Category::Tech, "Another story elsewhere", "d", 1)).unwrap());
+    }
+
+    #[test]
+    fn page_metadata_fills_gaps() {
+        let db = Db::open(":memory:").unwrap();
+        let mut undated = article("m1", Category::General, "No date in feed", "", 0);
+        undated.description = None;
+        undated.fetched_at = undated.published_at;
+        insert_one(&db, &undated).unwrap();
+        let mut dated = article("m2", Category::General, "Dated in feed", "Feed summary", 5);
+        dated.fetched_at = Utc::now();
+        insert_one(&db, &dated).unwrap();
+
+        let published: DateTime<Utc> = (Utc::now() - Duration::hours(30)).to_rfc3339().parse().unwrap();
+        let meta = PageMetadata {
+            description: Some("From the page".into()),
+            image_url: Some("https://example.com/og.jpg".into()),
+            published_at: Some(published),
+            section: Some("社会".into()),
+            ..Default::default()
+        };
+        db.update_page_metadata("m1", &meta).unwrap();
+        db.update_page_metadata("m2", &meta).unwrap();
+
+        let m1 = db.get_article_by_id("m1").unwrap().unwrap();
+        assert_eq!(m1.description.as_deref(), Some("From the page"));
+        assert_eq!(m1.image_url.as_deref(), Some("https://example.com/og.jpg"));
+        assert_eq!(m1.published_at, published);
+        assert_eq!(m1.metadata.unwrap().section.as_deref(), Some("社会"));
+
+        let m2 = db.get_article_by_id("m2").unwrap().unwrap();
+        assert_eq!(m2.description.as_deref(), Some("Feed summary"));
+        assert_eq!(m2.published_at, dated.published_at);
+    }
+
+    #[test]
+    fn ai_category_files_article_under_a_second_category() {
+        let db = Db::open(":memory:").unwrap();
+        db.insert_articles(&[
+            article("n1", Category::General, "大谷が2本塁打", "ドジャースが勝利", 1),
+            article("n2", Category::General, "首相が会見", "", 2),
+        ])
+        .unwrap();
+        let sports = |db: &Db| -> Vec<String> {
+            let (listed, _) = db.query_articles(Some(&Category::Sports), None, 10, None).unwrap();
+            listed.into_iter().map(|a| a.id).collect()
+        };
+        assert!(sports(&db).is_empty());
+
+        db.update_article_analysis("n1", "要約", &[], "positive", 0.5, "sports").unwrap();
+        db.update_article_analysis("n2", "要約", &[], "neutral", 0.5, "other").unwrap();
+        assert_eq!(sports(&db), vec!["n1"]);
+        let fresh = db.get_fresh_articles(Some(&Category::Sports), None, 180, 10).unwrap();
+        assert_eq!(fresh.len(), 1);
+        // Still listed under the feed's category, which stays the primary one
+        let (general, _) = db.query_articles(Some(&Category::General), None, 10, None).unwrap();
+        assert_eq!(general.len(), 2);
+        let n1 = db.get_article_by_id("n1").unwrap().unwrap();
+        assert_eq!(n1.category, Category::General);
+        let labels: Vec<Category> = n1.categories.iter().map(|l| l.category.clone()).collect();
+        assert_eq!(labels, vec![Category::Sports, Category::General]);
+
+        let (pairs, articles) = db.category_disagreements(10).unwrap();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].feed_category.as_str(), pairs[0].ai_category.as_str()), ("general", "sports"));
+        assert_eq!(pairs[0].count, 1);
+        assert_eq!(articles.len(), 1);
+        assert_eq!(articles[0].article_id, "n1");
+
+        // A newer general story takes the General pick; n1 keeps its slot under Sports
+        insert_one(&db, &article("n3", Category::General, "国会が閉会", "", 0)).unwrap();
+        let top: Vec<String> = db.top_articles_per_category(1).unwrap().into_iter().map(|a| a.id).collect();
+        assert_eq!(top, vec!["n3", "n1"]);
+    }
+
+    #[test]
+    fn page_metadata_counts_towards_categories() {
+        let db = Db::open(":memory:").unwrap();
+        insert_one(&db, &article("p1", Category::General, "週末の結果", "", 1)).unwrap();
+        let meta = PageMetadata {
+            section: Some("スポーツ".into()),
+            keywords: vec!["野球".into(), "五輪".into()],
+            ..Default::default()
+        };
+        db.update_page_metadata("p1", &meta).unwrap();
+        let (sports, _) = db.query_articles(Some(&Category::Sports), None, 10, None).unwrap();
+        assert_eq!(sports.len(), 1);
+        assert_eq!(sports[0].category, Category::General);
+    }
+
+    #[test]
+    fn keyword_rules_file_articles_at_insert() {
+        let db = Db::open(":memory:").unwrap();
+        db.insert_articles(&[article("k1", Category::General, "日経平均が反落", "円高と金利上昇を嫌気", 1)])
+            .unwrap();
+        let (business, _) = db.query_articles(Some(&Category::Business), None, 10, None).unwrap();
+        assert_eq!(business.len(), 1);
+        let hits = db.search_articles("日経平均", Some(&Category::Business), None, 10, None).unwrap().0;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].article.categories[0].category, Category::General);
+    }
+
+    #[test]
+    fn language_filter_keeps_undetected_articles() {
+        let db = Db::open(":memory:").unwrap();
+        let mut unknown = article("u1", Category::Tech, "12345", "", 3);
+        unknown.description = None;
+        db.insert_articles(&[
+            article("j1", Category::Tech, "政府が新たな経済対策を発表", "補正予算を編成へ", 1),
+            article("e1", Category::Tech, "Government unveils new economic stimulus package", "", 2),
+            unknown,
+        ])
+        .unwrap();
+        let ids = |articles: Vec<Article>| -> Vec<String> { articles.into_iter().map(|a| a.id).collect() };
+
+        let (ja, _) = db.query_articles(None, Some("ja"), 10, None).unwrap();
+        assert_eq!(ids(ja), vec!["j1", "u1"]);
+        let (en, _) = db.query_articles(Some(&Category::Tech), Some("en"), 10, None).unwrap();
+        assert_eq!(ids(en), vec!["e1", "u1"]);
+        let fresh = db.get_fresh_articles(None, Some("en"), 180, 10).unwrap();
+        assert_eq!(ids(fresh), vec!["e1"]);
+        let (all, _) = db.query_articles(None, None, 10, None).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].lang.as_deref(), Some("ja"));
+
+        let hits = db.search_articles("economic", None, Some("ja"), 10, None).unwrap().0;
+        assert!(hits.is_empty());
+        let hits = db.search_articles("economic", None, Some("en"), 10, None).unwrap().0;
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn translations_are_served_in_the_requested_language() {
+        let db = Db::open(":memory:").unwrap();
+        db.insert_articles(&[
+            article("j1", Category::General, "政府が新たな経済対策を発表", "補正予算を編成へ", 1),
+            article("j2", Category::General, "首相が衆院解散を表明", "来月に総選挙", 2),
+        ])
+        .unwrap();
+        let since = Utc::now() - Duration::hours(24);
+        assert_eq!(db.articles_to_translate("en", &since, 10).unwrap().len(), 2);
+        assert!(db.articles_to_translate("ja", &since, 10).unwrap().is_empty());
+
+        db.put_translation(&ArticleTranslation {
+            article_id: "j1".into(),
+            lang: "en".into(),
+            source_lang: Some("ja".into()),
+            title: "Government unveils new economic package".into(),
+            description: Some("A supplementary budget will follow".into()),
+            summary: None,
+            created_at: Utc::now(),
+        })
+        .unwrap();
+        let remaining = db.articles_to_translate("en", &since, 10).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, "j2");
+
+        // English readers get the translated article; the untranslated one is filtered out
+        let (en, _) = db.query_articles(None, Some("en"), 10, None).unwrap();
+        assert_eq!(en.len(), 1);
+        assert_eq!(en[0].title, "Government unveils new economic package");
+        assert_eq!(en[0].lang.as_deref(), Some("en"));
+        assert_eq!(en[0].translated_from.as_deref(), Some("ja"));
+        let fresh = db.get_fresh_articles(None, Some("en"), 180, 10).unwrap();
+        assert_eq!(fresh[0].translated_from.as_deref(), Some("ja"));
+        let (hits, _) = db.search_articles("経済対策", None, Some("en"), 10, None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].article.title, "Government unveils new economic package");
+        assert!(db.search_articles("衆院解散", None, Some("en"), 10, None).unwrap().0.is_empty());
+
+        // Japanese readers still get the original
+        let (ja, _) = db.query_articles(None, Some("ja"), 10, None).unwrap();
+        assert_eq!(ja.len(), 2);
+        assert!(ja.iter().all(|a| a.translated_from.is_none()));
+
+        // Translated before analysis: translated again once there is a summary
+        db.update_article_analysis("j1", "政府は経済対策を発表した", &[], "neutral", 0.5, "general").unwrap();
+        let remaining = db.articles_to_translate("en", &since, 10).unwrap();
+        assert_eq!(remaining.len(), 2);
+
+        // Translations go with the live article
+        db.archive_old_articles(&Utc::now()).unwrap();
+        assert!(db.get_translation("j1", "en").unwrap().is_none());
+    }
+
+    #[test]
+    fn failed_translations_back_off() {
+        let db = Db::open(":memory:").unwrap();
+        db.insert_articles(&[article("j1", Category::General, "政府が新たな経済対策を発表", "補正予算を編成へ", 1)])
+            .unwrap();
+        let since = Utc::now() - Duration::hours(24);
+        let backoff = Duration::minutes(30);
+
+        assert_eq!(db.record_translation_failure("j1", "en", "timeout", backoff, Duration::hours(12)).unwrap(), 1);
+        assert!(db.articles_to_translate("en", &since, 10).unwrap().is_empty());
+        assert_eq!(db.record_translation_failure("j1", "en", "timeout", backoff, Duration::hours(12)).unwrap(), 2);
+
+        // Once the wait is over it is tried again, and a success clears the record
+        db.record_translation_failure("j1", "en", "timeout", Duration::zero(), Duration::zero())
+            .unwrap();
+        assert_eq!(db.articles_to_translate("en", &since, 10).unwrap().len(), 1);
+        db.put_translation(&ArticleTranslation {
+            article_id: "j1".into(),
+            lang: "en".into(),
+            source_lang: Some("ja".into()),
+            title: "Government unveils new economic package".into(),
+            description: None,
+            summary: None,
+            created_at: Utc::now(),
+        })
+        .unwrap();
+        assert_eq!(db.record_translation_failure("j1", "en", "timeout", backoff, Duration::hours(12)).unwrap(), 1);
+    }
+
+    #[test]
+    fn failed_page_fetches_back_off() {
+        let db = Db::open(":memory:").unwrap();
+        db.insert_articles(&[article("p1", Category::General, "首相が会見", "", 1)]).unwrap();
+        let since = Utc::now() - Duration::hours(24);
+        let backoff = Duration::minutes(30);
+        assert_eq!(db.articles_needing_page_metadata(10, &since).unwrap().len(), 1);
+
+        // A failed fetch stores no metadata, so the page is tried again after the wait
+        assert_eq!(db.record_page_fetch_failure("p1", backoff, Duration::hours(24)).unwrap(), 1);
+        assert!(db.articles_needing_page_metadata(10, &since).unwrap().is_empty());
+        assert_eq!(db.record_page_fetch_failure("p1", backoff, Duration::hours(24)).unwrap(), 2);
+        db.record_page_fetch_failure("p1", Duration::zero(), Duration::zero()).unwrap();
+        assert_eq!(db.articles_needing_page_metadata(10, &since).unwrap().len(), 1);
+        assert!(db.get_article_by_id("p1").unwrap().unwrap().metadata.is_none());
+
+        // Stored metadata clears the record
+        db.update_page_metadata("p1", &PageMetadata::default()).unwrap();
+        assert_eq!(db.record_page_fetch_failure("p1", backoff, Duration::hours(24)).unwrap(), 1);
+    }
+
+    #[test]
+    fn edited_articles_keep_their_revisions() {
+        let db = Db::open(":memory:").unwrap();
+        let original = article("r1", Category::General, "震度5弱の地震", "被害を確認中", 1);
+        assert_eq!(db.insert_articles(std::slice::from_ref(&original)).unwrap().len(), 1);
+
+        // Re-fetching the same version, or one that lost its description, changes nothing
+        db.insert_articles(std::slice::from_ref(&original)).unwrap();
+        let mut stripped = original.clone();
+        stripped.description = None;
+        db.insert_articles(&[stripped]).unwrap();
+        assert_eq!(db.get_article_revisions("r1").unwrap().unwrap().len(), 1);
+        assert!(db.get_article_by_id("r1").unwrap().unwrap().updated_at.is_none());
+
+        // An image is added, then the headline is corrected
+        let mut with_image = original.clone();
+        with_image.image_url = Some("https://example.com/quake.jpg".into());
+        db.insert_articles(&[with_image.clone()]).unwrap();
+        let stored = db.get_article_by_id("r1").unwrap().unwrap();
+        assert!(stored.updated_at.is_some());
+        assert!(!stored.headline_changed);
+
+        let mut corrected = with_image;
+        corrected.title = "震度6弱の地震 津波の心配なし".into();
+        assert!(db.insert_articles(&[corrected]).unwrap().is_empty());
+        let stored = db.get_article_by_id("r1").unwrap().unwrap();
+        assert_eq!(stored.title, "震度6弱の地震 津波の心配なし");
+        assert_eq!(stored.image_url.as_deref(), Some("https://example.com/quake.jpg"));
+        assert_eq!(stored.description.as_deref(), Some("被害を確認中"));
+        assert!(stored.headline_changed);
+
+        let revisions = db.get_article_revisions("r1").unwrap().unwrap();
+        let titles: Vec<&str> = revisions.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["震度5弱の地震", "震度5弱の地震", "震度6弱の地震 津波の心配なし"]);
+        assert!(revisions[0].image_url.is_none());
+        assert!(revisions[..2].iter().all(|r| r.replaced_at.is_some()));
+        assert!(revisions[2].replaced_at.is_none());
+
+        // Search sees the corrected headline
+        let hits = db.search_articles("震度6弱", None, None, 10, None).unwrap().0;
+        assert_eq!(hits.len(), 1);
+        assert!(db.get_article_revisions("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn degraded_images_are_not_revisions() {
+        let db = Db::open(":memory:").unwrap();
+        let articles: Vec<Article> = (1..=2)
+            .map(|i| Article {
+                image_url: Some(format!("https://example.com/d{i}.jpg")),
+                ..article(&format!("d{i}"), Category::General, &format!("記事{i}"), "", 3)
+            })
+            .collect();
+        db.insert_articles(&articles).unwrap();
+        db.increment_view_count("d1").unwrap();
+        for _ in 0..5 {
+            db.increment_view_count("d2").unwrap();
+        }
+        assert_eq!(db.degrade_old_unpopular_images(1).unwrap(), 1);
+
+        // The feed still carries the image
+        db.insert_articles(&articles[..1]).unwrap();
+        let stored = db.get_article_by_id("d1").unwrap().unwrap();
+        assert!(stored.image_url.is_none());
+        assert!(stored.updated_at.is_none());
+        assert_eq!(db.get_article_revisions("d1").unwrap().unwrap().len(), 1);
+
+        // A real edit is recorded, and the article stays degraded
+        let mut edited = articles[0].clone();
+        edited.title = "記事1 更新".into();
+        db.insert_articles(&[edited]).unwrap();
+        let stored = db.get_article_by_id("d1").unwrap().unwrap();
+        assert_eq!(stored.title, "記事1 更新");
+        assert!(stored.image_url.is_none());
+        assert_eq!(db.get_article_revisions("d1").unwrap().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn revisions_redo_language_and_categories_and_are_capped() {
+        let db = Db::open(":memory:").unwrap();
+        let original = article("r2", Category::General, "Press conference at noon", "Details to follow", 1);
+        db.insert_articles(std::slice::from_ref(&original)).unwrap();
+        assert_eq!(db.get_article_by_id("r2").unwrap().unwrap().lang.as_deref(), Some("en"));
+
+        let mut rewritten = original.clone();
+        rewritten.title = "サッカー日本代表が会見".into();
+        rewritten.description = Some("W杯予選に向けて監督が選手を発表した".into());
+        db.insert_articles(&[rewritten.clone()]).unwrap();
+        let stored = db.get_article_by_id("r2").unwrap().unwrap();
+        assert_eq!(stored.lang.as_deref(), Some("ja"));
+        let (sports, _) = db.query_articles(Some(&Category::Sports), None, 10, None).unwrap();
+        assert_eq!(sports.len(), 1);
+
+        for i in 0..MAX_REVISIONS + 5 {
+            rewritten.title = format!("サッカー日本代表が会見 第{i}報");
+            db.insert_articles(&[rewritten.clone()]).unwrap();
+        }
+        // The stored revisions plus the current version
+        let revisions = db.get_article_revisions("r2").unwrap().unwrap();
+        assert_eq!(revisions.len() as i64, MAX_REVISIONS + 1);
+        assert_eq!(revisions.last().unwrap().title, format!("サッカー日本代表が会見 第{}報", MAX_REVISIONS + 4));
+    }
+
+    #[test]
+    fn podcast_audio_round_trips() {
+        let db = Db::open(":memory:").unwrap();
+        let mut episode = article("p1", Category::Podcast, "Episode 7", "Show notes", 1);
+        episode.audio = Some(AudioEnclosure {
+            url: "https://cdn.example.com/7.mp3".into(),
+            mime_type: Some("audio/mpeg".into()),
+            duration_secs: Some(1800),
+            episode: Some(7),
+        });
+        db.insert_articles(&[episode.clone(), article("p2", Category::Podcast, "Trailer", "", 2)])
+            .unwrap();
+
+        let (listed, _) = db.query_articles(Some(&Category::Podcast), None, 10, None).unwrap();
+        assert_eq!(listed[0].audio, episode.audio);
+        assert_eq!(listed[1].audio, None);
+        let hits = db.search_articles("Episode", None, None, 10, None).unwrap().0;
+        assert_eq!(hits[0].article.audio, episode.audio);
+    }
+
+    #[test]
+    fn content_policy_follows_article_source() {
+        let db = seeded_db();
+        let url = "https://example.com/a1";
+        assert_eq!(db.content_policy_for_url(url).unwrap(), ContentPolicy::FullText);
+
+        db.put_feed(&DynamicFeed {
+            feed_id: "example".into(),
+            url: "https://example.com/rss".into(),
+            source: "Example".into(),
+            category: "tech".into(),
+            enabled: true,
+            added_by: None,
+            poll_interval_secs: None,
+            quarantine_reason: None,
+            content_policy: ContentPolicy::NoFetch,
+            source_type: SourceType::Scrape,
+            scrape: Some(ScrapeRules {
+                item: "li".into(),
+                link: "a".into(),
+                title: None,
+                date: Some("time".into()),
+                image: None,
+            }),
+            filters: Some(FeedFilters {
+                exclude_keywords: vec!["PR".into()],
+                min_description_len: Some(20),
+                ..Default::default()
+            }),
+        })
+        .unwrap();
+        assert_eq!(db.content_policy_for_url(url).unwrap(), ContentPolicy::NoFetch);
+        let stored = &db.get_all_feeds().unwrap()[0];
+        assert_eq!(stored.content_policy, ContentPolicy::NoFetch);
+        assert_eq!(stored.source_type, SourceType::Scrape);
+        assert_eq!(stored.scrape.as_ref().unwrap().date.as_deref(), Some("time"));
+        let filters = stored.filters.as_ref().unwrap();
+        assert_eq!(filters.exclude_keywords, vec!["PR"]);
+        assert_eq!(filters.min_description_len, Some(20));
+        assert_eq!(
+            db.content_policy_for_url("https://elsewhere.example/x").unwrap(),
+            ContentPolicy::NoFetch
+        );
+
+        // Another feed from the same source doesn't loosen the policy
+        db.put_feed(&DynamicFeed {
+            feed_id: "example-2".into(),
+            url: "https://example.com/rss2".into(),
+            content_policy: ContentPolicy::FullText,
+            source_type: SourceType::Rss,
+            scrape: None,
+            filters: None,
+            ..stored.clone()
+        })
+        .unwrap();
+        assert_eq!(db.content_policy_for_url(url).unwrap(), ContentPolicy::NoFetch);
+    }
+
+    #[tokio::test]
+    async fn admin_actions_run_through_the_storage_traits() {
+        use news_core::store::{self, CategoryStorage, FeedStorage};
+
+        let db = Db::open(":memory:").unwrap();
+        db.seed_default_categories().unwrap();
+        let quarantined = DynamicFeed {
+            feed_id: "f1".into(),
+            url: "https://example.com/rss".into(),
+            source: "Example".into(),
+            category: "tech".into(),
+            enabled: false,
+            added_by: None,
+            poll_interval_secs: None,
+            quarantine_reason: Some("5 consecutive failures".into()),
+            content_policy: ContentPolicy::FullText,
+            source_type: SourceType::Rss,
+            scrape: None,
+            filters: None,
+        };
+        db.put_feed(&quarantined).unwrap();
+
+        let actions = [
+            AdminAction::EnableFeed { feed_id: "f1".into() },
+            AdminAction::AddCategory { id: "lifestyle".into(), label_ja: "ライフスタイル".into() },
+            AdminAction::RenameCategory { id: "tech".into(), label_ja: "IT・テック".into() },
+            AdminAction::SetGroupingThreshold { threshold: 0.5 },
+        ];
+        for action in &actions {
+            store::apply_action(&db, action).await.unwrap();
+        }
+
+        let feed = FeedStorage::get_feed(&db, "f1").await.unwrap().unwrap();
+        assert!(feed.enabled && feed.quarantine_reason.is_none());
+        let categories = CategoryStorage::get_categories(&db).await.unwrap();
+        assert_eq!(categories.last().unwrap().id, "lifestyle");
+        assert!(categories.iter().any(|c| c.id == "tech" && c.label_ja == "IT・テック"));
+        assert_eq!(db.get_feature_flags().unwrap().grouping_threshold, 0.5);
+
+        let missing = AdminAction::RenameCategory { id: "nope".into(), label_ja: "x".into() };
+        assert!(matches!(store::apply_action(&db, &missing).await, Err(news_core::AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn opml_import_keeps_feed_settings() {
+        let db = Db::open(":memory:").unwrap();
+        let xml = format!(
+            r#"<opml version="2.0" xmlns:news="{}"><body>
+              <outline text="Tech">
+                <outline text="Blog" xmlUrl="https://blog.example/feed" news:contentPolicy="metadata_only" news:pollIntervalSecs="3600"/>
+                <outline text="Plain" xmlUrl="https://plain.example/rss"/>
+              </outline>
+            </body></opml>"#,
+            news_core::opml::OPML_NS
+        );
+        let imported = news_core::opml::parse_opml(&xml).unwrap();
+        let plan = news_core::opml::plan_import(imported, &[], &news_core::models::CategoryInfo::all(), None);
+        assert_eq!(db.write_import(&plan, "opml-import").unwrap(), 2);
+
+        let feeds = db.get_all_feeds().unwrap();
+        let blog = feeds.iter().find(|f| f.source == "Blog").unwrap();
+        assert_eq!(blog.content_policy, ContentPolicy::MetadataOnly);
+        assert_eq!(blog.poll_interval_secs, Some(3600));
+        let plain = feeds.iter().find(|f| f.source == "Plain").unwrap();
+        assert_eq!(plain.content_policy, ContentPolicy::default());
+        assert_eq!(plain.poll_interval_secs, None);
+    }
+
+    /// A database file in the temp directory, removed (with its WAL files) on drop.
+    struct TempDb {
+        db: Db,
+        path: std::path::PathBuf,
+    }
+
+    impl TempDb {
+        fn open(readers: usize) -> Self {
+            let path = std::env::temp_dir().join(format!("hypernews-test-{}.db", uuid::Uuid::new_v4()));
+            let db = Db::open_with_readers(path.to_str().unwrap(), readers).unwrap();
+            Self { db, path }
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{suffix}", self.path.display()));
+            }
+        }
+    }
+
+    fn batch(prefix: &str, n: usize) -> Vec<Article> {
+        (0..n)
+            .map(|i| {
+                article(
+                    &format!("{prefix}-{i}"),
+                    Category::General,
+                    &format!("Story {prefix} number {i} about markets"),
+                    "Longer description of the story for the search index",
+                    (i % 48) as i64,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_do_not_wait_for_the_writer() {
+        let temp = TempDb::open(2);
+        let db = &temp.db;
+        assert_eq!(db.insert_articles(&batch("a", 50)).unwrap().len(), 50);
+
+        // While the writer is held (e.g. mid fetch cycle), reads still go through
+        let writer = db.write().unwrap();
+        std::thread::scope(|scope| {
+            let reader = scope.spawn(|| {
+                let (articles, _) = db.query_articles(None, None, 10, None).unwrap();
+                let hits = db.search_articles("markets", None, None, 5, None).unwrap().0;
+                (articles.len(), hits.len())
+            });
+            let started = std::time::Instant::now();
+            while !reader.is_finished() {
+                assert!(started.elapsed() < std::time::Duration::from_secs(5), "read blocked on the writer");
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            assert_eq!(reader.join().unwrap(), (10, 5));
+        });
+        drop(writer);
+
+        // Reader connections are read-only and see later commits
+        assert!(db.read().unwrap().execute("DELETE FROM articles", []).is_err());
+        db.insert_articles(&batch("b", 5)).unwrap();
+        assert_eq!(db.query_articles(None, None, 100, None).unwrap().0.len(), 55);
+    }
+
+    #[test]
+    fn failing_articles_do_not_take_their_batch_with_them() {
+        let db = Db::open(":memory:").unwrap();
+        let mut articles = batch("t", 3);
+        articles.push(articles[0].clone());
+        assert_eq!(db.insert_articles(&articles).unwrap().len(), 3);
+
+        db.write()
+            .unwrap()
+            .execute_batch("CREATE TRIGGER reject BEFORE INSERT ON articles WHEN NEW.id = 'u-2' BEGIN SELECT RAISE(ABORT, 'rejected'); END;")
+            .unwrap();
+        let inserted = db.insert_articles(&batch("u", ARTICLE_CHUNK + 5)).unwrap();
+        assert_eq!(inserted.len(), ARTICLE_CHUNK + 4);
+        assert!(db.get_article_by_id("u-0").unwrap().is_some());
+        assert!(db.get_article_by_id("u-2").unwrap().is_none());
+        assert!(db.get_article_by_id(&format!("u-{}", ARTICLE_CHUNK + 4)).unwrap().is_some());
+    }
+
+    /// Small writes during a large ingest get the writer between its chunks instead of
+    /// after the whole batch (reads not waiting at all is covered by
+    /// `reads_do_not_wait_for_the_writer`). Depends on thread scheduling, so it only runs
+    /// on request.
+    #[test]
+    #[ignore = "load test"]
+    fn writes_interleave_with_ingest_chunks() {
+        let temp = TempDb::open(DEFAULT_READ_CONNECTIONS);
+        let db = &temp.db;
+        db.insert_articles(&batch("seed", 500)).unwrap();
+        let total = ARTICLE_CHUNK * 12;
+        let done = std::sync::atomic::AtomicBool::new(false);
+        // Ingested rows visible after each completed write
+        let seen = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                db.insert_articles(&batch("ingest", total)).unwrap();
+                done.store(true, Ordering::Relaxed);
+            });
+            scope.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    db.increment_view_count("seed-0").unwrap();
+                    let ingested: usize = db
+                        .read()
+                        .unwrap()
+                        .query_row("SELECT COUNT(*) FROM articles WHERE id LIKE 'ingest-%'", [], |row| row.get(0))
+                        .unwrap();
+                    seen.lock().unwrap().push(ingested);
+                    std::thread::sleep(std::time::Duration::from_millis(2));
+                }
+            });
+        });
+        let seen = seen.into_inner().unwrap();
+        assert!(
+            seen.iter().any(|&n| n > 0 && n < total),
+            "no write landed mid-ingest: {seen:?}"
+        );
+    }
+
+    #[test]
+    fn migrations_are_recorded_and_run_once() {
+        let temp = TempDb::open(0);
+        let path = temp.path.to_str().unwrap();
+        let latest = migrations::latest_version();
+        assert_eq!(migrations::current_version(&temp.db.write().unwrap()).unwrap(), latest);
+
+        let mut conn = Connection::open(path).unwrap();
+        assert!(migrations::migrate(&mut conn).unwrap().is_empty());
+        let recorded: u32 = conn
+            .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(recorded, latest);
+    }
+
+    #[test]
+    fn newer_databases_are_refused() {
+        let temp = TempDb::open(0);
+        let newer = migrations::latest_version() + 1;
+        temp.db
+            .write()
+            .unwrap()
+            .execute(
+                "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, 'future', ?2)",
+                params![newer, Utc::now().to_rfc3339()],
+            )
+            .unwrap();
+
+        let err = Db::open_with_readers(temp.path.to_str().unwrap(), 0).err().unwrap();
+        assert!(err.contains("newer than this binary"), "{err}");
+    }
+
+    #[test]
+    fn expired_articles_move_to_the_archive() {
+        let db = Db::open(":memory:").unwrap();
+        db.insert_articles(&[
+            article("x1", Category::Tech, "Rust compiler gets faster", "Incremental builds improve", 200),
+            article("x2", Category::Tech, "Rust edition guide updated", "New chapters", 200),
+            article("x3", Category::Tech, "Rust conference announced", "Tickets on sale", 1),
+        ])
+        .unwrap();
+        db.merge_article_into("x2", "x1").unwrap();
+        db.update_article_analysis("x1", "要約", &["rust".to_string()], "positive", 0.8, "tech").unwrap();
+
+        let archived = db.archive_old_articles(&(Utc::now() - Duration::days(7))).unwrap();
+        assert_eq!(archived, 1);
+        assert!(db.get_article_by_id("x1").unwrap().is_none());
+
+        // Reachable by ID and by merged duplicate ID, with its analysis
+        let x1 = db.get_archived_article("x1").unwrap().unwrap();
+        assert_eq!(x1.article.title, "Rust compiler gets faster");
+        assert_eq!(x1.ai_summary.as_deref(), Some("要約"));
+        assert_eq!(x1.ai_keywords, vec!["rust"]);
+        assert_eq!(db.get_archived_article("x2").unwrap().unwrap().article.id, "x1");
+
+        // Feeds still carrying the article don't bring it back, under either ID
+        let again = [
+            article("x1", Category::Tech, "Rust compiler gets faster", "Incremental builds improve", 200),
+            article("x2", Category::Tech, "Rust edition guide updated", "New chapters", 200),
+        ];
+        assert!(db.insert_articles(&again).unwrap().is_empty());
+        assert!(db.get_article_by_id("x1").unwrap().is_none());
+
+        let (live, _) = db.search_articles("rust", None, None, 10, None).unwrap();
+        assert_eq!(live.iter().map(|h| h.article.id.as_str()).collect::<Vec<_>>(), ["x3"]);
+        let (hits, _) = db.search_archive("compiler", Some(&Category::Tech), None, 10, None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].snippet.as_deref().unwrap_or("").contains("<mark>"));
+        let (hits, _) = db.search_archive("compiler", Some(&Category::Business), None, 10, None).unwrap();
+        assert!(hits.is_empty());
+
+        assert_eq!(db.purge_archive(&(Utc::now() + Duration::seconds(1))).unwrap(), 1);
+        assert!(db.get_archived_article("x2").unwrap().is_none());
+        let (hits, _) = db.search_archive("compiler", None, None, 10, None).unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn archiving_again_keeps_the_earlier_analysis_and_counts() {
+        let db = Db::open(":memory:").unwrap();
+        let x = article("x1", Category::Tech, "Rust compiler gets faster", "Incremental builds improve", 200);
+        db.insert_articles(std::slice::from_ref(&x)).unwrap();
+        db.update_article_analysis("x1", "要約", &["rust".to_string()], "positive", 0.8, "tech").unwrap();
+        db.increment_view_count("x1").unwrap();
+        db.archive_old_articles(&(Utc::now() - Duration::days(7))).unwrap();
+
+        // A live copy that bypassed the archive check, as ingest before it did
+        db.write()
+            .unwrap()
+            .execute(
+                "INSERT INTO articles (id, category, title, url, source, published_at, fetched_at)
+                 VALUES (?1, 'tech', ?2, ?3, ?4, ?5, ?6)",
+                params![x.id, x.title, x.url, x.source, x.published_at.to_rfc3339(), x.fetched_at.to_rfc3339()],
+            )
+            .unwrap();
+        db.increment_view_count("x1").unwrap();
+        assert_eq!(db.archive_old_articles(&(Utc::now() - Duration::days(7))).unwrap(), 1);
+
+        let x1 = db.get_archived_article("x1").unwrap().unwrap();
+        assert_eq!(x1.ai_summary.as_deref(), Some("要約"));
+        assert_eq!(x1.view_count, 2);
+        let (hits, _) = db.search_archive("compiler", None, None, 10, None).unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn archiving_commits_in_batches() {
+        let db = Db::open(":memory:").unwrap();
+        let articles: Vec<Article> = (0..450)
+            .map(|i| article(&format!("old{i}"), Category::General, &format!("記事{i}"), "", 200))
+            .collect();
+        db.insert_articles(&articles).unwrap();
+
+        assert_eq!(db.archive_old_articles(&(Utc::now() - Duration::days(7))).unwrap(), 450);
+        assert!(db.get_archived_article("old449").unwrap().is_some());
+        assert!(db.get_article_by_id("old0").unwrap().is_none());
+    }
+
+    #[test]
+    fn archived_articles_keep_their_degraded_images() {
+        let db = Db::open(":memory:").unwrap();
+        let articles: Vec<Article> = (1..=3)
+            .map(|i| Article {
+                image_url: Some(format!("https://example.com/y{i}.jpg")),
+                ..article(&format!("y{i}"), Category::General, &format!("記事{i}"), "", 3)
+            })
+            .collect();
+        db.insert_articles(&articles).unwrap();
+        for (id, views) in [("y1", 1), ("y2", 2), ("y3", 10)] {
+            for _ in 0..views {
+                db.increment_view_count(id).unwrap();
+            }
+        }
+
+        assert_eq!(db.degrade_old_unpopular_images(1).unwrap(), 1);
+        assert!(db.get_article_by_id("y1").unwrap().unwrap().image_url.is_none());
+
+        // The most popular 20% stay live
+        assert_eq!(db.archive_unpopular_articles(0).unwrap(), 2);
+        assert!(db.get_article_by_id("y3").unwrap().is_some());
+        let y1 = db.get_archived_article("y1").unwrap().unwrap();
+        assert_eq!(y1.article.image_url.as_deref(), Some("https://example.com/y1.jpg"));
+        assert_eq!(y1.reason, archive::ArchiveReason::Unpopular);
+        assert_eq!(y1.view_count, 1);
+    }
+}
@@ -0,0 +1,345 @@
+//! Archive tier: articles past their retention window move out of `articles` into
+//! `archived_articles` instead of being deleted. They keep their page metadata and AI
+//! analysis, stay reachable by ID (including merged duplicate IDs) and can be searched
+//! with [`Db::search_archive`].
+
+use super::{resolve_alias, row_to_article, Db, ARTICLE_COLUMNS, ARTICLE_COLUMN_COUNT};
+use chrono::{DateTime, Duration, Utc};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use news_core::models::Article;
+use rusqlite::types::ToSql;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use tracing::{info, warn};
+
+/// Articles moved per write transaction, so archiving doesn't hold the writer for long.
+const ARCHIVE_BATCH: usize = 200;
+
+/// How long articles and their images stay in each tier, from the environment:
+///
+/// - `ARTICLE_RETENTION_DAYS` (default 7): every article is archived after this.
+/// - `UNPOPULAR_RETENTION_DAYS` (default 1): the least popular 80% are archived after this.
+/// - `IMAGE_RETENTION_HOURS` (default 1): images of below-median articles stop being served.
+/// - `ARCHIVE_RETENTION_DAYS` (default: forever): archived articles are deleted after this.
+///
+/// Values that aren't whole numbers are logged and the default is used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retention {
+    pub article_days: i64,
+    pub unpopular_days: i64,
+    pub image_hours: i64,
+    pub archive_days: Option<i64>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            article_days: 7,
+            unpopular_days: 1,
+            image_hours: 1,
+            archive_days: None,
+        }
+    }
+}
+
+impl Retention {
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            let value = std::env::var(name).ok()?;
+            match value.trim().parse::<i64>() {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    warn!(name, value = %value, error = %e, "Invalid retention setting, using the default");
+                    None
+                }
+            }
+        };
+        let defaults = Self::default();
+        Self {
+            article_days: var("ARTICLE_RETENTION_DAYS").unwrap_or(defaults.article_days),
+            unpopular_days: var("UNPOPULAR_RETENTION_DAYS").unwrap_or(defaults.unpopular_days),
+            image_hours: var("IMAGE_RETENTION_HOURS").unwrap_or(defaults.image_hours),
+            archive_days: var("ARCHIVE_RETENTION_DAYS").filter(|d| *d > 0),
+        }
+    }
+}
+
+/// Why an article left the live table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveReason {
+    /// Older than the article retention window.
+    Expired,
+    /// Older than a day and outside the most popular 20%.
+    Unpopular,
+}
+
+impl ArchiveReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Expired => "expired",
+            Self::Unpopular => "unpopular",
+        }
+    }
+}
+
+/// An archived article with what we knew about it when it was archived.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedArticle {
+    /// With its original image, even if the live copy's image had been degraded.
+    pub article: Article,
+    pub reason: ArchiveReason,
+    pub archived_at: DateTime<Utc>,
+    pub view_count: i64,
+    pub click_count: i64,
+    pub popularity_score: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ai_keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_sentiment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_importance: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_category: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analyzed_at: Option<String>,
+}
+
+impl Db {
+    /// Archive every article published before `before`. Returns how many moved.
+    pub fn archive_old_articles(&self, before: &DateTime<Utc>) -> Result<usize, String> {
+        self.archive_where("published_at < ?1", &[&before.to_rfc3339()], ArchiveReason::Expired)
+    }
+
+    /// Archive the bottom 80% by popularity of articles older than `days_old`, keeping
+    /// the top 20% live.
+    pub fn archive_unpopular_articles(&self, days_old: i64) -> Result<usize, String> {
+        let cutoff = (Utc::now() - Duration::days(days_old)).to_rfc3339();
+
+        // Get 20th percentile popularity score for old articles
+        let percentile_20_score: f64 = self
+            .read()?
+            .query_row(
+                "SELECT popularity_score FROM articles
+                 WHERE published_at < ?1
+                 ORDER BY popularity_score DESC
+                 LIMIT 1 OFFSET (SELECT COUNT(*) * 20 / 100 FROM articles WHERE published_at < ?1)",
+                params![cutoff],
+                |row| row.get(0),
+            )
+            .unwrap_or(0.0);
+
+        self.archive_where(
+            "published_at < ?1 AND popularity_score < ?2",
+            &[&cutoff, &percentile_20_score],
+            ArchiveReason::Unpopular,
+        )
+    }
+
+    /// An archived article by ID; IDs of merged duplicates resolve to the kept article.
+    pub fn get_archived_article(&self, id: &str) -> Result<Option<ArchivedArticle>, String> {
+        let conn = self.read()?;
+        let id = match resolve_alias(&conn, id)? {
+            Some(live) => live,
+            None => conn
+                .query_row(
+                    "SELECT article_id FROM archived_aliases WHERE alias_id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| format!("Archived alias: {e}"))?
+                .unwrap_or_else(|| id.to_string()),
+        };
+        let data: Option<Vec<u8>> = conn
+            .query_row("SELECT data FROM archived_articles WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()
+            .map_err(|e| format!("Archived article: {e}"))?;
+        data.map(|d| decode(&d)).transpose()
+    }
+
+    /// Delete archived articles archived before `before`, for deployments that don't
+    /// keep the archive forever.
+    pub fn purge_archive(&self, before: &DateTime<Utc>) -> Result<usize, String> {
+        let conn = self.write()?;
+        conn.execute(
+            "DELETE FROM archived_articles WHERE archived_at < ?1",
+            params![before.to_rfc3339()],
+        )
+        .map_err(|e| format!("Purge archive: {e}"))
+    }
+
+    /// Move the live articles matching `condition` into the archive, with their
+    /// aliases, committing every [`ARCHIVE_BATCH`] articles.
+    fn archive_where(&self, condition: &str, params: &[&dyn ToSql], reason: ArchiveReason) -> Result<usize, String> {
+        let mut archived = 0;
+        loop {
+            let mut conn = self.write()?;
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            let moved = archive_batch(&tx, condition, params, reason)?;
+            tx.commit().map_err(|e| format!("Commit archive: {e}"))?;
+            drop(conn);
+            archived += moved;
+            if moved < ARCHIVE_BATCH {
+                break;
+            }
+            std::thread::yield_now();
+        }
+        if archived > 0 {
+            info!(archived, reason = reason.as_str(), "Articles archived");
+        }
+        Ok(archived)
+    }
+}
+
+/// Whether `id` was archived, as an article or as one of its merged duplicates.
+/// Archived articles aren't ingested again.
+pub(super) fn is_archived(conn: &Connection, id: &str) -> Result<bool, String> {
+    conn.prepare_cached(
+        "SELECT EXISTS (SELECT 1 FROM archived_articles WHERE id = ?1)
+             OR EXISTS (SELECT 1 FROM archived_aliases WHERE alias_id = ?1)",
+    )
+    .and_then(|mut stmt| stmt.query_row(params![id], |row| row.get(0)))
+    .map_err(|e| format!("Archived lookup: {e}"))
+}
+
+/// Move up to [`ARCHIVE_BATCH`] live articles matching `condition` into the archive.
+/// An article that is already archived keeps its earlier analysis and counts.
+fn archive_batch(
+    conn: &Connection,
+    condition: &str,
+    params: &[&dyn ToSql],
+    reason: ArchiveReason,
+) -> Result<usize, String> {
+    let sql = format!(
+        "SELECT {ARTICLE_COLUMNS},
+                original_image_url, view_count, click_count, popularity_score,
+                ai_summary, ai_keywords, ai_sentiment, ai_importance, ai_category, analyzed_at
+         FROM articles WHERE {condition} LIMIT {ARCHIVE_BATCH}"
+    );
+    let archived_at = Utc::now();
+    let rows: Vec<ArchivedArticle> = {
+        let mut stmt = conn.prepare(&sql).map_err(|e| format!("Archive select: {e}"))?;
+        let rows = stmt
+            .query_map(params, |row| {
+                let mut article = row_to_article(row)?;
+                let n = ARTICLE_COLUMN_COUNT;
+                let original_image: Option<String> = row.get(n)?;
+                if article.image_url.is_none() {
+                    article.image_url = original_image;
+                }
+                let keywords: Option<String> = row.get(n + 5)?;
+                Ok(ArchivedArticle {
+                    article,
+                    reason,
+                    archived_at,
+                    view_count: row.get(n + 1)?,
+                    click_count: row.get(n + 2)?,
+                    popularity_score: row.get(n + 3)?,
+                    ai_summary: row.get(n + 4)?,
+                    ai_keywords: keywords.and_then(|k| serde_json::from_str(&k).ok()).unwrap_or_default(),
+                    ai_sentiment: row.get(n + 6)?,
+                    ai_importance: row.get(n + 7)?,
+                    ai_category: row.get(n + 8)?,
+                    analyzed_at: row.get(n + 9)?,
+                })
+            })
+            .map_err(|e| format!("Archive select: {e}"))?;
+        rows.collect::<rusqlite::Result<_>>()
+            .map_err(|e| format!("Archive select: {e}"))?
+    };
+
+    let moved = rows.len();
+    for mut archived in rows {
+        let previous: Option<Vec<u8>> = conn
+            .query_row("SELECT data FROM archived_articles WHERE id = ?1", params![archived.article.id], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|e| format!("Archive lookup: {e}"))?;
+        if let Some(previous) = previous {
+            merge_previous(&mut archived, decode(&previous)?);
+        }
+        let article = &archived.article;
+        let mut category_ids: Vec<&str> = vec![article.category.as_str()];
+        category_ids.extend(
+            article
+                .categories
+                .iter()
+                .map(|l| l.category.as_str())
+                .filter(|c| *c != article.category.as_str()),
+        );
+        let category_ids = serde_json::to_string(&category_ids).map_err(|e| e.to_string())?;
+
+        // Replacing a row doesn't fire the FTS delete trigger, so clear it first
+        conn.execute("DELETE FROM archived_articles WHERE id = ?1", params![article.id])
+            .map_err(|e| format!("Archive replace: {e}"))?;
+        conn.execute(
+            "INSERT INTO archived_articles
+                (id, category, category_ids, lang, source, title, description, published_at,
+                 archived_at, reason, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                article.id,
+                article.category.as_str(),
+                category_ids,
+                article.lang,
+                article.source,
+                article.title,
+                article.description,
+                article.published_at.to_rfc3339(),
+                archived_at.to_rfc3339(),
+                reason.as_str(),
+                encode(&archived)?,
+            ],
+        )
+        .map_err(|e| format!("Archive insert: {e}"))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO archived_aliases (alias_id, article_id)
+             SELECT alias_id, article_id FROM article_aliases WHERE article_id = ?1",
+            params![article.id],
+        )
+        .map_err(|e| format!("Archive aliases: {e}"))?;
+        conn.execute("DELETE FROM articles WHERE id = ?1", params![article.id])
+            .map_err(|e| format!("Archive delete: {e}"))?;
+    }
+    Ok(moved)
+}
+
+/// Carry over what an earlier archived copy of the same article knew.
+fn merge_previous(archived: &mut ArchivedArticle, previous: ArchivedArticle) {
+    archived.view_count += previous.view_count;
+    archived.click_count += previous.click_count;
+    archived.popularity_score = archived.popularity_score.max(previous.popularity_score);
+    if archived.analyzed_at.is_none() {
+        archived.ai_summary = previous.ai_summary;
+        archived.ai_keywords = previous.ai_keywords;
+        archived.ai_sentiment = previous.ai_sentiment;
+        archived.ai_importance = previous.ai_importance;
+        archived.ai_category = previous.ai_category;
+        archived.analyzed_at = previous.analyzed_at;
+    }
+    if archived.article.metadata.is_none() {
+        archived.article.metadata = previous.article.metadata;
+    }
+}
+
+fn encode(archived: &ArchivedArticle) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(archived).map_err(|e| e.to_string())?;
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&json).map_err(|e| format!("Compress archive: {e}"))?;
+    encoder.finish().map_err(|e| format!("Compress archive: {e}"))
+}
+
+pub(super) fn decode(data: &[u8]) -> Result<ArchivedArticle, String> {
+    let mut json = Vec::new();
+    ZlibDecoder::new(data)
+        .read_to_end(&mut json)
+        .map_err(|e| format!("Decompress archive: {e}"))?;
+    serde_json::from_slice(&json).map_err(|e| format!("Archived article: {e}"))
+}
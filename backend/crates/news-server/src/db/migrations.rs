@@ -0,0 +1,935 @@
+//! Versioned schema migrations for the SQLite store.
+//!
+//! Each migration runs in its own transaction and is recorded in `schema_version`, so
+//! a database is only ever moved forward one known step at a time. A database whose
+//! version is newer than the binary's is refused rather than written to. To change
+//! the schema, append a migration to [`MIGRATIONS`]; never edit one that has shipped.
+
+use super::archive::decode as decode_archived;
+use super::{row_to_article, write_categories};
+use chrono::Utc;
+use news_core::categorize;
+use news_core::dedup;
+use news_core::lang;
+use news_core::models::Article;
+use rusqlite::{params, Connection};
+use tracing::info;
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    up: fn(&Connection) -> Result<(), String>,
+}
+
+/// Every migration, in order. Versions start at 1 and have no gaps.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        up: baseline,
+    },
+    Migration {
+        version: 2,
+        name: "feed_fetch_state",
+        up: feed_fetch_state,
+    },
+    Migration {
+        version: 3,
+        name: "adaptive_polling",
+        up: adaptive_polling,
+    },
+    Migration {
+        version: 4,
+        name: "feed_health",
+        up: feed_health,
+    },
+    Migration {
+        version: 5,
+        name: "full_text_search",
+        up: full_text_search,
+    },
+    Migration {
+        version: 6,
+        name: "story_groups",
+        up: story_groups,
+    },
+    Migration {
+        version: 7,
+        name: "article_dedup",
+        up: article_dedup,
+    },
+    Migration {
+        version: 8,
+        name: "page_metadata",
+        up: page_metadata,
+    },
+    Migration {
+        version: 9,
+        name: "content_policy",
+        up: content_policy,
+    },
+    Migration {
+        version: 10,
+        name: "source_types",
+        up: source_types,
+    },
+    Migration {
+        version: 11,
+        name: "scrape_rules",
+        up: scrape_rules,
+    },
+    Migration {
+        version: 12,
+        name: "websub",
+        up: websub,
+    },
+    Migration {
+        version: 13,
+        name: "podcast_audio",
+        up: podcast_audio,
+    },
+    Migration {
+        version: 14,
+        name: "article_categories",
+        up: article_categories,
+    },
+    Migration {
+        version: 15,
+        name: "article_languages",
+        up: article_languages,
+    },
+    Migration {
+        version: 16,
+        name: "article_translations",
+        up: article_translations,
+    },
+    Migration {
+        version: 17,
+        name: "feed_filters",
+        up: feed_filters,
+    },
+    Migration {
+        version: 18,
+        name: "article_revisions",
+        up: article_revisions,
+    },
+    Migration {
+        version: 19,
+        name: "article_archive",
+        up: article_archive,
+    },
+    Migration {
+        version: 20,
+        name: "canonical_id_aliases",
+        up: canonical_id_aliases,
+    },
+    Migration {
+        version: 21,
+        name: "translation_failures",
+        up: translation_failures,
+    },
+    Migration {
+        version: 22,
+        name: "page_fetch_failures",
+        up: page_fetch_failures,
+    },
+];
+
+/// Schema version this binary migrates databases to.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// Version recorded in the database, 0 if it has never been migrated.
+pub fn current_version(conn: &Connection) -> Result<u32, String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        );",
+    )
+    .map_err(|e| format!("Schema version table: {e}"))?;
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))
+        .map_err(|e| format!("Schema version: {e}"))
+}
+
+/// Apply every migration newer than the database's version, each in its own
+/// transaction. Returns the versions applied. Fails without touching the database if
+/// it was migrated by a newer binary.
+pub fn migrate(conn: &mut Connection) -> Result<Vec<u32>, String> {
+    apply(conn, MIGRATIONS)
+}
+
+fn apply(conn: &mut Connection, migrations: &[Migration]) -> Result<Vec<u32>, String> {
+    let current = current_version(conn)?;
+    let latest = migrations.last().map_or(0, |m| m.version);
+    if current > latest {
+        return Err(format!(
+            "Database schema version {current} is newer than this binary supports ({latest}); refusing to open it"
+        ));
+    }
+    let mut applied = Vec::new();
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        info!(version = migration.version, name = migration.name, "Applying schema migration");
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        (migration.up)(&tx).map_err(|e| format!("Migration {} ({}): {e}", migration.version, migration.name))?;
+        tx.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.name, Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| format!("Record migration {}: {e}", migration.version))?;
+        tx.commit().map_err(|e| format!("Commit migration {}: {e}", migration.version))?;
+        applied.push(migration.version);
+    }
+    Ok(applied)
+}
+
+/// The schema as it stood before versioned migrations. Written to be idempotent, so it
+/// brings both new databases and any unversioned production database up to date.
+fn baseline(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS articles (
+            id TEXT PRIMARY KEY,
+            category TEXT NOT NULL,
+            title TEXT NOT NULL,
+            url TEXT NOT NULL,
+            description TEXT,
+            image_url TEXT,
+            source TEXT NOT NULL,
+            published_at TEXT NOT NULL,
+            fetched_at TEXT NOT NULL,
+            group_id TEXT,
+            group_count INTEGER,
+            view_count INTEGER NOT NULL DEFAULT 0,
+            click_count INTEGER NOT NULL DEFAULT 0,
+            enrichment_status TEXT,
+            enriched_at TEXT,
+            popularity_score REAL NOT NULL DEFAULT 0.0,
+            ai_summary TEXT,
+            ai_keywords TEXT,
+            ai_sentiment TEXT,
+            ai_importance REAL,
+            ai_category TEXT,
+            analyzed_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_articles_cat_pub
+            ON articles(category, published_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_articles_pub
+            ON articles(published_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_articles_popularity
+            ON articles(popularity_score DESC, published_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_articles_enrichment_status
+            ON articles(enrichment_status);
+
+        CREATE TABLE IF NOT EXISTS feeds (
+            feed_id TEXT PRIMARY KEY,
+            url TEXT NOT NULL,
+            source TEXT NOT NULL,
+            category TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            added_by TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS features (
+            feature TEXT PRIMARY KEY,
+            enabled INTEGER NOT NULL DEFAULT 0,
+            extra_json TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS changes (
+            change_id TEXT PRIMARY KEY,
+            status TEXT NOT NULL,
+            command_text TEXT NOT NULL,
+            interpretation TEXT NOT NULL DEFAULT '',
+            actions_json TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS categories (
+            id TEXT PRIMARY KEY,
+            label_ja TEXT NOT NULL,
+            label_en TEXT NOT NULL DEFAULT '',
+            sort_order INTEGER NOT NULL DEFAULT 0,
+            visible INTEGER NOT NULL DEFAULT 1
+        );
+
+        CREATE TABLE IF NOT EXISTS subscriptions (
+            api_token TEXT PRIMARY KEY,
+            stripe_customer_id TEXT NOT NULL,
+            stripe_subscription_id TEXT NOT NULL UNIQUE,
+            status TEXT NOT NULL DEFAULT 'active',
+            current_period_end TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_subs_stripe_sub_id
+            ON subscriptions(stripe_subscription_id);
+        CREATE INDEX IF NOT EXISTS idx_subs_stripe_cust_id
+            ON subscriptions(stripe_customer_id);
+
+        CREATE TABLE IF NOT EXISTS usage_limits (
+            device_id TEXT NOT NULL,
+            feature TEXT NOT NULL,
+            used_date TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (device_id, feature, used_date)
+        );
+
+        CREATE TABLE IF NOT EXISTS ai_cache (
+            cache_key TEXT PRIMARY KEY,
+            endpoint TEXT NOT NULL,
+            response_json TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            expires_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_ai_cache_expires
+            ON ai_cache(expires_at);
+
+        CREATE TABLE IF NOT EXISTS users (
+            id TEXT PRIMARY KEY,
+            email TEXT NOT NULL UNIQUE,
+            name TEXT NOT NULL DEFAULT '',
+            picture_url TEXT,
+            google_id TEXT NOT NULL UNIQUE,
+            auth_token TEXT NOT NULL UNIQUE,
+            device_id TEXT,
+            konami_claimed INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_users_auth_token ON users(auth_token);
+
+        CREATE TABLE IF NOT EXISTS enrichments (
+            enrichment_id TEXT PRIMARY KEY,
+            article_id TEXT NOT NULL,
+            agent_type TEXT NOT NULL,
+            content_type TEXT NOT NULL,
+            data_json TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            error_message TEXT,
+            created_at TEXT NOT NULL,
+            completed_at TEXT,
+            FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_enrichments_article
+            ON enrichments(article_id, status);",
+    )
+    .map_err(|e| format!("SQLite schema: {e}"))?;
+
+    // Databases from before AI analysis lack its columns
+    for (column, decl) in [
+        ("ai_summary", "TEXT"),
+        ("ai_keywords", "TEXT"),
+        ("ai_sentiment", "TEXT"),
+        ("ai_importance", "REAL"),
+        ("ai_category", "TEXT"),
+        ("analyzed_at", "TEXT"),
+    ] {
+        ensure_column(conn, "articles", column, decl)?;
+    }
+    Ok(())
+}
+
+/// Conditional request validators and fetch statistics, per feed.
+fn feed_fetch_state(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE feed_fetch_state (
+            feed_id TEXT PRIMARY KEY,
+            etag TEXT,
+            last_modified TEXT,
+            last_status INTEGER,
+            last_success_at TEXT,
+            consecutive_failures INTEGER NOT NULL DEFAULT 0,
+            avg_latency_ms REAL,
+            updated_at TEXT NOT NULL
+        );",
+    )
+    .map_err(|e| format!("Fetch state schema: {e}"))
+}
+
+/// Per-feed poll schedule: an admin override, the next poll time, and the intervals
+/// it is worked out from.
+fn adaptive_polling(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE feeds ADD COLUMN poll_interval_secs INTEGER;
+        ALTER TABLE feed_fetch_state ADD COLUMN next_poll_at TEXT;
+        ALTER TABLE feed_fetch_state ADD COLUMN publish_interval_secs INTEGER;
+        ALTER TABLE feed_fetch_state ADD COLUMN update_hint_secs INTEGER;",
+    )
+    .map_err(|e| format!("Polling schema: {e}"))
+}
+
+/// Feed health: the last error and its class, recent item counts, and why a feed
+/// was quarantined.
+fn feed_health(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE feeds ADD COLUMN quarantine_reason TEXT;
+        ALTER TABLE feed_fetch_state ADD COLUMN last_error_class TEXT;
+        ALTER TABLE feed_fetch_state ADD COLUMN last_error TEXT;
+        ALTER TABLE feed_fetch_state ADD COLUMN recent_item_counts TEXT;
+        ALTER TABLE feed_fetch_state ADD COLUMN latest_item_at TEXT;",
+    )
+    .map_err(|e| format!("Feed health schema: {e}"))
+}
+
+/// Full-text index over articles, kept in sync by triggers. The trigram tokenizer
+/// makes Japanese (no word boundaries) searchable.
+fn full_text_search(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE articles_fts USING fts5(
+            title, description,
+            content = 'articles', content_rowid = 'rowid',
+            tokenize = 'trigram'
+        );
+        CREATE TRIGGER articles_fts_insert AFTER INSERT ON articles BEGIN
+            INSERT INTO articles_fts(rowid, title, description)
+            VALUES (new.rowid, new.title, new.description);
+        END;
+        CREATE TRIGGER articles_fts_delete AFTER DELETE ON articles BEGIN
+            INSERT INTO articles_fts(articles_fts, rowid, title, description)
+            VALUES ('delete', old.rowid, old.title, old.description);
+        END;
+        CREATE TRIGGER articles_fts_update AFTER UPDATE OF title, description ON articles BEGIN
+            INSERT INTO articles_fts(articles_fts, rowid, title, description)
+            VALUES ('delete', old.rowid, old.title, old.description);
+            INSERT INTO articles_fts(rowid, title, description)
+            VALUES (new.rowid, new.title, new.description);
+        END;
+        INSERT INTO articles_fts(articles_fts) VALUES ('rebuild');",
+    )
+    .map_err(|e| format!("FTS schema: {e}"))
+}
+
+fn story_groups(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("CREATE INDEX idx_articles_group ON articles(group_id);")
+        .map_err(|e| format!("Story group index: {e}"))
+}
+
+/// Duplicate detection: URLs that were merged into another article, and the
+/// title+source fingerprint index used to spot re-published copies.
+fn article_dedup(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE articles ADD COLUMN fingerprint TEXT;
+        ALTER TABLE articles ADD COLUMN canonical_url TEXT;
+        CREATE INDEX idx_articles_fingerprint
+            ON articles(fingerprint, published_at);
+        CREATE TABLE article_aliases (
+            alias_id TEXT PRIMARY KEY,
+            article_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
+        );
+        CREATE INDEX idx_article_aliases_article
+            ON article_aliases(article_id);",
+    )
+    .map_err(|e| format!("Dedup schema: {e}"))?;
+    backfill_fingerprints(conn)
+}
+
+fn page_metadata(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("ALTER TABLE articles ADD COLUMN page_metadata TEXT;")
+        .map_err(|e| format!("Page metadata schema: {e}"))
+}
+
+fn content_policy(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("ALTER TABLE feeds ADD COLUMN content_policy TEXT NOT NULL DEFAULT 'full_text';")
+        .map_err(|e| format!("Content policy schema: {e}"))
+}
+
+fn source_types(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("ALTER TABLE feeds ADD COLUMN source_type TEXT NOT NULL DEFAULT 'rss';")
+        .map_err(|e| format!("Source type schema: {e}"))
+}
+
+fn scrape_rules(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("ALTER TABLE feeds ADD COLUMN scrape_rules TEXT;")
+        .map_err(|e| format!("Scrape rules schema: {e}"))
+}
+
+/// Hubs advertised by feeds, and our subscriptions to them.
+fn websub(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE feed_fetch_state ADD COLUMN websub_hub TEXT;
+        ALTER TABLE feed_fetch_state ADD COLUMN websub_topic TEXT;
+        CREATE TABLE websub_subscriptions (
+            feed_id TEXT PRIMARY KEY,
+            hub TEXT NOT NULL,
+            topic TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            status TEXT NOT NULL,
+            lease_expires_at TEXT,
+            requested_at TEXT NOT NULL,
+            last_push_at TEXT,
+            last_error TEXT
+        );",
+    )
+    .map_err(|e| format!("WebSub schema: {e}"))
+}
+
+fn podcast_audio(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE articles ADD COLUMN audio_url TEXT;
+        ALTER TABLE articles ADD COLUMN audio_mime_type TEXT;
+        ALTER TABLE articles ADD COLUMN audio_duration_secs INTEGER;
+        ALTER TABLE articles ADD COLUMN audio_episode INTEGER;",
+    )
+    .map_err(|e| format!("Audio schema: {e}"))
+}
+
+/// Every category an article is filed under, one row per category, with the same
+/// labels cached as JSON on the article for reads.
+fn article_categories(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE articles ADD COLUMN category_labels TEXT;
+        CREATE TABLE article_categories (
+            article_id TEXT NOT NULL,
+            category TEXT NOT NULL,
+            confidence REAL NOT NULL,
+            sources TEXT NOT NULL,
+            PRIMARY KEY (article_id, category),
+            FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
+        );
+        CREATE INDEX idx_article_categories_cat
+            ON article_categories(category, article_id);",
+    )
+    .map_err(|e| format!("Category schema: {e}"))?;
+    backfill_categories(conn)
+}
+
+/// Article language (ISO 639-1), detected at ingest and filtered on per site.
+fn article_languages(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE articles ADD COLUMN lang TEXT;
+        CREATE INDEX idx_articles_lang_pub ON articles(lang, published_at DESC);",
+    )
+    .map_err(|e| format!("Language schema: {e}"))?;
+    backfill_languages(conn)
+}
+
+/// Machine translations of title, description and AI summary, kept as long as the
+/// article is.
+fn article_translations(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE article_translations (
+            article_id TEXT NOT NULL,
+            lang TEXT NOT NULL,
+            source_lang TEXT,
+            title TEXT NOT NULL,
+            description TEXT,
+            summary TEXT,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (article_id, lang),
+            FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
+        );",
+    )
+    .map_err(|e| format!("Translation schema: {e}"))
+}
+
+fn feed_filters(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE feeds ADD COLUMN filters TEXT;
+        ALTER TABLE feed_fetch_state ADD COLUMN last_filtered_count INTEGER NOT NULL DEFAULT 0;",
+    )
+    .map_err(|e| format!("Filter schema: {e}"))
+}
+
+/// Earlier versions of articles whose publisher changed the title, description or
+/// image after we first stored them.
+fn article_revisions(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE articles ADD COLUMN updated_at TEXT;
+        ALTER TABLE articles ADD COLUMN headline_changed INTEGER NOT NULL DEFAULT 0;
+        CREATE TABLE article_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            article_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT,
+            image_url TEXT,
+            seen_at TEXT NOT NULL,
+            replaced_at TEXT NOT NULL,
+            FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
+        );
+        CREATE INDEX idx_article_revisions_article
+            ON article_revisions(article_id, id);",
+    )
+    .map_err(|e| format!("Revision schema: {e}"))
+}
+
+/// Archive tier for articles evicted from the live table. Searchable fields are plain
+/// columns; the full article with its AI analysis is a deflated JSON blob in `data`.
+/// Degraded images keep their URL in `original_image_url` for the archive.
+fn article_archive(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE articles ADD COLUMN original_image_url TEXT;
+
+        CREATE TABLE archived_articles (
+            id TEXT PRIMARY KEY,
+            category TEXT NOT NULL,
+            category_ids TEXT NOT NULL,
+            lang TEXT,
+            source TEXT NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT,
+            published_at TEXT NOT NULL,
+            archived_at TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            data BLOB NOT NULL
+        );
+        CREATE INDEX idx_archived_articles_pub ON archived_articles(published_at DESC);
+        CREATE INDEX idx_archived_articles_archived ON archived_articles(archived_at);
+
+        CREATE TABLE archived_aliases (
+            alias_id TEXT PRIMARY KEY,
+            article_id TEXT NOT NULL REFERENCES archived_articles(id) ON DELETE CASCADE
+        );
+        CREATE INDEX idx_archived_aliases_article ON archived_aliases(article_id);
+
+        CREATE VIRTUAL TABLE archived_articles_fts USING fts5(
+            title, description,
+            content = 'archived_articles', content_rowid = 'rowid',
+            tokenize = 'trigram'
+        );
+        CREATE TRIGGER archived_articles_fts_insert AFTER INSERT ON archived_articles BEGIN
+            INSERT INTO archived_articles_fts(rowid, title, description)
+            VALUES (new.rowid, new.title, new.description);
+        END;
+        CREATE TRIGGER archived_articles_fts_delete AFTER DELETE ON archived_articles BEGIN
+            INSERT INTO archived_articles_fts(archived_articles_fts, rowid, title, description)
+            VALUES ('delete', old.rowid, old.title, old.description);
+        END;",
+    )
+    .map_err(|e| format!("Archive schema: {e}"))
+}
+
+/// Articles stored before IDs came from canonical URLs keep their old ID, so existing
+/// links keep working; their canonical ID becomes an alias so the next fetch of the same
+/// URL is recognized instead of stored again. Per-domain `url_rules` aren't known here,
+/// only the built-in rules.
+fn canonical_id_aliases(conn: &Connection) -> Result<(), String> {
+    let live: Vec<(String, String)> = {
+        let mut stmt = conn.prepare("SELECT id, url FROM articles").map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+    let archived: Vec<(String, String)> = {
+        let mut stmt = conn
+            .prepare("SELECT id, data FROM archived_articles")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .filter_map(|(id, data)| Some((id, decode_archived(&data).ok()?.article.url)))
+            .collect();
+        rows
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let mut aliased = 0;
+    let mut live_alias = conn
+        .prepare(
+            "INSERT OR IGNORE INTO article_aliases (alias_id, article_id, created_at)
+             SELECT ?1, ?2, ?3 WHERE NOT EXISTS (SELECT 1 FROM articles WHERE id = ?1)",
+        )
+        .map_err(|e| e.to_string())?;
+    for (id, url) in &live {
+        let canonical = dedup::article_id_from_url(url);
+        if canonical != *id {
+            aliased += live_alias
+                .execute(params![canonical, id, now])
+                .map_err(|e| format!("Canonical ID alias: {e}"))?;
+        }
+    }
+    let mut archived_alias = conn
+        .prepare(
+            "INSERT OR IGNORE INTO archived_aliases (alias_id, article_id)
+             SELECT ?1, ?2 WHERE NOT EXISTS (SELECT 1 FROM archived_articles WHERE id = ?1)",
+        )
+        .map_err(|e| e.to_string())?;
+    for (id, url) in &archived {
+        let canonical = dedup::article_id_from_url(url);
+        if canonical != *id {
+            aliased += archived_alias
+                .execute(params![canonical, id])
+                .map_err(|e| format!("Canonical ID alias: {e}"))?;
+        }
+    }
+    info!(aliased, "Migration: canonical article IDs aliased");
+    Ok(())
+}
+
+/// Background translations that failed, so they are retried with backoff instead of
+/// holding the front of the queue.
+fn translation_failures(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE translation_failures (
+            article_id TEXT NOT NULL REFERENCES articles(id) ON DELETE CASCADE,
+            lang TEXT NOT NULL,
+            failures INTEGER NOT NULL,
+            last_error TEXT,
+            retry_at TEXT NOT NULL,
+            PRIMARY KEY (article_id, lang)
+        );",
+    )
+    .map_err(|e| format!("Translation failure schema: {e}"))
+}
+
+fn page_fetch_failures(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE page_fetch_failures (
+            article_id TEXT PRIMARY KEY REFERENCES articles(id) ON DELETE CASCADE,
+            failures INTEGER NOT NULL,
+            retry_at TEXT NOT NULL
+        );",
+    )
+    .map_err(|e| format!("Page fetch failure schema: {e}"))
+}
+
+/// Compute fingerprints for articles stored before they existed.
+fn backfill_fingerprints(conn: &Connection) -> Result<(), String> {
+    let missing: Vec<(String, String, String)> = {
+        let mut stmt = conn
+            .prepare("SELECT id, title, source FROM articles WHERE fingerprint IS NULL")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let mut stmt = conn
+        .prepare("UPDATE articles SET fingerprint = ?1 WHERE id = ?2")
+        .map_err(|e| e.to_string())?;
+    for (id, title, source) in &missing {
+        stmt.execute(params![dedup::fingerprint(title, source), id])
+            .map_err(|e| format!("Fingerprint backfill: {e}"))?;
+    }
+    info!(count = missing.len(), "Migration: article fingerprints backfilled");
+    Ok(())
+}
+
+/// Detect the language of articles stored before languages were.
+fn backfill_languages(conn: &Connection) -> Result<(), String> {
+    let rows: Vec<(String, String, Option<String>)> = {
+        let mut stmt = conn
+            .prepare("SELECT id, title, description FROM articles")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+    let mut stmt = conn
+        .prepare("UPDATE articles SET lang = ?1 WHERE id = ?2")
+        .map_err(|e| e.to_string())?;
+    for (id, title, description) in &rows {
+        if let Some(lang) = lang::article_language(title, description.as_deref(), None) {
+            stmt.execute(params![lang, id])
+                .map_err(|e| format!("Language backfill: {e}"))?;
+        }
+    }
+    info!(count = rows.len(), "Migration: article languages backfilled");
+    Ok(())
+}
+
+/// Assign categories to articles stored before category assignment existed, taking
+/// into account any AI category they were already given.
+fn backfill_categories(conn: &Connection) -> Result<(), String> {
+    let missing: Vec<(Article, Option<String>)> = {
+        let mut stmt = conn
+            .prepare(
+                // lang, updated_at and headline_changed come in later migrations
+                "SELECT id, category, title, url, description, image_url, source,
+                        published_at, fetched_at, group_id, group_count, page_metadata,
+                        audio_url, audio_mime_type, audio_duration_secs, audio_episode, category_labels, NULL,
+                        NULL, NULL, ai_category
+                 FROM articles WHERE category_labels IS NULL",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row_to_article(row)?, row.get(20)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+    if missing.is_empty() {
+        return Ok(());
+    }
+    for (article, ai) in &missing {
+        let ai = ai.as_deref().and_then(categorize::ai_category);
+        write_categories(conn, &article.id, &categorize::for_article(article, ai.as_ref()))
+            .map_err(|e| format!("Category backfill: {e}"))?;
+    }
+    info!(count = missing.len(), "Migration: article categories backfilled");
+    Ok(())
+}
+
+/// Whether `table` has a column named `column`.
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool, String> {
+    let exists: i64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM pragma_table_info('{table}') WHERE name = ?1"),
+            params![column],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Column check {table}.{column}: {e}"))?;
+    Ok(exists > 0)
+}
+
+/// Add a column declared as `decl` to `table` unless it already has one by that name,
+/// for the idempotent [`baseline`] only; later migrations add columns unconditionally.
+fn ensure_column(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<(), String> {
+    if !has_column(conn, table, column)? {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl};"))
+            .map_err(|e| format!("Migration {table}.{column}: {e}"))?;
+        info!(table, column, "Migration: column added");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database migrated up to and including `version`.
+    fn migrated_to(version: u32) -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let upto = MIGRATIONS.iter().position(|m| m.version == version).unwrap();
+        apply(&mut conn, &MIGRATIONS[..=upto]).unwrap();
+        conn
+    }
+
+    #[test]
+    fn articles_keep_their_pre_canonical_ids() {
+        let conn = migrated_to(MIGRATIONS.iter().find(|m| m.name == "article_archive").unwrap().version);
+        let url = "http://www.example.com/news/1?utm_source=rss";
+        let legacy_id = dedup::legacy_article_id(url);
+        conn.execute(
+            "INSERT INTO articles (id, category, title, url, source, published_at, fetched_at)
+             VALUES (?1, 'general', 'Story', ?2, 'Example', ?3, ?3)",
+            params![legacy_id, url, Utc::now().to_rfc3339()],
+        )
+        .unwrap();
+
+        canonical_id_aliases(&conn).unwrap();
+        let target: String = conn
+            .query_row(
+                "SELECT article_id FROM article_aliases WHERE alias_id = ?1",
+                params![dedup::article_id_from_url(url)],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(target, legacy_id);
+    }
+
+    #[test]
+    fn pre_series_databases_are_upgraded_with_their_data() {
+        // The schema the server created before versioned migrations, without the
+        // tables it never touched
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE articles (
+                id TEXT PRIMARY KEY,
+                category TEXT NOT NULL,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL,
+                description TEXT,
+                image_url TEXT,
+                source TEXT NOT NULL,
+                published_at TEXT NOT NULL,
+                fetched_at TEXT NOT NULL,
+                group_id TEXT,
+                group_count INTEGER,
+                view_count INTEGER NOT NULL DEFAULT 0,
+                click_count INTEGER NOT NULL DEFAULT 0,
+                enrichment_status TEXT,
+                enriched_at TEXT,
+                popularity_score REAL NOT NULL DEFAULT 0.0
+            );
+            CREATE TABLE feeds (
+                feed_id TEXT PRIMARY KEY,
+                url TEXT NOT NULL,
+                source TEXT NOT NULL,
+                category TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                added_by TEXT
+            );
+            INSERT INTO feeds (feed_id, url, source, category)
+                VALUES ('f1', 'https://example.com/rss', 'Example', 'general');",
+        )
+        .unwrap();
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO articles (id, category, title, url, description, source, published_at, fetched_at)
+             VALUES ('a1', 'general', '大谷が2本塁打', 'https://example.com/news/1', 'ドジャースが勝利', 'Example', ?1, ?1)",
+            params![now],
+        )
+        .unwrap();
+
+        let applied = migrate(&mut conn).unwrap();
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+        let (lang, fingerprint, labels): (Option<String>, Option<String>, Option<String>) = conn
+            .query_row(
+                "SELECT lang, fingerprint, category_labels FROM articles WHERE id = 'a1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(lang.as_deref(), Some("ja"));
+        assert_eq!(fingerprint, Some(dedup::fingerprint("大谷が2本塁打", "Example")));
+        assert!(labels.is_some_and(|l| l.contains("general")));
+        let found: String = conn
+            .query_row("SELECT title FROM articles_fts WHERE articles_fts MATCH '本塁打'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(found, "大谷が2本塁打");
+        let (policy, source_type): (String, String) = conn
+            .query_row("SELECT content_policy, source_type FROM feeds WHERE feed_id = 'f1'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((policy.as_str(), source_type.as_str()), ("full_text", "rss"));
+    }
+
+    #[test]
+    fn failing_migrations_roll_back() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let broken = [
+            Migration {
+                version: 1,
+                name: "baseline",
+                up: baseline,
+            },
+            Migration {
+                version: 2,
+                name: "broken",
+                up: |conn| {
+                    conn.execute_batch(
+                        "ALTER TABLE articles ADD COLUMN half_done TEXT;
+                        ALTER TABLE no_such_table ADD COLUMN x TEXT;",
+                    )
+                    .map_err(|e| e.to_string())
+                },
+            },
+        ];
+        let err = apply(&mut conn, &broken).unwrap_err();
+        assert!(err.contains("Migration 2 (broken)"), "{err}");
+
+        // The first migration stays applied; none of the second is left behind
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        assert!(!has_column(&conn, "articles", "half_done").unwrap());
+        assert_eq!(apply(&mut conn, &broken[..1]).unwrap(), Vec::<u32>::new());
+    }
+}
@@ -0,0 +1,140 @@
+//! The shared storage traits over [`Db`], so code written for every backend (like the
+//! admin chat's `apply_action`) runs against SQLite too. The SQLite calls are quick
+//! enough to make inline, as the route handlers already do.
+
+use super::Db;
+use async_trait::async_trait;
+use news_core::changes::{ChangeRequest, ChangeStatus};
+use news_core::config::{DynamicFeed, FeatureFlags};
+use news_core::error::{AppError, Result};
+use news_core::models::{Article, Category, PageMetadata};
+use news_core::opml::ImportPlan;
+use news_core::store::{
+    ArticleStorage, CategoryRecord, CategoryStorage, ChangeStorage, FeatureStorage, FeedStorage,
+};
+
+#[async_trait]
+impl ArticleStorage for Db {
+    async fn put_articles(&self, articles: &[Article]) -> Result<usize> {
+        Ok(self.insert_articles(articles).map_err(AppError::DbError)?.len())
+    }
+
+    async fn query_articles(
+        &self,
+        category: Option<&Category>,
+        lang: Option<&str>,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<Article>, Option<String>)> {
+        Db::query_articles(self, category, lang, limit as i64, cursor).map_err(AppError::DbError)
+    }
+
+    async fn update_page_metadata(&self, article: &Article, metadata: &PageMetadata) -> Result<()> {
+        Db::update_page_metadata(self, &article.id, metadata).map_err(AppError::DbError)
+    }
+}
+
+#[async_trait]
+impl FeedStorage for Db {
+    async fn get_all_feeds(&self) -> Result<Vec<DynamicFeed>> {
+        Db::get_all_feeds(self).map_err(AppError::DbError)
+    }
+
+    async fn get_enabled_feeds(&self) -> Result<Vec<DynamicFeed>> {
+        Db::get_enabled_feeds(self).map_err(AppError::DbError)
+    }
+
+    async fn put_feed(&self, feed: &DynamicFeed) -> Result<()> {
+        Db::put_feed(self, feed).map_err(AppError::DbError)
+    }
+
+    async fn delete_feed(&self, feed_id: &str) -> Result<()> {
+        Db::delete_feed(self, feed_id).map_err(AppError::DbError)
+    }
+
+    async fn import_feeds(&self, plan: &ImportPlan, added_by: &str) -> Result<usize> {
+        Db::write_import(self, plan, added_by).map_err(AppError::DbError)
+    }
+
+    /// Also resets the feed's failure count and schedule.
+    async fn release_quarantine(&self, feed_id: &str) -> Result<()> {
+        Db::release_quarantine(self, feed_id).map_err(AppError::DbError)
+    }
+}
+
+#[async_trait]
+impl FeatureStorage for Db {
+    async fn get_feature_flags(&self) -> Result<FeatureFlags> {
+        Db::get_feature_flags(self).map_err(AppError::DbError)
+    }
+
+    async fn set_feature_flag(
+        &self,
+        feature: &str,
+        enabled: bool,
+        extra: Option<&serde_json::Value>,
+    ) -> Result<()> {
+        let extra = extra.map(|v| v.to_string());
+        Db::set_feature_flag(self, feature, enabled, extra.as_deref()).map_err(AppError::DbError)
+    }
+}
+
+#[async_trait]
+impl ChangeStorage for Db {
+    async fn create_change(&self, change: &ChangeRequest) -> Result<()> {
+        Db::create_change(self, change).map_err(AppError::DbError)
+    }
+
+    async fn get_change(&self, change_id: &str) -> Result<Option<ChangeRequest>> {
+        Db::get_change(self, change_id).map_err(AppError::DbError)
+    }
+
+    async fn update_change_status(&self, change_id: &str, status: ChangeStatus) -> Result<()> {
+        Db::update_change_status(self, change_id, status).map_err(AppError::DbError)
+    }
+
+    async fn list_changes(&self, limit: usize) -> Result<Vec<ChangeRequest>> {
+        Db::list_changes(self, limit as i64).map_err(AppError::DbError)
+    }
+}
+
+#[async_trait]
+impl CategoryStorage for Db {
+    async fn get_categories(&self) -> Result<Vec<CategoryRecord>> {
+        let categories = Db::get_categories(self).map_err(AppError::DbError)?;
+        Ok(categories
+            .into_iter()
+            .map(|(id, label_ja, label_en, sort_order, visible)| CategoryRecord {
+                id,
+                label_ja,
+                label_en,
+                sort_order,
+                visible,
+            })
+            .collect())
+    }
+
+    async fn put_category(&self, category: &CategoryRecord) -> Result<()> {
+        Db::put_category(self, &category.id, &category.label_ja, &category.label_en, category.sort_order)
+            .map_err(AppError::DbError)
+    }
+
+    async fn rename_category(&self, id: &str, label_ja: &str) -> Result<()> {
+        let known = Db::get_categories(self)
+            .map_err(AppError::DbError)?
+            .iter()
+            .any(|c| c.0 == id);
+        if !known {
+            return Err(AppError::NotFound(format!("category {id}")));
+        }
+        Db::rename_category(self, id, label_ja).map_err(AppError::DbError)
+    }
+
+    async fn delete_category(&self, id: &str) -> Result<()> {
+        Db::delete_category(self, id).map_err(AppError::DbError)
+    }
+
+    async fn reorder_categories(&self, order: &[String]) -> Result<()> {
+        Db::reorder_categories(self, order).map_err(AppError::DbError)
+    }
+}
@@ -4,8 +4,10 @@ use tokio::time::{interval, Duration};
 use tracing::{info, warn};
 
 /// Degradation agent that runs periodically to:
-/// 1. Degrade images for old, unpopular articles (1 hour+ old with low popularity)
-/// 2. Delete 80% of articles older than 1 day (keeping top 20% by popularity)
+/// 1. Degrade images for old, unpopular articles (by default 1 hour+ old with low popularity)
+/// 2. Archive 80% of articles older than a day by default (keeping top 20% by popularity live)
+///
+/// Both windows come from [`crate::db::Retention`].
 pub async fn run(state: Arc<AppState>) {
     info!("Degradation agent starting");
 
@@ -25,8 +27,8 @@ pub async fn run(state: Arc<AppState>) {
 async fn run_cycle(state: &Arc<AppState>) -> Result<(), String> {
     info!("Starting degradation cycle");
 
-    // Step 1: Degrade images for old unpopular articles (older than 1 hour)
-    match state.db.degrade_old_unpopular_images(1) {
+    // Step 1: Degrade images for old unpopular articles
+    match state.db.degrade_old_unpopular_images(state.retention.image_hours) {
         Ok(degraded) => {
             if degraded > 0 {
                 info!(degraded, "Degraded images for old unpopular articles");
@@ -35,14 +37,15 @@ async fn run_cycle(state: &Arc<AppState>) -> Result<(), String> {
         Err(e) => warn!(error = %e, "Failed to degrade images"),
     }
 
-    // Step 2: Delete bottom 80% of articles older than 1 day
-    match state.db.cleanup_old_articles_bottom_80(1) {
-        Ok(deleted) => {
-            if deleted > 0 {
-                info!(deleted, "Deleted bottom 80% of articles older than 1 day");
+    // Step 2: Archive bottom 80% of old articles
+    let days = state.retention.unpopular_days;
+    match state.db.archive_unpopular_articles(days) {
+        Ok(archived) => {
+            if archived > 0 {
+                info!(archived, days, "Archived bottom 80% of old articles");
             }
         }
-        Err(e) => warn!(error = %e, "Failed to cleanup old articles"),
+        Err(e) => warn!(error = %e, "Failed to archive old articles"),
     }
 
     info!("Degradation cycle completed");
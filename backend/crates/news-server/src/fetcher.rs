@@ -1,8 +1,15 @@
-use crate::db::Db;
+use crate::db::{Db, Retention};
+use crate::websub;
 use chrono::{Duration, Utc};
-use news_core::feeds::{fetch_all_feeds, FeedConfig, FeedsConfig};
-use news_core::ogp;
+use news_core::config::DynamicFeed;
+use news_core::dedup::Canonicalizer;
+use news_core::feeds::{fetch_feeds_with_state, FeedConfig, FeedsConfig, FetchState};
+use news_core::models::Article;
+use news_core::politeness::PoliteClient;
+use news_core::{health, ogp, schedule};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 use tracing::{info, warn};
 
 const FEEDS_TOML: &str = include_str!("../../../feeds.toml");
@@ -13,18 +20,33 @@ fn fallback_feeds() -> Vec<FeedConfig> {
         .unwrap_or_default()
 }
 
+/// URL canonicalization with the per-domain `[[url_rules]]` from feeds.toml.
+pub fn canonicalizer() -> Canonicalizer {
+    Canonicalizer::new(
+        FeedsConfig::from_toml(FEEDS_TOML)
+            .map(|c| c.url_rules)
+            .unwrap_or_default(),
+    )
+}
+
+pub fn feed_config(f: DynamicFeed) -> FeedConfig {
+    FeedConfig {
+        feed_id: Some(f.feed_id),
+        url: f.url,
+        source: f.source,
+        category: f.category,
+        poll_interval_secs: f.poll_interval_secs,
+        source_type: f.source_type,
+        scrape: f.scrape,
+        filters: f.filters,
+    }
+}
+
 fn load_feeds(db: &Db) -> Vec<FeedConfig> {
     match db.get_enabled_feeds() {
         Ok(feeds) if !feeds.is_empty() => {
             info!(count = feeds.len(), "Loaded feeds from DB");
-            feeds
-                .into_iter()
-                .map(|f| FeedConfig {
-                    url: f.url,
-                    source: f.source,
-                    category: f.category,
-                })
-                .collect()
+            feeds.into_iter().map(feed_config).collect()
         }
         Ok(_) => {
             info!("DB feeds empty, using fallback feeds.toml");
@@ -37,23 +59,77 @@ fn load_feeds(db: &Db) -> Vec<FeedConfig> {
     }
 }
 
-pub async fn run(db: Arc<Db>, http_client: reqwest::Client) {
-    let mut fetch_interval = tokio::time::interval(std::time::Duration::from_secs(600));
+/// How often the scheduler checks which feeds are due.
+const SCHEDULER_TICK_SECS: u64 = 30;
+/// How often articles without an image are enriched via OGP.
+const OGP_INTERVAL_SECS: u64 = 600;
+/// Articles newer than this get their page checked for a canonical URL.
+const CANONICAL_CHECK_HOURS: i64 = 24;
+/// How long an article whose page couldn't be fetched waits before it is tried again;
+/// doubled on each further failure, up to `MAX_PAGE_RETRY_HOURS`.
+const PAGE_RETRY_MINUTES: i64 = 30;
+const MAX_PAGE_RETRY_HOURS: i64 = 24;
+/// How often WebSub subscriptions are created for new hubs and renewed before their lease ends.
+const WEBSUB_INTERVAL_SECS: u64 = 600;
+
+pub async fn run(
+    db: Arc<Db>,
+    http_client: reqwest::Client,
+    polite_client: PoliteClient,
+    base_url: String,
+    retention: Retention,
+) {
+    let mut schedule_interval =
+        tokio::time::interval(std::time::Duration::from_secs(SCHEDULER_TICK_SECS));
+    let mut ogp_interval = tokio::time::interval(std::time::Duration::from_secs(OGP_INTERVAL_SECS));
     let mut cleanup_interval = tokio::time::interval(std::time::Duration::from_secs(86400));
+    let mut websub_interval = tokio::time::interval(std::time::Duration::from_secs(WEBSUB_INTERVAL_SECS));
+
+    let canonicalizer = canonicalizer();
+    // The running fetch cycle, which hands back the fetch state of fallback feeds.toml
+    // entries (they have no feed_id to persist under) when it ends
+    let mut fetch_task: Option<JoinHandle<HashMap<String, FetchState>>> = None;
 
     // Tick once immediately for cleanup (first tick is instant)
     cleanup_interval.tick().await;
 
     loop {
         tokio::select! {
-            _ = fetch_interval.tick() => {
-                fetch_cycle(&db, &http_client).await;
+            _ = schedule_interval.tick() => {
+                // Run the cycle in its own task so a slow one doesn't hold up the other
+                // timers; ticks that land while it is still running are skipped
+                if fetch_task.as_ref().is_none_or(|task| task.is_finished()) {
+                    let mut fallback_states = match fetch_task.take() {
+                        Some(task) => task.await.unwrap_or_default(),
+                        None => HashMap::new(),
+                    };
+                    let (db, client, canonicalizer) = (db.clone(), polite_client.clone(), canonicalizer.clone());
+                    fetch_task = Some(tokio::spawn(async move {
+                        fetch_cycle(&db, &client, &canonicalizer, &mut fallback_states).await;
+                        fallback_states
+                    }));
+                }
+            }
+            _ = ogp_interval.tick() => {
+                enrich_images(&db, &polite_client, &canonicalizer).await;
+            }
+            _ = websub_interval.tick() => {
+                if db.get_feature_flags().unwrap_or_default().websub_enabled {
+                    websub::renew_subscriptions(&db, &http_client, &base_url).await;
+                }
             }
             _ = cleanup_interval.tick() => {
-                let cutoff = Utc::now() - Duration::days(7);
-                match db.delete_old_articles(&cutoff) {
-                    Ok(n) => info!(deleted = n, "Old articles cleaned up"),
-                    Err(e) => warn!(error = %e, "Failed to clean old articles"),
+                let cutoff = Utc::now() - Duration::days(retention.article_days);
+                match db.archive_old_articles(&cutoff) {
+                    Ok(n) => info!(archived = n, "Old articles archived"),
+                    Err(e) => warn!(error = %e, "Failed to archive old articles"),
+                }
+                if let Some(days) = retention.archive_days {
+                    match db.purge_archive(&(Utc::now() - Duration::days(days))) {
+                        Ok(n) if n > 0 => info!(deleted = n, "Expired archived articles deleted"),
+                        Err(e) => warn!(error = %e, "Failed to purge archive"),
+                        _ => {}
+                    }
                 }
                 match db.cleanup_old_usage(7) {
                     Ok(n) if n > 0 => info!(deleted = n, "Old usage records cleaned up"),
@@ -70,34 +146,151 @@ pub async fn run(db: Arc<Db>, http_client: reqwest::Client) {
     }
 }
 
-async fn fetch_cycle(db: &Db, http_client: &reqwest::Client) {
+/// Fetch every feed whose next poll time has passed, then reschedule it.
+async fn fetch_cycle(
+    db: &Db,
+    client: &PoliteClient,
+    canonicalizer: &Canonicalizer,
+    fallback_states: &mut HashMap<String, FetchState>,
+) {
+    let now = Utc::now();
     let feeds = load_feeds(db);
+    let mut states = db.get_fetch_states().unwrap_or_else(|e| {
+        warn!(error = %e, "Failed to load feed fetch state");
+        Default::default()
+    });
+
+    let mut due = Vec::new();
+    for feed in feeds {
+        let state = match &feed.feed_id {
+            Some(id) => states.remove(id),
+            None => fallback_states.remove(&feed.url),
+        }
+        .unwrap_or_default();
+        if schedule::is_due(&state, now) {
+            due.push((feed, state));
+        } else if feed.feed_id.is_none() {
+            fallback_states.insert(feed.url, state);
+        }
+    }
+    if due.is_empty() {
+        return;
+    }
+
+    let articles = fetch_feeds_with_state(client, &mut due).await;
+    let not_modified = due.iter().filter(|(_, s)| s.not_modified()).count();
+    info!(
+        due = due.len(),
+        total_articles = articles.len(),
+        not_modified,
+        "Fetched due feeds"
+    );
+
+    let flags = db.get_feature_flags().unwrap_or_default();
+    let now = Utc::now();
+    let mut updated = Vec::new();
+    for (feed, mut state) in due {
+        state.next_poll_at = Some(schedule::next_poll_at(&state, feed.poll_interval_secs, now));
+        match feed.feed_id {
+            Some(id) => {
+                if flags.auto_quarantine_enabled
+                    && health::should_quarantine(&state, flags.quarantine_after_failures)
+                {
+                    let reason = health::quarantine_reason(&state, now);
+                    warn!(feed_id = %id, source = %feed.source, reason = %reason, "Quarantining failing feed");
+                    if let Err(e) = db.quarantine_feed(&id, &reason) {
+                        warn!(error = %e, feed_id = %id, "Failed to quarantine feed");
+                    }
+                }
+                updated.push((id, state));
+            }
+            None => {
+                fallback_states.insert(feed.url, state);
+            }
+        }
+    }
+    if let Err(e) = db.put_fetch_states(&updated) {
+        warn!(error = %e, "Failed to store feed fetch state");
+    }
 
-    let feeds_config = FeedsConfig { feeds };
-    let articles = fetch_all_feeds(http_client, &feeds_config).await;
-    info!(total_articles = articles.len(), "Fetched all feeds");
+    if !articles.is_empty() {
+        store_articles(db, canonicalizer, articles, flags.grouping_threshold);
+    }
+}
 
+/// Canonicalize, insert and cluster new articles, whether polled or pushed by a hub.
+pub fn store_articles(db: &Db, canonicalizer: &Canonicalizer, mut articles: Vec<Article>, grouping_threshold: f64) -> usize {
+    for article in &mut articles {
+        canonicalizer.apply(article);
+    }
     match db.insert_articles(&articles) {
-        Ok(inserted) => info!(inserted, "Articles stored"),
-        Err(e) => warn!(error = %e, "Failed to store articles"),
+        Ok(inserted) => {
+            info!(inserted = inserted.len(), "Articles stored");
+            match db.cluster_articles(&inserted, grouping_threshold) {
+                Ok(n) if n > 0 => info!(clustered = n, "Articles attached to stories"),
+                Err(e) => warn!(error = %e, "Failed to cluster articles"),
+                _ => {}
+            }
+            inserted.len()
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to store articles");
+            0
+        }
     }
+}
 
-    // OGP enrichment — always run to ensure articles have images
-    let no_image = match db.articles_without_image(50) {
+/// OGP enrichment — always run to ensure articles have images. The same page fetch
+/// stores the page metadata and yields the canonical URL, which merges duplicates
+/// reached through other URLs. Sources whose content policy is `no_fetch` are skipped,
+/// and a page that can't be fetched (or that robots.txt disallows) is retried later.
+async fn enrich_images(db: &Db, client: &PoliteClient, canonicalizer: &Canonicalizer) {
+    let since = Utc::now() - Duration::hours(CANONICAL_CHECK_HOURS);
+    let pending = match db.articles_needing_page_metadata(50, &since) {
         Ok(a) => a,
         Err(_) => return,
     };
-    if !no_image.is_empty() {
-        let mut ogp_count = 0;
-        for article in &no_image {
-            if let Some(img_url) = ogp::fetch_og_image(http_client, &article.url).await {
-                if db.update_image_url(&article.id, &img_url).is_ok() {
-                    ogp_count += 1;
-                }
+    if pending.is_empty() {
+        return;
+    }
+
+    let no_fetch: HashSet<String> = db
+        .get_all_feeds()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|f| !f.content_policy.allows_page_fetch())
+        .map(|f| f.source)
+        .collect();
+
+    let mut ogp_count = 0;
+    let mut merged = 0;
+    for article in pending.iter().filter(|a| !no_fetch.contains(&a.source)) {
+        let Some(metadata) = ogp::fetch_page_metadata(client, &article.url).await else {
+            let failures = db.record_page_fetch_failure(
+                &article.id,
+                Duration::minutes(PAGE_RETRY_MINUTES),
+                Duration::hours(MAX_PAGE_RETRY_HOURS),
+            );
+            if let Err(e) = failures {
+                warn!(error = %e, article_id = %article.id, "Failed to record page fetch failure");
             }
+            continue;
+        };
+        if article.image_url.is_none() && metadata.image_url.is_some() {
+            ogp_count += 1;
         }
-        if ogp_count > 0 {
-            info!(ogp_enriched = ogp_count, total_checked = no_image.len(), "OGP enrichment complete");
+        if let Err(e) = db.update_page_metadata(&article.id, &metadata) {
+            warn!(error = %e, article_id = %article.id, "Failed to store page metadata");
         }
+        // Without a canonical link the article's own URL is canonical
+        let canonical = canonicalizer.canonical_url(metadata.canonical_url.as_deref().unwrap_or(&article.url));
+        match db.record_canonical(&article.id, &canonical, &canonicalizer.article_id(&canonical)) {
+            Ok(Some(_)) => merged += 1,
+            Ok(None) => {}
+            Err(e) => warn!(error = %e, article_id = %article.id, "Failed to record canonical URL"),
+        }
+    }
+    if ogp_count > 0 || merged > 0 {
+        info!(ogp_enriched = ogp_count, merged, total_checked = pending.len(), "OGP enrichment complete");
     }
 }
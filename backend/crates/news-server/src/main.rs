@@ -9,7 +9,9 @@ mod fetcher;
 mod mcp;
 mod routes;
 mod stripe;
+mod translation;
 mod tts_cache;
+mod websub;
 
 use axum::body::Body;
 use axum::extract::Request;
@@ -18,9 +20,10 @@ use axum::middleware::{self, Next};
 use axum::response::{IntoResponse, Redirect};
 use axum::routing::{delete, get, post, put};
 use axum::Router;
-use db::Db;
+use db::{Db, Retention};
 use news_core::config::DynamicFeed;
 use news_core::feeds::FeedsConfig;
+use news_core::politeness::PoliteClient;
 use routes::AppState;
 use std::sync::Arc;
 use tower::limit::ConcurrencyLimitLayer;
@@ -60,11 +63,27 @@ async fn main() {
     let admin_secret = std::env::var("ADMIN_SECRET").unwrap_or_default();
     let base_url = std::env::var("BASE_URL").unwrap_or_else(|_| "https://news.xyz".into());
     let google_client_id = std::env::var("GOOGLE_CLIENT_ID").unwrap_or_default();
+    let retention = Retention::from_env();
     let port: u16 = std::env::var("PORT")
         .ok()
         .and_then(|p| p.parse().ok())
         .unwrap_or(8080);
 
+    // `--migrate-only`: bring the schema up to date and exit, so a deploy can migrate
+    // once before new instances start serving.
+    if std::env::args().any(|a| a == "--migrate-only") {
+        match Db::open_with_readers(&db_path, 0) {
+            Ok(_) => {
+                info!(path = %db_path, version = db::migrations::latest_version(), "Schema is up to date");
+                return;
+            }
+            Err(e) => {
+                tracing::error!(path = %db_path, "Migration failed: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
     let db = Arc::new(Db::open(&db_path).expect("Failed to open SQLite database"));
 
     // Seed feeds from feeds.toml if DB is empty
@@ -78,6 +97,12 @@ async fn main() {
                     category: feed.category.clone(),
                     enabled: true,
                     added_by: Some("seed".into()),
+                    poll_interval_secs: feed.poll_interval_secs,
+                    quarantine_reason: None,
+                    content_policy: Default::default(),
+                    source_type: feed.source_type,
+                    scrape: feed.scrape.clone(),
+                    filters: feed.filters.clone(),
                 };
                 let _ = db.put_feed(&dynamic);
             }
@@ -103,11 +128,16 @@ async fn main() {
         .build()
         .expect("Failed to build RunPod HTTP client");
 
+    // Shared by the fetcher and the AI routes so per-host limits apply across both
+    let polite_client = PoliteClient::new(http_client.clone());
+
     // Spawn background fetcher
     let fetcher_db = Arc::clone(&db);
     let fetcher_client = http_client.clone();
+    let fetcher_polite = polite_client.clone();
+    let fetcher_base_url = base_url.clone();
     tokio::spawn(async move {
-        fetcher::run(fetcher_db, fetcher_client).await;
+        fetcher::run(fetcher_db, fetcher_client, fetcher_polite, fetcher_base_url, retention).await;
     });
 
     // NOTE: TTS pre-cache task is spawned after state construction (see below)
@@ -115,6 +145,7 @@ async fn main() {
     let state = Arc::new(AppState {
         db,
         http_client,
+        polite_client,
         api_key,
         elevenlabs_api_key,
         openai_api_key,
@@ -133,6 +164,7 @@ async fn main() {
         admin_secret,
         base_url,
         google_client_id,
+        retention,
     });
 
     // Spawn TTS pre-cache background task
@@ -147,6 +179,9 @@ async fn main() {
     // Spawn AI analyzer background task (ChatWeb.ai)
     tokio::spawn(analyzer::run(Arc::clone(&state)));
 
+    // Spawn background translation of popular articles between site languages
+    tokio::spawn(translation::run(Arc::clone(&state)));
+
     let index_path = std::path::PathBuf::from(&static_dir).join("index.html");
     let api_routes = Router::new()
         .route("/article/:id", get({
@@ -170,11 +205,14 @@ async fn main() {
         }))
         .route("/api/articles", get(routes::get_articles))
         .route("/api/articles/:id", get(routes::get_article_by_id))
+        .route("/api/articles/:id/revisions", get(routes::get_article_revisions))
+        .route("/api/articles/:id/translate", post(routes::handle_article_translate))
         .route("/api/articles/:id/view", post(routes::handle_article_view))
         .route("/api/articles/:id/click", post(routes::handle_article_click))
         .route("/api/articles/:id/enrichments", get(routes::handle_get_enrichments))
         .route("/api/categories", get(routes::get_categories))
         .route("/api/search", get(routes::handle_search))
+        .route("/api/stories/:group_id", get(routes::get_story))
         .route("/api/image-proxy", get(routes::handle_image_proxy))
         .route("/health", get(routes::health))
         .route("/api/articles/summarize", post(routes::handle_summarize))
@@ -191,9 +229,19 @@ async fn main() {
         .route("/api/feed", get(routes::get_feed))
         .route("/api/admin/feeds", get(routes::list_feeds))
         .route("/api/admin/feeds", post(routes::add_feed))
+        .route("/api/admin/feeds/health", get(routes::feeds_health))
+        .route("/api/admin/feeds.opml", get(routes::export_feeds_opml))
+        .route("/api/admin/feeds/import", post(routes::import_feeds))
+        .route("/api/admin/feeds/test", post(routes::test_feed))
+        .route("/api/admin/feeds/discover", post(routes::discover_feeds))
+        .route("/api/admin/websub", get(routes::list_websub_subscriptions))
         .route("/api/admin/feeds/:feed_id", delete(routes::delete_feed))
         .route("/api/admin/feeds/:feed_id", put(routes::update_feed))
         .route("/api/admin/categories", post(routes::handle_categories_manage))
+        .route(
+            "/api/admin/categories/disagreements",
+            get(routes::list_category_disagreements),
+        )
         .route("/api/admin/command", post(routes::handle_command))
         .route("/api/admin/features", post(routes::handle_toggle_feature))
         .route("/api/admin/changes", get(routes::list_changes))
@@ -208,6 +256,7 @@ async fn main() {
         // Subscription routes
         .route("/api/subscribe", post(routes::handle_subscribe))
         .route("/api/stripe/webhook", post(routes::handle_stripe_webhook))
+        .route("/api/websub/:feed_id", get(routes::websub_verify).post(routes::websub_receive))
         .route("/api/subscription/status", get(routes::handle_subscription_status))
         .route("/api/subscription/portal", post(routes::handle_billing_portal))
         .route("/api/usage", get(routes::handle_usage))
@@ -301,9 +350,7 @@ async fn set_cache_headers(req: Request, next: Next) -> impl IntoResponse {
     let mut res = next.run(req).await;
     let cache_value = if query.contains("v=") {
         Some("public, max-age=31536000, immutable")
-    } else if path == "/sw.js" {
-        Some("no-cache")
-    } else if path.ends_with(".html") || path == "/" {
+    } else if path == "/sw.js" || path.ends_with(".html") || path == "/" {
         Some("no-cache")
     } else if path.starts_with("/icons/") {
         Some("public, max-age=604800")
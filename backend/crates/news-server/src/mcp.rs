@@ -1,11 +1,13 @@
 use crate::claude;
-use crate::routes::AppState;
+use crate::routes::{resolve_lang, AppState};
 use axum::extract::State;
-use axum::http::{header, StatusCode};
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use news_core::config::DynamicFeed;
+use news_core::discovery;
 use news_core::models::Category;
+use news_core::sources::{ScrapeRules, SourceType};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::Arc;
@@ -58,6 +60,7 @@ fn error(id: Value, code: i32, message: &str) -> JsonRpcResponse {
 
 pub async fn handle_mcp(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(req): Json<JsonRpcRequest>,
 ) -> Response {
     let id = req.id.clone().unwrap_or(Value::Null);
@@ -67,7 +70,7 @@ pub async fn handle_mcp(
     let response = match req.method.as_str() {
         "initialize" => handle_initialize(id),
         "tools/list" => handle_tools_list(id),
-        "tools/call" => handle_tools_call(id, &req.params, &state).await,
+        "tools/call" => handle_tools_call(id, &req.params, &state, &headers).await,
         "resources/list" => handle_resources_list(id),
         "resources/read" => handle_resources_read(id, &req.params, &state).await,
         "ping" => success(id, json!({})),
@@ -108,7 +111,8 @@ fn handle_tools_list(id: Value) -> JsonRpcResponse {
                 "inputSchema": {
                     "type": "object",
                     "properties": {
-                        "category": { "type": "string", "description": "Category filter: general, tech, business, entertainment, sports, science" },
+                        "category": { "type": "string", "description": "Category filter: general, tech, business, entertainment, sports, science (matches any category an article is filed under)" },
+                        "lang": { "type": "string", "description": "Language: ja, en, ... or all (default: the site's language)" },
                         "limit": { "type": "integer", "description": "Number of articles (1-100, default 20)" },
                         "cursor": { "type": "string", "description": "Pagination cursor from previous response" }
                     }
@@ -116,12 +120,15 @@ fn handle_tools_list(id: Value) -> JsonRpcResponse {
             },
             {
                 "name": "search_articles",
-                "description": "Search articles by keyword in title or description",
+                "description": "Full-text search over article titles and descriptions, ranked by relevance and recency. Supports \"exact phrases\", OR, and -excluded terms",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
-                        "query": { "type": "string", "description": "Search keyword" },
-                        "limit": { "type": "integer", "description": "Max results (default 20)" }
+                        "query": { "type": "string", "description": "Search query" },
+                        "category": { "type": "string", "description": "Category filter: general, tech, business, entertainment, sports, science (matches any category an article is filed under)" },
+                        "lang": { "type": "string", "description": "Language: ja, en, ... or all (default: the site's language)" },
+                        "limit": { "type": "integer", "description": "Max results (1-100, default 20)" },
+                        "cursor": { "type": "string", "description": "Pagination cursor from previous response" }
                     },
                     "required": ["query"]
                 }
@@ -133,13 +140,26 @@ fn handle_tools_list(id: Value) -> JsonRpcResponse {
             },
             {
                 "name": "add_feed",
-                "description": "Add a new RSS feed",
+                "description": "Add a new RSS feed. The URL is fetched and must parse; otherwise feeds found on the page are suggested",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
                         "url": { "type": "string", "description": "RSS feed URL" },
                         "source": { "type": "string", "description": "Source name (e.g. Reuters)" },
-                        "category": { "type": "string", "description": "Category: general, tech, business, entertainment, sports, science" }
+                        "category": { "type": "string", "description": "Category: general, tech, business, entertainment, sports, science" },
+                        "source_type": { "type": "string", "enum": ["rss", "jsonfeed", "news_sitemap", "scrape"], "description": "Feed format (default: rss)" },
+                        "scrape": {
+                            "type": "object",
+                            "description": "CSS selectors for scrape sources; url is the listing page",
+                            "properties": {
+                                "item": { "type": "string" },
+                                "link": { "type": "string" },
+                                "title": { "type": "string" },
+                                "date": { "type": "string" },
+                                "image": { "type": "string" }
+                            },
+                            "required": ["item", "link"]
+                        }
                     },
                     "required": ["url", "source", "category"]
                 }
@@ -218,15 +238,15 @@ fn handle_tools_list(id: Value) -> JsonRpcResponse {
 
 // --- tools/call ---
 
-async fn handle_tools_call(id: Value, params: &Value, state: &AppState) -> JsonRpcResponse {
+async fn handle_tools_call(id: Value, params: &Value, state: &AppState, headers: &HeaderMap) -> JsonRpcResponse {
     let tool_name = params["name"].as_str().unwrap_or("");
     let args = &params["arguments"];
 
     match tool_name {
-        "list_articles" => tool_list_articles(id, args, state),
-        "search_articles" => tool_search_articles(id, args, state),
+        "list_articles" => tool_list_articles(id, args, state, headers),
+        "search_articles" => tool_search_articles(id, args, state, headers),
         "list_feeds" => tool_list_feeds(id, state),
-        "add_feed" => tool_add_feed(id, args, state),
+        "add_feed" => tool_add_feed(id, args, state).await,
         "remove_feed" => tool_remove_feed(id, args, state),
         "toggle_feed" => tool_toggle_feed(id, args, state),
         "list_categories" => tool_list_categories(id, state),
@@ -238,12 +258,13 @@ async fn handle_tools_call(id: Value, params: &Value, state: &AppState) -> JsonR
     }
 }
 
-fn tool_list_articles(id: Value, args: &Value, state: &AppState) -> JsonRpcResponse {
+fn tool_list_articles(id: Value, args: &Value, state: &AppState, headers: &HeaderMap) -> JsonRpcResponse {
     let category = args["category"].as_str().and_then(Category::from_str);
-    let limit = args["limit"].as_i64().unwrap_or(20).min(100).max(1);
+    let lang = resolve_lang(args["lang"].as_str(), headers);
+    let limit = args["limit"].as_i64().unwrap_or(20).clamp(1, 100);
     let cursor = args["cursor"].as_str();
 
-    match state.db.query_articles(category.as_ref(), limit, cursor) {
+    match state.db.query_articles(category.as_ref(), lang.as_deref(), limit, cursor) {
         Ok((articles, next_cursor)) => {
             let items: Vec<Value> = articles.iter().map(|a| json!({
                 "id": a.id,
@@ -253,6 +274,9 @@ fn tool_list_articles(id: Value, args: &Value, state: &AppState) -> JsonRpcRespo
                 "url": a.url,
                 "description": a.description,
                 "published_at": a.published_at.to_rfc3339(),
+                "lang": a.lang,
+                "audio": a.audio,
+                "categories": a.categories.iter().map(|l| l.category.as_str()).collect::<Vec<_>>(),
             })).collect();
             success(id, json!({
                 "content": [{ "type": "text", "text": serde_json::to_string_pretty(&json!({
@@ -266,39 +290,39 @@ fn tool_list_articles(id: Value, args: &Value, state: &AppState) -> JsonRpcRespo
     }
 }
 
-fn tool_search_articles(id: Value, args: &Value, state: &AppState) -> JsonRpcResponse {
+fn tool_search_articles(id: Value, args: &Value, state: &AppState, headers: &HeaderMap) -> JsonRpcResponse {
     let query = args["query"].as_str().unwrap_or("");
-    let limit = args["limit"].as_i64().unwrap_or(20).min(100).max(1);
+    let limit = args["limit"].as_i64().unwrap_or(20).clamp(1, 100);
 
     if query.is_empty() {
         return error(id, -32602, "query is required");
     }
 
-    // Fetch recent articles and filter by keyword
-    match state.db.query_articles(None, 200, None) {
-        Ok((articles, _)) => {
-            let query_lower = query.to_lowercase();
-            let matched: Vec<Value> = articles.iter()
-                .filter(|a| {
-                    a.title.to_lowercase().contains(&query_lower) ||
-                    a.description.as_deref().unwrap_or("").to_lowercase().contains(&query_lower)
-                })
-                .take(limit as usize)
-                .map(|a| json!({
-                    "id": a.id,
-                    "title": a.title,
-                    "source": a.source,
-                    "category": a.category.as_str(),
-                    "url": a.url,
-                    "description": a.description,
-                    "published_at": a.published_at.to_rfc3339(),
+    let category = args["category"].as_str().and_then(Category::from_str);
+    let lang = resolve_lang(args["lang"].as_str(), headers);
+    let cursor = args["cursor"].as_str();
+
+    match state.db.search_articles(query, category.as_ref(), lang.as_deref(), limit, cursor) {
+        Ok((hits, next_cursor)) => {
+            let results: Vec<Value> = hits.iter()
+                .map(|h| json!({
+                    "id": h.article.id,
+                    "title": h.article.title,
+                    "source": h.article.source,
+                    "category": h.article.category.as_str(),
+                    "lang": h.article.lang,
+                    "url": h.article.url,
+                    "snippet": h.snippet,
+                    "score": h.score,
+                    "published_at": h.article.published_at.to_rfc3339(),
                 }))
                 .collect();
             success(id, json!({
                 "content": [{ "type": "text", "text": serde_json::to_string_pretty(&json!({
                     "query": query,
-                    "results": matched,
-                    "count": matched.len(),
+                    "results": results,
+                    "count": results.len(),
+                    "next_cursor": next_cursor,
                 })).unwrap_or_default() }]
             }))
         }
@@ -327,14 +351,30 @@ fn tool_list_feeds(id: Value, state: &AppState) -> JsonRpcResponse {
     }
 }
 
-fn tool_add_feed(id: Value, args: &Value, state: &AppState) -> JsonRpcResponse {
+async fn tool_add_feed(id: Value, args: &Value, state: &AppState) -> JsonRpcResponse {
     let url = args["url"].as_str().unwrap_or("");
     let source = args["source"].as_str().unwrap_or("");
     let category = args["category"].as_str().unwrap_or("general");
-
+    let source_type = match args["source_type"].as_str() {
+        None => SourceType::Rss,
+        Some(t) => match SourceType::from_str(t) {
+            Some(t) => t,
+            None => return error(id, -32602, "source_type must be rss, jsonfeed, news_sitemap or scrape"),
+        },
+    };
+    let scrape: Option<ScrapeRules> = match args.get("scrape").filter(|v| !v.is_null()) {
+        None => None,
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(rules) => Some(rules),
+            Err(e) => return error(id, -32602, &format!("Invalid scrape rules: {}", e)),
+        },
+    };
     if url.is_empty() || source.is_empty() {
         return error(id, -32602, "url and source are required");
     }
+    if let Err(e) = discovery::validate_feed(&state.http_client, url, source_type, scrape.as_ref()).await {
+        return error(id, -32602, &e.to_string());
+    }
 
     let feed_id = format!("feed-{}", uuid::Uuid::new_v4().to_string().split('-').next().unwrap_or("x"));
     let feed = DynamicFeed {
@@ -344,6 +384,12 @@ fn tool_add_feed(id: Value, args: &Value, state: &AppState) -> JsonRpcResponse {
         category: category.to_string(),
         enabled: true,
         added_by: Some("mcp".into()),
+        poll_interval_secs: None,
+        quarantine_reason: None,
+        content_policy: Default::default(),
+        source_type,
+        scrape,
+        filters: None,
     };
 
     match state.db.put_feed(&feed) {
@@ -465,14 +511,14 @@ async fn tool_ask_question(id: Value, args: &Value, state: &AppState) -> JsonRpc
 }
 
 async fn tool_summarize_news(id: Value, args: &Value, state: &AppState) -> JsonRpcResponse {
-    let minutes = args["minutes"].as_u64().unwrap_or(3).min(10).max(1) as usize;
+    let minutes = args["minutes"].as_u64().unwrap_or(3).clamp(1, 10) as usize;
     let target_chars = minutes * 300;
 
     if state.api_key.is_empty() {
         return error(id, -32000, "Anthropic API key not configured");
     }
 
-    let articles = match state.db.query_articles(None, 30, None) {
+    let articles = match state.db.query_articles(None, None, 30, None) {
         Ok((arts, _)) => arts,
         Err(e) => return error(id, -32000, &format!("Failed to query articles: {}", e)),
     };
@@ -564,7 +610,7 @@ async fn handle_resources_read(id: Value, params: &Value, state: &AppState) -> J
 
     match uri {
         "news://articles" => {
-            match state.db.query_articles(None, 30, None) {
+            match state.db.query_articles(None, None, 30, None) {
                 Ok((articles, _)) => {
                     let items: Vec<Value> = articles.iter().map(|a| json!({
                         "id": a.id,
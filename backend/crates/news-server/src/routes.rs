@@ -1,14 +1,25 @@
 use crate::claude;
-use crate::db::Db;
+use crate::db::{Db, Retention};
 use crate::stripe;
+use crate::translation;
+use crate::websub;
 use axum::extract::{Path, Query, State};
 use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use news_core::changes::{AdminAction, ChangeRequest, ChangeStatus};
-use news_core::config::DynamicFeed;
+use news_core::config::{ContentPolicy, DynamicFeed};
+use news_core::discovery;
+use news_core::filters::FeedFilters;
 use news_core::grouping;
+use news_core::health::{self, HealthStatus};
+use news_core::lang;
 use news_core::models::{ArticlesResponse, Category, CategoryInfo};
+use news_core::opml;
+use news_core::feeds::{fetch_feed, FeedConfig, FetchState};
+use news_core::sources::{ScrapeRules, SourceType};
+use news_core::store;
+use news_core::politeness::PoliteClient;
 use axum::body::Body;
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
@@ -27,6 +38,8 @@ pub(crate) fn cache_key(endpoint: &str, body: &str) -> String {
 pub struct AppState {
     pub db: Arc<Db>,
     pub http_client: reqwest::Client,
+    /// Client for publisher pages: honours robots.txt and rate-limits per host.
+    pub polite_client: PoliteClient,
     pub api_key: String,
     pub elevenlabs_api_key: String,
     pub openai_api_key: String,
@@ -45,9 +58,28 @@ pub struct AppState {
     pub admin_secret: String,
     pub base_url: String,
     pub google_client_id: String,
+    /// When articles and their images move out of the live tables.
+    pub retention: Retention,
+}
+
+/// Full text of an article page for AI features, or empty when there is no URL, the
+/// URL isn't a stored article, the source's content policy forbids it, or the
+/// publisher opts out.
+async fn article_text(state: &AppState, url: Option<&str>) -> String {
+    let Some(url) = url.filter(|u| !u.is_empty()) else {
+        return String::new();
+    };
+    let policy = state.db.content_policy_for_url(url).unwrap_or_default();
+    if !policy.allows_full_text() {
+        return String::new();
+    }
+    news_core::ogp::fetch_article_content(&state.polite_client, url)
+        .await
+        .unwrap_or_default()
 }
 
 /// Check admin auth. Returns error response if unauthorized.
+#[allow(clippy::result_large_err)]
 fn check_admin_auth(headers: &HeaderMap, state: &AppState) -> Result<(), Response> {
     if state.admin_secret.is_empty() {
         // No secret configured = open (dev mode)
@@ -136,6 +168,7 @@ const FEATURE_LIMITS: &[FeatureLimit] = &[
     FeatureLimit { name: "to_reading", daily_limit: 30 },
     FeatureLimit { name: "podcast", daily_limit: 10 },
     FeatureLimit { name: "murmur", daily_limit: 50 },
+    FeatureLimit { name: "translate", daily_limit: 30 },
 ];
 
 fn get_daily_limit(feature: &str) -> i64 {
@@ -146,6 +179,7 @@ fn get_daily_limit(feature: &str) -> i64 {
         .unwrap_or(5)
 }
 
+#[allow(clippy::result_large_err)]
 fn check_rate_limit(
     db: &Db,
     tier: &UserTier,
@@ -226,6 +260,8 @@ pub struct ArticlesQuery {
     pub cursor: Option<String>,
     /// Freshness filter in minutes (e.g., 10 for articles from last 10 minutes)
     pub freshness: Option<i64>,
+    /// Language filter ("ja", "en", or "all"); defaults to the site's language
+    pub lang: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -248,64 +284,31 @@ pub struct ToggleFeatureRequest {
 
 pub async fn get_articles(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(params): Query<ArticlesQuery>,
 ) -> Response {
     let category = params.category.as_deref().and_then(Category::from_str);
-    let limit = params.limit.unwrap_or(30).min(100).max(1);
+    let limit = params.limit.unwrap_or(30).clamp(1, 100);
+    let lang = resolve_lang(params.lang.as_deref(), &headers);
 
     // Check if freshness filter is requested (e.g., ?freshness=10 for 10 minutes)
     let result = if let Some(minutes) = params.freshness {
         state
             .db
-            .get_fresh_articles(category.as_ref(), minutes, limit)
+            .get_fresh_articles(category.as_ref(), lang.as_deref(), minutes, limit)
             .map(|articles| (articles, None))
     } else {
         state
             .db
-            .query_articles(category.as_ref(), limit, params.cursor.as_deref())
+            .query_articles(category.as_ref(), lang.as_deref(), limit, params.cursor.as_deref())
     };
 
     match result {
         Ok((mut articles, next_cursor)) => {
-            // Apply grouping if feature is enabled
+            // Stories are clustered at ingest; show one article per story
             if let Ok(flags) = state.db.get_feature_flags() {
-                if flags.grouping_enabled && articles.len() > 1 {
-                    let titles: Vec<&str> =
-                        articles.iter().map(|a| a.title.as_str()).collect();
-                    let groups =
-                        grouping::group_articles(&titles, flags.grouping_threshold);
-
-                    for group in &groups {
-                        if group.len() > 1 {
-                            let group_id = uuid::Uuid::new_v4().to_string();
-                            let count = group.len() as u32;
-                            for (i, &idx) in group.iter().enumerate() {
-                                articles[idx].group_id = Some(group_id.clone());
-                                if i == 0 {
-                                    articles[idx].group_count = Some(count);
-                                }
-                            }
-                        }
-                    }
-
-                    let keep_indices: std::collections::HashSet<usize> = groups
-                        .iter()
-                        .flat_map(|g| {
-                            if g.len() > 1 {
-                                vec![g[0]]
-                            } else {
-                                g.clone()
-                            }
-                        })
-                        .collect();
-
-                    let filtered: Vec<_> = articles
-                        .into_iter()
-                        .enumerate()
-                        .filter(|(i, _)| keep_indices.contains(i))
-                        .map(|(_, a)| a)
-                        .collect();
-                    articles = filtered;
+                if flags.grouping_enabled {
+                    articles = grouping::collapse_stories(articles);
                 }
             }
 
@@ -389,15 +392,80 @@ pub async fn health(State(state): State<Arc<AppState>>) -> Response {
     }
 }
 
+/// GET /api/articles/:id — `lang` (default: the site's language) serves the stored
+/// translation when the article is in another language, else the original text.
 pub async fn get_article_by_id(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Path(id): Path<String>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
 ) -> Response {
+    let lang = resolve_lang(params.get("lang").map(|l| l.as_str()), &headers);
     match state.db.get_article_by_id(&id) {
-        Ok(Some(article)) => (
+        Ok(Some(mut article)) => {
+            let translation = match lang {
+                Some(ref lang) if translation::needs_translation(&article, lang) => {
+                    state.db.get_translation(&article.id, lang).ok().flatten()
+                }
+                _ => None,
+            };
+            let summary = match translation {
+                Some(ref t) => {
+                    t.apply(&mut article);
+                    t.summary.clone()
+                }
+                None => None,
+            };
+            let summary = summary.or_else(|| state.db.get_ai_summary(&article.id).ok().flatten());
+            (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, "application/json; charset=utf-8")],
+                Json(serde_json::json!({"article": article, "summary": summary})),
+            )
+                .into_response()
+        }
+        // Articles past their retention window are still served from the archive
+        Ok(None) => match state.db.get_archived_article(&id) {
+            Ok(Some(archived)) => (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, "application/json; charset=utf-8")],
+                Json(serde_json::json!({
+                    "article": archived.article,
+                    "summary": archived.ai_summary,
+                    "archived": true,
+                    "archived_at": archived.archived_at,
+                })),
+            )
+                .into_response(),
+            Ok(None) => (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": "Article not found"})),
+            )
+                .into_response(),
+            Err(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": e})),
+            )
+                .into_response(),
+        },
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/articles/:id/revisions — every version of an article's title,
+/// description and image, oldest first and ending with the current one.
+pub async fn get_article_revisions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Response {
+    match state.db.get_article_revisions(&id) {
+        Ok(Some(revisions)) => (
             StatusCode::OK,
-            [(header::CONTENT_TYPE, "application/json; charset=utf-8")],
-            Json(serde_json::json!({"article": article})),
+            Json(serde_json::json!({"article_id": id, "revisions": revisions})),
         )
             .into_response(),
         Ok(None) => (
@@ -407,14 +475,130 @@ pub async fn get_article_by_id(
             .into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": e.to_string()})),
+            Json(serde_json::json!({"error": e})),
         )
             .into_response(),
     }
 }
 
+#[derive(Deserialize)]
+pub struct TranslateRequest {
+    pub lang: String,
+}
+
+/// POST /api/articles/:id/translate — translate an article into `lang` now, reusing
+/// the stored translation if there is one.
+pub async fn handle_article_translate(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(body): Json<TranslateRequest>,
+) -> Response {
+    let Some(lang) = lang::normalize(&body.lang) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "langが不正です"})),
+        )
+            .into_response();
+    };
+    let mut article = match state.db.get_article_by_id(&id) {
+        Ok(Some(article)) => article,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": "Article not found"})),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e}))).into_response()
+        }
+    };
+    if !translation::needs_translation(&article, &lang) {
+        let summary = state.db.get_ai_summary(&article.id).ok().flatten();
+        return (StatusCode::OK, Json(serde_json::json!({"article": article, "summary": summary}))).into_response();
+    }
+
+    // Stored translations are free; only new ones count against the daily limit
+    let stored = state.db.get_translation(&article.id, &lang).ok().flatten();
+    let tier = extract_user_tier(&headers, &state.db);
+    if stored.is_none() {
+        if let Err(resp) = check_rate_limit(&state.db, &tier, "translate") {
+            return resp;
+        }
+        if state.api_key.is_empty() {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({"error": "APIキーが設定されていません"})),
+            )
+                .into_response();
+        }
+    }
+
+    match translation::translate(&state, &article, &lang).await {
+        Ok(t) => {
+            if stored.is_none() {
+                increment_usage_if_needed(&state.db, &tier, "translate");
+            }
+            t.apply(&mut article);
+            (StatusCode::OK, Json(serde_json::json!({"article": article, "summary": t.summary}))).into_response()
+        }
+        Err(e) => {
+            warn!(error = %e, article_id = %id, "Translation failed");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "翻訳に失敗しました"})),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// GET /api/stories/:group_id — every article covering the same story, oldest first.
+pub async fn get_story(
+    State(state): State<Arc<AppState>>,
+    Path(group_id): Path<String>,
+) -> Response {
+    match state.db.get_story(&group_id) {
+        Ok(articles) if articles.is_empty() => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Story not found"})),
+        )
+            .into_response(),
+        Ok(articles) => {
+            let mut sources: Vec<&str> = articles.iter().map(|a| a.source.as_str()).collect();
+            sources.sort_unstable();
+            sources.dedup();
+            (
+                StatusCode::OK,
+                [
+                    (header::CACHE_CONTROL, "public, max-age=120"),
+                    (header::CONTENT_TYPE, "application/json; charset=utf-8"),
+                ],
+                Json(serde_json::json!({
+                    "group_id": group_id,
+                    "count": articles.len(),
+                    "source_count": sources.len(),
+                    "sources": sources,
+                    "articles": articles,
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to load story");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
+    }
+}
+
 pub async fn handle_search(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(params): Query<std::collections::HashMap<String, String>>,
 ) -> Response {
     let q = params.get("q").cloned().unwrap_or_default();
@@ -429,13 +613,22 @@ pub async fn handle_search(
         .get("limit")
         .and_then(|l| l.parse::<i64>().ok())
         .unwrap_or(20)
-        .min(100)
-        .max(1);
-    match state.db.search_articles(&q, limit) {
-        Ok(articles) => (
+        .clamp(1, 100);
+    let category = params.get("category").and_then(|c| Category::from_str(c));
+    let cursor = params.get("cursor").map(|c| c.as_str());
+    let lang = resolve_lang(params.get("lang").map(|l| l.as_str()), &headers);
+    // `archive=true` searches articles that have left the live tables
+    let archive = params.get("archive").is_some_and(|a| a == "true" || a == "1");
+    let result = if archive {
+        state.db.search_archive(&q, category.as_ref(), lang.as_deref(), limit, cursor)
+    } else {
+        state.db.search_articles(&q, category.as_ref(), lang.as_deref(), limit, cursor)
+    };
+    match result {
+        Ok((articles, next_cursor)) => (
             StatusCode::OK,
             [(header::CONTENT_TYPE, "application/json; charset=utf-8")],
-            Json(serde_json::json!({"articles": articles, "query": q})),
+            Json(serde_json::json!({"articles": articles, "query": q, "next_cursor": next_cursor})),
         )
             .into_response(),
         Err(e) => {
@@ -509,10 +702,10 @@ pub async fn handle_summarize(
             .into_response();
     }
 
-    let minutes = body.minutes.max(1).min(10);
+    let minutes = body.minutes.clamp(1, 10);
     let target_chars = (minutes as usize) * 300;
 
-    let articles = match state.db.query_articles(None, 30, None) {
+    let articles = match state.db.query_articles(None, None, 30, None) {
         Ok((arts, _)) => arts,
         Err(e) => {
             warn!(error = %e, "Failed to query articles for summary");
@@ -641,6 +834,7 @@ pub async fn handle_to_reading(
 
 #[derive(Deserialize)]
 pub struct PodcastGenerateRequest {
+    #[allow(dead_code)]
     pub article_id: Option<String>,
     pub title: String,
     pub description: String,
@@ -702,15 +896,7 @@ pub async fn handle_podcast_generate(
     }
 
     // Fetch article content if URL provided
-    let article_content = if let Some(ref url) = body.url {
-        if !url.is_empty() {
-            news_core::ogp::fetch_article_content(&state.http_client, url).await.unwrap_or_default()
-        } else {
-            String::new()
-        }
-    } else {
-        String::new()
-    };
+    let article_content = article_text(&state, body.url.as_deref()).await;
 
     // Generate dialogue script
     let dialogue = match claude::generate_dialogue_script(
@@ -857,18 +1043,22 @@ pub struct FeedQuery {
     pub category: Option<String>,
     pub limit: Option<i64>,
     pub cursor: Option<String>,
+    /// Language filter ("ja", "en", or "all"); defaults to the site's language
+    pub lang: Option<String>,
 }
 
 pub async fn get_feed(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(params): Query<FeedQuery>,
 ) -> Response {
     let category = params.category.as_deref().and_then(Category::from_str);
-    let limit = params.limit.unwrap_or(10).min(20).max(1);
+    let limit = params.limit.unwrap_or(10).clamp(1, 20);
+    let lang = resolve_lang(params.lang.as_deref(), &headers);
 
     let result = state
         .db
-        .query_articles(category.as_ref(), limit, params.cursor.as_deref());
+        .query_articles(category.as_ref(), lang.as_deref(), limit, params.cursor.as_deref());
 
     match result {
         Ok((articles, next_cursor)) => {
@@ -904,6 +1094,7 @@ pub struct MurmurGenerateRequest {
     pub title: String,
     pub description: String,
     pub source: String,
+    #[allow(dead_code)]
     pub article_id: Option<String>,
 }
 
@@ -1097,16 +1288,51 @@ pub struct ArticleAskRequest {
 
 // --- Feed Management API ---
 
+#[derive(Deserialize)]
+pub struct ImportFeedsRequest {
+    pub opml: String,
+    /// Report what would change without writing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Category for feeds that have none in the file.
+    pub default_category: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct AddFeedRequest {
     pub url: String,
     pub source: String,
     pub category: String,
+    #[serde(default)]
+    pub source_type: SourceType,
+    pub scrape: Option<ScrapeRules>,
+    pub filters: Option<FeedFilters>,
+}
+
+#[derive(Deserialize)]
+pub struct TestFeedRequest {
+    pub url: String,
+    #[serde(default)]
+    pub source_type: SourceType,
+    pub scrape: Option<ScrapeRules>,
+    /// Filters to try out; the response reports how many items they would drop.
+    pub filters: Option<FeedFilters>,
 }
 
+#[derive(Deserialize)]
+pub struct DiscoverFeedsRequest {
+    pub url: String,
+}
+
+/// Items returned by the feed test endpoint, at most.
+const TEST_FEED_MAX_ITEMS: usize = 50;
+
 #[derive(Deserialize)]
 pub struct UpdateFeedRequest {
     pub enabled: Option<bool>,
+    /// Fixed poll interval override in seconds; 0 returns the feed to adaptive polling.
+    pub poll_interval_secs: Option<u64>,
+    pub content_policy: Option<ContentPolicy>,
 }
 
 pub async fn list_feeds(
@@ -1128,6 +1354,12 @@ pub async fn add_feed(
     if body.url.is_empty() || body.source.is_empty() || body.category.is_empty() {
         return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "url, source, category are required"}))).into_response();
     }
+    if let Some(Err(e)) = body.filters.as_ref().map(FeedFilters::validate) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e.to_string()}))).into_response();
+    }
+    if let Err(e) = discovery::validate_feed(&state.http_client, &body.url, body.source_type, body.scrape.as_ref()).await {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e.to_string(), "candidates": e.candidates}))).into_response();
+    }
     let feed_id = format!("feed-{}", uuid::Uuid::new_v4().to_string().split('-').next().unwrap_or("x"));
     let feed = DynamicFeed {
         feed_id: feed_id.clone(),
@@ -1136,6 +1368,12 @@ pub async fn add_feed(
         category: body.category,
         enabled: true,
         added_by: Some("settings".into()),
+        poll_interval_secs: None,
+        quarantine_reason: None,
+        content_policy: Default::default(),
+        source_type: body.source_type,
+        scrape: body.scrape,
+        filters: body.filters,
     };
     match state.db.put_feed(&feed) {
         Ok(()) => (StatusCode::OK, Json(serde_json::json!({"status": "ok", "feed_id": feed_id, "message": "フィードを追加しました"}))).into_response(),
@@ -1143,6 +1381,133 @@ pub async fn add_feed(
     }
 }
 
+/// POST /api/admin/feeds/test — fetch a candidate source and return what it parses to, without saving.
+pub async fn test_feed(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<TestFeedRequest>,
+) -> Response {
+    if let Err(resp) = check_admin_auth(&headers, &state) { return resp; }
+    if body.url.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "url is required"}))).into_response();
+    }
+    if let Err(e) = body.source_type.check_rules(body.scrape.as_ref()) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e.to_string()}))).into_response();
+    }
+    if let Some(Err(e)) = body.filters.as_ref().map(FeedFilters::validate) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e.to_string()}))).into_response();
+    }
+    let feed = FeedConfig {
+        feed_id: None,
+        url: body.url,
+        source: "test".into(),
+        category: "general".into(),
+        poll_interval_secs: None,
+        source_type: body.source_type,
+        scrape: body.scrape,
+        filters: body.filters,
+    };
+    let mut fetch_state = FetchState::default();
+    match fetch_feed(&state.polite_client, &feed, &mut fetch_state).await {
+        Ok(mut articles) => {
+            let count = articles.len();
+            articles.truncate(TEST_FEED_MAX_ITEMS);
+            (StatusCode::OK, Json(serde_json::json!({"status": "ok", "count": count, "filtered": fetch_state.last_filtered_count, "items": articles}))).into_response()
+        }
+        Err(e) => (StatusCode::BAD_GATEWAY, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// POST /api/admin/feeds/discover — find the feeds a page links to or serves at common paths.
+pub async fn discover_feeds(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<DiscoverFeedsRequest>,
+) -> Response {
+    if let Err(resp) = check_admin_auth(&headers, &state) { return resp; }
+    if body.url.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "url is required"}))).into_response();
+    }
+    match discovery::discover_feeds(&state.http_client, &body.url).await {
+        Ok(candidates) => (StatusCode::OK, Json(serde_json::json!({"status": "ok", "candidates": candidates}))).into_response(),
+        Err(e) => (StatusCode::BAD_GATEWAY, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// GET /api/admin/feeds.opml — every feed as OPML 2.0, grouped by category.
+pub async fn export_feeds_opml(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(resp) = check_admin_auth(&headers, &state) { return resp; }
+    let feeds = match state.db.get_all_feeds() {
+        Ok(f) => f,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e}))).into_response(),
+    };
+    let categories = category_infos(&state.db).unwrap_or_default();
+    let xml = opml::to_opml(&feeds, &categories, "HyperNews feeds");
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/x-opml; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"feeds.opml\""),
+        ],
+        xml,
+    )
+        .into_response()
+}
+
+/// POST /api/admin/feeds/import — add the feeds of an OPML document, or just report with `dry_run`.
+pub async fn import_feeds(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<ImportFeedsRequest>,
+) -> Response {
+    if let Err(resp) = check_admin_auth(&headers, &state) { return resp; }
+    let plan = match store::import_plan(state.db.as_ref(), &body.opml, body.default_category.as_deref()).await {
+        Ok(p) => p,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+    };
+    if body.dry_run {
+        return (StatusCode::OK, Json(serde_json::json!({"status": "dry_run", "plan": plan}))).into_response();
+    }
+    match state.db.write_import(&plan, "opml-import") {
+        Ok(added) => {
+            info!(
+                added,
+                duplicates = plan.duplicates.len(),
+                unknown_category = plan.unknown_category.len(),
+                invalid = plan.invalid_urls.len(),
+                "OPML import"
+            );
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "status": "ok",
+                    "added": added,
+                    "plan": plan,
+                    "message": format!("{}件のフィードを追加しました", added),
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e}))).into_response(),
+    }
+}
+
+/// All configured categories, hidden ones included, for matching imported feeds.
+fn category_infos(db: &Db) -> Result<Vec<CategoryInfo>, String> {
+    Ok(db
+        .get_categories()?
+        .into_iter()
+        .map(|(id, label_ja, label_en, _, _)| CategoryInfo {
+            id,
+            label: if label_en.is_empty() { label_ja.clone() } else { label_en },
+            label_ja,
+        })
+        .collect())
+}
+
 pub async fn delete_feed(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
@@ -1170,11 +1535,25 @@ pub async fn update_feed(
         Some(f) => f,
         None => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "Feed not found"}))).into_response(),
     };
+    let release = body.enabled == Some(true) && feed.quarantine_reason.is_some();
     let updated = DynamicFeed {
         enabled: body.enabled.unwrap_or(feed.enabled),
+        poll_interval_secs: match body.poll_interval_secs {
+            Some(0) => None,
+            Some(secs) => Some(secs),
+            None => feed.poll_interval_secs,
+        },
+        content_policy: body.content_policy.unwrap_or(feed.content_policy),
         ..feed
     };
-    match state.db.put_feed(&updated) {
+    let result = state.db.put_feed(&updated).and_then(|()| {
+        if release {
+            state.db.release_quarantine(&updated.feed_id)
+        } else {
+            Ok(())
+        }
+    });
+    match result {
         Ok(()) => {
             let label = if updated.enabled { "有効" } else { "無効" };
             (StatusCode::OK, Json(serde_json::json!({"status": "ok", "message": format!("フィードを{}にしました", label)}))).into_response()
@@ -1183,6 +1562,40 @@ pub async fn update_feed(
     }
 }
 
+/// GET /api/admin/feeds/health — per-feed fetch health, quarantine status and freshness.
+pub async fn feeds_health(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(resp) = check_admin_auth(&headers, &state) { return resp; }
+    let feeds = match state.db.get_all_feeds() {
+        Ok(f) => f,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e}))).into_response(),
+    };
+    let fetch_states = match state.db.get_fetch_states() {
+        Ok(s) => s,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e}))).into_response(),
+    };
+
+    let now = chrono::Utc::now();
+    let report: Vec<_> = feeds
+        .iter()
+        .map(|f| health::feed_health(f, fetch_states.get(&f.feed_id), now))
+        .collect();
+    let count = |status: HealthStatus| report.iter().filter(|h| h.status == status).count();
+    let summary = serde_json::json!({
+        "total": report.len(),
+        "healthy": count(HealthStatus::Healthy),
+        "failing": count(HealthStatus::Failing),
+        "stale": count(HealthStatus::Stale),
+        "quarantined": count(HealthStatus::Quarantined),
+        "disabled": count(HealthStatus::Disabled),
+        "unknown": count(HealthStatus::Unknown),
+    });
+
+    (StatusCode::OK, Json(serde_json::json!({"summary": summary, "feeds": report}))).into_response()
+}
+
 pub async fn handle_article_questions(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
@@ -1211,15 +1624,7 @@ pub async fn handle_article_questions(
     }
 
     // Fetch article content if URL provided
-    let article_content = if let Some(ref url) = body.url {
-        if !url.is_empty() {
-            news_core::ogp::fetch_article_content(&state.http_client, url).await.unwrap_or_default()
-        } else {
-            String::new()
-        }
-    } else {
-        String::new()
-    };
+    let article_content = article_text(&state, body.url.as_deref()).await;
 
     match claude::generate_questions(
         &state.http_client,
@@ -1277,15 +1682,7 @@ pub async fn handle_article_ask(
     }
 
     // Fetch article content if URL provided
-    let article_content = if let Some(ref url) = body.url {
-        if !url.is_empty() {
-            news_core::ogp::fetch_article_content(&state.http_client, url).await.unwrap_or_default()
-        } else {
-            String::new()
-        }
-    } else {
-        String::new()
-    };
+    let article_content = article_text(&state, body.url.as_deref()).await;
 
     // Transform question to positive if needed
     let positive_question = claude::transform_question_to_positive(
@@ -1428,15 +1825,7 @@ pub async fn handle_action_plan(
     }
 
     // Fetch article content if URL provided
-    let article_content = if let Some(ref url) = body.url {
-        if !url.is_empty() {
-            news_core::ogp::fetch_article_content(&state.http_client, url).await.unwrap_or_default()
-        } else {
-            String::new()
-        }
-    } else {
-        String::new()
-    };
+    let article_content = article_text(&state, body.url.as_deref()).await;
 
     let classification = body.classification.as_deref().unwrap_or("general");
 
@@ -2248,8 +2637,8 @@ async fn runpod_runsync(
         .map_err(|e| format!("RunPod parse: {e}"))?;
 
     match result["status"].as_str() {
-        Some("COMPLETED") => return Ok(result["output"].clone()),
-        Some("FAILED") => return Err(format!("RunPod job failed: {}", result["error"].as_str().unwrap_or("unknown"))),
+        Some("COMPLETED") => Ok(result["output"].clone()),
+        Some("FAILED") => Err(format!("RunPod job failed: {}", result["error"].as_str().unwrap_or("unknown"))),
         Some("IN_QUEUE") | Some("IN_PROGRESS") => {
             // Cold start — fall back to polling
             let job_id = match result["id"].as_str() {
@@ -2339,7 +2728,7 @@ async fn runpod_async(
             Some("FAILED") => return Err(format!("RunPod job failed: {}", result["error"].as_str().unwrap_or("unknown"))),
             Some("IN_QUEUE") | Some("IN_PROGRESS") => continue,
             Some(s) => return Err(format!("RunPod unexpected status: {s}")),
-            None => return Err(format!("RunPod: no status in poll response")),
+            None => return Err("RunPod: no status in poll response".to_string()),
         }
     }
 
@@ -2513,6 +2902,19 @@ pub async fn handle_command(
         warn!(error = %e, "Failed to save change request");
     }
 
+    // Imports are previewed as a dry run so duplicates and unknown categories show up front
+    let mut import_previews: Vec<serde_json::Value> = Vec::new();
+    for action in &change.actions {
+        if let AdminAction::ImportFeeds { opml, default_category } = action {
+            import_previews.push(
+                match store::import_plan(state.db.as_ref(), opml, default_category.as_deref()).await {
+                    Ok(plan) => serde_json::json!(plan),
+                    Err(e) => serde_json::json!({"error": e.to_string()}),
+                },
+            );
+        }
+    }
+
     (
         StatusCode::OK,
         Json(serde_json::json!({
@@ -2520,7 +2922,8 @@ pub async fn handle_command(
             "change_id": change_id,
             "interpretation": interpretation.interpretation,
             "confidence": interpretation.confidence,
-            "actions": change.actions
+            "actions": change.actions,
+            "import_previews": import_previews
         })),
     )
         .into_response()
@@ -2582,7 +2985,14 @@ pub async fn apply_change(
     let mut errors = Vec::new();
 
     for action in &change.actions {
-        match apply_action(&state.db, action) {
+        // Feed URLs are checked here because apply_action can't fetch
+        if let AdminAction::AddFeed { url, source_type, scrape, .. } = action {
+            if let Err(e) = discovery::validate_feed(&state.http_client, url, *source_type, scrape.as_ref()).await {
+                errors.push(format!("{:?}: {}", action, e));
+                continue;
+            }
+        }
+        match store::apply_action(state.db.as_ref(), action).await {
             Ok(()) => applied += 1,
             Err(e) => errors.push(format!("{:?}: {}", action, e)),
         }
@@ -2677,6 +3087,78 @@ pub async fn handle_subscribe(
     }
 }
 
+/// GET /api/websub/:feed_id — a hub verifying a subscription we requested.
+pub async fn websub_verify(
+    State(state): State<Arc<AppState>>,
+    Path(feed_id): Path<String>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Response {
+    match websub::verify_intent(&state.db, &feed_id, &params) {
+        Ok(challenge) => (StatusCode::OK, [(header::CONTENT_TYPE, "text/plain")], challenge).into_response(),
+        Err(e) => {
+            warn!(error = %e, feed_id = %feed_id, "WebSub verification refused");
+            StatusCode::NOT_FOUND.into_response()
+        }
+    }
+}
+
+/// POST /api/websub/:feed_id — content pushed by a hub. Pushes that fail the signature
+/// check are still acknowledged, as WebSub requires, but ignored.
+pub async fn websub_receive(
+    State(state): State<Arc<AppState>>,
+    Path(feed_id): Path<String>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Response {
+    let signature = headers.get("x-hub-signature").and_then(|v| v.to_str().ok());
+    match websub::receive(&state.db, &feed_id, signature, &body) {
+        Ok(stored) => {
+            info!(feed_id = %feed_id, stored, "WebSub push received");
+            StatusCode::ACCEPTED.into_response()
+        }
+        Err(websub::PushError::NotSubscribed) => StatusCode::GONE.into_response(),
+        Err(e) => {
+            warn!(error = %e, feed_id = %feed_id, "WebSub push ignored");
+            StatusCode::ACCEPTED.into_response()
+        }
+    }
+}
+
+/// GET /api/admin/websub — hub subscriptions and their lease state.
+pub async fn list_websub_subscriptions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(resp) = check_admin_auth(&headers, &state) { return resp; }
+    match state.db.get_websub_subscriptions() {
+        Ok(subs) => (StatusCode::OK, Json(serde_json::json!({"subscriptions": subs}))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e}))).into_response(),
+    }
+}
+
+/// Analyzed articles the AI filed somewhere other than their feed's category, with
+/// counts per (feed category, AI category) pair, for tuning feeds and keyword rules.
+pub async fn list_category_disagreements(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Response {
+    if let Err(resp) = check_admin_auth(&headers, &state) { return resp; }
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(50)
+        .clamp(1, 500);
+    match state.db.category_disagreements(limit) {
+        Ok((pairs, articles)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({"pairs": pairs, "articles": articles})),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e}))).into_response(),
+    }
+}
+
 pub async fn handle_stripe_webhook(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
@@ -3172,61 +3654,6 @@ pub async fn handle_telemetry(body: axum::body::Bytes) -> Response {
     StatusCode::NO_CONTENT.into_response()
 }
 
-fn apply_action(db: &Db, action: &AdminAction) -> Result<(), String> {
-    match action {
-        AdminAction::AddFeed {
-            url,
-            source,
-            category,
-        } => {
-            let feed_id = format!(
-                "feed-{}",
-                uuid::Uuid::new_v4()
-                    .to_string()
-                    .split('-')
-                    .next()
-                    .unwrap_or("x")
-            );
-            let feed = DynamicFeed {
-                feed_id,
-                url: url.clone(),
-                source: source.clone(),
-                category: category.clone(),
-                enabled: true,
-                added_by: Some("admin-chat".into()),
-            };
-            db.put_feed(&feed)
-        }
-        AdminAction::RemoveFeed { feed_id } => db.delete_feed(feed_id),
-        AdminAction::EnableFeed { feed_id } => update_feed_enabled(db, feed_id, true),
-        AdminAction::DisableFeed { feed_id } => update_feed_enabled(db, feed_id, false),
-        AdminAction::ToggleFeature { feature, enabled } => {
-            db.set_feature_flag(feature, *enabled, None)
-        }
-        AdminAction::SetGroupingThreshold { threshold } => {
-            let extra = serde_json::json!({"similarity_threshold": threshold}).to_string();
-            db.set_feature_flag("grouping", true, Some(&extra))
-        }
-        AdminAction::AddCategory { id, label_ja } => {
-            let max_order = db.get_categories().map(|cats| cats.len() as i32).unwrap_or(0);
-            db.put_category(id, label_ja, "", max_order)
-        }
-        AdminAction::RemoveCategory { id } => db.delete_category(id),
-        AdminAction::RenameCategory { id, label_ja } => db.rename_category(id, label_ja),
-        AdminAction::ReorderCategories { order } => db.reorder_categories(order),
-    }
-}
-
-fn update_feed_enabled(db: &Db, feed_id: &str, enabled: bool) -> Result<(), String> {
-    let feeds = db.get_all_feeds()?;
-    let feed = feeds
-        .into_iter()
-        .find(|f| f.feed_id == feed_id)
-        .ok_or_else(|| format!("Feed not found: {}", feed_id))?;
-    let updated = DynamicFeed { enabled, ..feed };
-    db.put_feed(&updated)
-}
-
 // --- SEO / OGP per-domain ---
 
 struct SiteMeta {
@@ -3238,7 +3665,10 @@ struct SiteMeta {
     url: &'static str,
     image: &'static str,
     theme_color: &'static str,
+    /// Language of the site's own pages (`<html lang>`).
     lang: &'static str,
+    /// Language of the articles the site serves when the request doesn't pick one.
+    content_lang: &'static str,
     keywords: &'static str,
 }
 
@@ -3253,6 +3683,7 @@ const SITE_METAS: &[SiteMeta] = &[
         image: "https://news.xyz/icons/og-xyz.png",
         theme_color: "#1a1a2e",
         lang: "en",
+        content_lang: "ja",
         keywords: "news,AI,artificial intelligence,news aggregator,AI summary,voice news,tech news,breaking news,Rust,56000 dollar domain",
     },
     SiteMeta {
@@ -3265,6 +3696,7 @@ const SITE_METAS: &[SiteMeta] = &[
         image: "https://news.online/icons/icon-512.png",
         theme_color: "#000000",
         lang: "en",
+        content_lang: "ja",
         keywords: "coming soon,AI news,video news,audio news,56000 dollar domain,news online,Rust,AI broadcast",
     },
     SiteMeta {
@@ -3277,6 +3709,7 @@ const SITE_METAS: &[SiteMeta] = &[
         image: "https://news.cloud/icons/og-cloud.png",
         theme_color: "#0f172a",
         lang: "en",
+        content_lang: "ja",
         keywords: "news API,developer API,news aggregation,AI API,MCP,REST API,news data,news platform,Rust",
     },
     SiteMeta {
@@ -3289,6 +3722,7 @@ const SITE_METAS: &[SiteMeta] = &[
         image: "https://chatnews.link/icons/icon-512.png",
         theme_color: "#18181b",
         lang: "en",
+        content_lang: "en",
         keywords: "chat news,AI chat,conversational news,news AI,chatbot news,AI assistant,news summary",
     },
     SiteMeta {
@@ -3301,6 +3735,7 @@ const SITE_METAS: &[SiteMeta] = &[
         image: "https://yournews.link/icons/icon-512.png",
         theme_color: "#0c0a09",
         lang: "en",
+        content_lang: "en",
         keywords: "personalized news,AI curation,custom news feed,news for you,AI news,daily news,smart news",
     },
     SiteMeta {
@@ -3313,6 +3748,7 @@ const SITE_METAS: &[SiteMeta] = &[
         image: "https://velo.tech/icons/icon-512.png",
         theme_color: "#020617",
         lang: "en",
+        content_lang: "ja",
         keywords: "web performance,Core Web Vitals,speed test,website speed,performance audit,web optimization",
     },
     SiteMeta {
@@ -3325,6 +3761,7 @@ const SITE_METAS: &[SiteMeta] = &[
         image: "https://news.claud/icons/icon-512.png",
         theme_color: "#1c1917",
         lang: "en",
+        content_lang: "en",
         keywords: "Claude AI,AI news,news summary,Claude news,AI aggregator,smart news",
     },
 ];
@@ -3347,6 +3784,22 @@ fn detect_site(host: &str) -> &'static SiteMeta {
     }
 }
 
+/// Language to serve articles in: the `lang` parameter if given ("all" turns the
+/// filter off), else the language of the site the request came in on. A parameter
+/// that isn't a language tag gets the site's language too.
+pub fn resolve_lang(requested: Option<&str>, headers: &HeaderMap) -> Option<String> {
+    match requested.map(str::trim) {
+        Some("all") | Some("*") => None,
+        requested => requested.and_then(lang::normalize).or_else(|| {
+            let host = headers
+                .get("host")
+                .and_then(|h| h.to_str().ok())
+                .unwrap_or("news.xyz");
+            Some(detect_site(host).content_lang.to_string())
+        }),
+    }
+}
+
 /// Serve index.html with per-domain SEO/OGP meta tags injected server-side.
 /// This is critical because crawlers (Googlebot, Facebook, Twitter) do NOT execute JavaScript.
 /// Instead of fragile string replacements on the original template, we use placeholders.
@@ -3497,7 +3950,7 @@ pub async fn serve_sitemap_xml(
     }
 
     // Recent articles (up to 200 for sitemap coverage)
-    if let Ok((articles, _)) = state.db.query_articles(None, 200, None) {
+    if let Ok((articles, _)) = state.db.query_articles(None, Some(site.content_lang), 200, None) {
         for article in &articles {
             let lastmod = article.published_at.format("%Y-%m-%dT%H:%M:%S+00:00");
             // Use article ID as the URL fragment/path for the detail view
@@ -3625,3 +4078,34 @@ pub async fn handle_get_enrichments(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_site_defaults_to_its_content_language() {
+        let default_lang = |host: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("host", host.parse().unwrap());
+            resolve_lang(None, &headers)
+        };
+        for (host, lang) in [
+            ("news.xyz", "ja"),
+            ("news.online", "ja"),
+            ("news.cloud", "ja"),
+            ("chatnews.link", "en"),
+            ("yournews.link", "en"),
+            ("velo.tech", "ja"),
+            ("news.claud", "en"),
+            ("localhost:8080", "ja"),
+        ] {
+            assert_eq!(default_lang(host).as_deref(), Some(lang), "{host}");
+        }
+
+        let headers = HeaderMap::new();
+        assert_eq!(resolve_lang(Some("en-US"), &headers).as_deref(), Some("en"));
+        assert_eq!(resolve_lang(Some("all"), &headers), None);
+        assert_eq!(resolve_lang(Some("not a tag"), &headers).as_deref(), Some("ja"));
+    }
+}
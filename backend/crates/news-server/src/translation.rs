@@ -0,0 +1,141 @@
+/*
+ * Article translation - on demand and in the background
+ *
+ * Translates title, description and AI summary between the languages our sites
+ * are served in, and stores the result for as long as the article exists.
+ */
+
+use crate::claude;
+use crate::routes::AppState;
+use chrono::{DateTime, Utc};
+use news_core::models::Article;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Languages articles are translated into: the languages of our sites.
+pub const TARGET_LANGUAGES: &[&str] = &["ja", "en"];
+
+const CYCLE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const INITIAL_DELAY: Duration = Duration::from_secs(120);
+const INTER_REQUEST_DELAY: Duration = Duration::from_secs(1);
+/// Most popular untranslated articles translated into each language per cycle.
+const ARTICLES_PER_LANGUAGE: i64 = 10;
+/// Only recent articles are translated in the background.
+const BACKGROUND_WINDOW_HOURS: i64 = 24;
+/// How long an article whose translation failed waits before it is tried again;
+/// doubled on each further failure, up to `MAX_RETRY_BACKOFF_HOURS`.
+const RETRY_BACKOFF_MINUTES: i64 = 30;
+const MAX_RETRY_BACKOFF_HOURS: i64 = 12;
+
+/// An article's title, description and AI summary in another language.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArticleTranslation {
+    pub article_id: String,
+    pub lang: String,
+    /// Language of the original, if it was known.
+    pub source_lang: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub summary: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ArticleTranslation {
+    /// Serve `article` in this translation's language.
+    pub fn apply(&self, article: &mut Article) {
+        article.title = self.title.clone();
+        if self.description.is_some() {
+            article.description = self.description.clone();
+        }
+        article.translated_from = article.lang.replace(self.lang.clone());
+    }
+}
+
+/// Whether `article` would need translating to be read in `lang`. Articles of unknown
+/// language are left alone.
+pub fn needs_translation(article: &Article, lang: &str) -> bool {
+    article.lang.as_deref().is_some_and(|l| l != lang)
+}
+
+/// The stored translation of `article` into `lang`, translating and storing it first
+/// if there is none yet, or if it was made before the article had an AI summary.
+pub async fn translate(state: &AppState, article: &Article, lang: &str) -> Result<ArticleTranslation, String> {
+    let summary = state.db.get_ai_summary(&article.id)?;
+    if let Some(existing) = state.db.get_translation(&article.id, lang)? {
+        if existing.summary.is_some() || summary.is_none() {
+            return Ok(existing);
+        }
+    }
+    let translated = claude::translate_article(
+        &state.http_client,
+        &state.api_key,
+        &article.title,
+        article.description.as_deref(),
+        summary.as_deref(),
+        lang,
+    )
+    .await?;
+    let translation = ArticleTranslation {
+        article_id: article.id.clone(),
+        lang: lang.to_string(),
+        source_lang: article.lang.clone(),
+        title: translated.title,
+        description: translated.description.filter(|_| article.description.is_some()),
+        summary: translated.summary.filter(|_| summary.is_some()),
+        created_at: Utc::now(),
+    };
+    state.db.put_translation(&translation)?;
+    Ok(translation)
+}
+
+/// Run the background translation task: every cycle, translate the most popular
+/// recent articles each site can't read yet.
+pub async fn run(state: Arc<AppState>) {
+    tokio::time::sleep(INITIAL_DELAY).await;
+    loop {
+        if state.api_key.is_empty() {
+            info!("Translation skipped: Anthropic API key not configured");
+        } else {
+            run_cycle(&state).await;
+        }
+        tokio::time::sleep(CYCLE_INTERVAL).await;
+    }
+}
+
+async fn run_cycle(state: &AppState) {
+    let since = Utc::now() - chrono::Duration::hours(BACKGROUND_WINDOW_HOURS);
+    for lang in TARGET_LANGUAGES {
+        let articles = match state.db.articles_to_translate(lang, &since, ARTICLES_PER_LANGUAGE) {
+            Ok(articles) => articles,
+            Err(e) => {
+                warn!(error = %e, lang, "Translation: failed to list articles");
+                continue;
+            }
+        };
+        let mut translated = 0;
+        for article in &articles {
+            match translate(state, article, lang).await {
+                Ok(_) => translated += 1,
+                Err(e) => {
+                    let failures = state.db.record_translation_failure(
+                        &article.id,
+                        lang,
+                        &e,
+                        chrono::Duration::minutes(RETRY_BACKOFF_MINUTES),
+                        chrono::Duration::hours(MAX_RETRY_BACKOFF_HOURS),
+                    );
+                    match failures {
+                        Ok(failures) => warn!(error = %e, article_id = %article.id, lang, failures, "Translation failed"),
+                        Err(db_err) => warn!(error = %e, db_error = %db_err, article_id = %article.id, lang, "Translation failed"),
+                    }
+                }
+            }
+            tokio::time::sleep(INTER_REQUEST_DELAY).await;
+        }
+        if !articles.is_empty() {
+            info!(lang, translated, candidates = articles.len(), "Translation cycle complete");
+        }
+    }
+}
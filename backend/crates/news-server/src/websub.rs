@@ -0,0 +1,478 @@
+use crate::db::Db;
+use crate::fetcher;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use news_core::feeds;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use tracing::{info, warn};
+
+/// Lease we ask hubs for; they may grant a different one.
+const REQUESTED_LEASE_SECS: i64 = 7 * 86400;
+/// Renew an active subscription once less than this is left of its lease.
+const RENEW_BEFORE_SECS: i64 = 86400;
+/// Ask again if the hub hasn't verified a request within this long.
+const PENDING_RETRY_SECS: i64 = 3600;
+/// Back-off after a hub denied or refused a subscription.
+const DENIED_RETRY_SECS: i64 = 86400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    /// Requested; waiting for the hub's verification of intent.
+    Pending,
+    Active,
+    /// The hub denied the subscription.
+    Denied,
+    /// The subscribe request itself failed.
+    Failed,
+}
+
+impl SubscriptionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Active => "active",
+            Self::Denied => "denied",
+            Self::Failed => "failed",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "active" => Some(Self::Active),
+            "denied" => Some(Self::Denied),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A feed's subscription at its WebSub hub.
+#[derive(Debug, Clone, Serialize)]
+pub struct HubSubscription {
+    pub feed_id: String,
+    pub hub: String,
+    pub topic: String,
+    /// Shared with the hub to sign pushed content; never leaves the server otherwise.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub status: SubscriptionStatus,
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    pub requested_at: DateTime<Utc>,
+    pub last_push_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+impl HubSubscription {
+    /// Whether the subscription should be (re)requested from `hub` for `topic`.
+    fn needs_subscribe(&self, hub: &str, topic: &str, now: DateTime<Utc>) -> bool {
+        if self.hub != hub || self.topic != topic {
+            return true;
+        }
+        match self.status {
+            SubscriptionStatus::Active => self
+                .lease_expires_at
+                .is_none_or(|t| t - now < Duration::seconds(RENEW_BEFORE_SECS)),
+            SubscriptionStatus::Pending => now - self.requested_at > Duration::seconds(PENDING_RETRY_SECS),
+            SubscriptionStatus::Denied | SubscriptionStatus::Failed => {
+                now - self.requested_at > Duration::seconds(DENIED_RETRY_SECS)
+            }
+        }
+    }
+}
+
+/// Callback URL the hub verifies and pushes to for a feed.
+pub fn callback_url(base_url: &str, feed_id: &str) -> String {
+    format!("{}/api/websub/{}", base_url.trim_end_matches('/'), feed_id)
+}
+
+/// Ask `hub` to push `topic` to this server. The secret is kept across renewals at the
+/// same hub so content signed while a renewal is pending still verifies.
+pub async fn subscribe(
+    db: &Db,
+    client: &reqwest::Client,
+    base_url: &str,
+    feed_id: &str,
+    hub: &str,
+    topic: &str,
+) -> Result<(), String> {
+    let existing = db.get_websub_subscription(feed_id)?;
+    let secret = match existing {
+        Some(ref s) if s.hub == hub => s.secret.clone(),
+        _ => format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple()),
+    };
+    let callback = callback_url(base_url, feed_id);
+    let lease = REQUESTED_LEASE_SECS.to_string();
+    let result = client
+        .post(hub)
+        .form(&[
+            ("hub.mode", "subscribe"),
+            ("hub.topic", topic),
+            ("hub.callback", &callback),
+            ("hub.secret", &secret),
+            ("hub.lease_seconds", &lease),
+        ])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status());
+
+    let mut sub = HubSubscription {
+        feed_id: feed_id.to_string(),
+        hub: hub.to_string(),
+        topic: topic.to_string(),
+        secret,
+        status: SubscriptionStatus::Pending,
+        lease_expires_at: None,
+        requested_at: Utc::now(),
+        last_push_at: None,
+        last_error: None,
+    };
+    // A renewal keeps the current lease until the hub confirms the new one
+    if let Some(prev) = existing.filter(|s| s.hub == hub && s.topic == topic) {
+        sub.lease_expires_at = prev.lease_expires_at;
+        sub.last_push_at = prev.last_push_at;
+    }
+    let outcome = match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            sub.status = SubscriptionStatus::Failed;
+            sub.last_error = Some(e.to_string());
+            Err(format!("WebSub subscribe to {hub}: {e}"))
+        }
+    };
+    db.put_websub_subscription(&sub)?;
+    outcome
+}
+
+/// Subscribe every enabled feed whose last fetch advertised a hub, and renew leases
+/// that are about to run out. Returns how many requests were sent.
+pub async fn renew_subscriptions(db: &Db, client: &reqwest::Client, base_url: &str) -> usize {
+    let feeds = db.get_enabled_feeds().unwrap_or_default();
+    let states = db.get_fetch_states().unwrap_or_default();
+    let subs: HashMap<String, HubSubscription> = db
+        .get_websub_subscriptions()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|s| (s.feed_id.clone(), s))
+        .collect();
+
+    let now = Utc::now();
+    let mut sent = 0;
+    for feed in feeds {
+        let Some(state) = states.get(&feed.feed_id) else {
+            continue;
+        };
+        let (Some(hub), Some(topic)) = (&state.websub_hub, &state.websub_topic) else {
+            continue;
+        };
+        if subs.get(&feed.feed_id).is_some_and(|s| !s.needs_subscribe(hub, topic, now)) {
+            continue;
+        }
+        sent += 1;
+        match subscribe(db, client, base_url, &feed.feed_id, hub, topic).await {
+            Ok(()) => info!(feed_id = %feed.feed_id, hub = %hub, "WebSub subscription requested"),
+            Err(e) => warn!(error = %e, feed_id = %feed.feed_id, "WebSub subscription failed"),
+        }
+    }
+    sent
+}
+
+/// Answer a hub's verification of intent (`GET` on the callback). Returns the body to
+/// echo with 200, or why the request should be refused with 404.
+pub fn verify_intent(db: &Db, feed_id: &str, params: &HashMap<String, String>) -> Result<String, String> {
+    let param = |name: &str| params.get(name).map(String::as_str);
+    let mut sub = db
+        .get_websub_subscription(feed_id)?
+        .ok_or_else(|| format!("no subscription for {feed_id}"))?;
+    if param("hub.topic") != Some(sub.topic.as_str()) {
+        return Err(format!("topic mismatch for {feed_id}"));
+    }
+    match param("hub.mode") {
+        Some("subscribe") => {
+            if !matches!(sub.status, SubscriptionStatus::Pending | SubscriptionStatus::Active) {
+                return Err(format!("subscription for {feed_id} was not requested"));
+            }
+            let challenge = param("hub.challenge").ok_or("missing hub.challenge")?;
+            let lease = param("hub.lease_seconds")
+                .and_then(|s| s.parse::<i64>().ok())
+                .unwrap_or(REQUESTED_LEASE_SECS);
+            sub.status = SubscriptionStatus::Active;
+            sub.lease_expires_at = Some(Utc::now() + Duration::seconds(lease));
+            sub.last_error = None;
+            db.put_websub_subscription(&sub)?;
+            info!(feed_id, lease, "WebSub subscription verified");
+            Ok(challenge.to_string())
+        }
+        Some("denied") => {
+            sub.status = SubscriptionStatus::Denied;
+            sub.last_error = Some(param("hub.reason").unwrap_or("denied by hub").to_string());
+            db.put_websub_subscription(&sub)?;
+            warn!(feed_id, reason = ?sub.last_error, "WebSub subscription denied");
+            Ok(String::new())
+        }
+        // We never unsubscribe, so an unsubscribe request didn't come from us
+        Some(mode) => Err(format!("unexpected hub.mode {mode}")),
+        None => Err("missing hub.mode".into()),
+    }
+}
+
+/// Check an `X-Hub-Signature` header (`sha1=...`, `sha256=...`, ...) against the body.
+pub fn verify_signature(secret: &str, header: &str, body: &[u8]) -> bool {
+    let Some((method, hex_sig)) = header.trim().split_once('=') else {
+        return false;
+    };
+    let Ok(sig) = hex::decode(hex_sig) else {
+        return false;
+    };
+    fn check<M: Mac + hmac::digest::KeyInit>(secret: &str, body: &[u8], sig: &[u8]) -> bool {
+        match <M as Mac>::new_from_slice(secret.as_bytes()) {
+            Ok(mut mac) => {
+                mac.update(body);
+                mac.verify_slice(sig).is_ok()
+            }
+            Err(_) => false,
+        }
+    }
+    match method.to_ascii_lowercase().as_str() {
+        "sha1" => check::<Hmac<sha1::Sha1>>(secret, body, &sig),
+        "sha256" => check::<Hmac<sha2::Sha256>>(secret, body, &sig),
+        "sha384" => check::<Hmac<sha2::Sha384>>(secret, body, &sig),
+        "sha512" => check::<Hmac<sha2::Sha512>>(secret, body, &sig),
+        _ => false,
+    }
+}
+
+#[derive(Debug)]
+pub enum PushError {
+    /// No subscription for this callback; the hub should stop pushing.
+    NotSubscribed,
+    /// The push was received but ignored (bad signature, unparseable body, ...).
+    Rejected(String),
+}
+
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotSubscribed => write!(f, "not subscribed"),
+            Self::Rejected(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+/// Ingest content a hub pushed for `feed_id`, after checking its signature. Returns
+/// the number of new articles stored.
+pub fn receive(db: &Db, feed_id: &str, signature: Option<&str>, body: &[u8]) -> Result<usize, PushError> {
+    let mut sub = db
+        .get_websub_subscription(feed_id)
+        .map_err(PushError::Rejected)?
+        .ok_or(PushError::NotSubscribed)?;
+    let feed = db
+        .get_enabled_feeds()
+        .map_err(PushError::Rejected)?
+        .into_iter()
+        .find(|f| f.feed_id == feed_id)
+        .ok_or(PushError::NotSubscribed)?;
+    match signature {
+        Some(sig) if verify_signature(&sub.secret, sig, body) => {}
+        Some(_) => return Err(PushError::Rejected("signature mismatch".into())),
+        None => return Err(PushError::Rejected("missing X-Hub-Signature".into())),
+    }
+
+    let articles = feeds::parse_feed_body(&fetcher::feed_config(feed), body)
+        .map_err(|e| PushError::Rejected(e.to_string()))?;
+    let threshold = db.get_feature_flags().unwrap_or_default().grouping_threshold;
+    let stored = if articles.is_empty() {
+        0
+    } else {
+        fetcher::store_articles(db, &fetcher::canonicalizer(), articles, threshold)
+    };
+
+    sub.last_push_at = Some(Utc::now());
+    if let Err(e) = db.put_websub_subscription(&sub) {
+        warn!(error = %e, feed_id, "Failed to record WebSub push");
+    }
+    Ok(stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Form, Router};
+    use news_core::config::DynamicFeed;
+    use std::sync::{Arc, Mutex};
+
+    const TOPIC: &str = "https://example.com/rss";
+    const RSS: &str = r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Example</title>
+        <item><title>Breaking</title><link>https://example.com/breaking</link></item>
+        </channel></rss>"#;
+
+    fn db_with_feed() -> Db {
+        let db = Db::open(":memory:").unwrap();
+        db.put_feed(&DynamicFeed {
+            feed_id: "feed-1".into(),
+            url: TOPIC.into(),
+            source: "Example".into(),
+            category: "general".into(),
+            enabled: true,
+            added_by: None,
+            poll_interval_secs: None,
+            quarantine_reason: None,
+            content_policy: Default::default(),
+            source_type: Default::default(),
+            scrape: None,
+            filters: None,
+        })
+        .unwrap();
+        db
+    }
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    type HubRequests = Arc<Mutex<Vec<HashMap<String, String>>>>;
+
+    /// A stand-in hub that records subscribe requests and answers 202 Accepted.
+    async fn stand_in_hub() -> (String, HubRequests) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route(
+                "/hub",
+                post(
+                    |State(requests): State<HubRequests>,
+                     Form(form): Form<HashMap<String, String>>| async move {
+                        requests.lock().unwrap().push(form);
+                        StatusCode::ACCEPTED
+                    },
+                ),
+            )
+            .with_state(Arc::clone(&requests));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}/hub"), requests)
+    }
+
+    #[test]
+    fn signatures_are_checked_per_algorithm() {
+        let body = b"<rss/>";
+        assert!(verify_signature("s3cret", &sign("s3cret", body), body));
+        assert!(!verify_signature("other", &sign("s3cret", body), body));
+        assert!(!verify_signature("s3cret", &sign("s3cret", body), b"<rss>tampered</rss>"));
+
+        let mut mac = Hmac::<sha1::Sha1>::new_from_slice(b"s3cret").unwrap();
+        mac.update(body);
+        let sha1 = format!("sha1={}", hex::encode(mac.finalize().into_bytes()));
+        assert!(verify_signature("s3cret", &sha1, body));
+
+        assert!(!verify_signature("s3cret", "md5=abcd", body));
+        assert!(!verify_signature("s3cret", "garbage", body));
+    }
+
+    #[test]
+    fn renewal_is_due_near_lease_end_or_after_failures() {
+        let now = Utc::now();
+        let mut sub = HubSubscription {
+            feed_id: "feed-1".into(),
+            hub: "https://hub.example/".into(),
+            topic: TOPIC.into(),
+            secret: "s".into(),
+            status: SubscriptionStatus::Active,
+            lease_expires_at: Some(now + Duration::days(5)),
+            requested_at: now - Duration::days(2),
+            last_push_at: None,
+            last_error: None,
+        };
+        assert!(!sub.needs_subscribe("https://hub.example/", TOPIC, now));
+        assert!(sub.needs_subscribe("https://other-hub.example/", TOPIC, now));
+        sub.lease_expires_at = Some(now + Duration::hours(3));
+        assert!(sub.needs_subscribe("https://hub.example/", TOPIC, now));
+
+        sub.status = SubscriptionStatus::Pending;
+        sub.requested_at = now - Duration::minutes(5);
+        assert!(!sub.needs_subscribe("https://hub.example/", TOPIC, now));
+        sub.status = SubscriptionStatus::Denied;
+        assert!(!sub.needs_subscribe("https://hub.example/", TOPIC, now));
+        sub.requested_at = now - Duration::days(2);
+        assert!(sub.needs_subscribe("https://hub.example/", TOPIC, now));
+    }
+
+    #[tokio::test]
+    async fn subscribe_verify_and_receive_push() {
+        let (hub, requests) = stand_in_hub().await;
+        let db = db_with_feed();
+        let client = reqwest::Client::new();
+
+        // The feed advertised the hub on its last fetch
+        db.put_fetch_states(&[(
+            "feed-1".into(),
+            feeds::FetchState {
+                websub_hub: Some(hub.clone()),
+                websub_topic: Some(TOPIC.into()),
+                ..Default::default()
+            },
+        )])
+        .unwrap();
+        assert_eq!(renew_subscriptions(&db, &client, "https://news.example/").await, 1);
+        // Pending and not yet due again
+        assert_eq!(renew_subscriptions(&db, &client, "https://news.example/").await, 0);
+
+        let form = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(form["hub.mode"], "subscribe");
+        assert_eq!(form["hub.topic"], TOPIC);
+        assert_eq!(form["hub.callback"], "https://news.example/api/websub/feed-1");
+        let secret = form["hub.secret"].clone();
+        let sub = db.get_websub_subscription("feed-1").unwrap().unwrap();
+        assert_eq!(sub.status, SubscriptionStatus::Pending);
+        assert_eq!(sub.secret, secret);
+
+        // The hub verifies intent; a wrong topic is refused
+        let mut params: HashMap<String, String> = [
+            ("hub.mode", "subscribe"),
+            ("hub.topic", "https://evil.example/"),
+            ("hub.challenge", "c-123"),
+            ("hub.lease_seconds", "86400"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert!(verify_intent(&db, "feed-1", &params).is_err());
+        params.insert("hub.topic".into(), TOPIC.into());
+        assert_eq!(verify_intent(&db, "feed-1", &params).unwrap(), "c-123");
+        let sub = db.get_websub_subscription("feed-1").unwrap().unwrap();
+        assert_eq!(sub.status, SubscriptionStatus::Active);
+        assert!(sub.lease_expires_at.unwrap() <= Utc::now() + Duration::seconds(86400));
+        // A one-day lease is inside the renewal window
+        assert_eq!(renew_subscriptions(&db, &client, "https://news.example/").await, 1);
+        let renewal = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(renewal["hub.secret"], secret);
+        assert!(verify_intent(&db, "feed-1", &params).is_ok());
+
+        // Pushed content must be signed with the shared secret
+        let body = RSS.as_bytes();
+        assert!(matches!(
+            receive(&db, "feed-1", Some(&sign("wrong", body)), body),
+            Err(PushError::Rejected(_))
+        ));
+        assert!(matches!(receive(&db, "feed-1", None, body), Err(PushError::Rejected(_))));
+        assert!(matches!(
+            receive(&db, "feed-2", Some(&sign(&secret, body)), body),
+            Err(PushError::NotSubscribed)
+        ));
+        assert_eq!(receive(&db, "feed-1", Some(&sign(&secret, body)), body).unwrap(), 1);
+        let (articles, _) = db.query_articles(None, None, 10, None).unwrap();
+        assert_eq!(articles.len(), 1);
+        assert_eq!(articles[0].title, "Breaking");
+        assert_eq!(articles[0].source, "Example");
+        assert!(db.get_websub_subscription("feed-1").unwrap().unwrap().last_push_at.is_some());
+    }
+}